@@ -14,7 +14,7 @@ async fn main() -> Result<(), std::io::Error> {
     tracing_subscriber::fmt::init();
 
     let app = Route::new().at("/", get(hello));
-    let listener = UnixListener::bind("./unix-socket");
+    let listener = UnixListener::bind("./unix-socket").remove_stale_socket();
     Server::new(listener).run(app).await
 }
 
@@ -1,6 +1,6 @@
 use std::{
     fmt::{Debug, Formatter},
-    io::{Error as IoError, ErrorKind},
+    io::Error as IoError,
     pin::Pin,
     task::Poll,
 };
@@ -169,13 +169,18 @@ impl Body {
         size_hint.lower() == 0 && size_hint.upper() == Some(0)
     }
 
+    /// Returns the length of this body if it is known without reading it.
+    pub(crate) fn exact_len(&self) -> Option<u64> {
+        hyper::body::Body::size_hint(&self.0).exact()
+    }
+
     /// Consumes this body object to return a [`Bytes`] that contains all data.
     pub async fn into_bytes(self) -> Result<Bytes, ReadBodyError> {
         Ok(self
             .0
             .collect()
             .await
-            .map_err(|err| ReadBodyError::Io(IoError::new(ErrorKind::Other, err)))?
+            .map_err(ReadBodyError::from)?
             .to_bytes())
     }
 
@@ -269,6 +274,10 @@ impl Body {
     }
 
     /// Consumes this body object to return a bytes stream.
+    ///
+    /// The chunks are yielded as they are received without buffering the
+    /// body. The next chunk is only read from the connection when the stream
+    /// is polled, so a slow consumer applies backpressure to the client.
     pub fn into_bytes_stream(self) -> impl Stream<Item = Result<Bytes, IoError>> + Send + 'static {
         let mut body = self.0;
         futures_util::stream::poll_fn(move |ctx| {
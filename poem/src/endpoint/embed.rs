@@ -4,6 +4,7 @@ use rust_embed::RustEmbed;
 
 use crate::{
     Endpoint, Error, Request, Response,
+    endpoint::index_fallback::is_client_route,
     http::{Method, StatusCode, header},
 };
 
@@ -59,8 +60,25 @@ impl<E: RustEmbed + Send + Sync> Endpoint for EmbeddedFileEndpoint<E> {
 }
 
 /// An endpoint that wraps a `rust-embed` bundle.
+///
+/// # Example
+///
+/// ```
+/// use poem::{Route, endpoint::EmbeddedFilesEndpoint};
+/// use rust_embed::RustEmbed;
+///
+/// #[derive(RustEmbed)]
+/// #[folder = "src"]
+/// struct Assets;
+///
+/// let app = Route::new().nest(
+///     "/",
+///     EmbeddedFilesEndpoint::<Assets>::new().fallback_to_index(),
+/// );
+/// ```
 pub struct EmbeddedFilesEndpoint<E: RustEmbed + Send + Sync> {
     _embed: PhantomData<E>,
+    fallback_to_index: bool,
 }
 
 impl<E: RustEmbed + Sync + Send> Default for EmbeddedFilesEndpoint<E> {
@@ -75,6 +93,22 @@ impl<E: RustEmbed + Send + Sync> EmbeddedFilesEndpoint<E> {
     pub fn new() -> Self {
         EmbeddedFilesEndpoint {
             _embed: PhantomData,
+            fallback_to_index: false,
+        }
+    }
+
+    /// Serves the root `index.html` for the paths that don't match any file,
+    /// so that a single-page application can handle its own routes.
+    ///
+    /// The fallback only applies to paths whose last segment has no file
+    /// extension or requests with `Accept: text/html`, so requests for missing
+    /// assets such as `.js` and `.css` files still respond with `404 Not
+    /// Found`.
+    #[must_use]
+    pub fn fallback_to_index(self) -> Self {
+        Self {
+            fallback_to_index: true,
+            ..self
         }
     }
 }
@@ -96,6 +130,9 @@ impl<E: RustEmbed + Send + Sync> Endpoint for EmbeddedFilesEndpoint<E> {
                 .finish())
         } else if original_end_with_slash {
             let path = format!("{}index.html", path);
+            if self.fallback_to_index && E::get(&path).is_none() {
+                return EmbeddedFileEndpoint::<E>::new("index.html").call(req).await;
+            }
             EmbeddedFileEndpoint::<E>::new(&path).call(req).await
         } else if E::get(path).is_some() {
             EmbeddedFileEndpoint::<E>::new(path).call(req).await
@@ -104,6 +141,8 @@ impl<E: RustEmbed + Send + Sync> Endpoint for EmbeddedFilesEndpoint<E> {
                 .status(StatusCode::FOUND)
                 .header(LOCATION, format!("{}/", original_path))
                 .finish())
+        } else if self.fallback_to_index && is_client_route(&req, path) {
+            EmbeddedFileEndpoint::<E>::new("index.html").call(req).await
         } else {
             EmbeddedFileEndpoint::<E>::new(path).call(req).await
         }
@@ -4,7 +4,7 @@ use futures_util::{FutureExt, future::BoxFuture};
 
 use super::{
     After, AndThen, Around, Before, CatchAllError, CatchError, InspectAllError, InspectError, Map,
-    MapToResponse, ToResponse,
+    MapRequest, MapResponse, MapToResponse, ToResponse, WithWhen,
 };
 use crate::{
     Error, IntoResponse, Middleware, Request, Response, Result,
@@ -332,6 +332,50 @@ pub trait EndpointExt: IntoEndpoint {
         }
     }
 
+    /// Use middleware to transform this endpoint only for the requests for
+    /// which `predicate` returns `true`.
+    ///
+    /// Unlike [`with_if`](EndpointExt::with_if), which is decided when the
+    /// endpoint is built, the predicate is called for each request. Both the
+    /// transformed endpoint and the bare one are kept, and they share this
+    /// endpoint through an [`Arc`], so the only extra cost is building the
+    /// transformed endpoint once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{EndpointExt, Request, Route, handler, middleware::SetHeader, test::TestClient};
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let app = Route::new().at("/*path", index).with_when(
+    ///     |req: &Request| req.uri().path().starts_with("/api/"),
+    ///     SetHeader::new().overriding("Cache-Control", "no-store"),
+    /// );
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli.get("/api/users").send().await;
+    /// resp.assert_header("Cache-Control", "no-store");
+    ///
+    /// let resp = cli.get("/metrics").send().await;
+    /// resp.assert_header_is_not_exist("Cache-Control");
+    /// # });
+    /// ```
+    fn with_when<F, T>(self, predicate: F, middleware: T) -> WithWhen<Self::Endpoint, T::Output, F>
+    where
+        F: Fn(&Request) -> bool + Send + Sync,
+        T: Middleware<Arc<Self::Endpoint>>,
+        Self: Sized,
+    {
+        let inner = Arc::new(self.into_endpoint());
+        let transformed = middleware.transform(inner.clone());
+        WithWhen::new(inner, transformed, predicate)
+    }
+
     /// Attach a state data to the endpoint, similar to `with(AddData(T))`.
     ///
     /// # Example
@@ -580,6 +624,93 @@ pub trait EndpointExt: IntoEndpoint {
         Map::new(self.into_endpoint(), f)
     }
 
+    /// Maps the request of this endpoint with an infallible function.
+    ///
+    /// Unlike [`EndpointExt::before`], `f` returns the request directly
+    /// instead of a `Result`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     Endpoint, EndpointExt, Request, handler,
+    ///     http::{HeaderValue, Uri},
+    /// };
+    ///
+    /// #[handler]
+    /// async fn index(req: &Request) -> String {
+    ///     format!("{} {}", req.uri().path(), req.header("x-value").unwrap())
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = index
+    ///     .map_request(|mut req| async move {
+    ///         req.headers_mut()
+    ///             .insert("x-value", HeaderValue::from_static("hello"));
+    ///         *req.uri_mut() = Uri::from_static("/rewritten");
+    ///         req
+    ///     })
+    ///     .call(Request::default())
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     resp.into_body().into_string().await.unwrap(),
+    ///     "/rewritten hello"
+    /// );
+    /// # });
+    /// ```
+    fn map_request<F, Fut>(self, f: F) -> MapRequest<Self, F>
+    where
+        F: Fn(Request) -> Fut + Send + Sync,
+        Fut: Future<Output = Request> + Send,
+        Self: Sized,
+    {
+        MapRequest::new(self, f)
+    }
+
+    /// Maps the response of this endpoint.
+    ///
+    /// The output of this endpoint is converted into a [`Response`] before it
+    /// is passed to `f`, so `f` can change the status, the headers or the body
+    /// of any endpoint. In contrast, [`EndpointExt::map`] and
+    /// [`EndpointExt::after`] receive the output type of the endpoint.
+    ///
+    /// Errors are returned without calling `f`, use
+    /// [`EndpointExt::to_response`] first to map them too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{Endpoint, EndpointExt, Request, handler, http::HeaderValue};
+    ///
+    /// #[handler]
+    /// async fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = index
+    ///     .map_response(|mut resp| async move {
+    ///         resp.headers_mut()
+    ///             .insert("x-value", HeaderValue::from_static("1"));
+    ///         resp
+    ///     })
+    ///     .call(Request::default())
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(resp.header("x-value"), Some("1"));
+    /// assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+    /// # });
+    /// ```
+    fn map_response<F, Fut>(self, f: F) -> MapResponse<Self::Endpoint, F>
+    where
+        F: Fn(Response) -> Fut + Send + Sync,
+        Fut: Future<Output = Response> + Send,
+        Self: Sized,
+    {
+        MapResponse::new(self.into_endpoint(), f)
+    }
+
     /// Calls `f` if the result is `Ok`, otherwise returns the `Err` value of
     /// self.
     ///
@@ -783,7 +914,7 @@ mod test {
     use http::{HeaderValue, Uri};
 
     use crate::{
-        Endpoint, EndpointExt, Error, IntoEndpoint, Request, Route,
+        Endpoint, EndpointExt, Error, IntoEndpoint, IntoResponse, Request, Response, Route,
         endpoint::{make, make_sync},
         get, handler,
         http::{Method, StatusCode},
@@ -884,6 +1015,57 @@ mod test {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn test_map_request() {
+        let ep = make(|req| async move { req.into_body().into_string().await.unwrap() + "b" });
+        assert_eq!(
+            ep.map_request(|mut req| async move {
+                req.set_body("a");
+                req
+            })
+            .call(Request::default())
+            .await
+            .unwrap(),
+            "ab"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_map_response() {
+        let ep = make_sync(|_| "abc")
+            .map_response(|resp| async move {
+                let body = resp.into_body().into_string().await.unwrap();
+                Response::builder()
+                    .status(StatusCode::CREATED)
+                    .body(body + "def")
+            })
+            .map_response(|mut resp| async move {
+                resp.headers_mut()
+                    .insert("a", HeaderValue::from_static("1"));
+                resp
+            });
+        let mut resp = ep.call(Request::default()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.header("a"), Some("1"));
+        assert_eq!(resp.take_body().into_string().await.unwrap(), "abcdef");
+
+        // errors are not mapped
+        let resp =
+            make(|_| async { Err::<String, _>(Error::from_status(StatusCode::BAD_REQUEST)) })
+                .map_response(|_| async move { StatusCode::OK.into_response() })
+                .get_response(Request::default())
+                .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp =
+            make(|_| async { Err::<String, _>(Error::from_status(StatusCode::BAD_REQUEST)) })
+                .to_response()
+                .map_response(|_| async move { StatusCode::OK.into_response() })
+                .get_response(Request::default())
+                .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn test_around() {
         let ep = make(|req| async move { req.into_body().into_string().await.unwrap() + "b" });
@@ -0,0 +1,234 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use futures_util::future::{BoxFuture, join_all};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    Endpoint, IntoResponse, Request, Response, Result,
+    http::{Method, StatusCode},
+    web::Json,
+};
+
+type CheckFn = Box<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+struct Check {
+    name: String,
+    f: CheckFn,
+}
+
+/// A builder of the liveness and readiness probe endpoints.
+///
+/// The liveness endpoint always responds with `200 OK`. The readiness
+/// endpoint runs the registered checks concurrently, each with a timeout (5
+/// seconds by default), and responds with `200 OK` and `{"status": "ok"}` if
+/// all of them pass. Otherwise it responds with `503 Service Unavailable`
+/// and the errors of the failed checks:
+///
+/// ```json
+/// {"status": "error", "failed": {"database": "connection refused"}}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{Route, endpoint::HealthCheck, test::TestClient};
+///
+/// let health = HealthCheck::new()
+///     .timeout(Duration::from_secs(1))
+///     .check("database", || async { Ok(()) })
+///     .check("cache", || async { Err("connection refused".to_string()) });
+///
+/// let app = Route::new()
+///     .at("/livez", health.liveness())
+///     .at("/readyz", health.readiness());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/livez").send().await.assert_status_is_ok();
+///
+/// let resp = cli.get("/readyz").send().await;
+/// resp.assert_status(poem::http::StatusCode::SERVICE_UNAVAILABLE);
+/// resp.assert_json(serde_json::json!({
+///     "status": "error",
+///     "failed": {"cache": "connection refused"},
+/// }))
+/// .await;
+/// # });
+/// ```
+pub struct HealthCheck {
+    checks: Vec<Check>,
+    timeout: Duration,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthCheck {
+    /// Create a `HealthCheck` without checks.
+    pub fn new() -> Self {
+        Self {
+            checks: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Registers a readiness check, `f` returns an error message if the check
+    /// fails.
+    #[must_use]
+    pub fn check<F, Fut>(mut self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.checks.push(Check {
+            name: name.into(),
+            f: Box::new(move || Box::pin(f())),
+        });
+        self
+    }
+
+    /// Sets the timeout of each check.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Returns the liveness endpoint.
+    pub fn liveness(&self) -> LivenessEndpoint {
+        LivenessEndpoint
+    }
+
+    /// Returns the readiness endpoint.
+    pub fn readiness(self) -> ReadinessEndpoint {
+        ReadinessEndpoint {
+            checks: Arc::new(self.checks),
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// The liveness endpoint of [`HealthCheck`].
+pub struct LivenessEndpoint;
+
+impl Endpoint for LivenessEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            return Ok(StatusCode::METHOD_NOT_ALLOWED.into());
+        }
+        Ok(Json(json!({ "status": "ok" })).into_response())
+    }
+}
+
+/// The readiness endpoint of [`HealthCheck`].
+#[derive(Clone)]
+pub struct ReadinessEndpoint {
+    checks: Arc<Vec<Check>>,
+    timeout: Duration,
+}
+
+impl Endpoint for ReadinessEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            return Ok(StatusCode::METHOD_NOT_ALLOWED.into());
+        }
+
+        let results = join_all(self.checks.iter().map(|check| async move {
+            let res = match tokio::time::timeout(self.timeout, (check.f)()).await {
+                Ok(res) => res,
+                Err(_) => Err(format!("timed out after {:?}", self.timeout)),
+            };
+            (check.name.as_str(), res)
+        }))
+        .await;
+
+        let failed = results
+            .into_iter()
+            .filter_map(|(name, res)| Some((name.to_string(), Value::String(res.err()?))))
+            .collect::<Map<_, _>>();
+        if failed.is_empty() {
+            Ok(Json(json!({ "status": "ok" })).into_response())
+        } else {
+            Ok(Json(json!({ "status": "error", "failed": failed }))
+                .with_status(StatusCode::SERVICE_UNAVAILABLE)
+                .into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn readiness() {
+        let cli = TestClient::new(
+            HealthCheck::new()
+                .check("database", || async { Ok(()) })
+                .check("cache", || async { Ok(()) })
+                .readiness(),
+        );
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_json(json!({ "status": "ok" })).await;
+
+        let cli = TestClient::new(HealthCheck::new().readiness());
+        cli.get("/").send().await.assert_status_is_ok();
+        cli.post("/")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn failed_checks() {
+        let health = HealthCheck::new()
+            .timeout(Duration::from_millis(100))
+            .check("database", || async { Ok(()) })
+            .check("cache", || async { Err("connection refused".to_string()) })
+            .check("queue", || async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                Ok(())
+            })
+            .check("search", || async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                Ok(())
+            });
+        let cli = TestClient::new(health.readiness());
+
+        let start = Instant::now();
+        let resp = cli.get("/").send().await;
+        // the checks run concurrently
+        assert!(start.elapsed() < Duration::from_millis(300));
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        resp.assert_json(json!({
+            "status": "error",
+            "failed": {
+                "cache": "connection refused",
+                "queue": "timed out after 100ms",
+                "search": "timed out after 100ms",
+            },
+        }))
+        .await;
+    }
+
+    #[tokio::test]
+    async fn liveness() {
+        let health = HealthCheck::new().check("cache", || async { Err("down".to_string()) });
+        let cli = TestClient::new(health.liveness());
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_json(json!({ "status": "ok" })).await;
+    }
+}
@@ -0,0 +1,21 @@
+use crate::{Request, http::header};
+
+/// Returns `true` if a request for the missing file at `path` should be
+/// answered with the index file of a single-page application.
+///
+/// These are the paths whose last segment has no file extension and the
+/// requests that accept `text/html`, so that missing assets such as `.js` and
+/// `.css` files still respond with `404 Not Found`.
+pub(crate) fn is_client_route(req: &Request, path: &str) -> bool {
+    let has_extension = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.contains('.'));
+    !has_extension
+        || req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|accept| accept.contains("text/html"))
+}
@@ -0,0 +1,29 @@
+use std::future::Future;
+
+use crate::{Endpoint, Request, Result};
+
+/// Endpoint for the [`map_request`](super::EndpointExt::map_request) method.
+pub struct MapRequest<E, F> {
+    inner: E,
+    f: F,
+}
+
+impl<E, F> MapRequest<E, F> {
+    #[inline]
+    pub(crate) fn new(inner: E, f: F) -> MapRequest<E, F> {
+        Self { inner, f }
+    }
+}
+
+impl<E, F, Fut> Endpoint for MapRequest<E, F>
+where
+    E: Endpoint,
+    F: Fn(Request) -> Fut + Send + Sync,
+    Fut: Future<Output = Request> + Send,
+{
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        self.inner.call((self.f)(req).await).await
+    }
+}
@@ -10,9 +10,14 @@ mod catch_error;
 mod embed;
 #[allow(clippy::module_inception)]
 mod endpoint;
+mod health_check;
+#[cfg(any(feature = "embed", feature = "static-files"))]
+mod index_fallback;
 mod inspect_all_err;
 mod inspect_err;
 mod map;
+mod map_request;
+mod map_response;
 mod map_to_response;
 #[cfg(feature = "prometheus")]
 mod prometheus_exporter;
@@ -21,6 +26,7 @@ mod static_files;
 mod to_response;
 #[cfg(feature = "tower-compat")]
 mod tower_compat;
+mod with_when;
 
 pub use after::After;
 pub use and_then::AndThen;
@@ -34,9 +40,12 @@ pub use endpoint::{
     BoxEndpoint, DynEndpoint, EitherEndpoint, Endpoint, EndpointExt, IntoEndpoint, ToDynEndpoint,
     make, make_sync,
 };
+pub use health_check::{HealthCheck, LivenessEndpoint, ReadinessEndpoint};
 pub use inspect_all_err::InspectAllError;
 pub use inspect_err::InspectError;
 pub use map::Map;
+pub use map_request::MapRequest;
+pub use map_response::MapResponse;
 pub use map_to_response::MapToResponse;
 #[cfg(feature = "prometheus")]
 pub use prometheus_exporter::PrometheusExporter;
@@ -44,4 +53,5 @@ pub use prometheus_exporter::PrometheusExporter;
 pub use static_files::{StaticFileEndpoint, StaticFilesEndpoint};
 pub use to_response::ToResponse;
 #[cfg(feature = "tower-compat")]
-pub use tower_compat::TowerCompatExt;
+pub use tower_compat::{PoemServiceCompat, TowerCompatExt};
+pub use with_when::WithWhen;
@@ -1,55 +1,199 @@
 use std::{
-    ffi::OsStr,
+    collections::HashMap,
+    ffi::{OsStr, OsString},
     fmt::Write,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use http::header::LOCATION;
+use http::{HeaderMap, HeaderValue, header::LOCATION};
+use httpdate::HttpDate;
 
 use crate::{
     Body, Endpoint, FromRequest, IntoResponse, Request, Response, Result,
+    endpoint::index_fallback::is_client_route,
     error::StaticFileError,
     http::{Method, StatusCode, header},
-    web::StaticFileRequest,
+    web::{StaticFileRequest, StaticFileResponse, guess_content_type},
 };
 
+/// Precompressed variants in the order of preference, as the file extension
+/// and the content coding.
+const PRECOMPRESSED: &[(&str, &str)] = &[("br", "br"), ("gz", "gzip")];
+
+/// Returns the precompressed variant of the file accepted by the request, and
+/// its content coding.
+fn precompressed_variant(headers: &HeaderMap, path: &Path) -> Option<(PathBuf, &'static str)> {
+    let accepted = headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let coding = parts.next()?.trim();
+            let rejected = parts.any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .is_some_and(|q| q <= 0.0)
+            });
+            (!rejected).then_some(coding)
+        })
+        .collect::<Vec<_>>();
+
+    PRECOMPRESSED.iter().find_map(|(ext, coding)| {
+        if !accepted
+            .iter()
+            .any(|accepted| *accepted == "*" || accepted.eq_ignore_ascii_case(coding))
+        {
+            return None;
+        }
+        let mut variant = OsString::from(path.as_os_str());
+        variant.push(".");
+        variant.push(ext);
+        let variant = PathBuf::from(variant);
+        variant.is_file().then_some((variant, *coding))
+    })
+}
+
+type CacheControlFn = Box<dyn Fn(&Path) -> Option<HeaderValue> + Send + Sync>;
+
+/// The rules to set the `Cache-Control` header of the file responses.
+#[derive(Default)]
+struct CacheControlRules {
+    default: Option<HeaderValue>,
+    extensions: HashMap<String, HeaderValue>,
+    custom: Option<CacheControlFn>,
+}
+
+impl CacheControlRules {
+    fn resolve(&self, path: &Path) -> Option<HeaderValue> {
+        if let Some(value) = self.custom.as_ref().and_then(|custom| custom(path)) {
+            return Some(value);
+        }
+        path.extension()
+            .and_then(OsStr::to_str)
+            .and_then(|ext| self.extensions.get(&ext.to_ascii_lowercase()))
+            .or(self.default.as_ref())
+            .cloned()
+    }
+}
+
+fn parse_cache_control<T>(value: T) -> HeaderValue
+where
+    HeaderValue: TryFrom<T>,
+{
+    match HeaderValue::try_from(value) {
+        Ok(value) => value,
+        Err(_) => panic!("illegal cache control"),
+    }
+}
+
+async fn file_response(
+    req: &Request,
+    path: &Path,
+    prefer_utf8: bool,
+    no_cache: bool,
+    precompressed: bool,
+    cache_control: &CacheControlRules,
+) -> Result<Response> {
+    let mut resp = create_file_response(req, path, prefer_utf8, no_cache, precompressed).await?;
+    if !resp.headers().contains_key(header::CACHE_CONTROL) {
+        if let Some(value) = cache_control.resolve(path) {
+            resp.headers_mut().insert(header::CACHE_CONTROL, value);
+        }
+    }
+    Ok(resp)
+}
+
+async fn create_file_response(
+    req: &Request,
+    path: &Path,
+    prefer_utf8: bool,
+    no_cache: bool,
+    precompressed: bool,
+) -> Result<Response> {
+    let static_file = StaticFileRequest::from_request_without_body(req).await?;
+    if !precompressed {
+        return Ok(static_file
+            .create_response(path, prefer_utf8, no_cache)?
+            .into_response());
+    }
+
+    let mut resp = match precompressed_variant(req.headers(), path) {
+        Some((variant, coding)) => {
+            // The `ETag` and `Last-Modified` headers are of the variant, but
+            // the content type is of the original file.
+            let mut resp = static_file.create_response(&variant, prefer_utf8, no_cache)?;
+            if let StaticFileResponse::Ok { content_type, .. } = &mut resp {
+                *content_type = guess_content_type(path, prefer_utf8);
+            }
+            let mut resp = resp.into_response();
+            if resp.status() != StatusCode::NOT_MODIFIED {
+                resp.headers_mut()
+                    .insert(header::CONTENT_ENCODING, HeaderValue::from_static(coding));
+            }
+            resp
+        }
+        None => static_file
+            .create_response(path, prefer_utf8, no_cache)?
+            .into_response(),
+    };
+    resp.headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    Ok(resp)
+}
+
 struct DirectoryTemplate<'a> {
     path: &'a str,
+    parent_url: Option<String>,
     files: Vec<FileRef>,
 }
 
 impl DirectoryTemplate<'_> {
     fn render(&self) -> String {
+        let path = escape_html(self.path);
         let mut s = format!(
             r#"
         <html>
             <head>
-            <title>Index of {}</title>
+            <title>Index of {path}</title>
         </head>
         <body>
-        <h1>Index of /{}</h1>
-        <ul>"#,
-            self.path, self.path
+        <h1>Index of /{path}</h1>
+        <table>
+        <tr><th>Name</th><th>Size</th><th>Modified</th></tr>"#
         );
 
+        if let Some(parent_url) = &self.parent_url {
+            let _ = write!(
+                s,
+                r#"<tr><td><a href="{}">../</a></td><td></td><td></td></tr>"#,
+                escape_html(parent_url)
+            );
+        }
+
         for file in &self.files {
-            if file.is_dir {
-                let _ = write!(
-                    s,
-                    r#"<li><a href="{}">{}/</a></li>"#,
-                    file.url, file.filename
-                );
-            } else {
-                let _ = write!(
-                    s,
-                    r#"<li><a href="{}">{}</a></li>"#,
-                    file.url, file.filename
-                );
-            }
+            let _ = write!(
+                s,
+                r#"<tr><td><a href="{}">{}{}</a></td><td>{}</td><td>{}</td></tr>"#,
+                escape_html(&file.url),
+                escape_html(&file.filename),
+                if file.is_dir { "/" } else { "" },
+                match (file.is_dir, file.size) {
+                    (false, Some(size)) => size.to_string(),
+                    _ => "-".to_string(),
+                },
+                file.modified
+                    .map(|modified| HttpDate::from(modified).to_string())
+                    .unwrap_or_default(),
+            );
         }
 
         s.push_str(
-            r#"</ul>
+            r#"</table>
         </body>
         </html>"#,
         );
@@ -58,10 +202,27 @@ impl DirectoryTemplate<'_> {
     }
 }
 
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 struct FileRef {
     url: String,
     filename: String,
     is_dir: bool,
+    size: Option<u64>,
+    modified: Option<SystemTime>,
 }
 
 /// Static files handling service.
@@ -78,6 +239,8 @@ pub struct StaticFilesEndpoint {
     no_cache_index: bool,
     prefer_utf8: bool,
     redirect_to_slash: bool,
+    precompressed: bool,
+    cache_control: CacheControlRules,
 }
 
 impl StaticFilesEndpoint {
@@ -104,11 +267,17 @@ impl StaticFilesEndpoint {
             no_cache_index: false,
             prefer_utf8: true,
             redirect_to_slash: false,
+            precompressed: false,
+            cache_control: CacheControlRules::default(),
         }
     }
 
     /// Show files listing for directories.
     ///
+    /// The listing contains the name, size and modification time of each
+    /// entry, with directories first. Without it, requesting a directory that
+    /// has no index file is forbidden.
+    ///
     /// By default show files listing is disabled.
     #[must_use]
     pub fn show_files_listing(self) -> Self {
@@ -154,6 +323,11 @@ impl StaticFilesEndpoint {
     }
 
     /// Fall back to the configured index file if any, if the file is not found
+    ///
+    /// This is intended for single-page applications with client-side routing,
+    /// so it only applies to paths whose last segment has no file extension
+    /// and requests with `Accept: text/html`. Requests for missing assets such
+    /// as `.js` and `.css` files still respond with `404 Not Found`.
     #[must_use]
     pub fn fallback_to_index(self) -> Self {
         Self {
@@ -170,6 +344,92 @@ impl StaticFilesEndpoint {
             ..self
         }
     }
+
+    /// Specifies whether to serve precompressed variants of files.
+    ///
+    /// If the request accepts the encoding, `<file>.br` or `<file>.gz` is
+    /// served with the `Content-Encoding` header and the content type of
+    /// `<file>`. Otherwise the original file is served.
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn precompressed(self, value: bool) -> Self {
+        Self {
+            precompressed: value,
+            ..self
+        }
+    }
+
+    /// Sets the default `Cache-Control` header of the file responses.
+    ///
+    /// The header is set for the full, partial (`206`) and not modified
+    /// (`304`) responses. A rule set with
+    /// [`StaticFilesEndpoint::cache_control_for`] or
+    /// [`StaticFilesEndpoint::cache_control_with`] takes precedence, and
+    /// [`StaticFilesEndpoint::no_cache_index`] takes precedence over all of
+    /// them for the index file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::endpoint::StaticFilesEndpoint;
+    ///
+    /// let ep = StaticFilesEndpoint::new("/etc/www")
+    ///     .cache_control("public, max-age=31536000, immutable")
+    ///     .cache_control_for("html", "no-cache");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    #[must_use]
+    pub fn cache_control<T>(mut self, value: T) -> Self
+    where
+        HeaderValue: TryFrom<T>,
+    {
+        self.cache_control.default = Some(parse_cache_control(value));
+        self
+    }
+
+    /// Sets the `Cache-Control` header of the files with the extension `ext`,
+    /// such as `html`, compared case-insensitively.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    #[must_use]
+    pub fn cache_control_for<T>(mut self, ext: impl AsRef<str>, value: T) -> Self
+    where
+        HeaderValue: TryFrom<T>,
+    {
+        self.cache_control.extensions.insert(
+            ext.as_ref().trim_start_matches('.').to_ascii_lowercase(),
+            parse_cache_control(value),
+        );
+        self
+    }
+
+    /// Sets a function that returns the `Cache-Control` header of a file from
+    /// its path, or `None` to use the other rules.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{endpoint::StaticFilesEndpoint, http::HeaderValue};
+    ///
+    /// let ep = StaticFilesEndpoint::new("/etc/www").cache_control_with(|path| {
+    ///     path.starts_with("/etc/www/assets")
+    ///         .then(|| HeaderValue::from_static("public, max-age=31536000, immutable"))
+    /// });
+    /// ```
+    #[must_use]
+    pub fn cache_control_with(
+        mut self,
+        f: impl Fn(&Path) -> Option<HeaderValue> + Send + Sync + 'static,
+    ) -> Self {
+        self.cache_control.custom = Some(Box::new(f));
+        self
+    }
 }
 
 impl Endpoint for StaticFilesEndpoint {
@@ -206,14 +466,19 @@ impl Endpoint for StaticFilesEndpoint {
         }
 
         if !file_path.exists() {
-            if self.fallback_to_index {
+            if self.fallback_to_index && is_client_route(&req, &path) {
                 if let Some(index_file) = &self.index_file {
                     let index_path = self.path.join(index_file);
                     if index_path.is_file() {
-                        return Ok(StaticFileRequest::from_request_without_body(&req)
-                            .await?
-                            .create_response(&index_path, self.prefer_utf8, self.no_cache_index)?
-                            .into_response());
+                        return file_response(
+                            &req,
+                            &index_path,
+                            self.prefer_utf8,
+                            self.no_cache_index,
+                            self.precompressed,
+                            &self.cache_control,
+                        )
+                        .await;
                     }
                 }
             }
@@ -221,10 +486,15 @@ impl Endpoint for StaticFilesEndpoint {
         }
 
         if file_path.is_file() {
-            Ok(StaticFileRequest::from_request_without_body(&req)
-                .await?
-                .create_response(&file_path, self.prefer_utf8, false)?
-                .into_response())
+            file_response(
+                &req,
+                &file_path,
+                self.prefer_utf8,
+                false,
+                self.precompressed,
+                &self.cache_control,
+            )
+            .await
         } else {
             if self.redirect_to_slash
                 && !req.original_uri().path().ends_with('/')
@@ -240,17 +510,30 @@ impl Endpoint for StaticFilesEndpoint {
             if let Some(index_file) = &self.index_file {
                 let index_path = file_path.join(index_file);
                 if index_path.is_file() {
-                    return Ok(StaticFileRequest::from_request_without_body(&req)
-                        .await?
-                        .create_response(&index_path, self.prefer_utf8, self.no_cache_index)?
-                        .into_response());
+                    return file_response(
+                        &req,
+                        &index_path,
+                        self.prefer_utf8,
+                        self.no_cache_index,
+                        self.precompressed,
+                        &self.cache_control,
+                    )
+                    .await;
                 }
             }
 
             if self.show_files_listing {
                 let read_dir = file_path.read_dir().map_err(StaticFileError::Io)?;
+                let mut base_url = req.original_uri().path().to_string();
+                if !base_url.ends_with('/') {
+                    base_url.push('/');
+                }
                 let mut template = DirectoryTemplate {
                     path: &path,
+                    parent_url: (!path.is_empty()).then(|| {
+                        let trimmed = base_url.trim_end_matches('/');
+                        trimmed[..trimmed.rfind('/').map(|idx| idx + 1).unwrap_or(0)].to_string()
+                    }),
                     files: Vec::new(),
                 };
 
@@ -258,28 +541,32 @@ impl Endpoint for StaticFilesEndpoint {
                     let entry = res.map_err(StaticFileError::Io)?;
 
                     if let Some(filename) = entry.file_name().to_str() {
-                        let mut base_url = req.original_uri().path().to_string();
-                        if !base_url.ends_with('/') {
-                            base_url.push('/');
-                        }
                         let filename_url = percent_encoding::percent_encode(
                             filename.as_bytes(),
                             percent_encoding::NON_ALPHANUMERIC,
                         );
+                        let metadata = entry.path().metadata().ok();
                         template.files.push(FileRef {
                             url: format!("{base_url}{filename_url}"),
                             filename: filename.to_string(),
                             is_dir: entry.path().is_dir(),
+                            size: metadata.as_ref().map(|metadata| metadata.len()),
+                            modified: metadata.and_then(|metadata| metadata.modified().ok()),
                         });
                     }
                 }
 
+                // directories first, then files, each sorted by name
+                template
+                    .files
+                    .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.filename.cmp(&b.filename)));
+
                 let html = template.render();
                 Ok(Response::builder()
                     .header(header::CONTENT_TYPE, mime::TEXT_HTML_UTF_8.as_ref())
                     .body(Body::from_string(html)))
             } else {
-                Err(StaticFileError::NotFound.into())
+                Err(StaticFileError::Forbidden(path.to_string()).into())
             }
         }
     }
@@ -295,6 +582,8 @@ pub struct StaticFileEndpoint {
     path: PathBuf,
     prefer_utf8: bool,
     no_cache: bool,
+    precompressed: bool,
+    cache_control: CacheControlRules,
 }
 
 impl StaticFileEndpoint {
@@ -312,6 +601,8 @@ impl StaticFileEndpoint {
             path: path.into(),
             prefer_utf8: true,
             no_cache: false,
+            precompressed: false,
+            cache_control: CacheControlRules::default(),
         }
     }
 
@@ -337,15 +628,267 @@ impl StaticFileEndpoint {
             ..self
         }
     }
+
+    /// Specifies whether to serve a precompressed variant of the file.
+    ///
+    /// See [`StaticFilesEndpoint::precompressed`].
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn precompressed(self, value: bool) -> Self {
+        Self {
+            precompressed: value,
+            ..self
+        }
+    }
+
+    /// Sets the `Cache-Control` header of the responses.
+    ///
+    /// See [`StaticFilesEndpoint::cache_control`],
+    /// [`StaticFileEndpoint::no_cache`] takes precedence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a valid header value.
+    #[must_use]
+    pub fn cache_control<T>(mut self, value: T) -> Self
+    where
+        HeaderValue: TryFrom<T>,
+    {
+        self.cache_control.default = Some(parse_cache_control(value));
+        self
+    }
 }
 
 impl Endpoint for StaticFileEndpoint {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
-        Ok(StaticFileRequest::from_request_without_body(&req)
-            .await?
-            .create_response(&self.path, self.prefer_utf8, self.no_cache)?
-            .into_response())
+        file_response(
+            &req,
+            &self.path,
+            self.prefer_utf8,
+            self.no_cache,
+            self.precompressed,
+            &self.cache_control,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Uri;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn precompressed() {
+        let tmp = libtempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(dir.join("app.js"), "plain").unwrap();
+        std::fs::write(dir.join("app.js.gz"), "gzip").unwrap();
+        std::fs::write(dir.join("app.js.br"), "brotli").unwrap();
+        std::fs::write(dir.join("style.css"), "plain").unwrap();
+
+        let cli = TestClient::new(StaticFilesEndpoint::new(dir).precompressed(true));
+
+        let resp = cli
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "gzip, br")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::CONTENT_ENCODING, "br");
+        resp.assert_header(header::VARY, "accept-encoding");
+        resp.assert_content_type("text/javascript");
+        resp.assert_text("brotli").await;
+
+        let resp = cli
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "gzip, br;q=0")
+            .send()
+            .await;
+        resp.assert_header(header::CONTENT_ENCODING, "gzip");
+        let etag = resp.0.headers().get(header::ETAG).unwrap().clone();
+        resp.assert_text("gzip").await;
+
+        let resp = cli
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .header(header::IF_NONE_MATCH, etag)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+
+        let resp = cli.get("/app.js").send().await;
+        resp.assert_header_is_not_exist(header::CONTENT_ENCODING);
+        resp.assert_text("plain").await;
+
+        let resp = cli
+            .get("/style.css")
+            .header(header::ACCEPT_ENCODING, "gzip, br")
+            .send()
+            .await;
+        resp.assert_header_is_not_exist(header::CONTENT_ENCODING);
+        resp.assert_text("plain").await;
+
+        let resp = TestClient::new(StaticFilesEndpoint::new(dir))
+            .get("/app.js")
+            .header(header::ACCEPT_ENCODING, "gzip, br")
+            .send()
+            .await;
+        resp.assert_header_is_not_exist(header::CONTENT_ENCODING);
+        resp.assert_text("plain").await;
+    }
+
+    #[tokio::test]
+    async fn files_listing() {
+        let tmp = libtempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("sub/b_dir")).unwrap();
+        std::fs::create_dir_all(dir.join("sub/a_dir")).unwrap();
+        std::fs::write(dir.join("sub/<script>.txt"), "abc").unwrap();
+        std::fs::write(dir.join("sub/a.txt"), "hello").unwrap();
+
+        let ep = StaticFilesEndpoint::new(dir).show_files_listing();
+        let listing = |uri: &'static str| {
+            let mut req = Request::builder().uri(Uri::from_static(uri)).finish();
+            req.state_mut().original_uri = Uri::from_static(uri);
+            let ep = &ep;
+            async move {
+                let resp = ep.call(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::OK);
+                resp.into_body().into_string().await.unwrap()
+            }
+        };
+
+        let html = listing("/sub/").await;
+        assert!(html.contains(r#"<a href="/">../</a>"#));
+        assert!(html.contains("&lt;script&gt;.txt</a></td><td>3</td>"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains(r#"<a href="/sub/a%2Etxt">a.txt</a></td><td>5</td>"#));
+        let positions = ["a_dir/", "b_dir/", "&lt;script&gt;.txt", "a.txt"]
+            .map(|name| html.find(&format!(">{name}</a>")).unwrap());
+        assert!(positions.is_sorted());
+
+        let html = listing("/sub/a_dir").await;
+        assert!(html.contains(r#"<a href="/sub/">../</a>"#));
+
+        let html = listing("/").await;
+        assert!(!html.contains("../"));
+
+        TestClient::new(StaticFilesEndpoint::new(dir))
+            .get("/sub/")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn cache_control() {
+        let tmp = libtempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("index.html"), "index").unwrap();
+        std::fs::write(dir.join("app.3f2a.js"), "app").unwrap();
+        std::fs::write(dir.join("logo.PNG"), "logo").unwrap();
+        std::fs::write(dir.join("assets/font.woff2"), "font").unwrap();
+
+        let assets = dir.join("assets");
+        let cli = TestClient::new(
+            StaticFilesEndpoint::new(dir)
+                .cache_control("public, max-age=31536000, immutable")
+                .cache_control_for("html", "no-cache")
+                .cache_control_for(".png", "max-age=3600")
+                .cache_control_with(move |path| {
+                    path.starts_with(&assets)
+                        .then(|| HeaderValue::from_static("max-age=60"))
+                }),
+        );
+
+        for (uri, cache_control) in [
+            ("/index.html", "no-cache"),
+            ("/app.3f2a.js", "public, max-age=31536000, immutable"),
+            ("/logo.PNG", "max-age=3600"),
+            ("/assets/font.woff2", "max-age=60"),
+        ] {
+            let resp = cli.get(uri).send().await;
+            resp.assert_status_is_ok();
+            resp.assert_header(header::CACHE_CONTROL, cache_control);
+        }
+
+        // partial and not modified responses
+        let resp = cli
+            .get("/app.3f2a.js")
+            .header(header::RANGE, "bytes=0-1")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::PARTIAL_CONTENT);
+        resp.assert_header(header::CACHE_CONTROL, "public, max-age=31536000, immutable");
+        let etag = resp.0.headers().get(header::ETAG).unwrap().clone();
+        let resp = cli
+            .get("/app.3f2a.js")
+            .header(header::IF_NONE_MATCH, etag)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+        resp.assert_header(header::CACHE_CONTROL, "public, max-age=31536000, immutable");
+
+        // `no_cache_index` takes precedence
+        let cli = TestClient::new(
+            StaticFilesEndpoint::new(dir)
+                .index_file("index.html")
+                .no_cache_index()
+                .cache_control("max-age=3600"),
+        );
+        cli.get("/")
+            .send()
+            .await
+            .assert_header(header::CACHE_CONTROL, "no-cache");
+
+        let resp = TestClient::new(
+            StaticFileEndpoint::new(dir.join("index.html")).cache_control("max-age=10"),
+        )
+        .get("/")
+        .send()
+        .await;
+        resp.assert_header(header::CACHE_CONTROL, "max-age=10");
+    }
+
+    #[tokio::test]
+    async fn fallback_to_index() {
+        let tmp = libtempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(dir.join("index.html"), "index").unwrap();
+        std::fs::write(dir.join("app.js"), "app").unwrap();
+
+        let cli = TestClient::new(
+            StaticFilesEndpoint::new(dir)
+                .index_file("index.html")
+                .fallback_to_index(),
+        );
+
+        for uri in ["/users/1", "/settings/", "/"] {
+            let resp = cli.get(uri).send().await;
+            resp.assert_status_is_ok();
+            resp.assert_text("index").await;
+        }
+        cli.get("/app.js").send().await.assert_text("app").await;
+        cli.get("/missing.js")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        cli.get("/assets/app.css")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        cli.get("/users/jane.doe")
+            .header(header::ACCEPT, "text/html,application/xhtml+xml")
+            .send()
+            .await
+            .assert_text("index")
+            .await;
     }
 }
@@ -1,10 +1,18 @@
-use std::{error::Error as StdError, future::Future};
+use std::{
+    error::Error as StdError,
+    future::Future,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
 use bytes::Bytes;
+use futures_util::{FutureExt, future::BoxFuture};
 use http_body_util::BodyExt;
 use tower::{Service, ServiceExt};
 
-use crate::{Endpoint, Error, Request, Response, Result, body::BoxBody};
+use crate::{
+    Endpoint, Error, IntoEndpoint, IntoResponse, Request, Response, Result, body::BoxBody,
+};
 
 /// Extension trait for tower service compat.
 #[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
@@ -30,6 +38,36 @@ pub trait TowerCompatExt {
     {
         TowerCompatEndpoint(self)
     }
+
+    /// Converts a poem endpoint to a tower service.
+    ///
+    /// The service is always ready, and the errors of the endpoint are
+    /// returned as the error of the service.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{Request, endpoint::TowerCompatExt, handler};
+    /// use tower::ServiceExt;
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let svc = index.into_tower_service();
+    /// let resp = svc.oneshot(Request::default()).await.unwrap();
+    /// assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+    /// # });
+    /// ```
+    fn into_tower_service(self) -> PoemServiceCompat<Self::Endpoint>
+    where
+        Self: IntoEndpoint + Sized,
+        Self::Endpoint: 'static,
+    {
+        PoemServiceCompat(Arc::new(self.into_endpoint()))
+    }
 }
 
 impl<T> TowerCompatExt for T {}
@@ -72,17 +110,43 @@ where
     }
 }
 
+/// A poem endpoint to tower service adapter.
+#[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
+pub struct PoemServiceCompat<E>(Arc<E>);
+
+impl<E> Clone for PoemServiceCompat<E> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<E> Service<Request> for PoemServiceCompat<E>
+where
+    E: Endpoint + 'static,
+{
+    type Response = Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let ep = self.0.clone();
+        async move { ep.call(req).await.map(IntoResponse::into_response) }.boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{
-        convert::Infallible,
-        task::{Context, Poll},
-    };
+    use std::convert::Infallible;
 
     use futures_util::future::Ready;
+    use http::StatusCode;
 
     use super::*;
-    use crate::test::TestClient;
+    use crate::{error::NotFoundError, handler, test::TestClient};
 
     #[tokio::test]
     async fn test_tower_compat() {
@@ -108,4 +172,35 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_text("abc").await;
     }
+
+    #[tokio::test]
+    async fn test_into_tower_service() {
+        #[handler(internal)]
+        fn index(body: String) -> String {
+            body
+        }
+
+        let mut svc = index.into_tower_service();
+        let resp = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::builder().body("abc"))
+            .await
+            .unwrap();
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "abc");
+
+        #[handler(internal)]
+        fn not_found() -> Result<(), NotFoundError> {
+            Err(NotFoundError)
+        }
+
+        let err = not_found
+            .into_tower_service()
+            .oneshot(Request::default())
+            .await
+            .unwrap_err();
+        assert!(err.is::<NotFoundError>());
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use crate::{Endpoint, IntoResponse, Request, Response, Result};
+
+/// Endpoint for the [`with_when`](super::EndpointExt::with_when) method.
+pub struct WithWhen<E, T, F> {
+    inner: Arc<E>,
+    transformed: T,
+    predicate: F,
+}
+
+impl<E, T, F> WithWhen<E, T, F> {
+    #[inline]
+    pub(crate) fn new(inner: Arc<E>, transformed: T, predicate: F) -> Self {
+        Self {
+            inner,
+            transformed,
+            predicate,
+        }
+    }
+}
+
+impl<E, T, F> Endpoint for WithWhen<E, T, F>
+where
+    E: Endpoint,
+    T: Endpoint,
+    F: Fn(&Request) -> bool + Send + Sync,
+{
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if (self.predicate)(&req) {
+            self.transformed
+                .call(req)
+                .await
+                .map(IntoResponse::into_response)
+        } else {
+            self.inner.call(req).await.map(IntoResponse::into_response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EndpointExt, Request, Route, handler, middleware::SetHeader, test::TestClient};
+
+    #[tokio::test]
+    async fn with_when() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let app = Route::new().at("/*path", index).with_when(
+            |req: &Request| req.uri().path().starts_with("/api/"),
+            SetHeader::new().overriding("x-api", "1"),
+        );
+        let cli = TestClient::new(app);
+
+        let resp = cli.get("/api/users").send().await;
+        resp.assert_header("x-api", "1");
+        resp.assert_text("hello").await;
+
+        let resp = cli.get("/metrics").send().await;
+        resp.assert_header_is_not_exist("x-api");
+        resp.assert_text("hello").await;
+    }
+}
@@ -5,10 +5,11 @@ use std::{
     error::Error as StdError,
     fmt::{self, Debug, Display, Formatter},
     string::FromUtf8Error,
+    time::Duration,
 };
 
-use headers::{ContentRange, HeaderMapExt};
-use http::{Extensions, Method};
+use headers::{Allow, ContentRange, HeaderMapExt};
+use http::{Extensions, Method, header};
 
 use crate::{IntoResponse, Response, http::StatusCode};
 
@@ -504,6 +505,217 @@ impl Error {
     pub fn set_error_message(&mut self, msg: impl Into<String>) {
         self.msg = Some(msg.into());
     }
+
+    /// Returns the details of the error if it was returned by a built-in
+    /// extractor, such as [`Json`](crate::web::Json) or
+    /// [`Query`](crate::web::Query).
+    ///
+    /// This allows a central error handler to render all the extractor errors
+    /// in one format without downcasting to each error type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use poem::{
+    ///     Error,
+    ///     error::{ExtractorErrorKind, ParseTypedHeaderError},
+    /// };
+    ///
+    /// let err = Error::from(ParseTypedHeaderError::HeaderRequired(
+    ///     "authorization".to_string(),
+    /// ));
+    /// let details = err.extractor_error().unwrap();
+    /// assert_eq!(details.kind(), ExtractorErrorKind::Header);
+    /// assert_eq!(details.field(), Some("authorization"));
+    /// ```
+    pub fn extractor_error(&self) -> Option<ExtractorError<'_>> {
+        macro_rules! body_error {
+            ($($(#[$meta:meta])* $ty:ident),*) => {
+                $(
+                    $(#[$meta])*
+                    if let Some(err) = self.downcast_ref::<$ty>() {
+                        return Some(match err {
+                            $ty::InvalidContentType(_) | $ty::ContentTypeRequired => {
+                                ExtractorError::new(ExtractorErrorKind::ContentType)
+                            }
+                            $ty::Parse(err) => {
+                                ExtractorError::new(ExtractorErrorKind::ParseBody).with_cause(err)
+                            }
+                        });
+                    }
+                )*
+            };
+        }
+
+        if self.is::<ParsePathError>() {
+            return Some(ExtractorError::new(ExtractorErrorKind::Path));
+        }
+        if let Some(err) = self.downcast_ref::<ParsePathParamError>() {
+            return Some(ExtractorError::new(ExtractorErrorKind::Path).with_field(&err.name));
+        }
+        let field = self.data::<ExtractorField>().map(|field| field.0.as_str());
+        if let Some(err) = self.downcast_ref::<ParseQueryError>() {
+            let details = ExtractorError::new(ExtractorErrorKind::Query).with_cause(&err.0);
+            return Some(match field {
+                Some(field) => details.with_field(field),
+                None => details,
+            });
+        }
+        if let Some(err) = self.downcast_ref::<ParseTypedHeaderError>() {
+            return Some(match err {
+                ParseTypedHeaderError::HeaderRequired(name) => {
+                    ExtractorError::new(ExtractorErrorKind::Header).with_field(name)
+                }
+                ParseTypedHeaderError::TypedHeader(err) => {
+                    let details = ExtractorError::new(ExtractorErrorKind::Header).with_cause(err);
+                    match field {
+                        Some(field) => details.with_field(field),
+                        None => details,
+                    }
+                }
+            });
+        }
+        #[cfg(feature = "cookie")]
+        if let Some(err) = self.downcast_ref::<ParseCookieError>() {
+            let details = ExtractorError::new(ExtractorErrorKind::Cookie);
+            return Some(match err {
+                ParseCookieError::ParseJsonValue(err) => details.with_cause(err),
+                _ => details,
+            });
+        }
+        if let Some(err) = self.downcast_ref::<ReadBodyError>() {
+            return Some(match err {
+                ReadBodyError::Utf8(err) => {
+                    ExtractorError::new(ExtractorErrorKind::ParseBody).with_cause(err)
+                }
+                ReadBodyError::Io(err) => {
+                    ExtractorError::new(ExtractorErrorKind::ReadBody).with_cause(err)
+                }
+                _ => ExtractorError::new(ExtractorErrorKind::ReadBody),
+            });
+        }
+        if let Some(err) = self.downcast_ref::<ParseFormError>() {
+            return Some(match err {
+                ParseFormError::InvalidContentType(_) | ParseFormError::ContentTypeRequired => {
+                    ExtractorError::new(ExtractorErrorKind::ContentType)
+                }
+                ParseFormError::UrlDecode(err) => {
+                    ExtractorError::new(ExtractorErrorKind::ParseBody).with_cause(err)
+                }
+            });
+        }
+        body_error!(
+            ParseJsonError,
+            #[cfg(feature = "xml")]
+            ParseXmlError,
+            #[cfg(feature = "yaml")]
+            ParseYamlError,
+            #[cfg(feature = "cbor")]
+            ParseCborError,
+            #[cfg(feature = "msgpack")]
+            ParseMsgPackError
+        );
+        #[cfg(feature = "multipart")]
+        if let Some(err) = self.downcast_ref::<ParseMultipartError>() {
+            let details = ExtractorError::new(ExtractorErrorKind::ParseBody);
+            return Some(match err {
+                ParseMultipartError::InvalidContentType(_)
+                | ParseMultipartError::ContentTypeRequired => {
+                    ExtractorError::new(ExtractorErrorKind::ContentType)
+                }
+                ParseMultipartError::Multipart(err) => details.with_cause(err),
+                ParseMultipartError::Utf8(err) => details.with_cause(err),
+                ParseMultipartError::Io(err) => details.with_cause(err),
+            });
+        }
+        #[cfg(feature = "validator")]
+        if let Some(err) = self.downcast_ref::<ValidationError>() {
+            return Some(ExtractorError::new(ExtractorErrorKind::Validation).with_cause(&err.0));
+        }
+        None
+    }
+}
+
+/// The field involved in an error returned by a built-in extractor, when the
+/// error type does not hold it, stored in the data of the [`Error`].
+#[derive(Clone)]
+pub(crate) struct ExtractorField(pub(crate) String);
+
+/// The kind of an error returned by a built-in extractor, see
+/// [`Error::extractor_error`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ExtractorErrorKind {
+    /// The path parameters can not be parsed.
+    Path,
+    /// The query string can not be parsed.
+    Query,
+    /// A header is missing or can not be parsed.
+    Header,
+    /// A cookie is missing or can not be parsed.
+    Cookie,
+    /// The `Content-Type` of the body is missing or not supported.
+    ContentType,
+    /// The body can not be read, for example it is too large or the
+    /// connection is closed.
+    ReadBody,
+    /// The body can not be parsed.
+    ParseBody,
+    /// The value does not pass the validation.
+    Validation,
+}
+
+/// The details of an error returned by a built-in extractor, see
+/// [`Error::extractor_error`].
+#[derive(Debug, Copy, Clone)]
+pub struct ExtractorError<'a> {
+    kind: ExtractorErrorKind,
+    field: Option<&'a str>,
+    cause: Option<&'a (dyn StdError + 'static)>,
+}
+
+impl<'a> ExtractorError<'a> {
+    fn new(kind: ExtractorErrorKind) -> Self {
+        Self {
+            kind,
+            field: None,
+            cause: None,
+        }
+    }
+
+    fn with_field(self, field: &'a str) -> Self {
+        Self {
+            field: Some(field),
+            ..self
+        }
+    }
+
+    fn with_cause(self, cause: &'a (dyn StdError + 'static)) -> Self {
+        Self {
+            cause: Some(cause),
+            ..self
+        }
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ExtractorErrorKind {
+        self.kind
+    }
+
+    /// Returns the name of the path parameter, the query field or the header
+    /// that can not be parsed, if known.
+    ///
+    /// The fields of the query string are written as a path, such as
+    /// `filter.tags[1]`.
+    pub fn field(&self) -> Option<&'a str> {
+        self.field
+    }
+
+    /// Returns the underlying error, such as the error of the deserializer,
+    /// if any.
+    pub fn cause(&self) -> Option<&'a (dyn StdError + 'static)> {
+        self.cause
+    }
 }
 
 define_http_error!(
@@ -653,9 +865,67 @@ define_simple_errors!(
     (NotFoundError, NOT_FOUND, "not found");
 
     /// Error occurred in the router.
+    ///
+    /// When it is returned by the router, the response has an `Allow` header
+    /// listing the allowed methods, which can also be read from the data of
+    /// the [`Error`] as [`headers::Allow`].
     (MethodNotAllowedError, METHOD_NOT_ALLOWED, "method not allowed");
+
+    /// Error occurred in the `Timeout` middleware.
+    (TimeoutError, GATEWAY_TIMEOUT, "request timeout");
+
+    /// Error occurred in the `ConcurrencyLimit` middleware.
+    (ConcurrencyLimitError, SERVICE_UNAVAILABLE, "too many concurrent requests");
+
+    /// Error occurred in the `CircuitBreaker` middleware.
+    (CircuitOpenError, SERVICE_UNAVAILABLE, "circuit breaker is open");
+
+    /// Error occurred in the `LoadShed` middleware.
+    (LoadShedError, SERVICE_UNAVAILABLE, "server is overloaded");
+
+    /// Error occurred in the `IpFilter` middleware.
+    (IpFilterError, FORBIDDEN, "ip address not allowed");
 );
 
+/// Creates a [`MethodNotAllowedError`] whose response has an `Allow` header
+/// listing `allowed_methods`, which are also stored in the data of the error.
+pub(crate) fn method_not_allowed(allowed_methods: Allow) -> Error {
+    let mut err = Error {
+        as_response: AsResponse::Fn(
+            |err| {
+                let mut resp = MethodNotAllowedError.as_response();
+                if let Some(allow) = err.data::<Allow>() {
+                    resp.headers_mut().typed_insert(allow.clone());
+                }
+                resp
+            },
+            |_| StatusCode::METHOD_NOT_ALLOWED,
+        ),
+        source: Some(ErrorSource::BoxedError(Box::new(MethodNotAllowedError))),
+        extensions: Extensions::default(),
+        msg: None,
+    };
+    err.set_data(allowed_methods);
+    err
+}
+
+/// A possible error value when a path parameter matched by the router can
+/// not be parsed.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("invalid path parameter \"{name}\": {reason}")]
+pub struct ParsePathParamError {
+    /// The name of the parameter.
+    pub name: String,
+    /// The reason for the error.
+    pub reason: String,
+}
+
+impl ResponseError for ParsePathParamError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 /// A possible error value when reading the body.
 #[derive(Debug, thiserror::Error)]
 pub enum ReadBodyError {
@@ -673,7 +943,20 @@ pub enum ReadBodyError {
 
     /// Io error.
     #[error("io: {0}")]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for ReadBodyError {
+    fn from(err: std::io::Error) -> Self {
+        // the body limit is reported through the io error of the body stream
+        match err
+            .get_ref()
+            .and_then(|err| err.downcast_ref::<ReadBodyError>())
+        {
+            Some(ReadBodyError::PayloadTooLarge) => ReadBodyError::PayloadTooLarge,
+            _ => ReadBodyError::Io(err),
+        }
+    }
 }
 
 impl ResponseError for ReadBodyError {
@@ -729,6 +1012,18 @@ impl ResponseError for GetDataError {
     }
 }
 
+/// A possible error value occurred in the `Config` extractor, when the
+/// configuration type was not added to the request data.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("config type `{0}` not registered")]
+pub struct ConfigNotRegisteredError(pub &'static str);
+
+impl ResponseError for ConfigNotRegisteredError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 /// A possible error value when parsing form.
 #[derive(Debug, thiserror::Error)]
 pub enum ParseFormError {
@@ -843,6 +1138,62 @@ impl ResponseError for ParseYamlError {
     }
 }
 
+/// A possible error value when parsing CBOR.
+#[cfg(feature = "cbor")]
+#[derive(Debug, thiserror::Error)]
+pub enum ParseCborError {
+    /// Invalid content type.
+    #[error("invalid content type `{0}`, expect: `application/cbor`")]
+    InvalidContentType(String),
+
+    /// `Content-Type` header is required.
+    #[error("expect content type `application/cbor`")]
+    ContentTypeRequired,
+
+    /// Url decode error.
+    #[error("parse error: {0}")]
+    Parse(#[from] ciborium::de::Error<std::io::Error>),
+}
+
+#[cfg(feature = "cbor")]
+impl ResponseError for ParseCborError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ParseCborError::InvalidContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ParseCborError::ContentTypeRequired => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ParseCborError::Parse(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// A possible error value when parsing MessagePack.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, thiserror::Error)]
+pub enum ParseMsgPackError {
+    /// Invalid content type.
+    #[error("invalid content type `{0}`, expect: `application/msgpack`")]
+    InvalidContentType(String),
+
+    /// `Content-Type` header is required.
+    #[error("expect content type `application/msgpack`")]
+    ContentTypeRequired,
+
+    /// Url decode error.
+    #[error("parse error: {0}")]
+    Parse(#[from] rmp_serde::decode::Error),
+}
+
+#[cfg(feature = "msgpack")]
+impl ResponseError for ParseMsgPackError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ParseMsgPackError::InvalidContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ParseMsgPackError::ContentTypeRequired => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ParseMsgPackError::Parse(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 /// A possible error value when parsing query.
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
@@ -886,13 +1237,108 @@ impl ResponseError for ParseMultipartError {
         match self {
             ParseMultipartError::InvalidContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             ParseMultipartError::ContentTypeRequired => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-            ParseMultipartError::Multipart(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::Multipart(err) => multipart_error_status(err),
             ParseMultipartError::Utf8(_) => StatusCode::BAD_REQUEST,
-            ParseMultipartError::Io(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::Io(err) => match err
+                .get_ref()
+                .and_then(|err| err.downcast_ref::<multer::Error>())
+            {
+                Some(err) => multipart_error_status(err),
+                None => StatusCode::BAD_REQUEST,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "multipart")]
+fn multipart_error_status(err: &multer::Error) -> StatusCode {
+    match err {
+        multer::Error::FieldSizeExceeded { .. } | multer::Error::StreamSizeExceeded { .. } => {
+            StatusCode::PAYLOAD_TOO_LARGE
+        }
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// A possible error value when validating with [`Valid`](crate::web::Valid).
+///
+/// The response is `422 Unprocessable Entity` with a JSON body that lists
+/// every failed validation, ordered by field:
+///
+/// ```json
+/// {
+///   "errors": [
+///     { "field": "address.city", "code": "length", "message": null },
+///     { "field": "email", "code": "email", "message": "invalid email" },
+///     { "field": "tags[1]", "code": "length", "message": null }
+///   ]
+/// }
+/// ```
+///
+/// `field` is the path of the field, with nested structs separated by `.`
+/// and list items written as `[index]`. `code` is the name of the validator,
+/// or the `code` set in the validation attribute, and `message` is the
+/// `message` set in the validation attribute.
+#[cfg(feature = "validator")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validator")))]
+#[derive(Debug, thiserror::Error)]
+#[error("validation failed: {0}")]
+pub struct ValidationError(pub validator::ValidationErrors);
+
+#[cfg(feature = "validator")]
+impl ValidationError {
+    fn collect(
+        prefix: &str,
+        errors: &validator::ValidationErrors,
+        output: &mut Vec<serde_json::Value>,
+    ) {
+        use validator::ValidationErrorsKind;
+
+        let mut fields = errors.errors().iter().collect::<Vec<_>>();
+        fields.sort_by_key(|(field, _)| *field);
+
+        for (field, kind) in fields {
+            let path = if prefix.is_empty() {
+                field.to_string()
+            } else {
+                format!("{prefix}.{field}")
+            };
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    output.extend(errors.iter().map(|err| {
+                        serde_json::json!({
+                            "field": path,
+                            "code": err.code,
+                            "message": err.message,
+                        })
+                    }));
+                }
+                ValidationErrorsKind::Struct(errors) => Self::collect(&path, errors, output),
+                ValidationErrorsKind::List(items) => {
+                    for (index, errors) in items {
+                        Self::collect(&format!("{path}[{index}]"), errors, output);
+                    }
+                }
+            }
         }
     }
 }
 
+#[cfg(feature = "validator")]
+impl ResponseError for ValidationError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn as_response(&self) -> Response {
+        let mut errors = Vec::new();
+        Self::collect("", &self.0, &mut errors);
+        let mut resp = crate::web::Json(serde_json::json!({ "errors": errors })).into_response();
+        resp.set_status(self.status());
+        resp
+    }
+}
+
 /// A possible error value when parsing typed headers.
 #[derive(Debug, thiserror::Error)]
 pub enum ParseTypedHeaderError {
@@ -920,6 +1366,10 @@ pub enum WebSocketError {
     #[error("invalid protocol")]
     InvalidProtocol,
 
+    /// None of the supported subprotocols are offered by the client.
+    #[error("unsupported subprotocol")]
+    UnsupportedProtocol,
+
     /// Upgrade Error
     #[error(transparent)]
     UpgradeError(#[from] UpgradeError),
@@ -930,6 +1380,7 @@ impl ResponseError for WebSocketError {
     fn status(&self) -> StatusCode {
         match self {
             WebSocketError::InvalidProtocol => StatusCode::BAD_REQUEST,
+            WebSocketError::UnsupportedProtocol => StatusCode::BAD_REQUEST,
             WebSocketError::UpgradeError(err) => err.status(),
         }
     }
@@ -1036,8 +1487,141 @@ impl ResponseError for SizedLimitError {
     }
 }
 
+/// A possible error value occurred in the `RateLimit` middleware.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum RateLimitError {
+    /// Too many requests
+    #[error("too many requests")]
+    TooManyRequests {
+        /// Time until the next request is allowed
+        retry_after: Duration,
+    },
+}
+
+impl ResponseError for RateLimitError {
+    fn status(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn as_response(&self) -> Response {
+        let mut resp = self.to_string().into_response();
+        resp.set_status(self.status());
+        let RateLimitError::TooManyRequests { retry_after } = self;
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        resp.headers_mut().insert(header::RETRY_AFTER, secs.into());
+        resp
+    }
+}
+
+/// A possible error value occurred in the `BasicAuth` middleware.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("unauthorized")]
+pub struct BasicAuthError {
+    /// The realm of the protection space
+    pub realm: String,
+}
+
+impl ResponseError for BasicAuthError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn as_response(&self) -> Response {
+        let mut resp = self.to_string().into_response();
+        resp.set_status(self.status());
+        let realm = self.realm.replace('\\', "\\\\").replace('"', "\\\"");
+        if let Ok(value) = format!("Basic realm=\"{realm}\"").try_into() {
+            resp.headers_mut().insert(header::WWW_AUTHENTICATE, value);
+        }
+        resp
+    }
+}
+
+/// A possible error value occurred in the `Decompression` middleware.
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum DecompressionError {
+    /// Unsupported content encoding
+    #[error("unsupported content encoding `{0}`")]
+    UnsupportedEncoding(String),
+}
+
+#[cfg(feature = "compression")]
+impl ResponseError for DecompressionError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+}
+
+/// A possible error value occurred in the `MethodOverride` middleware.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum MethodOverrideError {
+    /// Invalid override method
+    #[error("invalid override method `{0}`")]
+    InvalidMethod(String),
+}
+
+impl ResponseError for MethodOverrideError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// A possible error value occurred in the `Consumes` middleware.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum ConsumesError {
+    /// Invalid content type.
+    #[error("unsupported content type `{0}`")]
+    InvalidContentType(String),
+
+    /// `Content-Type` header is required.
+    #[error("missing content type")]
+    ContentTypeRequired,
+}
+
+impl ResponseError for ConsumesError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+}
+
+/// Error occurred in the `Produces` middleware, when the `Accept` header of
+/// the request does not accept any of the produced media types.
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+#[error("not acceptable")]
+pub struct NotAcceptableError;
+
+impl ResponseError for NotAcceptableError {
+    fn status(&self) -> StatusCode {
+        StatusCode::NOT_ACCEPTABLE
+    }
+}
+
+/// A possible error value occurred in the `VerifyDigest` middleware.
+#[cfg(feature = "digest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum VerifyDigestError {
+    /// The `Content-MD5` or `Digest` header is invalid.
+    #[error("invalid digest")]
+    InvalidDigest,
+
+    /// The digest of the body does not match the declared one.
+    #[error("digest mismatch")]
+    Mismatch,
+}
+
+#[cfg(feature = "digest")]
+impl ResponseError for VerifyDigestError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 /// A possible error value occurred when adding a route.
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum RouteError {
     /// Invalid path
     #[error("invalid path: {0}")]
@@ -1056,6 +1640,10 @@ pub enum RouteError {
         /// Regex
         regex: String,
     },
+
+    /// Duplicate route name
+    #[error("duplicate route name: {0}")]
+    DuplicateName(String),
 }
 
 impl ResponseError for RouteError {
@@ -1064,6 +1652,35 @@ impl ResponseError for RouteError {
     }
 }
 
+/// A possible error value when generating the URL of a named route.
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum UrlForError {
+    /// No route with the name
+    #[error("route not found: {0}")]
+    RouteNotFound(String),
+
+    /// A parameter of the route is missing
+    #[error("missing parameter `{param}` for route `{name}`")]
+    MissingParam {
+        /// Route name
+        name: String,
+
+        /// Parameter name
+        param: String,
+    },
+
+    /// The path of the route contains a segment without a name, such as an
+    /// anonymous regex
+    #[error("route `{0}` contains an unnamed segment")]
+    UnnamedSegment(String),
+}
+
+impl ResponseError for UrlForError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 /// A possible error value occurred in the `Cors` middleware.
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 pub enum CorsError {
@@ -1086,6 +1703,52 @@ impl ResponseError for CorsError {
     }
 }
 
+/// Error occurred in the `Csrf` middleware in the double-submit cookie mode,
+/// when the token of an unsafe request is missing or does not match the
+/// cookie.
+#[cfg(feature = "csrf")]
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+#[error("missing or mismatched csrf token")]
+pub struct CsrfTokenMismatchError;
+
+#[cfg(feature = "csrf")]
+impl ResponseError for CsrfTokenMismatchError {
+    fn status(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+}
+
+/// A possible error value occurred in the `SignedUrl` extractor.
+#[cfg(feature = "signed-url")]
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum SignedUrlError {
+    /// The `SignedUrlKey` is not in the request data.
+    #[error("missing signed url key")]
+    MissingKey,
+
+    /// The URL has no `signature` or `expires` parameter.
+    #[error("missing url signature")]
+    MissingSignature,
+
+    /// The signature does not match the URL.
+    #[error("invalid url signature")]
+    InvalidSignature,
+
+    /// The URL has expired.
+    #[error("signed url has expired")]
+    Expired,
+}
+
+#[cfg(feature = "signed-url")]
+impl ResponseError for SignedUrlError {
+    fn status(&self) -> StatusCode {
+        match self {
+            SignedUrlError::MissingKey => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
 /// A possible error value occurred when loading i18n resources.
 #[cfg(feature = "i18n")]
 #[derive(Debug, thiserror::Error)]
@@ -1243,4 +1906,102 @@ mod tests {
             "my error message"
         );
     }
+
+    #[tokio::test]
+    async fn extractor_error() {
+        use serde::Deserialize;
+
+        use crate::{
+            Endpoint, FromRequest, Request,
+            http::header,
+            web::{Json, Query, TypedHeader, headers::ContentLength},
+        };
+
+        #[derive(Debug, Deserialize)]
+        struct Filter {
+            #[allow(dead_code)]
+            tags: Vec<u32>,
+        }
+
+        let details = |err: &Error| {
+            let details = err.extractor_error().unwrap();
+            (
+                details.kind(),
+                details.field().map(ToString::to_string),
+                details.cause().map(ToString::to_string),
+            )
+        };
+
+        let err = Request::builder()
+            .uri_str("/?tags=1&tags=a")
+            .finish()
+            .params::<Filter>()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "tags[1]: invalid digit found in string");
+
+        let (req, mut body) = Request::builder()
+            .uri_str("/?tags=1&tags=a")
+            .finish()
+            .split();
+        let err = <Query<Filter>>::from_request(&req, &mut body)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            details(&err),
+            (
+                ExtractorErrorKind::Query,
+                Some("tags[1]".to_string()),
+                Some("tags[1]: invalid digit found in string".to_string())
+            )
+        );
+
+        let ep = crate::endpoint::make(|req| async move {
+            let (req, mut body) = req.split();
+            <TypedHeader<ContentLength>>::from_request(&req, &mut body).await?;
+            <Json<serde_json::Value>>::from_request(&req, &mut body).await?;
+            Ok::<_, Error>(())
+        });
+        let err = ep
+            .call(
+                Request::builder()
+                    .header(header::CONTENT_LENGTH, "a")
+                    .finish(),
+            )
+            .await
+            .unwrap_err();
+        let (kind, field, cause) = details(&err);
+        assert_eq!(kind, ExtractorErrorKind::Header);
+        assert_eq!(field.as_deref(), Some("content-length"));
+        assert!(cause.is_some());
+
+        let err = ep
+            .call(
+                Request::builder()
+                    .header(header::CONTENT_LENGTH, "0")
+                    .finish(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(details(&err), (ExtractorErrorKind::ContentType, None, None));
+
+        let err = ep
+            .call(
+                Request::builder()
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(header::CONTENT_LENGTH, "1")
+                    .body("{"),
+            )
+            .await
+            .unwrap_err();
+        let (kind, field, cause) = details(&err);
+        assert_eq!((kind, field), (ExtractorErrorKind::ParseBody, None));
+        assert!(cause.is_some());
+
+        assert!(Error::from(NotFoundError).extractor_error().is_none());
+        assert!(
+            Error::from_status(StatusCode::BAD_REQUEST)
+                .extractor_error()
+                .is_none()
+        );
+    }
 }
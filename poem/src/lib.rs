@@ -234,6 +234,8 @@
 //! |compression  | Support decompress request body and compress response body |
 //! |cookie            | Support for Cookie             |
 //! |csrf | Support for Cross-Site Request Forgery (CSRF) protection |
+//! |csp | Support for Content Security Policy (CSP) |
+//! |digest | Support for verifying the `Content-MD5` and `Digest` of the request body |
 //! |multipart         | Support for Multipart          |
 //! |native-tls        | Support for HTTP server over TLS with [`native-tls`](https://crates.io/crates/native-tls)  |
 //! |openssl-tls        | Support for HTTP server over TLS with [`openssl-tls`](https://crates.io/crates/openssl)  |
@@ -242,6 +244,7 @@
 //! |redis-session     | Support for RedisSession     |
 //! |rustls            | Support for HTTP server over TLS with [`rustls`](https://crates.io/crates/rustls)  |
 //! |session           | Support for session    |
+//! |signed-url        | Support for signed URLs with an expiration time |
 //! |sse               | Support Server-Sent Events (SSE)       |
 //! |tempfile          | Support for [`tempfile`](https://crates.io/crates/tempfile) |
 //! |test              | Test utilities to test your endpoints. |
@@ -256,6 +259,10 @@
 //! | embed  | Integrate with [`rust-embed`](https://crates.io/crates/rust-embed) crate. |
 //! | xml | Integrate with [`quick-xml`](https://crates.io/crates/quick-xml) crate. |
 //! | yaml | Integrate with [`serde-yaml`](https://crates.io/crates/serde-yaml) crate.                   |
+//! | cbor | Integrate with [`ciborium`](https://crates.io/crates/ciborium) crate. |
+//! | msgpack | Integrate with [`rmp-serde`](https://crates.io/crates/rmp-serde) crate. |
+//! | askama | Integrate with [`askama`](https://crates.io/crates/askama) crate. |
+//! | validator | Integrate with [`validator`](https://crates.io/crates/validator) crate. |
 //! |sonic-rs          | Uses [`sonic-rs`](https://github.com/cloudwego/sonic-rs) instead of `serde_json`. Pls, checkout `sonic-rs` requirements to properly enable `sonic-rs` capabilities |
 
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/poem-web/poem/master/favicon.ico")]
@@ -303,9 +310,9 @@ pub use poem_derive::handler;
 pub use request::{OnUpgrade, Request, RequestBuilder, RequestParts, Upgraded};
 pub use response::{Response, ResponseBuilder, ResponseParts};
 pub use route::{
-    PathPattern, Route, RouteDomain, RouteMethod, RouteScheme, connect, delete, get, head, options,
-    patch, post, put, trace,
+    DomainCaptures, PathPattern, Route, RouteDomain, RouteMethod, RouteScheme, RouteUrls, connect,
+    delete, get, head, options, patch, post, put, trace,
 };
 #[cfg(feature = "server")]
-pub use server::Server;
+pub use server::{Disconnect, Server, ShutdownSignal, ShutdownStatus};
 pub use web::{FromRequest, IntoResponse, RequestBody};
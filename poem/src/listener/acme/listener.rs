@@ -22,13 +22,14 @@ use x509_parser::prelude::{FromDer, X509Certificate};
 
 use crate::{
     listener::{
-        Acceptor, HandshakeStream, Listener,
+        Acceptor, ConnectionExtensions, HandshakeStream, Listener,
         acme::{
             AutoCert, ChallengeType, Http01TokensMap,
             client::AcmeClient,
             jose,
             resolver::{ACME_TLS_ALPN_NAME, ResolveServerCert},
         },
+        rustls::tls_connection_extensions,
     },
     web::{LocalAddr, RemoteAddr},
 };
@@ -220,9 +221,13 @@ impl<T: Acceptor> Acceptor for AutoCertAcceptor<T> {
 
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
         let (stream, local_addr, remote_addr, _) = self.inner.accept().await?;
-        let stream = HandshakeStream::new(self.acceptor.accept(stream));
+        let stream = HandshakeStream::new(self.acceptor.accept(stream), tls_connection_extensions);
         Ok((stream, local_addr, remote_addr, Scheme::HTTPS))
     }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        io.connection_extensions()
+    }
 }
 
 fn gen_acme_cert(domain: &str, acme_hash: &[u8]) -> IoResult<CertifiedKey> {
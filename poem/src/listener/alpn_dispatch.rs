@@ -0,0 +1,278 @@
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
+
+use futures_util::{FutureExt, StreamExt, future::BoxFuture, stream::FuturesUnordered};
+use http::uri::Scheme;
+use tokio::io::{AsyncRead, AsyncWrite, Error as IoError, ErrorKind, Result as IoResult};
+
+use crate::{
+    listener::{Acceptor, AlpnProtocol, BoxIo, ConnectionExtensions, HandshakeStream, Listener},
+    web::{LocalAddr, RemoteAddr},
+};
+
+type Handler = Arc<dyn Fn(BoxIo, LocalAddr, RemoteAddr) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// A wrapper around a TLS listener which dispatches the connections to
+/// handlers by the protocol negotiated with ALPN.
+///
+/// The connections that negotiated a protocol with a registered handler are
+/// passed to it in a new task, the others are served by the server as usual.
+/// The TLS handshakes are completed concurrently in the acceptor, and the
+/// connections that fail the handshake, or do not complete it within the
+/// [handshake timeout](AlpnDispatch::handshake_timeout), are dropped.
+///
+/// The protocols must also be offered by the TLS config, for example with
+/// [`RustlsConfig::alpn_protocols`](crate::listener::RustlsConfig::alpn_protocols).
+///
+/// # Example
+///
+/// ```
+/// use poem::listener::{AlpnDispatch, Listener, RustlsCertificate, RustlsConfig, TcpListener};
+/// use tokio::io::AsyncWriteExt;
+///
+/// # let (cert, key) = (Vec::<u8>::new(), Vec::<u8>::new());
+/// let config = RustlsConfig::new()
+///     .fallback(RustlsCertificate::new().cert(cert).key(key))
+///     .alpn_protocols(["h2", "http/1.1", "my-protocol"]);
+/// let listener = AlpnDispatch::new(TcpListener::bind("0.0.0.0:3000").rustls(config)).protocol(
+///     "my-protocol",
+///     |mut io, _local_addr, _remote_addr| async move {
+///         let _ = io.write_all(b"hello").await;
+///     },
+/// );
+/// ```
+pub struct AlpnDispatch<T> {
+    inner: T,
+    handlers: HashMap<Vec<u8>, Handler>,
+    handshake_timeout: Duration,
+}
+
+impl<T> AlpnDispatch<T> {
+    /// Create an `AlpnDispatch` listener that wraps `inner`.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            handlers: HashMap::new(),
+            handshake_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Sets the time allowed to complete the TLS handshake.
+    ///
+    /// Default is `10s`.
+    #[must_use]
+    pub fn handshake_timeout(self, timeout: Duration) -> Self {
+        Self {
+            handshake_timeout: timeout,
+            ..self
+        }
+    }
+
+    /// Passes the connections that negotiated `protocol` to `handler`.
+    #[must_use]
+    pub fn protocol<F, Fut>(mut self, protocol: impl Into<Vec<u8>>, handler: F) -> Self
+    where
+        F: Fn(BoxIo, LocalAddr, RemoteAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.insert(
+            protocol.into(),
+            Arc::new(move |io, local_addr, remote_addr| {
+                handler(io, local_addr, remote_addr).boxed()
+            }),
+        );
+        self
+    }
+}
+
+impl<T, S> Listener for AlpnDispatch<T>
+where
+    T: Listener,
+    T::Acceptor: Acceptor<Io = HandshakeStream<S>>,
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    type Acceptor = AlpnDispatchAcceptor<T::Acceptor>;
+
+    async fn into_acceptor(self) -> IoResult<Self::Acceptor> {
+        Ok(AlpnDispatchAcceptor {
+            inner: self.inner.into_acceptor().await?,
+            handlers: Arc::new(self.handlers),
+            handshake_timeout: self.handshake_timeout,
+            pending: FuturesUnordered::new(),
+        })
+    }
+}
+
+type PendingConnection<T> =
+    BoxFuture<'static, IoResult<Option<(T, LocalAddr, RemoteAddr, Scheme)>>>;
+
+/// An acceptor that dispatches the connections by the protocol negotiated
+/// with ALPN.
+pub struct AlpnDispatchAcceptor<A: Acceptor> {
+    inner: A,
+    handlers: Arc<HashMap<Vec<u8>, Handler>>,
+    handshake_timeout: Duration,
+    pending: FuturesUnordered<PendingConnection<A::Io>>,
+}
+
+impl<A, S> Acceptor for AlpnDispatchAcceptor<A>
+where
+    A: Acceptor<Io = HandshakeStream<S>>,
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    type Io = A::Io;
+
+    fn local_addr(&self) -> Vec<LocalAddr> {
+        self.inner.local_addr()
+    }
+
+    async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        loop {
+            tokio::select! {
+                res = self.inner.accept() => {
+                    let (mut io, local_addr, remote_addr, scheme) = res?;
+                    let handlers = self.handlers.clone();
+                    let handshake_timeout = self.handshake_timeout;
+                    self.pending.push(
+                        async move {
+                            tokio::time::timeout(handshake_timeout, io.handshake())
+                                .await
+                                .map_err(|_| IoError::new(ErrorKind::TimedOut, "tls handshake timed out"))??;
+                            let connection_extensions = A::connection_extensions(&io);
+                            let handler = connection_extensions
+                                .get()
+                                .and_then(|extensions| extensions.get::<AlpnProtocol>())
+                                .and_then(|protocol| handlers.get(&protocol.0));
+                            match handler {
+                                Some(handler) => {
+                                    let io = BoxIo::new(io, connection_extensions.clone());
+                                    tokio::spawn(handler(io, local_addr, remote_addr));
+                                    Ok(None)
+                                }
+                                None => Ok(Some((io, local_addr, remote_addr, scheme))),
+                            }
+                        }
+                        .boxed(),
+                    );
+                }
+                Some(res) = self.pending.next(), if !self.pending.is_empty() => match res {
+                    Ok(Some(conn)) => return Ok(conn),
+                    Ok(None) => {}
+                    Err(err) => tracing::debug!(error = %err, "tls handshake failed"),
+                },
+            }
+        }
+    }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        A::connection_extensions(io)
+    }
+}
+
+#[cfg(all(test, feature = "rustls"))]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, pki_types::ServerName};
+
+    use super::*;
+    use crate::listener::{RustlsCertificate, RustlsConfig, TcpListener};
+
+    #[tokio::test]
+    async fn alpn_dispatch() {
+        let _ = tokio_rustls::rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let listener = AlpnDispatch::new(
+            TcpListener::bind("127.0.0.1:0").rustls(
+                RustlsConfig::new()
+                    .fallback(
+                        RustlsCertificate::new()
+                            .cert(include_bytes!("certs/cert1.pem").as_ref())
+                            .key(include_bytes!("certs/key1.pem").as_ref()),
+                    )
+                    .alpn_protocols(["h2", "custom"]),
+            ),
+        )
+        .protocol("custom", |mut io, _, _| async move {
+            let n = io.read_i32().await.unwrap();
+            io.write_i32(n * 2).await.unwrap();
+        });
+        let mut acceptor = listener.into_acceptor().await.unwrap();
+        let local_addr = *acceptor
+            .local_addr()
+            .pop()
+            .unwrap()
+            .as_socket_addr()
+            .unwrap();
+
+        let connect = move |protocol: &'static str| async move {
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_pemfile::certs(&mut include_bytes!("certs/chain1.pem").as_ref()) {
+                roots.add(cert.unwrap()).unwrap();
+            }
+            let mut config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            config.alpn_protocols = vec![protocol.into()];
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+            let stream = TcpStream::connect(local_addr).await.unwrap();
+            connector
+                .connect(ServerName::try_from("testserver.com").unwrap(), stream)
+                .await
+                .unwrap()
+        };
+
+        // dispatched to the handler
+        let client = tokio::spawn(async move {
+            let mut stream = connect("custom").await;
+            stream.write_i32(21).await.unwrap();
+            stream.read_i32().await.unwrap()
+        });
+        let accepted =
+            tokio::time::timeout(std::time::Duration::from_millis(200), acceptor.accept());
+        assert!(accepted.await.is_err());
+        assert_eq!(client.await.unwrap(), 42);
+
+        // served by the server
+        tokio::spawn(async move {
+            let mut stream = connect("h2").await;
+            stream.write_i32(10).await.unwrap();
+        });
+        let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
+        assert_eq!(stream.read_i32().await.unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn handshake_timeout() {
+        let listener = AlpnDispatch::new(
+            TcpListener::bind("127.0.0.1:0").rustls(
+                RustlsConfig::new().fallback(
+                    RustlsCertificate::new()
+                        .cert(include_bytes!("certs/cert1.pem").as_ref())
+                        .key(include_bytes!("certs/key1.pem").as_ref()),
+                ),
+            ),
+        )
+        .handshake_timeout(std::time::Duration::from_millis(100));
+        let mut acceptor = listener.into_acceptor().await.unwrap();
+        let local_addr = *acceptor
+            .local_addr()
+            .pop()
+            .unwrap()
+            .as_socket_addr()
+            .unwrap();
+
+        // the connection is closed when the handshake is not completed in time
+        let client = async move {
+            let mut stream = TcpStream::connect(local_addr).await.unwrap();
+            let mut buf = Vec::new();
+            stream.read_to_end(&mut buf).await.unwrap()
+        };
+        tokio::select! {
+            _ = acceptor.accept() => unreachable!(),
+            n = client => assert_eq!(n, 0),
+            _ = tokio::time::sleep(std::time::Duration::from_secs(5)) => panic!("timed out"),
+        }
+    }
+}
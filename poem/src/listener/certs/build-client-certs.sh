@@ -0,0 +1,47 @@
+#!/bin/sh
+
+set -xe
+
+rm -f client_ca.pem client_cert.pem client_key.pem
+mkdir -p client/
+
+openssl req -nodes \
+          -x509 \
+          -days 3650 \
+          -newkey rsa:2048 \
+          -keyout client/ca.key \
+          -out client/ca.cert \
+          -sha256 \
+          -batch \
+          -subj "/CN=ponytown client CA"
+
+openssl req -nodes \
+          -newkey rsa:2048 \
+          -keyout client/client.key \
+          -out client/client.req \
+          -sha256 \
+          -batch \
+          -subj "/CN=ponytown client"
+
+cat > client/ext.cnf <<EXT
+basicConstraints = critical,CA:false
+keyUsage = nonRepudiation, digitalSignature
+extendedKeyUsage = critical, clientAuth
+subjectAltName = DNS:client.testserver.com, email:client@testserver.com
+EXT
+
+openssl x509 -req \
+        -in client/client.req \
+        -out client/client.cert \
+        -CA client/ca.cert \
+        -CAkey client/ca.key \
+        -sha256 \
+        -days 3650 \
+        -set_serial 789 \
+        -extfile client/ext.cnf
+
+cp client/ca.cert client_ca.pem
+cp client/client.cert client_cert.pem
+cp client/client.key client_key.pem
+
+rm -rf client
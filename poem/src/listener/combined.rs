@@ -7,7 +7,7 @@ use http::uri::Scheme;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, Result as IoResult};
 
 use crate::{
-    listener::{Acceptor, Listener},
+    listener::{Acceptor, ConnectionExtensions, Listener},
     web::{LocalAddr, RemoteAddr},
 };
 
@@ -58,6 +58,13 @@ impl<A: Acceptor, B: Acceptor> Acceptor for Combined<A, B> {
             }
         }
     }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        match io {
+            CombinedStream::A(io) => A::connection_extensions(io),
+            CombinedStream::B(io) => B::connection_extensions(io),
+        }
+    }
 }
 
 /// A IO stream for CombinedAcceptor.
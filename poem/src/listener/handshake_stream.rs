@@ -1,13 +1,16 @@
 use std::{
-    future::Future,
+    future::{Future, poll_fn},
     io::{Error, ErrorKind},
     pin::Pin,
     task::{Context, Poll},
 };
 
 use futures_util::{FutureExt, future::BoxFuture};
+use http::Extensions;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, Result};
 
+use crate::listener::ConnectionExtensions;
+
 enum State<S> {
     Handshaking(BoxFuture<'static, Result<S>>),
     Ready(S),
@@ -17,17 +20,58 @@ enum State<S> {
 /// A handshake stream for tls.
 pub struct HandshakeStream<S> {
     state: State<S>,
+    connection_extensions: ConnectionExtensions,
 }
 
 impl<S> HandshakeStream<S> {
-    pub(crate) fn new<F>(handshake: F) -> Self
+    /// Create a handshake stream that sets the connection extensions returned
+    /// by `f` once the handshake is completed.
+    pub(crate) fn new<F, G>(handshake: F, f: G) -> Self
     where
         F: Future<Output = Result<S>> + Send + 'static,
+        G: FnOnce(&S) -> Extensions + Send + 'static,
+        S: 'static,
     {
+        let connection_extensions = ConnectionExtensions::default();
+        let handshake = {
+            let connection_extensions = connection_extensions.clone();
+            async move {
+                let stream = handshake.await?;
+                connection_extensions.set(f(&stream));
+                Ok(stream)
+            }
+        };
         Self {
             state: State::Handshaking(handshake.boxed()),
+            connection_extensions,
         }
     }
+
+    #[inline]
+    pub(crate) fn connection_extensions(&self) -> ConnectionExtensions {
+        self.connection_extensions.clone()
+    }
+
+    /// Completes the handshake, which is otherwise done on the first read or
+    /// write.
+    pub async fn handshake(&mut self) -> Result<()> {
+        poll_fn(|cx| match &mut self.state {
+            State::Handshaking(fut) => match fut.poll_unpin(cx) {
+                Poll::Ready(Ok(s)) => {
+                    self.state = State::Ready(s);
+                    Poll::Ready(Ok(()))
+                }
+                Poll::Ready(Err(err)) => {
+                    self.state = State::Error;
+                    Poll::Ready(Err(err))
+                }
+                Poll::Pending => Poll::Pending,
+            },
+            State::Ready(_) => Poll::Ready(Ok(())),
+            State::Error => Poll::Ready(Err(invalid_data_error())),
+        })
+        .await
+    }
 }
 
 impl<S> AsyncRead for HandshakeStream<S>
@@ -0,0 +1,183 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::uri::Scheme;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf, Result as IoResult},
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+use crate::{
+    listener::{Acceptor, ConnectionExtensions, Listener},
+    web::{LocalAddr, RemoteAddr},
+};
+
+/// A wrapper around an underlying listener which limits the number of
+/// simultaneously open connections.
+///
+/// When the limit is reached, no more connections are accepted until one of
+/// the open connections is closed, so new connections wait in the accept
+/// backlog of the operating system instead of being accepted and closed
+/// immediately.
+///
+/// Unlike the [`ConcurrencyLimit`](crate::middleware::ConcurrencyLimit)
+/// middleware, which limits the number of requests being processed, this
+/// limits the number of sockets, including idle keep-alive connections.
+///
+/// # Example
+///
+/// ```
+/// use poem::listener::{LimitConnections, Listener, TcpListener};
+///
+/// let listener = LimitConnections::new(TcpListener::bind("0.0.0.0:3000"), 1024);
+/// ```
+pub struct LimitConnections<T> {
+    inner: T,
+    max: usize,
+}
+
+impl<T> LimitConnections<T> {
+    /// Create a `LimitConnections` listener that allows at most `max` open
+    /// connections accepted from `inner`.
+    pub fn new(inner: T, max: usize) -> Self {
+        Self { inner, max }
+    }
+}
+
+impl<T: Listener> Listener for LimitConnections<T> {
+    type Acceptor = LimitConnectionsAcceptor<T::Acceptor>;
+
+    async fn into_acceptor(self) -> IoResult<Self::Acceptor> {
+        Ok(LimitConnectionsAcceptor {
+            inner: self.inner.into_acceptor().await?,
+            semaphore: Arc::new(Semaphore::new(self.max)),
+        })
+    }
+}
+
+/// An acceptor that limits the number of simultaneously open connections.
+pub struct LimitConnectionsAcceptor<A> {
+    inner: A,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<A> LimitConnectionsAcceptor<A> {
+    /// Returns the number of connections that can still be accepted before
+    /// the limit is reached.
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+}
+
+impl<A: Acceptor> Acceptor for LimitConnectionsAcceptor<A> {
+    type Io = LimitConnectionsStream<A::Io>;
+
+    fn local_addr(&self) -> Vec<LocalAddr> {
+        self.inner.local_addr()
+    }
+
+    async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        // the permit is acquired before accepting, so that the pending
+        // connections stay in the backlog while the limit is reached
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed");
+        let (io, local_addr, remote_addr, scheme) = self.inner.accept().await?;
+        Ok((
+            LimitConnectionsStream {
+                inner: io,
+                _permit: permit,
+            },
+            local_addr,
+            remote_addr,
+            scheme,
+        ))
+    }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        A::connection_extensions(&io.inner)
+    }
+}
+
+/// A stream that releases its slot in [`LimitConnections`] when dropped.
+pub struct LimitConnectionsStream<T> {
+    inner: T,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for LimitConnectionsStream<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for LimitConnectionsStream<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use super::*;
+    use crate::listener::TcpListener;
+
+    #[tokio::test]
+    async fn limit_connections() {
+        let mut acceptor = LimitConnections::new(TcpListener::bind("127.0.0.1:0"), 1)
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+
+        let mut a = TcpStream::connect(addr).await.unwrap();
+        let mut b = TcpStream::connect(addr).await.unwrap();
+
+        let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
+        assert_eq!(acceptor.available(), 0);
+        a.write_u8(1).await.unwrap();
+        assert_eq!(stream.read_u8().await.unwrap(), 1);
+
+        // the second connection waits until the first one is closed
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), acceptor.accept())
+                .await
+                .is_err()
+        );
+
+        drop(stream);
+        assert_eq!(acceptor.available(), 1);
+        let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
+        b.write_u8(2).await.unwrap();
+        assert_eq!(stream.read_u8().await.unwrap(), 2);
+    }
+}
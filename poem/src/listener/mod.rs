@@ -3,13 +3,17 @@
 #[cfg(feature = "acme-base")]
 #[cfg_attr(docsrs, doc(cfg(feature = "acme-base")))]
 pub mod acme;
+#[cfg(any(feature = "native-tls", feature = "rustls", feature = "openssl-tls"))]
+mod alpn_dispatch;
 mod combined;
 #[cfg(any(feature = "native-tls", feature = "rustls", feature = "openssl-tls"))]
 mod handshake_stream;
+mod limit_connections;
 #[cfg(feature = "native-tls")]
 mod native_tls;
 #[cfg(feature = "openssl-tls")]
 mod openssl_tls;
+mod proxy_protocol;
 #[cfg(feature = "rustls")]
 mod rustls;
 mod tcp;
@@ -22,45 +26,79 @@ use std::{
     convert::Infallible,
     io::Error,
     pin::Pin,
+    sync::{Arc, OnceLock},
     task::{Context, Poll},
 };
 
 use futures_util::{Future, FutureExt, TryFutureExt, future::BoxFuture};
-use http::uri::Scheme;
+use http::{Extensions, uri::Scheme};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf, Result as IoResult};
 
 #[cfg(feature = "acme-base")]
 use self::acme::{AutoCert, AutoCertListener};
 #[cfg(any(feature = "native-tls", feature = "rustls", feature = "openssl-tls"))]
+pub use self::alpn_dispatch::{AlpnDispatch, AlpnDispatchAcceptor};
+#[cfg(any(feature = "native-tls", feature = "rustls", feature = "openssl-tls"))]
 pub use self::handshake_stream::HandshakeStream;
 #[cfg(feature = "native-tls")]
 pub use self::native_tls::{NativeTlsAcceptor, NativeTlsConfig, NativeTlsListener};
 #[cfg(feature = "openssl-tls")]
 pub use self::openssl_tls::{OpensslTlsAcceptor, OpensslTlsConfig, OpensslTlsListener};
 #[cfg(feature = "rustls")]
-pub use self::rustls::{RustlsAcceptor, RustlsCertificate, RustlsConfig, RustlsListener};
+pub use self::rustls::{
+    ClientCert, RustlsAcceptor, RustlsCertificate, RustlsConfig, RustlsListener,
+};
 #[cfg(any(feature = "rustls", feature = "native-tls", feature = "openssl-tls"))]
-pub use self::tls::IntoTlsConfigStream;
+pub use self::tls::{AlpnProtocol, IntoTlsConfigStream};
 #[cfg(unix)]
 pub use self::unix::{UnixAcceptor, UnixListener};
 pub use self::{
     combined::{Combined, CombinedStream},
-    tcp::{TcpAcceptor, TcpListener},
+    limit_connections::{LimitConnections, LimitConnectionsAcceptor, LimitConnectionsStream},
+    proxy_protocol::{ProxyProtocol, ProxyProtocolAcceptor, ProxyProtocolStream},
+    tcp::{TcpAcceptor, TcpKeepalive, TcpListener},
 };
 use crate::web::{LocalAddr, RemoteAddr};
 
+/// The values of an accepted connection, such as the TLS client certificate,
+/// which are inserted into the extensions of each request of the connection.
+///
+/// The values are set once the connection is established, e.g. after the TLS
+/// handshake, so they are available to all requests.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionExtensions(Arc<OnceLock<Extensions>>);
+
+impl ConnectionExtensions {
+    /// Sets the values of the connection, does nothing if they are already
+    /// set.
+    pub fn set(&self, extensions: Extensions) {
+        let _ = self.0.set(extensions);
+    }
+
+    /// Returns the values of the connection, `None` if they are not set yet.
+    #[inline]
+    pub fn get(&self) -> Option<&Extensions> {
+        self.0.get()
+    }
+}
+
 /// An IO type for BoxAcceptor.
 pub struct BoxIo {
     reader: Box<dyn AsyncRead + Send + Unpin + 'static>,
     writer: Box<dyn AsyncWrite + Send + Unpin + 'static>,
+    connection_extensions: ConnectionExtensions,
 }
 
 impl BoxIo {
-    fn new(io: impl AsyncRead + AsyncWrite + Send + Unpin + 'static) -> Self {
+    fn new(
+        io: impl AsyncRead + AsyncWrite + Send + Unpin + 'static,
+        connection_extensions: ConnectionExtensions,
+    ) -> Self {
         let (reader, writer) = tokio::io::split(io);
         Self {
             reader: Box::new(reader),
             writer: Box::new(writer),
+            connection_extensions,
         }
     }
 }
@@ -123,7 +161,8 @@ impl<A: Acceptor> DynAcceptor for ToDynAcceptor<A> {
     fn accept(&mut self) -> BoxFuture<IoResult<(BoxIo, LocalAddr, RemoteAddr, Scheme)>> {
         async move {
             let (io, local_addr, remote_addr, scheme) = self.0.accept().await?;
-            let io = BoxIo::new(io);
+            let connection_extensions = A::connection_extensions(&io);
+            let io = BoxIo::new(io, connection_extensions);
             Ok((io, local_addr, remote_addr, scheme))
         }
         .boxed()
@@ -142,6 +181,11 @@ impl Acceptor for dyn DynAcceptor + '_ {
     async fn accept(&mut self) -> IoResult<(BoxIo, LocalAddr, RemoteAddr, Scheme)> {
         DynAcceptor::accept(self).await
     }
+
+    #[inline]
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        io.connection_extensions.clone()
+    }
 }
 
 /// Represents a acceptor type.
@@ -160,6 +204,15 @@ pub trait Acceptor: Send {
     fn accept(
         &mut self,
     ) -> impl Future<Output = IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)>> + Send;
+
+    /// Returns the values of the accepted connection `io` that are inserted
+    /// into the extensions of its requests.
+    ///
+    /// The default implementation returns an empty [`ConnectionExtensions`].
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        let _ = io;
+        ConnectionExtensions::default()
+    }
 }
 
 /// An owned dynamically typed Acceptor for use in cases where you can’t
@@ -249,6 +302,35 @@ pub trait Listener: Send {
     }
 
     /// Consume this listener and return a new TLS listener with [`rustls`](https://crates.io/crates/rustls).
+    ///
+    /// `config_stream` can be a single [`RustlsConfig`] or a stream of them.
+    /// Each config produced by the stream replaces the previous one for new
+    /// handshakes, while existing connections are not interrupted, so
+    /// certificates can be rotated without restarting the server. A config
+    /// that fails to load is logged and ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use poem::listener::{Listener, RustlsCertificate, RustlsConfig, TcpListener};
+    ///
+    /// fn load_config() -> RustlsConfig {
+    ///     RustlsConfig::new().fallback(
+    ///         RustlsCertificate::new()
+    ///             .cert(std::fs::read("cert.pem").unwrap_or_default())
+    ///             .key(std::fs::read("key.pem").unwrap_or_default()),
+    ///     )
+    /// }
+    ///
+    /// let listener = TcpListener::bind("0.0.0.0:443").rustls(async_stream::stream! {
+    ///     loop {
+    ///         yield load_config();
+    ///         tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+    ///     }
+    /// });
+    /// ```
     #[cfg(feature = "rustls")]
     #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
     #[must_use]
@@ -353,6 +435,10 @@ impl<T: Acceptor + ?Sized> Acceptor for Box<T> {
     async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
         self.as_mut().accept().await
     }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        T::connection_extensions(io)
+    }
 }
 
 impl Acceptor for Infallible {
@@ -2,12 +2,12 @@ use futures_util::{
     Stream, StreamExt, TryFutureExt,
     stream::{BoxStream, Chain, Pending},
 };
-use http::uri::Scheme;
+use http::{Extensions, uri::Scheme};
 use tokio::io::{Error as IoError, ErrorKind, Result as IoResult};
 use tokio_native_tls::{TlsStream, native_tls::Identity};
 
 use crate::{
-    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener},
+    listener::{Acceptor, ConnectionExtensions, HandshakeStream, IntoTlsConfigStream, Listener},
     web::{LocalAddr, RemoteAddr},
 };
 
@@ -173,12 +173,16 @@ where
                         None => return Err(IoError::new(ErrorKind::Other, "no valid tls config.")),
                     };
                     let fut = async move { tls_acceptor.accept(stream).map_err(|err| IoError::new(ErrorKind::Other, err.to_string())).await };
-                    let stream = HandshakeStream::new(fut);
+                    let stream = HandshakeStream::new(fut, |_| Extensions::new());
                     return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
                 }
             }
         }
     }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        io.connection_extensions()
+    }
 }
 
 #[cfg(test)]
@@ -7,7 +7,7 @@ use futures_util::{
     Stream, StreamExt,
     stream::{BoxStream, Chain, Pending},
 };
-use http::uri::Scheme;
+use http::{Extensions, uri::Scheme};
 use openssl::{
     pkey::PKey,
     ssl::{Ssl, SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod, SslRef},
@@ -18,7 +18,10 @@ use tokio_openssl::SslStream;
 use tokio_util::either::Either;
 
 use crate::{
-    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener},
+    listener::{
+        Acceptor, AlpnProtocol, ConnectionExtensions, HandshakeStream, IntoTlsConfigStream,
+        Listener,
+    },
     web::{LocalAddr, RemoteAddr},
 };
 
@@ -226,12 +229,22 @@ where
                         Pin::new(&mut tls_stream).accept().await.map_err(|err|
                             IoError::new(ErrorKind::Other, err.to_string()))?;
                         Ok(tls_stream) };
-                    let stream = HandshakeStream::new(fut);
+                    let stream = HandshakeStream::new(fut, |stream: &SslStream<T::Io>| {
+                        let mut extensions = Extensions::new();
+                        if let Some(protocol) = stream.ssl().selected_alpn_protocol() {
+                            extensions.insert(AlpnProtocol(protocol.to_vec()));
+                        }
+                        extensions
+                    });
                     return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
                 }
             }
         }
     }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        io.connection_extensions()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,483 @@
+use std::{
+    io::{Error as IoError, ErrorKind},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::{Buf, BytesMut};
+use futures_util::{FutureExt, StreamExt, future::BoxFuture, stream::FuturesUnordered};
+use http::uri::Scheme;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf, Result as IoResult};
+
+use crate::{
+    listener::{Acceptor, ConnectionExtensions, Listener},
+    web::{LocalAddr, RemoteAddr},
+};
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// A wrapper around an underlying listener which reads the
+/// [PROXY protocol](https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt)
+/// header sent by a load balancer, such as HAProxy or AWS NLB.
+///
+/// Both version 1 (text) and version 2 (binary) headers are supported. The
+/// source address in the header is used as the [`RemoteAddr`] of the
+/// connection, so handlers and middlewares see the address of the client
+/// instead of the load balancer.
+///
+/// By default, connections that do not start with a PROXY protocol header
+/// are rejected. Any client that can reach the listener directly can send a
+/// header with an arbitrary source address, so it should only be reachable
+/// by the load balancer.
+///
+/// The PROXY protocol header is sent before the TLS handshake, so this must
+/// wrap the TCP listener, not the TLS listener.
+///
+/// # Example
+///
+/// ```
+/// use poem::listener::{Listener, ProxyProtocol, TcpListener};
+///
+/// let listener = ProxyProtocol::new(TcpListener::bind("0.0.0.0:3000"));
+/// ```
+pub struct ProxyProtocol<T> {
+    inner: T,
+    options: Options,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Options {
+    allow_direct: bool,
+    header_timeout: Duration,
+}
+
+impl<T> ProxyProtocol<T> {
+    /// Create a `ProxyProtocol` listener that wraps `inner`.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            options: Options {
+                allow_direct: false,
+                header_timeout: Duration::from_secs(5),
+            },
+        }
+    }
+
+    /// Accepts connections without a PROXY protocol header, and uses the
+    /// address of the peer as the remote address of them.
+    ///
+    /// Connections with a malformed header are still rejected.
+    #[must_use]
+    pub fn allow_direct(self) -> Self {
+        Self {
+            options: Options {
+                allow_direct: true,
+                ..self.options
+            },
+            ..self
+        }
+    }
+
+    /// Sets the time to wait for the PROXY protocol header, after which the
+    /// connection is rejected.
+    ///
+    /// Default is 5 seconds.
+    #[must_use]
+    pub fn header_timeout(self, timeout: Duration) -> Self {
+        Self {
+            options: Options {
+                header_timeout: timeout,
+                ..self.options
+            },
+            ..self
+        }
+    }
+}
+
+impl<T: Listener> Listener for ProxyProtocol<T> {
+    type Acceptor = ProxyProtocolAcceptor<T::Acceptor>;
+
+    async fn into_acceptor(self) -> IoResult<Self::Acceptor> {
+        Ok(ProxyProtocolAcceptor {
+            inner: self.inner.into_acceptor().await?,
+            options: self.options,
+            pending: FuturesUnordered::new(),
+        })
+    }
+}
+
+type PendingConnection<T> =
+    BoxFuture<'static, IoResult<(ProxyProtocolStream<T>, LocalAddr, RemoteAddr, Scheme)>>;
+
+/// An acceptor that reads the PROXY protocol header of the accepted
+/// connections.
+pub struct ProxyProtocolAcceptor<A: Acceptor> {
+    inner: A,
+    options: Options,
+    pending: FuturesUnordered<PendingConnection<A::Io>>,
+}
+
+impl<A: Acceptor> Acceptor for ProxyProtocolAcceptor<A> {
+    type Io = ProxyProtocolStream<A::Io>;
+
+    fn local_addr(&self) -> Vec<LocalAddr> {
+        self.inner.local_addr()
+    }
+
+    async fn accept(&mut self) -> IoResult<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        // the headers are read concurrently, so that a slow client does not
+        // block accepting other connections
+        loop {
+            tokio::select! {
+                res = self.inner.accept() => {
+                    let (io, local_addr, remote_addr, scheme) = res?;
+                    let options = self.options;
+                    self.pending.push(
+                        async move {
+                            let (io, source) = tokio::time::timeout(
+                                options.header_timeout,
+                                read_header(io, options.allow_direct),
+                            )
+                            .await
+                            .map_err(|_| {
+                                IoError::new(ErrorKind::TimedOut, "proxy protocol header timeout")
+                            })??;
+                            let remote_addr = source
+                                .map(|addr| RemoteAddr(addr.into()))
+                                .unwrap_or(remote_addr);
+                            Ok((io, local_addr, remote_addr, scheme))
+                        }
+                        .boxed(),
+                    );
+                }
+                Some(res) = self.pending.next(), if !self.pending.is_empty() => match res {
+                    Ok(conn) => return Ok(conn),
+                    Err(err) => tracing::debug!(
+                        error = %err,
+                        "rejected connection without a valid proxy protocol header",
+                    ),
+                },
+            }
+        }
+    }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        A::connection_extensions(&io.io)
+    }
+}
+
+async fn read_header<T: AsyncRead + Unpin>(
+    mut io: T,
+    allow_direct: bool,
+) -> IoResult<(ProxyProtocolStream<T>, Option<SocketAddr>)> {
+    let mut buf = BytesMut::with_capacity(256);
+    loop {
+        match parse_header(&buf) {
+            Header::Incomplete => {}
+            Header::NotProxy if allow_direct => {
+                return Ok((ProxyProtocolStream { prefix: buf, io }, None));
+            }
+            Header::NotProxy => {
+                return Err(IoError::new(
+                    ErrorKind::InvalidData,
+                    "missing proxy protocol header",
+                ));
+            }
+            Header::Invalid => {
+                return Err(IoError::new(
+                    ErrorKind::InvalidData,
+                    "invalid proxy protocol header",
+                ));
+            }
+            Header::Proxy { source, len } => {
+                buf.advance(len);
+                return Ok((ProxyProtocolStream { prefix: buf, io }, source));
+            }
+        }
+
+        if io.read_buf(&mut buf).await? == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Header {
+    Incomplete,
+    NotProxy,
+    Invalid,
+    Proxy {
+        source: Option<SocketAddr>,
+        len: usize,
+    },
+}
+
+fn parse_header(buf: &[u8]) -> Header {
+    if buf.starts_with(V2_SIGNATURE) {
+        parse_v2(buf)
+    } else if buf.starts_with(V1_PREFIX) {
+        parse_v1(buf)
+    } else if V2_SIGNATURE.starts_with(buf) || V1_PREFIX.starts_with(buf) {
+        Header::Incomplete
+    } else {
+        Header::NotProxy
+    }
+}
+
+fn parse_v1(buf: &[u8]) -> Header {
+    let Some(end) = buf
+        .windows(2)
+        .take(V1_MAX_LENGTH - 1)
+        .position(|w| w == b"\r\n")
+    else {
+        return if buf.len() >= V1_MAX_LENGTH {
+            Header::Invalid
+        } else {
+            Header::Incomplete
+        };
+    };
+    let len = end + 2;
+    let Ok(line) = std::str::from_utf8(&buf[V1_PREFIX.len()..end]) else {
+        return Header::Invalid;
+    };
+
+    let parts = line.split(' ').collect::<Vec<_>>();
+    let source = match parts.as_slice() {
+        ["UNKNOWN", ..] => None,
+        [proto @ ("TCP4" | "TCP6"), src, _dst, src_port, _dst_port] => {
+            let (Ok(ip), Ok(port)) = (src.parse::<IpAddr>(), src_port.parse::<u16>()) else {
+                return Header::Invalid;
+            };
+            if ip.is_ipv4() != (*proto == "TCP4") {
+                return Header::Invalid;
+            }
+            Some(SocketAddr::new(ip, port))
+        }
+        _ => return Header::Invalid,
+    };
+    Header::Proxy { source, len }
+}
+
+fn parse_v2(buf: &[u8]) -> Header {
+    if buf.len() < 16 {
+        return Header::Incomplete;
+    }
+    let len = 16 + u16::from_be_bytes([buf[14], buf[15]]) as usize;
+    if buf.len() < len {
+        return Header::Incomplete;
+    }
+
+    let version_command = buf[12];
+    if version_command >> 4 != 2 {
+        return Header::Invalid;
+    }
+    let addrs = &buf[16..len];
+    let source = match (version_command & 0x0f, buf[13] >> 4) {
+        // LOCAL command, the connection is established by the proxy itself
+        (0, _) => None,
+        // PROXY command over IPv4
+        (1, 1) if addrs.len() >= 12 => Some(SocketAddr::new(
+            Ipv4Addr::from(<[u8; 4]>::try_from(&addrs[..4]).unwrap()).into(),
+            u16::from_be_bytes([addrs[8], addrs[9]]),
+        )),
+        // PROXY command over IPv6
+        (1, 2) if addrs.len() >= 36 => Some(SocketAddr::new(
+            Ipv6Addr::from(<[u8; 16]>::try_from(&addrs[..16]).unwrap()).into(),
+            u16::from_be_bytes([addrs[32], addrs[33]]),
+        )),
+        // PROXY command with an unspecified or unix address
+        (1, 0 | 3) => None,
+        _ => return Header::Invalid,
+    };
+    Header::Proxy { source, len }
+}
+
+/// An IO stream for the [`ProxyProtocolAcceptor`].
+pub struct ProxyProtocolStream<T> {
+    prefix: BytesMut,
+    io: T,
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for ProxyProtocolStream<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = &mut *self;
+        if !this.prefix.is_empty() {
+            let len = this.prefix.len().min(buf.remaining());
+            buf.put_slice(&this.prefix[..len]);
+            this.prefix.advance(len);
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.io).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for ProxyProtocolStream<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{io::AsyncWriteExt, net::TcpStream};
+
+    use super::*;
+    use crate::listener::TcpListener;
+
+    #[test]
+    fn v1() {
+        let header = b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nGET /";
+        assert_eq!(
+            parse_header(header),
+            Header::Proxy {
+                source: Some("192.0.2.1:56324".parse().unwrap()),
+                len: header.len() - 5,
+            }
+        );
+        assert_eq!(
+            parse_header(b"PROXY TCP6 2001:db8::1 2001:db8::2 56324 443\r\n"),
+            Header::Proxy {
+                source: Some("[2001:db8::1]:56324".parse().unwrap()),
+                len: 46,
+            }
+        );
+        assert_eq!(
+            parse_header(b"PROXY UNKNOWN\r\n"),
+            Header::Proxy {
+                source: None,
+                len: 15
+            }
+        );
+        assert_eq!(parse_header(b"PRO"), Header::Incomplete);
+        assert_eq!(parse_header(b"PROXY TCP4 192.0.2.1"), Header::Incomplete);
+        assert_eq!(
+            parse_header(b"PROXY TCP6 192.0.2.1 198.51.100.1 56324 443\r\n"),
+            Header::Invalid
+        );
+        assert_eq!(parse_header(b"PROXY TCP4 a b c d\r\n"), Header::Invalid);
+        assert_eq!(parse_header(&[b'P'; 200]), Header::NotProxy);
+        let mut long = b"PROXY ".to_vec();
+        long.resize(200, b'a');
+        assert_eq!(parse_header(&long), Header::Invalid);
+        assert_eq!(parse_header(b"GET / HTTP/1.1\r\n"), Header::NotProxy);
+    }
+
+    #[test]
+    fn v2() {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x21, 0x11, 0, 12]);
+        header.extend_from_slice(&[192, 0, 2, 1, 198, 51, 100, 1]);
+        header.extend_from_slice(&56324u16.to_be_bytes());
+        header.extend_from_slice(&443u16.to_be_bytes());
+        assert_eq!(
+            parse_header(&header),
+            Header::Proxy {
+                source: Some("192.0.2.1:56324".parse().unwrap()),
+                len: 28,
+            }
+        );
+        assert_eq!(parse_header(&header[..20]), Header::Incomplete);
+
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x21, 0x21, 0, 36]);
+        header.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        header.extend_from_slice(&"2001:db8::2".parse::<Ipv6Addr>().unwrap().octets());
+        header.extend_from_slice(&56324u16.to_be_bytes());
+        header.extend_from_slice(&443u16.to_be_bytes());
+        assert_eq!(
+            parse_header(&header),
+            Header::Proxy {
+                source: Some("[2001:db8::1]:56324".parse().unwrap()),
+                len: 52,
+            }
+        );
+
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(
+            parse_header(&header),
+            Header::Proxy {
+                source: None,
+                len: 16
+            }
+        );
+
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x11, 0x11, 0, 0]);
+        assert_eq!(parse_header(&header), Header::Invalid);
+    }
+
+    #[tokio::test]
+    async fn proxy_protocol_listener() {
+        let mut acceptor = ProxyProtocol::new(TcpListener::bind("127.0.0.1:0"))
+            .into_acceptor()
+            .await
+            .unwrap();
+        let local_addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+
+        // a direct connection is rejected, and does not block the next one
+        let mut direct = TcpStream::connect(local_addr).await.unwrap();
+        direct.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+        let mut stream = TcpStream::connect(local_addr).await.unwrap();
+        stream
+            .write_all(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nhello")
+            .await
+            .unwrap();
+
+        let (mut io, _, remote_addr, _) = acceptor.accept().await.unwrap();
+        assert_eq!(
+            remote_addr.as_socket_addr(),
+            Some(&"192.0.2.1:56324".parse().unwrap())
+        );
+        let mut buf = [0; 5];
+        io.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        tokio::spawn(async move {
+            let _ = acceptor.accept().await;
+        });
+        assert_eq!(direct.read(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn allow_direct() {
+        let mut acceptor = ProxyProtocol::new(TcpListener::bind("127.0.0.1:0"))
+            .allow_direct()
+            .into_acceptor()
+            .await
+            .unwrap();
+        let local_addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+
+        let mut stream = TcpStream::connect(local_addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+
+        let (mut io, _, remote_addr, _) = acceptor.accept().await.unwrap();
+        assert_eq!(
+            remote_addr.as_socket_addr(),
+            Some(&stream.local_addr().unwrap())
+        );
+        let mut buf = [0; 16];
+        io.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"GET / HTTP/1.1\r\n");
+    }
+}
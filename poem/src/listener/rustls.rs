@@ -1,10 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, net::IpAddr, sync::Arc};
 
 use futures_util::{
     Stream, StreamExt,
     stream::{BoxStream, Chain, Pending},
 };
-use http::uri::Scheme;
+use http::{Extensions, uri::Scheme};
 use rustls_pemfile::Item;
 use tokio::io::{Error as IoError, ErrorKind, Result as IoResult};
 use tokio_rustls::{
@@ -16,9 +16,13 @@ use tokio_rustls::{
     },
     server::TlsStream,
 };
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
 
 use crate::{
-    listener::{Acceptor, HandshakeStream, IntoTlsConfigStream, Listener},
+    listener::{
+        Acceptor, AlpnProtocol, ConnectionExtensions, HandshakeStream, IntoTlsConfigStream,
+        Listener,
+    },
     web::{LocalAddr, RemoteAddr},
 };
 
@@ -109,6 +113,7 @@ pub struct RustlsConfig {
     certificates: HashMap<String, RustlsCertificate>,
     fallback: Option<RustlsCertificate>,
     client_auth: TlsClientAuth,
+    alpn_protocols: Vec<Vec<u8>>,
 }
 
 impl Default for RustlsConfig {
@@ -124,6 +129,7 @@ impl RustlsConfig {
             certificates: HashMap::new(),
             fallback: Default::default(),
             client_auth: TlsClientAuth::Off,
+            alpn_protocols: vec!["h2".into(), "http/1.1".into()],
         }
     }
 
@@ -215,6 +221,21 @@ impl RustlsConfig {
         self
     }
 
+    /// Sets the protocols offered with ALPN, in the order of preference.
+    ///
+    /// Default is `h2` and `http/1.1`. The connections that negotiated other
+    /// protocols can be handled with
+    /// [`AlpnDispatch`](crate::listener::AlpnDispatch).
+    #[must_use]
+    pub fn alpn_protocols<I, T>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Vec<u8>>,
+    {
+        self.alpn_protocols = protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
     fn create_server_config(&self) -> IoResult<ServerConfig> {
         let fallback = self
             .fallback
@@ -255,7 +276,7 @@ impl RustlsConfig {
             certificate_keys,
             fallback,
         }));
-        server_config.alpn_protocols = vec!["h2".into(), "http/1.1".into()];
+        server_config.alpn_protocols = self.alpn_protocols.clone();
 
         Ok(server_config)
     }
@@ -391,12 +412,99 @@ where
                         None => return Err(IoError::new(ErrorKind::Other, "no valid tls config.")),
                     };
 
-                    let stream = HandshakeStream::new(tls_acceptor.accept(stream));
+                    let stream = HandshakeStream::new(
+                        tls_acceptor.accept(stream),
+                        tls_connection_extensions,
+                    );
                     return Ok((stream, local_addr, remote_addr, Scheme::HTTPS));
                 }
             }
         }
     }
+
+    fn connection_extensions(io: &Self::Io) -> ConnectionExtensions {
+        io.connection_extensions()
+    }
+}
+
+/// The certificate chain presented by the client in the TLS handshake of a
+/// [`RustlsListener`] with client authentication.
+///
+/// It is available as a request extension, and is absent if the client
+/// authentication is optional and the client didn't present a certificate.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, listener::ClientCert, web::Data};
+///
+/// #[handler]
+/// fn index(cert: Data<&ClientCert>) -> String {
+///     format!("hello {}", cert.subject)
+/// }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClientCert {
+    /// The DER-encoded certificates, starting with the client certificate.
+    pub chain: Vec<Vec<u8>>,
+    /// The subject of the client certificate, e.g. `CN=client`.
+    pub subject: String,
+    /// The DNS names, email addresses, URIs and IP addresses in the subject
+    /// alternative name extension of the client certificate.
+    pub subject_alt_names: Vec<String>,
+}
+
+impl ClientCert {
+    fn new(chain: Vec<Vec<u8>>) -> Self {
+        let (subject, subject_alt_names) = chain
+            .first()
+            .and_then(|der| X509Certificate::from_der(der).ok())
+            .map(|(_, cert)| {
+                let subject_alt_names = match cert.subject_alternative_name() {
+                    Ok(Some(ext)) => ext
+                        .value
+                        .general_names
+                        .iter()
+                        .filter_map(|name| match name {
+                            GeneralName::DNSName(name)
+                            | GeneralName::RFC822Name(name)
+                            | GeneralName::URI(name) => Some(name.to_string()),
+                            GeneralName::IPAddress(addr) => match addr.len() {
+                                4 => Some(IpAddr::from(<[u8; 4]>::try_from(*addr).ok()?)),
+                                16 => Some(IpAddr::from(<[u8; 16]>::try_from(*addr).ok()?)),
+                                _ => None,
+                            }
+                            .map(|addr| addr.to_string()),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                (cert.subject().to_string(), subject_alt_names)
+            })
+            .unwrap_or_default();
+        Self {
+            chain,
+            subject,
+            subject_alt_names,
+        }
+    }
+}
+
+/// Returns the [`ClientCert`] and [`AlpnProtocol`] of an established
+/// connection.
+pub(crate) fn tls_connection_extensions<T>(stream: &TlsStream<T>) -> Extensions {
+    let (_, conn) = stream.get_ref();
+    let mut extensions = Extensions::new();
+    if let Some(certs) = conn.peer_certificates().filter(|certs| !certs.is_empty()) {
+        extensions.insert(ClientCert::new(
+            certs.iter().map(|cert| cert.to_vec()).collect(),
+        ));
+    }
+    if let Some(protocol) = conn.alpn_protocol() {
+        extensions.insert(AlpnProtocol(protocol.to_vec()));
+    }
+    extensions
 }
 
 #[derive(Debug)]
@@ -423,13 +531,11 @@ mod tests {
     use tokio_rustls::rustls::{ClientConfig, pki_types::ServerName};
 
     use super::*;
-    use crate::listener::TcpListener;
+    use crate::listener::{AcceptorExt, BoxAcceptor, TcpListener};
 
     #[tokio::test]
     async fn tls_listener() {
-        tokio_rustls::rustls::crypto::aws_lc_rs::default_provider()
-            .install_default()
-            .expect("Failed to install rustls crypto provider");
+        let _ = tokio_rustls::rustls::crypto::aws_lc_rs::default_provider().install_default();
 
         let listener = TcpListener::bind("127.0.0.1:0").rustls(
             RustlsConfig::new().fallback(
@@ -460,4 +566,148 @@ mod tests {
         let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
         assert_eq!(stream.read_i32().await.unwrap(), 10);
     }
+
+    #[tokio::test]
+    async fn client_cert() {
+        let _ = tokio_rustls::rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").rustls(
+            RustlsConfig::new()
+                .fallback(
+                    RustlsCertificate::new()
+                        .cert(include_bytes!("certs/cert1.pem").as_ref())
+                        .key(include_bytes!("certs/key1.pem").as_ref()),
+                )
+                .client_auth_optional(include_bytes!("certs/client_ca.pem").as_ref()),
+        );
+        let mut acceptor = listener.into_acceptor().await.unwrap().boxed();
+        let local_addr = *acceptor
+            .local_addr()
+            .pop()
+            .unwrap()
+            .as_socket_addr()
+            .unwrap();
+
+        for with_cert in [true, false] {
+            tokio::spawn(async move {
+                let builder = ClientConfig::builder().with_root_certificates(
+                    read_trust_anchor(include_bytes!("certs/chain1.pem")).unwrap(),
+                );
+                let mut config = if with_cert {
+                    let certs = rustls_pemfile::certs(
+                        &mut include_bytes!("certs/client_cert.pem").as_ref(),
+                    )
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                    let key = rustls_pemfile::private_key(
+                        &mut include_bytes!("certs/client_key.pem").as_ref(),
+                    )
+                    .unwrap()
+                    .unwrap();
+                    builder.with_client_auth_cert(certs, key).unwrap()
+                } else {
+                    builder.with_no_client_auth()
+                };
+                config.alpn_protocols = vec![b"h2".to_vec()];
+
+                let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+                let domain = ServerName::try_from("testserver.com").unwrap();
+                let stream = TcpStream::connect(local_addr).await.unwrap();
+                let mut stream = connector.connect(domain, stream).await.unwrap();
+                stream.write_i32(10).await.unwrap();
+            });
+
+            let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
+            let extensions = BoxAcceptor::connection_extensions(&stream);
+            assert!(extensions.get().is_none());
+            assert_eq!(stream.read_i32().await.unwrap(), 10);
+
+            let extensions = extensions.get().unwrap();
+            assert_eq!(
+                extensions.get::<AlpnProtocol>(),
+                Some(&AlpnProtocol(b"h2".to_vec()))
+            );
+            let cert = extensions.get::<ClientCert>();
+            if with_cert {
+                let cert = cert.unwrap();
+                assert_eq!(cert.chain.len(), 1);
+                assert_eq!(cert.subject, "CN=ponytown client");
+                assert_eq!(
+                    cert.subject_alt_names,
+                    ["client.testserver.com", "client@testserver.com"]
+                );
+            } else {
+                assert!(cert.is_none());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn tls_config_reload() {
+        let _ = tokio_rustls::rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let config = || {
+            RustlsConfig::new().fallback(
+                RustlsCertificate::new()
+                    .cert(include_bytes!("certs/cert1.pem").as_ref())
+                    .key(include_bytes!("certs/key1.pem").as_ref()),
+            )
+        };
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tx.send(config()).unwrap();
+        let config_stream = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|config| (config, rx))
+        });
+
+        let mut acceptor = TcpListener::bind("127.0.0.1:0")
+            .rustls(config_stream)
+            .into_acceptor()
+            .await
+            .unwrap();
+        let local_addr = *acceptor
+            .local_addr()
+            .pop()
+            .unwrap()
+            .as_socket_addr()
+            .unwrap();
+
+        let connect = move || async move {
+            let config = ClientConfig::builder()
+                .with_root_certificates(
+                    read_trust_anchor(include_bytes!("certs/chain1.pem")).unwrap(),
+                )
+                .with_no_client_auth();
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+            let domain = ServerName::try_from("testserver.com").unwrap();
+            let stream = TcpStream::connect(local_addr).await.unwrap();
+            connector.connect(domain, stream).await.unwrap()
+        };
+
+        let client = tokio::spawn(connect());
+        let (mut server_stream, _, _, _) = acceptor.accept().await.unwrap();
+        let server = tokio::spawn(async move {
+            let value = server_stream.read_i32().await.unwrap();
+            server_stream.write_i32(value + 1).await.unwrap();
+            server_stream
+        });
+        let mut client_stream = client.await.unwrap();
+
+        // an invalid config is ignored, and a valid one replaces the current one
+        tx.send(RustlsConfig::new().fallback(RustlsCertificate::new().key(b"invalid".as_ref())))
+            .unwrap();
+        tx.send(config()).unwrap();
+
+        let client = tokio::spawn(async move {
+            let mut stream = connect().await;
+            stream.write_i32(20).await.unwrap();
+        });
+        let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
+        assert_eq!(stream.read_i32().await.unwrap(), 20);
+        client.await.unwrap();
+
+        // the existing connection is not interrupted
+        client_stream.write_i32(10).await.unwrap();
+        assert_eq!(client_stream.read_i32().await.unwrap(), 11);
+        server.await.unwrap();
+    }
 }
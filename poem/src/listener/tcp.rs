@@ -1,6 +1,7 @@
-use std::io::Result;
+use std::{io::Result, time::Duration};
 
 use http::uri::Scheme;
+use socket2::SockRef;
 use tokio::{
     io::Result as IoResult,
     net::{TcpListener as TokioTcpListener, TcpStream, ToSocketAddrs},
@@ -11,15 +12,139 @@ use crate::{
     web::{LocalAddr, RemoteAddr},
 };
 
+/// TCP keepalive parameters, see [`TcpListener::keepalive`].
+///
+/// Fields that are `None` keep the default of the operating system.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TcpKeepalive {
+    /// Idle time before the first keepalive probe is sent.
+    pub time: Option<Duration>,
+    /// Time between keepalive probes.
+    pub interval: Option<Duration>,
+    /// Number of unacknowledged probes before the connection is dropped.
+    pub retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    fn to_socket2(self) -> socket2::TcpKeepalive {
+        let mut keepalive = socket2::TcpKeepalive::new();
+        if let Some(time) = self.time {
+            keepalive = keepalive.with_time(time);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "windows",
+        ))]
+        {
+            if let Some(interval) = self.interval {
+                keepalive = keepalive.with_interval(interval);
+            }
+            if let Some(retries) = self.retries {
+                keepalive = keepalive.with_retries(retries);
+            }
+        }
+        keepalive
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct TcpOptions {
+    nodelay: Option<bool>,
+    keepalive: Option<TcpKeepalive>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+}
+
+impl TcpOptions {
+    fn apply(&self, stream: &TcpStream) -> Result<()> {
+        if let Some(nodelay) = self.nodelay {
+            stream.set_nodelay(nodelay)?;
+        }
+        let socket = SockRef::from(stream);
+        if let Some(keepalive) = self.keepalive {
+            socket.set_tcp_keepalive(&keepalive.to_socket2())?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        Ok(())
+    }
+}
+
 /// A TCP listener.
+///
+/// The socket options set with the builder methods are applied to each
+/// accepted connection before it is handed to the HTTP layer. Options that
+/// are not set keep the defaults of the operating system.
 pub struct TcpListener<T> {
     addr: T,
+    options: TcpOptions,
 }
 
 impl<T> TcpListener<T> {
     /// Binds to the provided address, and returns a [`TcpListener<T>`].
     pub fn bind(addr: T) -> Self {
-        Self { addr }
+        Self {
+            addr,
+            options: TcpOptions::default(),
+        }
+    }
+
+    /// Sets the `TCP_NODELAY` option of accepted connections, which disables
+    /// Nagle's algorithm when `true`.
+    #[must_use]
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.options.nodelay = Some(nodelay);
+        self
+    }
+
+    /// Enables `SO_KEEPALIVE` on accepted connections with the specified
+    /// parameters.
+    ///
+    /// The interval and retries are ignored on platforms that do not support
+    /// configuring them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use poem::listener::{TcpKeepalive, TcpListener};
+    ///
+    /// let listener = TcpListener::bind("0.0.0.0:3000").keepalive(TcpKeepalive {
+    ///     time: Some(Duration::from_secs(60)),
+    ///     interval: Some(Duration::from_secs(10)),
+    ///     retries: Some(3),
+    /// });
+    /// ```
+    #[must_use]
+    pub fn keepalive(mut self, keepalive: TcpKeepalive) -> Self {
+        self.options.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Sets the `SO_RCVBUF` option of accepted connections.
+    #[must_use]
+    pub fn recv_buffer_size(mut self, size: usize) -> Self {
+        self.options.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the `SO_SNDBUF` option of accepted connections.
+    #[must_use]
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.options.send_buffer_size = Some(size);
+        self
     }
 }
 
@@ -32,6 +157,7 @@ impl<T: ToSocketAddrs + Send> Listener for TcpListener<T> {
         Ok(TcpAcceptor {
             local_addr,
             listener,
+            options: self.options,
         })
     }
 }
@@ -40,6 +166,7 @@ impl<T: ToSocketAddrs + Send> Listener for TcpListener<T> {
 pub struct TcpAcceptor {
     local_addr: LocalAddr,
     listener: TokioTcpListener,
+    options: TcpOptions,
 }
 
 impl TcpAcceptor {
@@ -49,6 +176,7 @@ impl TcpAcceptor {
         Ok(Self {
             local_addr,
             listener: TokioTcpListener::from_std(listener)?,
+            options: TcpOptions::default(),
         })
     }
 
@@ -58,6 +186,7 @@ impl TcpAcceptor {
         Ok(Self {
             local_addr,
             listener,
+            options: TcpOptions::default(),
         })
     }
 }
@@ -72,14 +201,14 @@ impl Acceptor for TcpAcceptor {
 
     #[inline]
     async fn accept(&mut self) -> Result<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
-        self.listener.accept().await.map(|(io, addr)| {
-            (
-                io,
-                self.local_addr.clone(),
-                RemoteAddr(addr.into()),
-                Scheme::HTTP,
-            )
-        })
+        let (io, addr) = self.listener.accept().await?;
+        self.options.apply(&io)?;
+        Ok((
+            io,
+            self.local_addr.clone(),
+            RemoteAddr(addr.into()),
+            Scheme::HTTP,
+        ))
     }
 }
 
@@ -105,4 +234,41 @@ mod tests {
         let (mut stream, _, _, _) = acceptor.accept().await.unwrap();
         assert_eq!(stream.read_i32().await.unwrap(), 10);
     }
+
+    #[tokio::test]
+    async fn socket_options() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .nodelay(true)
+            .keepalive(TcpKeepalive {
+                time: Some(Duration::from_secs(30)),
+                interval: Some(Duration::from_secs(5)),
+                retries: Some(3),
+            })
+            .recv_buffer_size(64 * 1024)
+            .send_buffer_size(64 * 1024);
+        let mut acceptor = listener.into_acceptor().await.unwrap();
+        let local_addr = acceptor.local_addr().remove(0);
+
+        let _client = TcpStream::connect(*local_addr.as_socket_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _, _, _) = acceptor.accept().await.unwrap();
+        assert!(stream.nodelay().unwrap());
+
+        let socket = SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(
+                socket.tcp_keepalive_time().unwrap(),
+                Duration::from_secs(30)
+            );
+            assert_eq!(
+                socket.tcp_keepalive_interval().unwrap(),
+                Duration::from_secs(5)
+            );
+            assert_eq!(socket.tcp_keepalive_retries().unwrap(), 3);
+        }
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+    }
 }
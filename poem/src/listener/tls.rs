@@ -2,6 +2,29 @@ use std::io::Result as IoResult;
 
 use futures_util::Stream;
 
+/// The application protocol negotiated with ALPN in the TLS handshake, e.g.
+/// `h2`.
+///
+/// It is available as a request extension on the connections accepted by the
+/// rustls and openssl listeners, the native-tls listener doesn't negotiate
+/// ALPN.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, listener::AlpnProtocol, web::Data};
+///
+/// #[handler]
+/// fn index(alpn: Option<Data<&AlpnProtocol>>) -> String {
+///     match alpn {
+///         Some(Data(alpn)) => String::from_utf8_lossy(&alpn.0).into_owned(),
+///         None => "none".to_string(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AlpnProtocol(pub Vec<u8>);
+
 /// Represents a type that can convert into tls config stream.
 #[cfg(any(feature = "rustls", feature = "native-tls", feature = "openssl-tls"))]
 pub trait IntoTlsConfigStream<C>: Send + 'static {
@@ -1,7 +1,8 @@
 use std::{
     fs::{Permissions, set_permissions},
-    io::Result,
-    path::Path,
+    io::{ErrorKind, Result},
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    path::{Path, PathBuf},
 };
 
 use http::uri::Scheme;
@@ -17,11 +18,14 @@ use crate::{
 };
 
 /// A Unix domain socket listener.
+///
+/// The socket file is removed when the [`UnixAcceptor`] is dropped.
 #[cfg_attr(docsrs, doc(cfg(unix)))]
 pub struct UnixListener<T> {
     path: T,
     permissions: Option<Permissions>,
     owner: Option<(Option<Uid>, Option<Gid>)>,
+    remove_stale_socket: bool,
 }
 
 impl<T> UnixListener<T> {
@@ -31,6 +35,7 @@ impl<T> UnixListener<T> {
             path,
             permissions: None,
             owner: None,
+            remove_stale_socket: false,
         }
     }
 
@@ -50,12 +55,41 @@ impl<T> UnixListener<T> {
             ..self
         }
     }
+
+    /// Removes the socket file before binding, if it is left by a process
+    /// that is no longer listening on it.
+    ///
+    /// Otherwise binding to an existing socket file fails with
+    /// [`ErrorKind::AddrInUse`].
+    #[must_use]
+    pub fn remove_stale_socket(self) -> Self {
+        Self {
+            remove_stale_socket: true,
+            ..self
+        }
+    }
+}
+
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        _ => return Ok(()),
+    }
+    match std::os::unix::net::UnixStream::connect(path) {
+        Err(err) if err.kind() == ErrorKind::ConnectionRefused => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
 }
 
 impl<T: AsRef<Path> + Send + Clone> Listener for UnixListener<T> {
     type Acceptor = UnixAcceptor;
 
     async fn into_acceptor(self) -> IoResult<Self::Acceptor> {
+        if self.remove_stale_socket {
+            remove_stale_socket(self.path.as_ref())?;
+        }
+        let path = self.path.as_ref().to_path_buf();
+
         let listener = match (self.permissions, self.owner) {
             (Some(permissions), Some((uid, gid))) => {
                 let listener = TokioUnixListener::bind(self.path.clone())?;
@@ -77,9 +111,14 @@ impl<T: AsRef<Path> + Send + Clone> Listener for UnixListener<T> {
         };
 
         let local_addr = listener.local_addr().map(|addr| LocalAddr(addr.into()))?;
+        // there is no file to remove for an abstract socket
+        let socket_file = std::fs::metadata(&path)
+            .ok()
+            .map(|metadata| (path, metadata.ino()));
         Ok(UnixAcceptor {
             local_addr,
             listener,
+            socket_file,
         })
     }
 }
@@ -89,20 +128,35 @@ impl<T: AsRef<Path> + Send + Clone> Listener for UnixListener<T> {
 pub struct UnixAcceptor {
     local_addr: LocalAddr,
     listener: TokioUnixListener,
+    socket_file: Option<(PathBuf, u64)>,
 }
 
 impl UnixAcceptor {
     /// Creates new `UnixAcceptor` from a `std::os::unix::net::UnixListener`.
+    ///
+    /// The socket file is not removed when the acceptor is dropped.
     pub fn from_std(listener: std::os::unix::net::UnixListener) -> Result<Self> {
         let listener = TokioUnixListener::from_std(listener)?;
         let local_addr = listener.local_addr().map(|addr| LocalAddr(addr.into()))?;
         Ok(Self {
             local_addr,
             listener,
+            socket_file: None,
         })
     }
 }
 
+impl Drop for UnixAcceptor {
+    fn drop(&mut self) {
+        // the file may have been replaced by another listener in the meantime
+        if let Some((path, inode)) = &self.socket_file {
+            if std::fs::metadata(path).is_ok_and(|metadata| metadata.ino() == *inode) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
 impl Acceptor for UnixAcceptor {
     type Io = UnixStream;
 
@@ -147,6 +201,45 @@ mod tests {
 
         tokio::time::sleep(Duration::from_secs(1)).await;
         drop(acceptor);
-        std::fs::remove_file("test-socket").unwrap();
+        assert!(!Path::new("test-socket").exists());
+    }
+
+    #[tokio::test]
+    async fn stale_socket() {
+        let dir = libtempfile::tempdir().unwrap();
+        let path = dir.path().join("socket");
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        assert_eq!(
+            UnixListener::bind(&path)
+                .into_acceptor()
+                .await
+                .err()
+                .unwrap()
+                .kind(),
+            ErrorKind::AddrInUse
+        );
+
+        let acceptor = UnixListener::bind(&path)
+            .remove_stale_socket()
+            .into_acceptor()
+            .await
+            .unwrap();
+
+        // a socket that is still listened on is kept
+        assert_eq!(
+            UnixListener::bind(&path)
+                .remove_stale_socket()
+                .into_acceptor()
+                .await
+                .err()
+                .unwrap()
+                .kind(),
+            ErrorKind::AddrInUse
+        );
+
+        drop(acceptor);
+        assert!(!path.exists());
     }
 }
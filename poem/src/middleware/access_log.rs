@@ -0,0 +1,654 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, Method, StatusCode, Version};
+use hyper::body::{Body as HttpBody, Frame, SizeHint};
+use parking_lot::Mutex;
+
+use crate::{
+    Body, Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result,
+    body::BoxBody, web::RealIp,
+};
+
+/// The format of the lines written by the [`AccessLog`] middleware.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum AccessLogFormat {
+    /// The Common Log Format, `%h %l %u %t "%r" %s %b`.
+    Common,
+
+    /// The Combined Log Format, `%h %l %u %t "%r" %s %b "%{Referer}i"
+    /// "%{User-Agent}i"`.
+    #[default]
+    Combined,
+
+    /// A custom template.
+    ///
+    /// The template supports the following tokens:
+    ///
+    /// | Token          | Description                                           |
+    /// |----------------|-------------------------------------------------------|
+    /// | `%h`           | The client IP address, see [`RealIp`]                 |
+    /// | `%l`, `%u`     | Always `-`                                            |
+    /// | `%t`           | The time the request was received                     |
+    /// | `%r`           | The request line, such as `GET /index.html HTTP/1.1`  |
+    /// | `%m`           | The request method                                    |
+    /// | `%U`           | The request path                                      |
+    /// | `%q`           | The query string prefixed with `?`, or an empty string |
+    /// | `%H`           | The request protocol                                  |
+    /// | `%s`           | The response status code                              |
+    /// | `%b`           | The size of the response body in bytes, or `-` if `0` |
+    /// | `%B`           | The size of the response body in bytes                |
+    /// | `%D`           | The time taken to serve the request in microseconds   |
+    /// | `%T`           | The time taken to serve the request in seconds        |
+    /// | `%{Header}i`   | The value of a request header                         |
+    /// | `%{Header}o`   | The value of a response header                        |
+    /// | `%%`           | A literal `%`                                         |
+    ///
+    /// Unknown tokens are written unchanged.
+    Custom(String),
+}
+
+impl AccessLogFormat {
+    fn template(&self) -> &str {
+        match self {
+            AccessLogFormat::Common => r#"%h %l %u %t "%r" %s %b"#,
+            AccessLogFormat::Combined => r#"%h %l %u %t "%r" %s %b "%{Referer}i" "%{User-Agent}i""#,
+            AccessLogFormat::Custom(template) => template,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Literal(String),
+    Dash,
+    RemoteHost,
+    Time,
+    RequestLine,
+    Method,
+    Path,
+    Query,
+    Protocol,
+    Status,
+    Bytes,
+    BytesNumeric,
+    DurationMicros,
+    DurationSecs,
+    RequestHeader(HeaderName),
+    ResponseHeader(HeaderName),
+}
+
+fn parse_template(template: &str) -> Vec<Token> {
+    fn push_literal(tokens: &mut Vec<Token>, s: &str) {
+        match tokens.last_mut() {
+            Some(Token::Literal(literal)) => literal.push_str(s),
+            _ => tokens.push(Token::Literal(s.to_string())),
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(idx) = rest.find('%') {
+        push_literal(&mut tokens, &rest[..idx]);
+        rest = &rest[idx + 1..];
+
+        let token = match rest.chars().next() {
+            Some('{') => {
+                let parsed = rest.find('}').and_then(|end| {
+                    let name = HeaderName::try_from(&rest[1..end]).ok()?;
+                    let token = match rest[end + 1..].chars().next()? {
+                        'i' => Token::RequestHeader(name),
+                        'o' => Token::ResponseHeader(name),
+                        _ => return None,
+                    };
+                    Some((token, end + 2))
+                });
+                match parsed {
+                    Some((token, len)) => {
+                        tokens.push(token);
+                        rest = &rest[len..];
+                    }
+                    None => push_literal(&mut tokens, "%"),
+                }
+                continue;
+            }
+            Some('%') => Token::Literal("%".to_string()),
+            Some('l' | 'u') => Token::Dash,
+            Some('h') => Token::RemoteHost,
+            Some('t') => Token::Time,
+            Some('r') => Token::RequestLine,
+            Some('m') => Token::Method,
+            Some('U') => Token::Path,
+            Some('q') => Token::Query,
+            Some('H') => Token::Protocol,
+            Some('s') => Token::Status,
+            Some('b') => Token::Bytes,
+            Some('B') => Token::BytesNumeric,
+            Some('D') => Token::DurationMicros,
+            Some('T') => Token::DurationSecs,
+            _ => {
+                push_literal(&mut tokens, "%");
+                continue;
+            }
+        };
+        match token {
+            Token::Literal(s) => push_literal(&mut tokens, &s),
+            token => tokens.push(token),
+        }
+        rest = &rest[1..];
+    }
+    push_literal(&mut tokens, rest);
+    tokens
+}
+
+/// Escapes the quotes, backslashes and non-printable characters of a value.
+fn write_escaped(s: &mut String, value: &[u8]) {
+    for &b in value {
+        match b {
+            b'"' => s.push_str("\\\""),
+            b'\\' => s.push_str("\\\\"),
+            0x20..=0x7e => s.push(b as char),
+            _ => {
+                let _ = write!(s, "\\x{b:02x}");
+            }
+        }
+    }
+}
+
+/// Writes the time in the `[10/Oct/2000:13:55:36 +0000]` format.
+fn write_time(s: &mut String, time: SystemTime) {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let _ = write!(
+        s,
+        "[{day:02}/{}/{year}:{:02}:{:02}:{:02} +0000]",
+        MONTHS[month as usize - 1],
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+}
+
+fn version_str(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "-",
+    }
+}
+
+enum Sink {
+    Writer(Mutex<Box<dyn Write + Send>>),
+    Fn(Box<dyn Fn(&str) + Send + Sync>),
+}
+
+struct Inner {
+    tokens: Vec<Token>,
+    sink: Arc<Sink>,
+}
+
+/// Middleware that writes a line for each request in an access log, such as
+/// the Combined Log Format used by Apache and Nginx.
+///
+/// The line is written when the response body has been sent or dropped, so
+/// the size is the number of bytes that have actually been sent, and `%D`
+/// includes the time taken to send the body. If
+/// the inner endpoint returns an error, the status code of the error is
+/// written, and the error is returned unchanged.
+///
+/// The lines can be written to any [`Write`], or passed to a function, for
+/// example to forward them to the `log` crate.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     middleware::{AccessLog, AccessLogFormat},
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// // writes `127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET / HTTP/1.1" 200 5 "-" "curl/8.0"`
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .with(AccessLog::stdout());
+///
+/// let app = Route::new().at("/", get(index)).with(
+///     AccessLog::with_fn(|line| tracing::info!(target: "access", "{line}"))
+///         .format(AccessLogFormat::Custom("%m %U%q %s %D".to_string())),
+/// );
+/// ```
+pub struct AccessLog {
+    tokens: Vec<Token>,
+    sink: Arc<Sink>,
+}
+
+impl AccessLog {
+    /// Create `AccessLog` middleware that writes the lines to `writer`.
+    ///
+    /// The writer is not flushed after each line.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self::with_sink(Sink::Writer(Mutex::new(Box::new(writer))))
+    }
+
+    /// Create `AccessLog` middleware that writes the lines to the standard
+    /// output.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Create `AccessLog` middleware that passes the lines, without the
+    /// trailing newline, to `f`.
+    pub fn with_fn(f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self::with_sink(Sink::Fn(Box::new(f)))
+    }
+
+    fn with_sink(sink: Sink) -> Self {
+        Self {
+            tokens: parse_template(AccessLogFormat::default().template()),
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Sets the format of the lines.
+    ///
+    /// Default is [`AccessLogFormat::Combined`].
+    #[must_use]
+    pub fn format(self, format: AccessLogFormat) -> Self {
+        Self {
+            tokens: parse_template(format.template()),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for AccessLog {
+    type Output = AccessLogEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        AccessLogEndpoint {
+            inner: ep,
+            log: Arc::new(Inner {
+                tokens: self.tokens.clone(),
+                sink: self.sink.clone(),
+            }),
+        }
+    }
+}
+
+/// Endpoint for the AccessLog middleware.
+pub struct AccessLogEndpoint<E> {
+    inner: E,
+    log: Arc<Inner>,
+}
+
+/// A log entry, the line is written when it is dropped.
+struct Entry {
+    log: Arc<Inner>,
+    remote_host: Option<IpAddr>,
+    time: SystemTime,
+    start: Instant,
+    method: Method,
+    path: String,
+    query: Option<String>,
+    version: Version,
+    request_headers: HeaderMap,
+    status: StatusCode,
+    response_headers: HeaderMap,
+    bytes: u64,
+}
+
+impl Entry {
+    fn render(&self, duration: Duration) -> String {
+        let mut s = String::new();
+        for token in &self.log.tokens {
+            match token {
+                Token::Literal(literal) => s.push_str(literal),
+                Token::Dash => s.push('-'),
+                Token::RemoteHost => match self.remote_host {
+                    Some(addr) => {
+                        let _ = write!(s, "{addr}");
+                    }
+                    None => s.push('-'),
+                },
+                Token::Time => write_time(&mut s, self.time),
+                Token::RequestLine => {
+                    let _ = write!(s, "{} ", self.method);
+                    write_escaped(&mut s, self.path.as_bytes());
+                    if let Some(query) = &self.query {
+                        s.push('?');
+                        write_escaped(&mut s, query.as_bytes());
+                    }
+                    let _ = write!(s, " {}", version_str(self.version));
+                }
+                Token::Method => {
+                    let _ = write!(s, "{}", self.method);
+                }
+                Token::Path => write_escaped(&mut s, self.path.as_bytes()),
+                Token::Query => {
+                    if let Some(query) = &self.query {
+                        s.push('?');
+                        write_escaped(&mut s, query.as_bytes());
+                    }
+                }
+                Token::Protocol => s.push_str(version_str(self.version)),
+                Token::Status => {
+                    let _ = write!(s, "{}", self.status.as_u16());
+                }
+                Token::Bytes if self.bytes == 0 => s.push('-'),
+                Token::Bytes | Token::BytesNumeric => {
+                    let _ = write!(s, "{}", self.bytes);
+                }
+                Token::DurationMicros => {
+                    let _ = write!(s, "{}", duration.as_micros());
+                }
+                Token::DurationSecs => {
+                    let _ = write!(s, "{}", duration.as_secs());
+                }
+                Token::RequestHeader(name) => write_header(&mut s, &self.request_headers, name),
+                Token::ResponseHeader(name) => write_header(&mut s, &self.response_headers, name),
+            }
+        }
+        s
+    }
+}
+
+fn write_header(s: &mut String, headers: &HeaderMap, name: &HeaderName) {
+    match headers.get(name) {
+        Some(value) => write_escaped(s, value.as_bytes()),
+        None => s.push('-'),
+    }
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        let line = self.render(self.start.elapsed());
+        match &*self.log.sink {
+            Sink::Writer(writer) => {
+                let mut writer = writer.lock();
+                let _ = writer.write_all(line.as_bytes());
+                let _ = writer.write_all(b"\n");
+            }
+            Sink::Fn(f) => f(&line),
+        }
+    }
+}
+
+struct LoggingBody {
+    inner: BoxBody,
+    entry: Entry,
+}
+
+impl HttpBody for LoggingBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let res = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &res {
+            if let Some(data) = frame.data_ref() {
+                this.entry.bytes += data.len() as u64;
+            }
+        }
+        res
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<E: Endpoint> Endpoint for AccessLogEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let start = Instant::now();
+        let remote_host = RealIp::from_request_without_body(&req)
+            .await
+            .ok()
+            .and_then(|real_ip| real_ip.0);
+        let needs_request_headers = self
+            .log
+            .tokens
+            .iter()
+            .any(|token| matches!(token, Token::RequestHeader(_)));
+        let mut entry = Entry {
+            log: self.log.clone(),
+            remote_host,
+            time: SystemTime::now(),
+            start,
+            method: req.method().clone(),
+            path: req.original_uri().path().to_string(),
+            query: req.original_uri().query().map(ToString::to_string),
+            version: req.version(),
+            request_headers: if needs_request_headers {
+                req.headers().clone()
+            } else {
+                HeaderMap::new()
+            },
+            status: StatusCode::OK,
+            response_headers: HeaderMap::new(),
+            bytes: 0,
+        };
+
+        let mut resp = match self.inner.call(req).await {
+            Ok(resp) => resp.into_response(),
+            Err(err) => {
+                entry.status = err.status();
+                return Err(err);
+            }
+        };
+
+        entry.status = resp.status();
+        if self
+            .log
+            .tokens
+            .iter()
+            .any(|token| matches!(token, Token::ResponseHeader(_)))
+        {
+            entry.response_headers = resp.headers().clone();
+        }
+
+        // the entry is logged when the body is finished or dropped
+        let body = resp.take_body();
+        resp.set_body(Body(BoxBody::new(LoggingBody {
+            inner: body.0,
+            entry,
+        })));
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use http::header;
+
+    use super::*;
+    use crate::{EndpointExt, Error, endpoint::make, test::TestClient, web::headers::UserAgent};
+
+    fn lines() -> (Arc<Mutex<Vec<String>>>, AccessLog) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let log = AccessLog::with_fn({
+            let lines = lines.clone();
+            move |line| lines.lock().push(line.to_string())
+        });
+        (lines, log)
+    }
+
+    #[test]
+    fn time() {
+        let mut s = String::new();
+        write_time(&mut s, UNIX_EPOCH + Duration::from_secs(971186136));
+        assert_eq!(s, "[10/Oct/2000:13:55:36 +0000]");
+
+        let mut s = String::new();
+        write_time(&mut s, UNIX_EPOCH + Duration::from_secs(951782400));
+        assert_eq!(s, "[29/Feb/2000:00:00:00 +0000]");
+    }
+
+    #[tokio::test]
+    async fn combined() {
+        let (lines, log) = lines();
+        let cli = TestClient::new(make(|_| async { "hello" }).with(log));
+        cli.get("/a%20b")
+            .query("q", &"\"x\"")
+            .header(header::REFERER, "http://example.com/")
+            .typed_header(UserAgent::from_static("curl/8.0"))
+            .send()
+            .await
+            .assert_text("hello")
+            .await;
+
+        let lines = lines.lock();
+        assert_eq!(lines.len(), 1);
+        let (prefix, rest) = lines[0].split_once(" [").unwrap();
+        assert_eq!(prefix, "- - -");
+        let (_, rest) = rest.split_once("] ").unwrap();
+        assert_eq!(
+            rest,
+            r#""GET /a%20b?q=%22x%22 HTTP/1.1" 200 5 "http://example.com/" "curl/8.0""#
+        );
+    }
+
+    #[tokio::test]
+    async fn custom() {
+        let (lines, log) = lines();
+        let log = log.format(AccessLogFormat::Custom(
+            r#"%m %U%q %H %s %b %B %{x-trace}o "%{X-Request}i" 100%% %x %{bad"#.to_string(),
+        ));
+        let cli = TestClient::new(
+            make(|_| async {
+                Response::builder()
+                    .status(StatusCode::CREATED)
+                    .header("x-trace", "abc")
+                    .finish()
+            })
+            .with(log),
+        );
+        cli.post("/users")
+            .header("x-request", "a\"b\t")
+            .send()
+            .await
+            .assert_status(StatusCode::CREATED);
+        assert_eq!(
+            lines.lock().as_slice(),
+            [r#"POST /users HTTP/1.1 201 - 0 abc "a\"b\x09" 100% %x %{bad"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_and_error() {
+        let (lines, log) = lines();
+        let log = log.format(AccessLogFormat::Common);
+        let ep = make(|req: Request| async move {
+            if req.uri().path() == "/error" {
+                return Err(Error::from_status(StatusCode::BAD_REQUEST));
+            }
+            Ok(Body::from_bytes_stream(stream::iter(
+                ["abc", "de"].map(Ok::<_, io::Error>),
+            )))
+        })
+        .with(log);
+        let cli = TestClient::new(ep);
+
+        cli.get("/stream").send().await.assert_text("abcde").await;
+        cli.get("/error")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        let lines = lines.lock();
+        assert!(
+            lines[0].ends_with(r#""GET /stream HTTP/1.1" 200 5"#),
+            "{}",
+            lines[0]
+        );
+        assert!(
+            lines[1].ends_with(r#""GET /error HTTP/1.1" 400 -"#),
+            "{}",
+            lines[1]
+        );
+    }
+
+    #[tokio::test]
+    async fn logged_when_finished() {
+        let (lines, log) = lines();
+        let ep = make(|_| async { "hello" })
+            .with(log.format(AccessLogFormat::Custom("%s %B".to_string())));
+        let body = ep.call(Request::default()).await.unwrap().into_body();
+        // the length of the body is still known
+        assert_eq!(body.exact_len(), Some(5));
+        assert!(lines.lock().is_empty());
+
+        // the client disconnects before the body is sent
+        drop(body);
+        assert_eq!(lines.lock().as_slice(), ["200 0"]);
+    }
+
+    #[tokio::test]
+    async fn writer() {
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let log = AccessLog::new(buffer.clone()).format(AccessLogFormat::Custom("%s".to_string()));
+        let cli = TestClient::new(make(|_| async { "hello" }).with(log));
+        cli.get("/").send().await.assert_status_is_ok();
+        cli.get("/").send().await.assert_status_is_ok();
+        assert_eq!(buffer.0.lock().as_slice(), b"200\n200\n");
+    }
+}
@@ -0,0 +1,196 @@
+use std::{future::Future, sync::Arc};
+
+use headers::{Authorization, authorization::Basic};
+
+use crate::{
+    Endpoint, Middleware, Request, Result, error::BasicAuthError, web::headers::HeaderMapExt,
+};
+
+/// The username authenticated by the [`BasicAuth`] middleware.
+///
+/// It is inserted into the request extensions, so handlers can read it with
+/// [`Data<&BasicAuthUser>`](crate::web::Data).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BasicAuthUser(pub String);
+
+/// Middleware for HTTP Basic authentication.
+///
+/// The validator receives the username and password from the `Authorization`
+/// header. Missing, malformed or rejected credentials result in a
+/// `401 Unauthorized` response with a `WWW-Authenticate` header.
+///
+/// # Errors
+///
+/// - [`BasicAuthError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, handler,
+///     http::StatusCode,
+///     middleware::{BasicAuth, BasicAuthUser},
+///     test::TestClient,
+///     web::Data,
+/// };
+///
+/// #[handler]
+/// fn index(Data(user): Data<&BasicAuthUser>) -> String {
+///     format!("hello: {}", user.0)
+/// }
+///
+/// let ep = index.with(
+///     BasicAuth::new(|username: &str, password: &str| {
+///         let valid = username == "admin" && password == "123456";
+///         async move { valid }
+///     })
+///     .realm("admin area"),
+/// );
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status(StatusCode::UNAUTHORIZED);
+/// resp.assert_header("www-authenticate", r#"Basic realm="admin area""#);
+///
+/// let resp = cli
+///     .get("/")
+///     .header("authorization", "Basic YWRtaW46MTIzNDU2")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("hello: admin").await;
+/// # });
+/// ```
+pub struct BasicAuth<F> {
+    validator: Arc<F>,
+    realm: Arc<str>,
+}
+
+impl<F, Fut> BasicAuth<F>
+where
+    F: Fn(&str, &str) -> Fut + Send + Sync,
+    Fut: Future<Output = bool> + Send,
+{
+    /// Create `BasicAuth` middleware with a function that validates the
+    /// username and password.
+    pub fn new(validator: F) -> Self {
+        Self {
+            validator: Arc::new(validator),
+            realm: Arc::from("Restricted"),
+        }
+    }
+
+    /// Sets the realm in the `WWW-Authenticate` header.
+    ///
+    /// Default is `Restricted`.
+    #[must_use]
+    pub fn realm(self, realm: impl AsRef<str>) -> Self {
+        Self {
+            realm: Arc::from(realm.as_ref()),
+            ..self
+        }
+    }
+}
+
+impl<E, F, Fut> Middleware<E> for BasicAuth<F>
+where
+    E: Endpoint,
+    F: Fn(&str, &str) -> Fut + Send + Sync,
+    Fut: Future<Output = bool> + Send,
+{
+    type Output = BasicAuthEndpoint<E, F>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        BasicAuthEndpoint {
+            inner: ep,
+            validator: self.validator.clone(),
+            realm: self.realm.clone(),
+        }
+    }
+}
+
+/// Endpoint for the BasicAuth middleware.
+pub struct BasicAuthEndpoint<E, F> {
+    inner: E,
+    validator: Arc<F>,
+    realm: Arc<str>,
+}
+
+impl<E, F, Fut> Endpoint for BasicAuthEndpoint<E, F>
+where
+    E: Endpoint,
+    F: Fn(&str, &str) -> Fut + Send + Sync,
+    Fut: Future<Output = bool> + Send,
+{
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let unauthorized = || BasicAuthError {
+            realm: self.realm.to_string(),
+        };
+
+        // An undecodable header is reported by `typed_get` as a missing one, so
+        // both cases end up as `401 Unauthorized`.
+        let credentials = req
+            .headers()
+            .typed_get::<Authorization<Basic>>()
+            .ok_or_else(unauthorized)?;
+
+        if !(self.validator)(credentials.username(), credentials.password()).await {
+            return Err(unauthorized().into());
+        }
+
+        req.extensions_mut()
+            .insert(BasicAuthUser(credentials.username().to_string()));
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient, web::Data};
+
+    #[tokio::test]
+    async fn basic_auth() {
+        #[handler(internal)]
+        fn index(Data(user): Data<&BasicAuthUser>) -> String {
+            user.0.clone()
+        }
+
+        let ep = index.with(BasicAuth::new(|username: &str, password: &str| {
+            let valid = username == "user" && password == "pass";
+            async move { valid }
+        }));
+        let cli = TestClient::new(ep);
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        resp.assert_header("www-authenticate", r#"Basic realm="Restricted""#);
+
+        // user:wrong
+        cli.get("/")
+            .header("authorization", "Basic dXNlcjp3cm9uZw==")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        cli.get("/")
+            .header("authorization", "Basic !!!not-base64!!!")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        // user:pass
+        let resp = cli
+            .get("/")
+            .header("authorization", "Basic dXNlcjpwYXNz")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("user").await;
+    }
+}
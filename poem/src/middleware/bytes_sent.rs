@@ -0,0 +1,240 @@
+use std::{
+    io::Error as IoError,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use hyper::body::{Body as HttpBody, Frame, SizeHint};
+
+use crate::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result, body::BoxBody};
+
+type Callback = Arc<dyn Fn(&Request, u64) + Send + Sync>;
+
+/// Middleware that counts the body bytes of each response and passes the
+/// total to a callback when the response is complete, for example for
+/// billing or metrics.
+///
+/// The bytes are counted as the body is polled by the server, so the count
+/// of a streaming body, such as server-sent events, is the number of bytes
+/// that have actually been produced. The callback is invoked when the body is
+/// dropped, either after it has been fully written or when the client
+/// disconnects early, in which case it receives the partial count. The
+/// `Content-Length` of the response is preserved.
+///
+/// If the inner endpoint returns an error, the callback is invoked with `0`
+/// and the error is returned unchanged.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::{
+///     Arc,
+///     atomic::{AtomicU64, Ordering},
+/// };
+///
+/// use poem::{EndpointExt, Request, handler, middleware::BytesSent, test::TestClient};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let total = Arc::new(AtomicU64::new(0));
+/// let app = index.with(BytesSent::new({
+///     let total = total.clone();
+///     move |_req: &Request, bytes| {
+///         total.fetch_add(bytes, Ordering::Relaxed);
+///     }
+/// }));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/").send().await.assert_text("hello").await;
+/// assert_eq!(total.load(Ordering::Relaxed), 5);
+/// # });
+/// ```
+pub struct BytesSent {
+    callback: Callback,
+}
+
+impl BytesSent {
+    /// Create `BytesSent` middleware that passes the request and the number of
+    /// body bytes sent to `callback`.
+    pub fn new(callback: impl Fn(&Request, u64) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for BytesSent {
+    type Output = BytesSentEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        BytesSentEndpoint {
+            inner: ep,
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+/// Endpoint for the BytesSent middleware.
+pub struct BytesSentEndpoint<E> {
+    inner: E,
+    callback: Callback,
+}
+
+impl<E: Endpoint> Endpoint for BytesSentEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let counter = Counter {
+            req: req.clone_without_body(),
+            callback: self.callback.clone(),
+            bytes: 0,
+        };
+        let mut resp = self.inner.call(req).await?.into_response();
+        let body = resp.take_body();
+        resp.set_body(Body(BoxBody::new(CountingBody {
+            inner: body.0,
+            counter,
+        })));
+        Ok(resp)
+    }
+}
+
+/// Invokes the callback when it is dropped.
+struct Counter {
+    req: Request,
+    callback: Callback,
+    bytes: u64,
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        (self.callback)(&self.req, self.bytes);
+    }
+}
+
+struct CountingBody {
+    inner: BoxBody,
+    counter: Counter,
+}
+
+impl HttpBody for CountingBody {
+    type Data = Bytes;
+    type Error = IoError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let res = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &res {
+            if let Some(data) = frame.data_ref() {
+                this.counter.bytes += data.len() as u64;
+            }
+        }
+        res
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{StreamExt, stream};
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{EndpointExt, Error, endpoint::make, http::StatusCode, test::TestClient};
+
+    #[tokio::test]
+    async fn bytes_sent() {
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let mw = BytesSent::new({
+            let counts = counts.clone();
+            move |req: &Request, bytes| counts.lock().push((req.uri().path().to_string(), bytes))
+        });
+        let ep = make(|_| async { "hello" }).with(mw);
+        // the length of the body is still known
+        let body = ep.call(Request::default()).await.unwrap().into_body();
+        assert_eq!(body.exact_len(), Some(5));
+        drop(body);
+
+        let cli = TestClient::new(ep);
+        cli.get("/a").send().await.assert_text("hello").await;
+        assert_eq!(
+            counts.lock().as_slice(),
+            [("/".to_string(), 0), ("/a".to_string(), 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let mw = BytesSent::new({
+            let counts = counts.clone();
+            move |req: &Request, bytes| counts.lock().push((req.uri().path().to_string(), bytes))
+        });
+        let cli = TestClient::new(
+            make(|_| async {
+                Body::from_bytes_stream(stream::iter(["abc", "de"]).map(Ok::<_, IoError>))
+            })
+            .with(mw),
+        );
+        cli.get("/").send().await.assert_text("abcde").await;
+        assert_eq!(counts.lock().as_slice(), [("/".to_string(), 5)]);
+    }
+
+    #[tokio::test]
+    async fn disconnect() {
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let mw = BytesSent::new({
+            let counts = counts.clone();
+            move |req: &Request, bytes| counts.lock().push((req.uri().path().to_string(), bytes))
+        });
+        let ep = make(|_| async {
+            Body::from_bytes_stream(
+                stream::iter(["abc"])
+                    .chain(stream::pending())
+                    .map(Ok::<_, IoError>),
+            )
+        })
+        .with(mw);
+        let mut body = ep.call(Request::default()).await.unwrap().into_body().0;
+        let frame = http_body_util::BodyExt::frame(&mut body).await;
+        assert_eq!(frame.unwrap().unwrap().into_data().unwrap(), "abc");
+        assert!(counts.lock().is_empty());
+
+        // the client disconnects
+        drop(body);
+        assert_eq!(counts.lock().as_slice(), [("/".to_string(), 3)]);
+    }
+
+    #[tokio::test]
+    async fn error() {
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let mw = BytesSent::new({
+            let counts = counts.clone();
+            move |req: &Request, bytes| counts.lock().push((req.uri().path().to_string(), bytes))
+        });
+        let cli = TestClient::new(
+            make(|_| async { Err::<(), _>(Error::from_status(StatusCode::BAD_REQUEST)) }).with(mw),
+        );
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        assert_eq!(counts.lock().as_slice(), [("/".to_string(), 0)]);
+    }
+}
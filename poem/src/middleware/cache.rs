@@ -0,0 +1,413 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use headers::{CacheControl, HeaderMapExt};
+use http::{
+    HeaderMap, HeaderValue, Method, StatusCode,
+    header::{self, HeaderName},
+};
+use parking_lot::Mutex;
+
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+type KeyFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+const X_CACHE: HeaderName = HeaderName::from_static("x-cache");
+
+/// A response stored by the [`Cache`] middleware.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// The status code
+    pub status: StatusCode,
+    /// The headers
+    pub headers: HeaderMap,
+    /// The body
+    pub body: Bytes,
+}
+
+impl From<CachedResponse> for Response {
+    fn from(cached: CachedResponse) -> Self {
+        let mut resp = Response::builder().status(cached.status).body(cached.body);
+        *resp.headers_mut() = cached.headers;
+        resp
+    }
+}
+
+/// Represents a back-end storage for the [`Cache`] middleware.
+pub trait CacheStore: Send + Sync {
+    /// Load a response that has not expired.
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Future<Output = Result<Option<CachedResponse>>> + Send + 'a;
+
+    /// Insert or update a response that expires after `ttl`.
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        value: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<()>> + Send + 'a;
+}
+
+/// A cache storage using memory.
+///
+/// Expired entries are removed when they are looked up.
+#[derive(Default)]
+pub struct MemoryCacheStore {
+    entries: Mutex<HashMap<String, (Instant, CachedResponse)>>,
+}
+
+impl MemoryCacheStore {
+    /// Create a `MemoryCacheStore`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    async fn get<'a>(&'a self, key: &'a str) -> Result<Option<CachedResponse>> {
+        let mut entries = self.entries.lock();
+        match entries.get(key) {
+            Some((expires_at, value)) if *expires_at > Instant::now() => Ok(Some(value.clone())),
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set<'a>(&'a self, key: &'a str, value: CachedResponse, ttl: Duration) -> Result<()> {
+        self.entries
+            .lock()
+            .insert(key.to_string(), (Instant::now() + ttl, value));
+        Ok(())
+    }
+}
+
+/// Middleware for caching responses of `GET` requests.
+///
+/// Only `200 OK` responses whose body is fully buffered are cached. The
+/// responses that set a cookie, or that have a `Cache-Control` header with the
+/// `private`, `no-store` or `no-cache` directives, are never cached. Requests
+/// with `Cache-Control: no-store` bypass the cache. An `X-Cache` header with
+/// the value `HIT` or `MISS` is added to the response.
+///
+/// By default, the cache key is the host and the URI of the request, and the
+/// responses with a `Vary` header are not cached. Use [`Cache::key_fn`] to
+/// include the headers the response varies on in the key.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{EndpointExt, handler, middleware::Cache, test::TestClient};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let cli = TestClient::new(index.with(Cache::new(Duration::from_secs(60))));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_header("x-cache", "MISS");
+///
+/// let resp = cli.get("/").send().await;
+/// resp.assert_header("x-cache", "HIT");
+/// resp.assert_text("hello").await;
+/// # });
+/// ```
+pub struct Cache<T = MemoryCacheStore> {
+    ttl: Duration,
+    key_fn: Option<KeyFn>,
+    store: Arc<T>,
+}
+
+impl Cache {
+    /// Create a `Cache` middleware backed by a [`MemoryCacheStore`].
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            key_fn: None,
+            store: Arc::new(MemoryCacheStore::new()),
+        }
+    }
+}
+
+impl<T> Cache<T> {
+    /// Uses a closure to compute the cache key of a request.
+    ///
+    /// If the closure returns `None`, the request is not cached.
+    #[must_use]
+    pub fn key_fn(self, f: impl Fn(&Request) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            key_fn: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Sets the storage of the cached responses.
+    #[must_use]
+    pub fn store<T2>(self, store: T2) -> Cache<T2> {
+        Cache {
+            ttl: self.ttl,
+            key_fn: self.key_fn,
+            store: Arc::new(store),
+        }
+    }
+}
+
+impl<T: CacheStore, E: Endpoint> Middleware<E> for Cache<T> {
+    type Output = CacheEndpoint<T, E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CacheEndpoint {
+            inner: ep,
+            ttl: self.ttl,
+            key_fn: self.key_fn.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// Endpoint for the `Cache` middleware.
+pub struct CacheEndpoint<T, E> {
+    inner: E,
+    ttl: Duration,
+    key_fn: Option<KeyFn>,
+    store: Arc<T>,
+}
+
+fn default_key(req: &Request) -> String {
+    let host = req
+        .uri()
+        .authority()
+        .map(|authority| authority.as_str())
+        .or_else(|| {
+            req.headers()
+                .get(header::HOST)
+                .and_then(|value| value.to_str().ok())
+        })
+        .unwrap_or_default();
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or("/");
+    format!("{host}{path_and_query}")
+}
+
+impl<T, E> CacheEndpoint<T, E> {
+    fn is_cacheable(&self, headers: &HeaderMap) -> bool {
+        if headers.contains_key(header::SET_COOKIE)
+            || (self.key_fn.is_none() && headers.contains_key(header::VARY))
+        {
+            return false;
+        }
+        headers
+            .typed_get::<CacheControl>()
+            .map(|cache_control| {
+                !cache_control.private() && !cache_control.no_store() && !cache_control.no_cache()
+            })
+            .unwrap_or(true)
+    }
+}
+
+impl<T: CacheStore, E: Endpoint> Endpoint for CacheEndpoint<T, E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let no_store = req
+            .headers()
+            .typed_get::<CacheControl>()
+            .map(|cache_control| cache_control.no_store())
+            .unwrap_or_default();
+        let key = match &self.key_fn {
+            Some(key_fn) => key_fn(&req),
+            None => Some(default_key(&req)),
+        };
+        let key = match key {
+            Some(key) if req.method() == Method::GET && !no_store => key,
+            _ => return self.inner.call(req).await.map(IntoResponse::into_response),
+        };
+
+        if let Some(cached) = self.store.get(&key).await? {
+            let mut resp: Response = cached.into();
+            resp.headers_mut()
+                .insert(X_CACHE, HeaderValue::from_static("HIT"));
+            return Ok(resp);
+        }
+
+        let mut resp = self.inner.call(req).await?.into_response();
+        if resp.status() == StatusCode::OK && self.is_cacheable(resp.headers()) {
+            let body = resp.take_body();
+            if hyper::body::Body::size_hint(&body.0).exact().is_some() {
+                let data = body.into_bytes().await?;
+                let cached = CachedResponse {
+                    status: resp.status(),
+                    headers: resp.headers().clone(),
+                    body: data.clone(),
+                };
+                self.store.set(&key, cached, self.ttl).await?;
+                resp.set_body(data);
+            } else {
+                resp.set_body(body);
+            }
+        }
+
+        resp.headers_mut()
+            .insert(X_CACHE, HeaderValue::from_static("MISS"));
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{Body, EndpointExt, endpoint::make_sync, test::TestClient};
+
+    #[tokio::test]
+    async fn cache() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let ep = make_sync({
+            let counter = counter.clone();
+            move |_| counter.fetch_add(1, Ordering::SeqCst).to_string()
+        })
+        .with(Cache::new(Duration::from_secs(60)));
+        let cli = TestClient::new(ep);
+
+        let resp = cli.get("/").send().await;
+        resp.assert_header("x-cache", "MISS");
+        resp.assert_text("0").await;
+
+        let resp = cli.get("/").send().await;
+        resp.assert_header("x-cache", "HIT");
+        resp.assert_text("0").await;
+
+        let resp = cli.get("/a").send().await;
+        resp.assert_header("x-cache", "MISS");
+        resp.assert_text("1").await;
+
+        let resp = cli
+            .get("/")
+            .header("cache-control", "no-store")
+            .send()
+            .await;
+        resp.assert_header_is_not_exist("x-cache");
+        resp.assert_text("2").await;
+
+        let resp = cli.post("/").send().await;
+        resp.assert_header_is_not_exist("x-cache");
+        resp.assert_text("3").await;
+    }
+
+    #[tokio::test]
+    async fn cache_expired() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let ep = make_sync({
+            let counter = counter.clone();
+            move |_| counter.fetch_add(1, Ordering::SeqCst).to_string()
+        })
+        .with(Cache::new(Duration::from_millis(50)));
+        let cli = TestClient::new(ep);
+
+        cli.get("/").send().await.assert_text("0").await;
+        cli.get("/").send().await.assert_text("0").await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        cli.get("/").send().await.assert_text("1").await;
+    }
+
+    #[tokio::test]
+    async fn cache_skip_unbuffered_and_errors() {
+        let ep = make_sync(|req| {
+            if req.uri().path() == "/stream" {
+                Response::builder().body(Body::from_async_read(&b"hello"[..]))
+            } else {
+                Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body("not found")
+            }
+        })
+        .with(Cache::new(Duration::from_secs(60)));
+        let cli = TestClient::new(ep);
+
+        for _ in 0..2 {
+            cli.get("/stream")
+                .send()
+                .await
+                .assert_header("x-cache", "MISS");
+            cli.get("/missing")
+                .send()
+                .await
+                .assert_header("x-cache", "MISS");
+        }
+    }
+
+    #[tokio::test]
+    async fn cache_key_host() {
+        let ep = make_sync(|req| {
+            req.headers()
+                .get(header::HOST)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        })
+        .with(Cache::new(Duration::from_secs(60)));
+        let cli = TestClient::new(ep);
+
+        for host in ["a.com", "b.com"] {
+            let resp = cli.get("/").header(header::HOST, host).send().await;
+            resp.assert_header("x-cache", "MISS");
+            resp.assert_text(host).await;
+        }
+        let resp = cli.get("/").header(header::HOST, "a.com").send().await;
+        resp.assert_header("x-cache", "HIT");
+        resp.assert_text("a.com").await;
+    }
+
+    #[tokio::test]
+    async fn cache_skip_uncacheable_responses() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let ep = make_sync({
+            let counter = counter.clone();
+            move |req| {
+                let n = counter.fetch_add(1, Ordering::SeqCst).to_string();
+                let resp = Response::builder();
+                match req.uri().path() {
+                    "/cookie" => resp.header(header::SET_COOKIE, format!("session={n}")),
+                    "/private" => resp.header(header::CACHE_CONTROL, "private, max-age=60"),
+                    "/no-cache" => resp.header(header::CACHE_CONTROL, "no-cache"),
+                    "/no-store" => resp.header(header::CACHE_CONTROL, "no-store"),
+                    _ => resp.header(header::VARY, "accept-language"),
+                }
+                .body(n)
+            }
+        })
+        .with(Cache::new(Duration::from_secs(60)));
+        let cli = TestClient::new(ep);
+
+        let resp = cli.get("/cookie").send().await;
+        resp.assert_header("set-cookie", "session=0");
+        let resp = cli.get("/cookie").send().await;
+        resp.assert_header("x-cache", "MISS");
+        resp.assert_header("set-cookie", "session=1");
+        resp.assert_text("1").await;
+
+        for path in ["/private", "/no-cache", "/no-store", "/vary"] {
+            for _ in 0..2 {
+                cli.get(path).send().await.assert_header("x-cache", "MISS");
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{StreamExt, stream};
+
+use crate::{Body, Endpoint, Middleware, Request, Result};
+
+type Callback = Arc<dyn Fn(&Request, &CapturedBody) + Send + Sync>;
+
+/// The request body captured by the [`CaptureBody`] middleware.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CapturedBody {
+    /// The body, or its first `max_size` bytes if it is truncated.
+    pub data: Bytes,
+    /// `true` if the body is larger than `max_size`, or if reading it failed.
+    pub truncated: bool,
+}
+
+/// Middleware that passes the request body to a callback, for example to log
+/// it, before calling the inner endpoint.
+///
+/// The body is read up to `max_size` bytes, and the same bytes, followed by
+/// the rest of the body, are restored in the request, so the extractors of the
+/// inner endpoint read the whole body as usual. A body larger than `max_size`
+/// is passed to the callback truncated, at most `max_size` bytes (plus one
+/// chunk) are buffered.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler,
+///     middleware::{CaptureBody, CapturedBody},
+///     post,
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn transfer(body: String) -> String {
+///     body
+/// }
+///
+/// let app = Route::new().at(
+///     "/transfer",
+///     post(transfer).with(CaptureBody::new(
+///         64 * 1024,
+///         |req, body: &CapturedBody| {
+///             tracing::info!(
+///                 uri = %req.uri(),
+///                 body = %String::from_utf8_lossy(&body.data),
+///                 truncated = body.truncated,
+///                 "audit"
+///             );
+///         },
+///     )),
+/// );
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(app)
+///     .post("/transfer")
+///     .body("amount=100")
+///     .send()
+///     .await;
+/// resp.assert_text("amount=100").await;
+/// # });
+/// ```
+pub struct CaptureBody {
+    max_size: usize,
+    callback: Callback,
+}
+
+impl CaptureBody {
+    /// Create `CaptureBody` middleware that captures at most `max_size` bytes
+    /// of the body and passes them to `callback`.
+    pub fn new<F>(max_size: usize, callback: F) -> Self
+    where
+        F: Fn(&Request, &CapturedBody) + Send + Sync + 'static,
+    {
+        Self {
+            max_size,
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for CaptureBody {
+    type Output = CaptureBodyEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CaptureBodyEndpoint {
+            inner: ep,
+            max_size: self.max_size,
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+/// Endpoint for the CaptureBody middleware.
+pub struct CaptureBodyEndpoint<E> {
+    inner: E,
+    max_size: usize,
+    callback: Callback,
+}
+
+impl<E: Endpoint> Endpoint for CaptureBodyEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let mut body = req.take_body().into_bytes_stream();
+        let mut chunks = Vec::new();
+        let mut size = 0;
+        let mut error = None;
+
+        while size <= self.max_size {
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    size += chunk.len();
+                    chunks.push(chunk);
+                }
+                Some(Err(err)) => {
+                    error = Some(err);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        let mut data = BytesMut::with_capacity(size.min(self.max_size));
+        for chunk in &chunks {
+            let remaining = self.max_size - data.len();
+            data.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+        }
+        let captured = CapturedBody {
+            data: data.freeze(),
+            truncated: size > self.max_size || error.is_some(),
+        };
+
+        // the bytes that were read, then the error or the rest of the body
+        req.set_body(Body::from_bytes_stream(
+            stream::iter(chunks.into_iter().map(Ok))
+                .chain(stream::iter(error.map(Err)))
+                .chain(body),
+        ));
+        (self.callback)(&req, &captured);
+
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[tokio::test]
+    async fn capture_body() {
+        #[handler(internal)]
+        fn index(body: String) -> String {
+            body
+        }
+
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let cli = TestClient::new(index.with(CaptureBody::new(5, {
+            let captured = captured.clone();
+            move |req: &Request, body: &CapturedBody| {
+                captured
+                    .lock()
+                    .push((req.uri().path().to_string(), body.clone()))
+            }
+        })));
+
+        let chunked = |data: &'static [u8]| {
+            Body::from_bytes_stream(stream::iter(
+                data.chunks(2)
+                    .map(|chunk| Ok::<_, std::io::Error>(Bytes::from_static(chunk))),
+            ))
+        };
+
+        for (data, expected, truncated) in [
+            (&b""[..], "", false),
+            (&b"12345"[..], "12345", false),
+            (&b"1234567890"[..], "12345", true),
+        ] {
+            for body in [Body::from(data), chunked(data)] {
+                // the handler still reads the whole body
+                cli.post("/a")
+                    .body(body)
+                    .send()
+                    .await
+                    .assert_bytes(data)
+                    .await;
+                let (path, captured) = captured.lock().pop().unwrap();
+                assert_eq!(path, "/a");
+                assert_eq!(captured.data, expected);
+                assert_eq!(captured.truncated, truncated);
+            }
+        }
+    }
+}
@@ -1,10 +1,69 @@
-use std::{any::Any, panic::AssertUnwindSafe};
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::RefCell,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Once},
+};
 
-use futures_util::FutureExt;
-use http::StatusCode;
+use futures_util::{FutureExt, future::BoxFuture};
+use http::{Method, StatusCode, Uri};
 
 use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
 
+type Reporter = Arc<dyn Fn(PanicReport) -> BoxFuture<'static, ()> + Send + Sync>;
+
+thread_local! {
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Installs a panic hook that saves the backtrace of the last panic of the
+/// thread, and then calls the previous hook.
+fn install_backtrace_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let prev = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            BACKTRACE.with(|backtrace| *backtrace.borrow_mut() = Some(Backtrace::capture()));
+            prev(info);
+        }));
+    });
+}
+
+/// Returns the message of a panic, if the payload is a `&str` or a `String`,
+/// which is the case for the panics created by [`panic!`].
+///
+/// # Example
+///
+/// ```rust
+/// use poem::middleware::panic_message;
+///
+/// let err = std::panic::catch_unwind(|| panic!("oops {}", 1)).unwrap_err();
+/// assert_eq!(panic_message(&*err), Some("oops 1"));
+/// ```
+pub fn panic_message(err: &(dyn Any + Send)) -> Option<&str> {
+    err.downcast_ref::<&str>()
+        .copied()
+        .or_else(|| err.downcast_ref::<String>().map(String::as_str))
+}
+
+/// A panic caught by the [`CatchPanic`] middleware, passed to the reporter
+/// specified with [`CatchPanic::with_reporter`].
+#[derive(Debug)]
+pub struct PanicReport {
+    /// The method of the request.
+    pub method: Method,
+    /// The URI of the request.
+    pub uri: Uri,
+    /// The panic message, see [`panic_message`].
+    pub message: Option<String>,
+    /// The backtrace of the panic, captured according to the
+    /// `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE` environment variables like
+    /// [`Backtrace::capture`].
+    pub backtrace: Backtrace,
+}
+
 /// Panics handler
 pub trait PanicHandler: Clone + Sync + Send + 'static {
     /// Response type
@@ -37,6 +96,9 @@ where
 /// Middleware that catches panics and converts them into `500 INTERNAL SERVER
 /// ERROR` responses.
 ///
+/// Panics are caught while the inner endpoint is polled, so a panic after an
+/// `.await` in a handler is caught as well.
+///
 /// # Example
 ///
 /// ```rust
@@ -58,13 +120,17 @@ where
 /// ```
 pub struct CatchPanic<H> {
     panic_handler: H,
+    reporter: Option<Reporter>,
 }
 
 impl CatchPanic<()> {
     /// Create new `CatchPanic` middleware.
     #[inline]
     pub fn new() -> Self {
-        CatchPanic { panic_handler: () }
+        CatchPanic {
+            panic_handler: (),
+            reporter: None,
+        }
     }
 }
 
@@ -79,6 +145,9 @@ impl<H> CatchPanic<H> {
     /// Specifies a panic handler to be used to create a custom response when
     /// a panic occurs.
     ///
+    /// The handler receives the panic payload, use [`panic_message`] to get
+    /// the message of the panic.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -107,6 +176,71 @@ impl<H> CatchPanic<H> {
     pub fn with_handler<T: PanicHandler>(self, handler: T) -> CatchPanic<T> {
         CatchPanic {
             panic_handler: handler,
+            reporter: self.reporter,
+        }
+    }
+
+    /// Specifies an async function that is called with a [`PanicReport`]
+    /// when a panic occurs, before the response is created by the panic
+    /// handler.
+    ///
+    /// To capture the backtraces of the panics, this installs a panic hook
+    /// that calls the previous hook after saving the backtrace.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http::StatusCode;
+    /// use poem::{
+    ///     EndpointExt, IntoResponse, Route, handler,
+    ///     middleware::{CatchPanic, panic_message},
+    ///     test::TestClient,
+    ///     web::Html,
+    /// };
+    ///
+    /// #[handler]
+    /// async fn index() {
+    ///     tokio::task::yield_now().await;
+    ///     panic!("oops")
+    /// }
+    ///
+    /// let app = Route::new().at("/", index).with(
+    ///     CatchPanic::new()
+    ///         .with_reporter(|report| async move {
+    ///             tracing::error!(
+    ///                 message = report.message.as_deref(),
+    ///                 backtrace = %report.backtrace,
+    ///                 uri = %report.uri,
+    ///                 "panic"
+    ///             );
+    ///         })
+    ///         .with_handler(|err: Box<dyn std::any::Any + Send>| {
+    ///             Html(format!(
+    ///                 "<h1>Something went wrong</h1><p>{}</p>",
+    ///                 panic_message(&*err).unwrap_or_default()
+    ///             ))
+    ///             .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+    ///         }),
+    /// );
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let cli = TestClient::new(app);
+    /// let resp = cli.get("/").send().await;
+    /// resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    /// resp.assert_text("<h1>Something went wrong</h1><p>oops</p>")
+    ///     .await;
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn with_reporter<F, Fut>(self, reporter: F) -> Self
+    where
+        F: Fn(PanicReport) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        install_backtrace_hook();
+        Self {
+            reporter: Some(Arc::new(move |report| reporter(report).boxed())),
+            ..self
         }
     }
 }
@@ -118,6 +252,7 @@ impl<E: Endpoint, H: PanicHandler> Middleware<E> for CatchPanic<H> {
         CatchPanicEndpoint {
             inner: ep,
             panic_handler: self.panic_handler.clone(),
+            reporter: self.reporter.clone(),
         }
     }
 }
@@ -126,15 +261,91 @@ impl<E: Endpoint, H: PanicHandler> Middleware<E> for CatchPanic<H> {
 pub struct CatchPanicEndpoint<E, H> {
     inner: E,
     panic_handler: H,
+    reporter: Option<Reporter>,
 }
 
 impl<E: Endpoint, H: PanicHandler> Endpoint for CatchPanicEndpoint<E, H> {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
+        let request_info = self
+            .reporter
+            .as_ref()
+            .map(|_| (req.method().clone(), req.original_uri().clone()));
+
         match AssertUnwindSafe(self.inner.call(req)).catch_unwind().await {
             Ok(resp) => resp.map(IntoResponse::into_response),
-            Err(err) => Ok(self.panic_handler.get_response(err).into_response()),
+            Err(err) => {
+                if let (Some(reporter), Some((method, uri))) = (&self.reporter, request_info) {
+                    let backtrace = BACKTRACE
+                        .with(|backtrace| backtrace.borrow_mut().take())
+                        .unwrap_or_else(Backtrace::disabled);
+                    reporter(PanicReport {
+                        method,
+                        uri,
+                        message: panic_message(&*err).map(ToString::to_string),
+                        backtrace,
+                    })
+                    .await;
+                }
+                Ok(self.panic_handler.get_response(err).into_response())
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[tokio::test]
+    async fn reporter() {
+        #[handler(internal)]
+        async fn index() {
+            tokio::task::yield_now().await;
+            panic!("oops {}", 42)
+        }
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let app = index.with(
+            CatchPanic::new()
+                .with_reporter({
+                    let reports = reports.clone();
+                    move |report: PanicReport| {
+                        let reports = reports.clone();
+                        async move {
+                            reports
+                                .lock()
+                                .push((report.method, report.uri, report.message));
+                        }
+                    }
+                })
+                .with_handler(|err: Box<dyn Any + Send>| {
+                    panic_message(&*err).unwrap_or_default().to_string()
+                }),
+        );
+
+        let resp = TestClient::new(app).post("/a").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("oops 42").await;
+        assert_eq!(
+            *reports.lock(),
+            [(
+                Method::POST,
+                Uri::from_static("/a"),
+                Some("oops 42".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn message() {
+        let err = std::panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(panic_message(&*err), Some("static"));
+        let err = std::panic::catch_unwind(|| std::panic::panic_any(1)).unwrap_err();
+        assert_eq!(panic_message(&*err), None);
+    }
+}
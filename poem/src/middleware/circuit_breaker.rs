@@ -0,0 +1,514 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use http::StatusCode;
+use parking_lot::Mutex;
+
+use crate::{
+    Endpoint, IntoResponse, Middleware, Request, Response, Result, error::CircuitOpenError,
+};
+
+/// Middleware that stops calling the inner endpoint for a while when it fails
+/// too often.
+///
+/// The outcomes of the requests in the last `window` are recorded. When at
+/// least `min_requests` requests were made in the window and the ratio of
+/// failures reaches `failure_threshold`, the circuit opens and every request
+/// is rejected with `503 Service Unavailable` for `cooldown`. After the
+/// cooldown, the circuit is half-open: up to `half_open_requests` trial
+/// requests are passed to the inner endpoint, the circuit closes again if all
+/// of them succeed, and opens again as soon as one of them fails.
+///
+/// A request fails if the inner endpoint returns a response or an error whose
+/// status code is one of the failure status codes (`500`, `502`, `503` and
+/// `504` by default). With [`CircuitBreaker::failure_on_any_error`], every
+/// error is a failure.
+///
+/// The state is shared by all requests to the endpoint that the middleware
+/// wraps.
+///
+/// # Errors
+///
+/// - [`CircuitOpenError`]
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{EndpointExt, Route, get, handler, middleware::CircuitBreaker};
+///
+/// #[handler]
+/// async fn upstream() {}
+///
+/// let app = Route::new().at(
+///     "/",
+///     get(upstream).with(
+///         CircuitBreaker::new()
+///             .failure_threshold(0.5)
+///             .window(Duration::from_secs(10))
+///             .cooldown(Duration::from_secs(30)),
+///     ),
+/// );
+/// ```
+pub struct CircuitBreaker {
+    config: Config,
+}
+
+#[derive(Clone)]
+struct Config {
+    failure_threshold: f64,
+    window: Duration,
+    cooldown: Duration,
+    min_requests: usize,
+    half_open_requests: usize,
+    failure_status: HashSet<StatusCode>,
+    failure_on_any_error: bool,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    /// Create `CircuitBreaker` middleware.
+    ///
+    /// By default, the circuit opens when half of at least 10 requests in the
+    /// last 10s failed, and stays open for 30s.
+    pub fn new() -> Self {
+        Self {
+            config: Config {
+                failure_threshold: 0.5,
+                window: Duration::from_secs(10),
+                cooldown: Duration::from_secs(30),
+                min_requests: 10,
+                half_open_requests: 1,
+                failure_status: [
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    StatusCode::BAD_GATEWAY,
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    StatusCode::GATEWAY_TIMEOUT,
+                ]
+                .into_iter()
+                .collect(),
+                failure_on_any_error: false,
+            },
+        }
+    }
+
+    /// Sets the ratio of failed requests, between `0.0` and `1.0`, that opens
+    /// the circuit.
+    #[must_use]
+    pub fn failure_threshold(mut self, failure_threshold: f64) -> Self {
+        self.config.failure_threshold = failure_threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the duration over which the failure ratio is computed.
+    #[must_use]
+    pub fn window(mut self, window: Duration) -> Self {
+        self.config.window = window;
+        self
+    }
+
+    /// Sets how long the circuit stays open before trial requests are
+    /// allowed.
+    #[must_use]
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.config.cooldown = cooldown;
+        self
+    }
+
+    /// Sets the minimum number of requests in the window before the circuit
+    /// can open.
+    #[must_use]
+    pub fn min_requests(mut self, min_requests: usize) -> Self {
+        self.config.min_requests = min_requests.max(1);
+        self
+    }
+
+    /// Sets the number of trial requests allowed while the circuit is
+    /// half-open.
+    #[must_use]
+    pub fn half_open_requests(mut self, half_open_requests: usize) -> Self {
+        self.config.half_open_requests = half_open_requests.max(1);
+        self
+    }
+
+    /// Sets the status codes of the responses and errors that count as
+    /// failures.
+    #[must_use]
+    pub fn failure_status(mut self, status_codes: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.config.failure_status = status_codes.into_iter().collect();
+        self
+    }
+
+    /// If `true`, every error returned by the inner endpoint counts as a
+    /// failure, whatever its status code.
+    ///
+    /// Default is `false`, so that client errors such as a rejected request
+    /// body do not open the circuit.
+    #[must_use]
+    pub fn failure_on_any_error(mut self, enable: bool) -> Self {
+        self.config.failure_on_any_error = enable;
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for CircuitBreaker {
+    type Output = CircuitBreakerEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CircuitBreakerEndpoint {
+            inner: ep,
+            config: self.config.clone(),
+            state: Arc::new(Mutex::new(State::Closed {
+                outcomes: VecDeque::new(),
+            })),
+        }
+    }
+}
+
+enum State {
+    Closed {
+        /// The end time and whether it failed, of the requests in the window.
+        outcomes: VecDeque<(Instant, bool)>,
+    },
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        trials: usize,
+        successes: usize,
+    },
+}
+
+impl State {
+    fn open(cooldown: Duration) -> Self {
+        State::Open {
+            until: Instant::now() + cooldown,
+        }
+    }
+
+    fn closed() -> Self {
+        State::Closed {
+            outcomes: VecDeque::new(),
+        }
+    }
+}
+
+/// Reopens the circuit if a trial request is dropped before it completes.
+struct TrialGuard<'a> {
+    state: &'a Mutex<State>,
+    cooldown: Duration,
+    armed: bool,
+}
+
+impl Drop for TrialGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            *self.state.lock() = State::open(self.cooldown);
+        }
+    }
+}
+
+/// Endpoint for the CircuitBreaker middleware.
+pub struct CircuitBreakerEndpoint<E> {
+    inner: E,
+    config: Config,
+    state: Arc<Mutex<State>>,
+}
+
+impl<E> CircuitBreakerEndpoint<E> {
+    /// Returns `Some(true)` for a trial request, `Some(false)` for a request
+    /// with the circuit closed and `None` if the request is rejected.
+    fn acquire(&self) -> Option<bool> {
+        let mut state = self.state.lock();
+        if let State::Open { until } = *state {
+            if Instant::now() < until {
+                return None;
+            }
+            *state = State::HalfOpen {
+                trials: 0,
+                successes: 0,
+            };
+        }
+        match &mut *state {
+            State::Closed { .. } => Some(false),
+            State::HalfOpen { trials, .. } if *trials < self.config.half_open_requests => {
+                *trials += 1;
+                Some(true)
+            }
+            _ => None,
+        }
+    }
+
+    fn record(&self, trial: bool, failed: bool) {
+        let mut state = self.state.lock();
+        match &mut *state {
+            State::HalfOpen { successes, .. } if trial => {
+                if failed {
+                    *state = State::open(self.config.cooldown);
+                } else {
+                    *successes += 1;
+                    if *successes >= self.config.half_open_requests {
+                        *state = State::closed();
+                    }
+                }
+            }
+            State::Closed { outcomes } if !trial => {
+                let now = Instant::now();
+                outcomes.push_back((now, failed));
+                while let Some((time, _)) = outcomes.front() {
+                    if now.duration_since(*time) <= self.config.window {
+                        break;
+                    }
+                    outcomes.pop_front();
+                }
+
+                let failures = outcomes.iter().filter(|(_, failed)| *failed).count();
+                if outcomes.len() >= self.config.min_requests
+                    && failures as f64 >= outcomes.len() as f64 * self.config.failure_threshold
+                {
+                    *state = State::open(self.config.cooldown);
+                }
+            }
+            // the state changed while the request was running
+            _ => {}
+        }
+    }
+}
+
+impl<E: Endpoint> Endpoint for CircuitBreakerEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let trial = self.acquire().ok_or(CircuitOpenError)?;
+        let mut guard = TrialGuard {
+            state: &self.state,
+            cooldown: self.config.cooldown,
+            armed: trial,
+        };
+
+        let res = self.inner.call(req).await.map(IntoResponse::into_response);
+        let failed = match &res {
+            Ok(resp) => self.config.failure_status.contains(&resp.status()),
+            Err(err) => {
+                self.config.failure_on_any_error
+                    || self.config.failure_status.contains(&err.status())
+            }
+        };
+        guard.armed = false;
+        self.record(trial, failed);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        EndpointExt, Error,
+        endpoint::{make, make_sync},
+        test::TestClient,
+    };
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new()
+            .failure_threshold(0.5)
+            .min_requests(4)
+            .window(Duration::from_secs(10))
+            .cooldown(Duration::from_millis(50))
+    }
+
+    #[tokio::test]
+    async fn open_and_recover() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let healthy = Arc::new(AtomicBool::new(false));
+        let ep = make({
+            let calls = calls.clone();
+            let healthy = healthy.clone();
+            move |_| {
+                let calls = calls.clone();
+                let healthy = healthy.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    if healthy.load(Ordering::SeqCst) {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::BAD_GATEWAY
+                    }
+                }
+            }
+        });
+        let cli = TestClient::new(ep.with(breaker()));
+
+        for _ in 0..4 {
+            cli.get("/")
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_GATEWAY);
+        }
+        // the circuit is open, the upstream is not called
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // a failed trial request opens the circuit again
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_GATEWAY);
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        // a successful trial request closes the circuit
+        healthy.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        for _ in 0..3 {
+            cli.get("/").send().await.assert_status_is_ok();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 8);
+    }
+
+    #[tokio::test]
+    async fn below_threshold() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let healthy = Arc::new(AtomicBool::new(false));
+        let ep = make({
+            let calls = calls.clone();
+            let healthy = healthy.clone();
+            move |_| {
+                let calls = calls.clone();
+                let healthy = healthy.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    if healthy.load(Ordering::SeqCst) {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::BAD_GATEWAY
+                    }
+                }
+            }
+        });
+        let cli = TestClient::new(ep.with(breaker().failure_status([StatusCode::BAD_GATEWAY])));
+
+        for i in 0..10 {
+            healthy.store(i % 4 != 0, Ordering::SeqCst);
+            cli.get("/").send().await;
+        }
+        healthy.store(true, Ordering::SeqCst);
+        cli.get("/").send().await.assert_status_is_ok();
+        assert_eq!(calls.load(Ordering::SeqCst), 11);
+    }
+
+    #[tokio::test]
+    async fn not_failure_status() {
+        let cli = TestClient::new(
+            make_sync(|_| StatusCode::BAD_GATEWAY)
+                .with(breaker().failure_status([StatusCode::BAD_REQUEST])),
+        );
+        for _ in 0..5 {
+            cli.get("/")
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_GATEWAY);
+        }
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        // the status of an error is checked like the status of a response
+        let cli = TestClient::new(
+            make(|_| async { Err::<(), _>(Error::from_status(StatusCode::BAD_REQUEST)) })
+                .with(breaker()),
+        );
+        for _ in 0..5 {
+            cli.get("/")
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_REQUEST);
+        }
+
+        let cli = TestClient::new(
+            make(|_| async { Err::<(), _>(Error::from_status(StatusCode::BAD_GATEWAY)) })
+                .with(breaker()),
+        );
+        for _ in 0..4 {
+            cli.get("/")
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_GATEWAY);
+        }
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        let cli = TestClient::new(
+            make(|_| async { Err::<(), _>(Error::from_status(StatusCode::BAD_REQUEST)) })
+                .with(breaker().failure_on_any_error(true)),
+        );
+        for _ in 0..4 {
+            cli.get("/")
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_REQUEST);
+        }
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn half_open_limit() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let ep = make({
+            let calls = calls.clone();
+            move |_| {
+                let calls = calls.clone();
+                async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        StatusCode::BAD_GATEWAY
+                    } else {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        StatusCode::OK
+                    }
+                }
+            }
+        });
+        let cli = TestClient::new(ep.with(breaker().min_requests(2).half_open_requests(2)));
+
+        cli.get("/").send().await;
+        cli.get("/").send().await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        // only two trial requests are allowed at the same time
+        let (a, b, c) = tokio::join!(
+            cli.get("/").send(),
+            cli.get("/").send(),
+            cli.get("/").send()
+        );
+        a.assert_status_is_ok();
+        b.assert_status_is_ok();
+        c.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        // both trials succeeded
+        cli.get("/").send().await.assert_status_is_ok();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+}
@@ -1,5 +1,6 @@
-use std::{collections::HashSet, str::FromStr};
+use std::str::FromStr;
 
+use futures_util::{StreamExt, stream};
 use headers::HeaderMap;
 
 use crate::{
@@ -8,68 +9,96 @@ use crate::{
     web::{Compress, CompressionAlgo, CompressionLevel},
 };
 
-enum ContentCoding {
-    Brotli,
-    Deflate,
-    Gzip,
-    Star,
-    Zstd,
-}
-
-impl FromStr for ContentCoding {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.eq_ignore_ascii_case("deflate") {
-            Ok(ContentCoding::Deflate)
-        } else if s.eq_ignore_ascii_case("gzip") {
-            Ok(ContentCoding::Gzip)
-        } else if s.eq_ignore_ascii_case("br") {
-            Ok(ContentCoding::Brotli)
-        } else if s == "*" {
-            Ok(ContentCoding::Star)
-        } else if s == "zsdt" {
-            Ok(ContentCoding::Zstd)
-        } else {
-            Err(())
-        }
-    }
-}
+/// The content types that are not compressed when no deny list is specified,
+/// they are already compressed.
+const DEFAULT_DENY_CONTENT_TYPES: [&str; 14] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+    "video/*",
+    "audio/*",
+    "font/woff",
+    "font/woff2",
+    "application/zip",
+    "application/gzip",
+    "application/zstd",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+];
+
+/// The algorithms offered when none are specified, most preferred first.
+const DEFAULT_ALGORITHMS: [CompressionAlgo; 4] = [
+    CompressionAlgo::ZSTD,
+    CompressionAlgo::BR,
+    CompressionAlgo::GZIP,
+    CompressionAlgo::DEFLATE,
+];
 
 fn parse_accept_encoding(
     headers: &HeaderMap,
-    enabled_algorithms: &HashSet<CompressionAlgo>,
-) -> Option<ContentCoding> {
-    headers
+    algorithms: &[CompressionAlgo],
+) -> Option<CompressionAlgo> {
+    let algorithms = if algorithms.is_empty() {
+        &DEFAULT_ALGORITHMS[..]
+    } else {
+        algorithms
+    };
+
+    // `None` is the `*` wildcard, and `q` is `0` for the rejected codings.
+    let codings = headers
         .get_all(header::ACCEPT_ENCODING)
         .iter()
         .filter_map(|hval| hval.to_str().ok())
-        .flat_map(|s| s.split(',').map(str::trim))
+        .flat_map(|s| s.split(','))
         .filter_map(|v| {
-            let (e, q) = match v.split_once(";q=") {
-                Some((e, q)) => (e, (q.parse::<f32>().ok()? * 1000.0) as i32),
-                None => (v, 1000),
+            let mut parts = v.split(';').map(str::trim);
+            let coding = parts.next()?;
+            let q = match parts.find_map(|param| param.strip_prefix("q=")) {
+                Some(q) => ((q.parse::<f32>().ok()? * 1000.0) as i32).max(0),
+                None => 1000,
+            };
+            let algo = match coding {
+                "*" => None,
+                _ => Some(CompressionAlgo::from_str(&coding.to_ascii_lowercase()).ok()?),
             };
-            let coding: ContentCoding = e.parse().ok()?;
-            Some((coding, q))
+            Some((algo, q))
         })
-        .filter(|(encoding, _)| {
-            if !enabled_algorithms.is_empty() {
-                match encoding {
-                    ContentCoding::Brotli => enabled_algorithms.contains(&CompressionAlgo::BR),
-                    ContentCoding::Deflate => {
-                        enabled_algorithms.contains(&CompressionAlgo::DEFLATE)
-                    }
-                    ContentCoding::Gzip => enabled_algorithms.contains(&CompressionAlgo::GZIP),
-                    ContentCoding::Zstd => enabled_algorithms.contains(&CompressionAlgo::ZSTD),
-                    _ => true,
-                }
-            } else {
-                true
-            }
+        .collect::<Vec<_>>();
+
+    // The `*` wildcard only stands for the algorithms that are not listed, so
+    // it never selects an algorithm rejected with `q=0`.
+    let wildcard = algorithms
+        .iter()
+        .position(|algo| !codings.iter().any(|(item, _)| *item == Some(*algo)));
+
+    // The position in `algorithms` is used as the priority, a lower position
+    // is preferred.
+    codings
+        .iter()
+        .filter(|(_, q)| *q > 0)
+        .filter_map(|(algo, q)| {
+            let position = match algo {
+                Some(algo) => algorithms.iter().position(|item| item == algo)?,
+                None => wildcard?,
+            };
+            Some((position, *q))
         })
-        .max_by_key(|(coding, q)| (*q, coding_priority(coding)))
-        .map(|(coding, _)| coding)
+        .max_by_key(|(position, q)| (*q, std::cmp::Reverse(*position)))
+        .map(|(position, _)| algorithms[position])
+}
+
+/// Returns `true` if the content type matches the pattern, a pattern is
+/// either a media type such as `application/zip`, or a type followed by `/*`
+/// such as `image/*`.
+fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(ty) => content_type
+            .split_once('/')
+            .is_some_and(|(content_type, _)| content_type.eq_ignore_ascii_case(ty)),
+        None => pattern.eq_ignore_ascii_case(content_type),
+    }
 }
 
 /// Middleware to decompress the request body and compress the response body.
@@ -77,11 +106,71 @@ fn parse_accept_encoding(
 /// The decompression algorithm is selected according to the request
 /// `Content-Encoding` header, and the compression algorithm is selected
 /// according to the request `Accept-Encoding` header.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, handler,
+///     middleware::Compression,
+///     test::TestClient,
+///     web::{CompressionAlgo, CompressionLevel},
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let ep = index.with(
+///     Compression::new()
+///         .algorithms([CompressionAlgo::GZIP])
+///         .level(CompressionLevel::Precise(4)),
+/// );
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header("Accept-Encoding", "br, gzip;q=0.8")
+///     .send()
+///     .await;
+/// resp.assert_header("Content-Encoding", "gzip");
+/// # });
+/// ```
+///
+/// # Skipped responses
+///
+/// Responses smaller than [`Compression::min_size`] are not compressed, and
+/// responses are compressed according to their `Content-Type`, see
+/// [`Compression::allow_content_types`] and
+/// [`Compression::deny_content_types`].
+///
+/// ```
+/// use poem::{EndpointExt, handler, middleware::Compression, test::TestClient};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let ep = index.with(Compression::new().min_size(1024));
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("Accept-Encoding", "gzip").send().await;
+/// resp.assert_header_is_not_exist("Content-Encoding");
+/// resp.assert_text("hello").await;
+/// # });
+/// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 #[derive(Default)]
 pub struct Compression {
     level: Option<CompressionLevel>,
-    algorithms: HashSet<CompressionAlgo>,
+    algorithms: Vec<CompressionAlgo>,
+    min_size: usize,
+    allow_content_types: Option<Vec<String>>,
+    deny_content_types: Option<Vec<String>>,
 }
 
 impl Compression {
@@ -94,19 +183,83 @@ impl Compression {
     /// Specify the compression level
     #[must_use]
     #[inline]
-    pub fn with_quality(self, level: CompressionLevel) -> Self {
+    pub fn level(self, level: CompressionLevel) -> Self {
         Self {
             level: Some(level),
             ..self
         }
     }
 
-    /// Specify the enabled algorithms (defaults to all)
+    /// Specify the compression level
+    ///
+    /// This is the same as [`Compression::level`].
+    #[must_use]
+    #[inline]
+    pub fn with_quality(self, level: CompressionLevel) -> Self {
+        self.level(level)
+    }
+
+    /// Specify the enabled algorithms, most preferred first (defaults to
+    /// `zstd`, `br`, `gzip`, `deflate`)
+    ///
+    /// When the client accepts several of them with the same quality value,
+    /// the one that appears first is chosen.
     #[must_use]
     #[inline]
     pub fn algorithms(self, algorithms: impl IntoIterator<Item = CompressionAlgo>) -> Self {
+        let mut items = Vec::new();
+        for algo in algorithms {
+            if !items.contains(&algo) {
+                items.push(algo);
+            }
+        }
+        Self {
+            algorithms: items,
+            ..self
+        }
+    }
+
+    /// Specify the minimum size in bytes of a compressed response body
+    /// (defaults to `0`)
+    ///
+    /// A response body with a known length smaller than this is not
+    /// compressed. A body with an unknown length, such as a stream, is
+    /// buffered up to this size before deciding.
+    #[must_use]
+    #[inline]
+    pub fn min_size(self, min_size: usize) -> Self {
+        Self { min_size, ..self }
+    }
+
+    /// Specify the content types of the compressed responses
+    ///
+    /// A content type is either a media type such as `text/html`, or a type
+    /// followed by `/*` such as `text/*`. When specified, only the responses
+    /// with a matching `Content-Type` are compressed.
+    #[must_use]
+    pub fn allow_content_types(
+        self,
+        content_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
         Self {
-            algorithms: algorithms.into_iter().collect(),
+            allow_content_types: Some(content_types.into_iter().map(Into::into).collect()),
+            ..self
+        }
+    }
+
+    /// Specify the content types of the responses that are not compressed
+    ///
+    /// A content type is either a media type such as `application/zip`, or a
+    /// type followed by `/*` such as `video/*`. Defaults to the common
+    /// formats that are already compressed, such as images, videos and
+    /// archives.
+    #[must_use]
+    pub fn deny_content_types(
+        self,
+        content_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            deny_content_types: Some(content_types.into_iter().map(Into::into).collect()),
             ..self
         }
     }
@@ -120,6 +273,14 @@ impl<E: Endpoint> Middleware<E> for Compression {
             ep,
             level: self.level,
             algorithms: self.algorithms.clone(),
+            min_size: self.min_size,
+            allow_content_types: self.allow_content_types.clone(),
+            deny_content_types: self.deny_content_types.clone().unwrap_or_else(|| {
+                DEFAULT_DENY_CONTENT_TYPES
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect()
+            }),
         }
     }
 }
@@ -129,17 +290,73 @@ impl<E: Endpoint> Middleware<E> for Compression {
 pub struct CompressionEndpoint<E: Endpoint> {
     ep: E,
     level: Option<CompressionLevel>,
-    algorithms: HashSet<CompressionAlgo>,
+    algorithms: Vec<CompressionAlgo>,
+    min_size: usize,
+    allow_content_types: Option<Vec<String>>,
+    deny_content_types: Vec<String>,
 }
 
-#[inline]
-fn coding_priority(c: &ContentCoding) -> u8 {
-    match *c {
-        ContentCoding::Deflate => 1,
-        ContentCoding::Gzip => 2,
-        ContentCoding::Brotli => 3,
-        ContentCoding::Zstd => 4,
-        _ => 0,
+impl<E: Endpoint> CompressionEndpoint<E> {
+    fn is_compressible(&self, content_type: Option<&str>) -> bool {
+        let Some(content_type) = content_type else {
+            return self.allow_content_types.is_none();
+        };
+        let content_type = content_type.split(';').next().unwrap_or_default().trim();
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| content_type_matches(pattern, content_type))
+        };
+        match &self.allow_content_types {
+            Some(allow) => matches(allow),
+            None => !matches(&self.deny_content_types),
+        }
+    }
+
+    /// Returns `true` if the body is at least `min_size` bytes, the body of a
+    /// stream is buffered up to `min_size` bytes to find out.
+    async fn check_min_size(&self, resp: &mut Response) -> bool {
+        if self.min_size == 0 {
+            return true;
+        }
+
+        let body = resp.take_body();
+        let len = resp
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+            .or_else(|| body.exact_len());
+        if let Some(len) = len {
+            resp.set_body(body);
+            return len >= self.min_size as u64;
+        }
+
+        let mut body = body.into_bytes_stream();
+        let mut chunks = Vec::new();
+        let mut size = 0;
+        while size < self.min_size {
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    size += chunk.len();
+                    chunks.push(chunk);
+                }
+                Some(Err(err)) => {
+                    resp.set_body(Body::from_bytes_stream(
+                        stream::iter(chunks.into_iter().map(Ok))
+                            .chain(stream::once(async move { Err(err) })),
+                    ));
+                    return false;
+                }
+                None => {
+                    resp.set_body(chunks.concat());
+                    return false;
+                }
+            }
+        }
+        resp.set_body(Body::from_bytes_stream(
+            stream::iter(chunks.into_iter().map(Ok)).chain(body),
+        ));
+        true
     }
 }
 
@@ -159,24 +376,25 @@ impl<E: Endpoint> Endpoint for CompressionEndpoint<E> {
         }
 
         // negotiate content-encoding
-        let compress_algo =
-            parse_accept_encoding(req.headers(), &self.algorithms).map(|coding| match coding {
-                ContentCoding::Gzip => CompressionAlgo::GZIP,
-                ContentCoding::Deflate => CompressionAlgo::DEFLATE,
-                ContentCoding::Brotli => CompressionAlgo::BR,
-                ContentCoding::Star | ContentCoding::Zstd => CompressionAlgo::ZSTD,
-            });
-
-        let resp = self.ep.call(req).await?;
+        let compress_algo = parse_accept_encoding(req.headers(), &self.algorithms);
+
+        let mut resp = self.ep.call(req).await?.into_response();
+        if resp.headers().contains_key(header::CONTENT_ENCODING) {
+            // already encoded, e.g. a precompressed static file
+            return Ok(resp);
+        }
         match compress_algo {
-            Some(algo) => {
+            Some(algo)
+                if self.is_compressible(resp.content_type())
+                    && self.check_min_size(&mut resp).await =>
+            {
                 let mut compress = Compress::new(resp, algo);
                 if let Some(level) = self.level {
                     compress = compress.with_quality(level);
                 }
                 Ok(compress.into_response())
             }
-            None => Ok(resp.into_response()),
+            _ => Ok(resp),
         }
     }
 }
@@ -310,4 +528,171 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_header("Content-Encoding", "br");
     }
+
+    #[tokio::test]
+    async fn test_algorithms_order() {
+        let ep = index
+            .with(Compression::default().algorithms([CompressionAlgo::GZIP, CompressionAlgo::BR]));
+        let cli = TestClient::new(ep);
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "br, gzip")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header("Content-Encoding", "gzip");
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "br; q=0.9, gzip; q=0.5")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header("Content-Encoding", "br");
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "*")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header("Content-Encoding", "gzip");
+
+        // the wildcard does not select a rejected algorithm
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip;q=0, *")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header("Content-Encoding", "br");
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip;q=0, br;q=0, *")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist("Content-Encoding");
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip;q=0, zstd")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist("Content-Encoding");
+    }
+
+    #[tokio::test]
+    async fn test_level() {
+        let ep = index.with(
+            Compression::new()
+                .algorithms([CompressionAlgo::GZIP])
+                .level(CompressionLevel::Precise(4)),
+        );
+        let cli = TestClient::new(ep);
+
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header("Content-Encoding", "gzip");
+
+        let mut data = Vec::new();
+        let mut reader = CompressionAlgo::GZIP.decompress(resp.0.into_body().into_async_read());
+        reader.read_to_end(&mut data).await.unwrap();
+        assert_eq!(data, DATA_REV.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_min_size() {
+        let cli = TestClient::new(index.with(Compression::new().min_size(DATA.len())));
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_header("Content-Encoding", "gzip");
+
+        let cli = TestClient::new(index.with(Compression::new().min_size(DATA.len() + 1)));
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_header_is_not_exist("Content-Encoding");
+        resp.assert_text(DATA_REV).await;
+    }
+
+    #[tokio::test]
+    async fn test_min_size_stream() {
+        #[handler(internal)]
+        fn stream_body() -> Body {
+            Body::from_bytes_stream(futures_util::stream::iter(
+                DATA.as_bytes()
+                    .chunks(10)
+                    .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec())),
+            ))
+        }
+
+        let cli = TestClient::new(stream_body.with(Compression::new().min_size(DATA.len())));
+        let resp = cli.get("/").header("Accept-Encoding", "gzip").send().await;
+        resp.assert_header("Content-Encoding", "gzip");
+
+        let mut data = Vec::new();
+        let mut reader = CompressionAlgo::GZIP.decompress(resp.0.into_body().into_async_read());
+        reader.read_to_end(&mut data).await.unwrap();
+        assert_eq!(data, DATA.as_bytes());
+
+        let cli = TestClient::new(stream_body.with(Compression::new().min_size(DATA.len() + 1)));
+        let resp = cli.get("/").header("Accept-Encoding", "gzip").send().await;
+        resp.assert_header_is_not_exist("Content-Encoding");
+        resp.assert_text(DATA).await;
+    }
+
+    #[tokio::test]
+    async fn test_content_types() {
+        #[handler(internal)]
+        fn image() -> Response {
+            Response::builder()
+                .content_type("image/png; charset=binary")
+                .body(DATA)
+        }
+
+        let cli = TestClient::new(image.with(Compression::new()));
+        let resp = cli.get("/").header("Accept-Encoding", "gzip").send().await;
+        resp.assert_header_is_not_exist("Content-Encoding");
+        resp.assert_text(DATA).await;
+
+        let cli = TestClient::new(image.with(Compression::new().deny_content_types(["video/*"])));
+        let resp = cli.get("/").header("Accept-Encoding", "gzip").send().await;
+        resp.assert_header("Content-Encoding", "gzip");
+
+        let ep = Compression::new().allow_content_types(["text/*"]);
+        let cli = TestClient::new(image.with(ep));
+        let resp = cli.get("/").header("Accept-Encoding", "gzip").send().await;
+        resp.assert_header_is_not_exist("Content-Encoding");
+
+        let cli = TestClient::new(index.with(Compression::new().allow_content_types(["TEXT/*"])));
+        let resp = cli
+            .post("/")
+            .header("Accept-Encoding", "gzip")
+            .body(DATA)
+            .send()
+            .await;
+        resp.assert_header("Content-Encoding", "gzip");
+    }
 }
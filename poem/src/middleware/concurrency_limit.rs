@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::{Endpoint, Middleware, Request, Result, error::ConcurrencyLimitError};
+
+/// What the [`ConcurrencyLimit`] middleware does when all permits are taken.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum WhenFull {
+    /// Wait until a permit is released.
+    #[default]
+    Queue,
+    /// Immediately reject the request with `503 Service Unavailable`.
+    Reject,
+}
+
+/// Middleware for limiting the number of requests that execute the inner
+/// endpoint concurrently.
+///
+/// The permit is held until the inner endpoint completes, and is released
+/// even if it panics or the request future is dropped.
+///
+/// # Errors
+///
+/// - [`ConcurrencyLimitError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler,
+///     middleware::{ConcurrencyLimit, WhenFull},
+/// };
+///
+/// #[handler]
+/// async fn index() {}
+///
+/// let app = Route::new()
+///     .at("/", index)
+///     .with(ConcurrencyLimit::new(64).when_full(WhenFull::Reject));
+/// ```
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    when_full: WhenFull,
+}
+
+impl ConcurrencyLimit {
+    /// Create `ConcurrencyLimit` middleware that allows at most `max`
+    /// concurrent requests.
+    pub fn new(max: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max)),
+            when_full: WhenFull::Queue,
+        }
+    }
+
+    /// Sets what to do when all permits are taken.
+    ///
+    /// Default is [`WhenFull::Queue`].
+    #[must_use]
+    pub fn when_full(self, when_full: WhenFull) -> Self {
+        Self { when_full, ..self }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ConcurrencyLimit {
+    type Output = ConcurrencyLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ConcurrencyLimitEndpoint {
+            inner: ep,
+            semaphore: self.semaphore.clone(),
+            when_full: self.when_full,
+        }
+    }
+}
+
+/// Endpoint for the ConcurrencyLimit middleware.
+pub struct ConcurrencyLimitEndpoint<E> {
+    inner: E,
+    semaphore: Arc<Semaphore>,
+    when_full: WhenFull,
+}
+
+impl<E: Endpoint> Endpoint for ConcurrencyLimitEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let _permit = match self.when_full {
+            WhenFull::Queue => self
+                .semaphore
+                .acquire()
+                .await
+                .map_err(|_| ConcurrencyLimitError)?,
+            WhenFull::Reject => self
+                .semaphore
+                .try_acquire()
+                .map_err(|_| ConcurrencyLimitError)?,
+        };
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, middleware::CatchPanic, test::TestClient};
+
+    #[handler(internal)]
+    async fn index(req: &Request) {
+        if req.header("x-panic").is_some() {
+            panic!();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_reject() {
+        let cli = TestClient::new(index.with(ConcurrencyLimit::new(1).when_full(WhenFull::Reject)));
+
+        let (a, b) = tokio::join!(cli.get("/").send(), async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cli.get("/").send().await
+        });
+        a.assert_status_is_ok();
+        b.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        cli.get("/").send().await.assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_queue() {
+        let cli = TestClient::new(index.with(ConcurrencyLimit::new(1)));
+
+        let (a, b) = tokio::join!(cli.get("/").send(), cli.get("/").send());
+        a.assert_status_is_ok();
+        b.assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_release_on_panic() {
+        let cli = TestClient::new(
+            index
+                .with(ConcurrencyLimit::new(1).when_full(WhenFull::Reject))
+                .with(CatchPanic::new()),
+        );
+
+        cli.get("/")
+            .header("x-panic", "1")
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        cli.get("/").send().await.assert_status_is_ok();
+    }
+}
@@ -0,0 +1,241 @@
+use std::{str::FromStr, time::SystemTime};
+
+use headers::{ETag, HeaderMapExt, IfModifiedSince, IfNoneMatch, LastModified};
+use http::{Method, StatusCode, header};
+
+use crate::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Middleware for handling conditional `GET` and `HEAD` requests.
+///
+/// For successful responses with a buffered body no larger than
+/// [`max_body_size`](Conditional::max_body_size), a strong `ETag` is computed
+/// from the body and added to the response. If the inner endpoint already set
+/// an `ETag` header, that value is used as is.
+///
+/// The response is replaced with an empty `304 Not Modified` when the
+/// `If-None-Match` header of the request matches the `ETag`, or, if no
+/// `If-None-Match` is present, when the `If-Modified-Since` header is not older
+/// than the `Last-Modified` header of the response.
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, handler, http::StatusCode, middleware::Conditional, test::TestClient};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let cli = TestClient::new(index.with(Conditional::new()));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// let etag = resp.0.header("etag").unwrap().to_string();
+///
+/// let resp = cli.get("/").header("if-none-match", etag).send().await;
+/// resp.assert_status(StatusCode::NOT_MODIFIED);
+/// # });
+/// ```
+pub struct Conditional {
+    max_body_size: usize,
+}
+
+impl Default for Conditional {
+    fn default() -> Self {
+        Self {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+}
+
+impl Conditional {
+    /// Create `Conditional` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum size of the response body used to compute the
+    /// `ETag`.
+    ///
+    /// Bodies that are larger or whose size is not known in advance are left
+    /// untouched. Default is `1MB`.
+    #[must_use]
+    pub fn max_body_size(self, max_body_size: usize) -> Self {
+        Self { max_body_size }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Conditional {
+    type Output = ConditionalEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ConditionalEndpoint {
+            inner: ep,
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+/// Endpoint for the Conditional middleware.
+pub struct ConditionalEndpoint<E> {
+    inner: E,
+    max_body_size: usize,
+}
+
+/// Uses the 64-bit FNV-1a hash, which unlike `DefaultHasher` is stable across
+/// processes and Rust versions.
+fn compute_etag(data: &[u8]) -> ETag {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    ETag::from_str(&format!("\"{:x}-{hash:016x}\"", data.len())).unwrap()
+}
+
+impl<E: Endpoint> Endpoint for ConditionalEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let method = req.method().clone();
+        let if_none_match = req.headers().typed_get::<IfNoneMatch>();
+        let if_modified_since = req.headers().typed_get::<IfModifiedSince>();
+        let mut resp = self.inner.call(req).await?.into_response();
+
+        if !matches!(method, Method::GET | Method::HEAD) || resp.status() != StatusCode::OK {
+            return Ok(resp);
+        }
+
+        let etag = match resp.headers().typed_get::<ETag>() {
+            Some(etag) => Some(etag),
+            None => {
+                let body = resp.take_body();
+                match hyper::body::Body::size_hint(&body.0).exact() {
+                    Some(size) if size as usize <= self.max_body_size => {
+                        let data = body.into_bytes().await?;
+                        let etag = compute_etag(&data);
+                        resp.headers_mut().typed_insert(etag.clone());
+                        resp.set_body(data);
+                        Some(etag)
+                    }
+                    _ => {
+                        resp.set_body(body);
+                        None
+                    }
+                }
+            }
+        };
+
+        let not_modified = match (if_none_match, etag) {
+            (Some(if_none_match), Some(etag)) => !if_none_match.precondition_passes(&etag),
+            (Some(_), None) => false,
+            (None, _) => match (
+                if_modified_since,
+                resp.headers().typed_get::<LastModified>(),
+            ) {
+                (Some(if_modified_since), Some(last_modified)) => {
+                    !if_modified_since.is_modified(SystemTime::from(last_modified))
+                }
+                _ => false,
+            },
+        };
+
+        if not_modified {
+            resp.set_status(StatusCode::NOT_MODIFIED);
+            resp.set_body(Body::empty());
+            resp.headers_mut().remove(header::CONTENT_TYPE);
+            resp.headers_mut().remove(header::CONTENT_LENGTH);
+        }
+
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[tokio::test]
+    async fn etag() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let cli = TestClient::new(index.with(Conditional::new()));
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let etag = resp.0.header("etag").unwrap().to_string();
+        // stable across processes
+        assert_eq!(etag, "\"5-a430d84680aabd0b\"");
+        resp.assert_text("hello").await;
+
+        let resp = cli.get("/").header("if-none-match", &etag).send().await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+        resp.assert_header("etag", &etag);
+        resp.assert_text("").await;
+
+        let resp = cli
+            .get("/")
+            .header("if-none-match", "\"other\"")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello").await;
+
+        cli.post("/")
+            .header("if-none-match", &etag)
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn max_body_size() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let cli = TestClient::new(index.with(Conditional::new().max_body_size(4)));
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist("etag");
+        resp.assert_text("hello").await;
+    }
+
+    #[tokio::test]
+    async fn last_modified() {
+        #[handler(internal)]
+        fn index() -> Response {
+            let mut resp = Response::builder().body(Body::from_async_read(&b"hello"[..]));
+            resp.headers_mut().typed_insert(LastModified::from(
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000),
+            ));
+            resp
+        }
+
+        let cli = TestClient::new(index.with(Conditional::new()));
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist("etag");
+        let last_modified = resp.0.header("last-modified").unwrap().to_string();
+
+        cli.get("/")
+            .header("if-modified-since", &last_modified)
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+
+        cli.get("/")
+            .header("if-modified-since", "Thu, 01 Jan 1970 00:00:00 GMT")
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use http::header;
+use mime::Mime;
+
+use crate::{Endpoint, Middleware, Request, Result, error::ConsumesError};
+
+/// Parses the media types of the [`Consumes`] and
+/// [`Produces`](crate::middleware::Produces) middleware.
+///
+/// # Panics
+///
+/// Panics if a media type is invalid.
+pub(crate) fn parse_media_types<T: AsRef<str>>(types: impl IntoIterator<Item = T>) -> Arc<[Mime]> {
+    types
+        .into_iter()
+        .map(|ty| {
+            let ty = ty.as_ref();
+            ty.parse::<Mime>()
+                .unwrap_or_else(|_| panic!("invalid media type: {ty}"))
+        })
+        .collect()
+}
+
+/// Returns `true` if `mime` matches `pattern`, which can be a `type/*` or
+/// `*/*` wildcard. The parameters are ignored.
+pub(crate) fn media_type_matches(pattern: &Mime, mime: &Mime) -> bool {
+    (pattern.type_() == mime::STAR || pattern.type_() == mime.type_())
+        && (pattern.subtype() == mime::STAR || pattern.subtype() == mime.subtype())
+}
+
+/// Middleware that rejects the requests whose `Content-Type` is not one of
+/// the accepted media types with `415 Unsupported Media Type`, before the
+/// inner endpoint reads the body.
+///
+/// The media types can be `type/*` wildcards, the parameters of the
+/// `Content-Type`, such as `charset`, are ignored. Requests without a
+/// `Content-Type` are also rejected, so it should only wrap the endpoints
+/// that expect a body.
+///
+/// # Errors
+///
+/// - [`ConsumesError`]
+///
+/// # Panics
+///
+/// [`Consumes::new`] panics if a media type is invalid.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler, http::StatusCode, middleware::Consumes, post, test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn upload() {}
+///
+/// let app = Route::new().at(
+///     "/upload",
+///     post(upload.with(Consumes::new(["application/json", "image/*"]))),
+/// );
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.post("/upload")
+///     .content_type("image/png")
+///     .send()
+///     .await
+///     .assert_status_is_ok();
+///
+/// cli.post("/upload")
+///     .content_type("text/plain")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+/// # });
+/// ```
+pub struct Consumes {
+    types: Arc<[Mime]>,
+}
+
+impl Consumes {
+    /// Create `Consumes` middleware that accepts `types`.
+    pub fn new<T: AsRef<str>>(types: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            types: parse_media_types(types),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Consumes {
+    type Output = ConsumesEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ConsumesEndpoint {
+            inner: ep,
+            types: self.types.clone(),
+        }
+    }
+}
+
+/// Endpoint for the Consumes middleware.
+pub struct ConsumesEndpoint<E> {
+    inner: E,
+    types: Arc<[Mime]>,
+}
+
+impl<E: Endpoint> Endpoint for ConsumesEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(ConsumesError::ContentTypeRequired)?;
+        let accepted = content_type.parse::<Mime>().is_ok_and(|mime| {
+            self.types
+                .iter()
+                .any(|pattern| media_type_matches(pattern, &mime))
+        });
+        if !accepted {
+            return Err(ConsumesError::InvalidContentType(content_type.to_string()).into());
+        }
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[test]
+    fn matches() {
+        let matches = |pattern: &str, mime: &str| {
+            media_type_matches(&pattern.parse().unwrap(), &mime.parse().unwrap())
+        };
+        assert!(matches("application/json", "application/json"));
+        assert!(matches(
+            "application/json",
+            "Application/JSON; charset=utf-8"
+        ));
+        assert!(matches("application/*", "application/xml"));
+        assert!(matches("*/*", "text/plain"));
+        assert!(!matches("application/json", "application/xml"));
+        assert!(!matches("application/*", "text/plain"));
+        assert!(!matches("application/json", "application/*"));
+    }
+
+    #[tokio::test]
+    async fn consumes() {
+        let cli = TestClient::new(index.with(Consumes::new(["application/json", "text/*"])));
+
+        for content_type in ["application/json; charset=utf-8", "text/csv"] {
+            cli.post("/")
+                .content_type(content_type)
+                .send()
+                .await
+                .assert_text("hello")
+                .await;
+        }
+
+        for content_type in ["application/xml", "invalid"] {
+            cli.post("/")
+                .content_type(content_type)
+                .send()
+                .await
+                .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+
+        cli.post("/")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid media type: json")]
+    fn invalid_media_type() {
+        let _ = Consumes::new(["json"]);
+    }
+}
@@ -122,4 +122,75 @@ mod tests {
             .await
             .assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn test_cookie_jar_manager_round_trip() {
+        #[handler(internal)]
+        async fn set(cookie_jar: &CookieJar) {
+            cookie_jar
+                .private()
+                .add(Cookie::new_with_str("private", "secret"));
+            cookie_jar
+                .signed()
+                .add(Cookie::new_with_str("signed", "user"));
+        }
+
+        #[handler(internal)]
+        async fn get(cookie_jar: &CookieJar) -> String {
+            format!(
+                "{:?}:{:?}",
+                cookie_jar
+                    .private()
+                    .get("private")
+                    .map(|cookie| cookie.value_str().to_string()),
+                cookie_jar
+                    .signed()
+                    .get("signed")
+                    .map(|cookie| cookie.value_str().to_string()),
+            )
+        }
+
+        let key = CookieKey::generate();
+        let resp = TestClient::new(set.with(CookieJarManager::with_key(key.clone())))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        let cookies = resp
+            .0
+            .headers()
+            .get_all(http::header::SET_COOKIE)
+            .iter()
+            .map(|value| Cookie::parse(value.to_str().unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        let cookie = |name: &str| {
+            cookies
+                .iter()
+                .find(|cookie| cookie.name() == name)
+                .unwrap()
+                .value_str()
+                .to_string()
+        };
+        let private = cookie("private");
+        let signed = cookie("signed");
+        assert!(!private.contains("secret"));
+
+        let cli = TestClient::new(get.with(CookieJarManager::with_key(key)));
+        let resp = cli
+            .get("/")
+            .header("cookie", format!("private={private}; signed={signed}"))
+            .send()
+            .await;
+        resp.assert_text(r#"Some("secret"):Some("user")"#).await;
+
+        // tampered cookies are treated as absent
+        let forged = signed.replace("user", "admin");
+        let resp = cli
+            .get("/")
+            .header("cookie", format!("private={private}x; signed={forged}"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("None:None").await;
+    }
 }
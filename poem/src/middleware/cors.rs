@@ -3,14 +3,15 @@ use std::{collections::HashSet, str::FromStr, sync::Arc};
 use headers::{
     AccessControlAllowHeaders, AccessControlAllowMethods, AccessControlExposeHeaders, HeaderMapExt,
 };
+use regex::Regex;
 use wildmatch::WildMatch;
 
 use crate::{
-    IntoResponse, Result,
-    endpoint::Endpoint,
+    IntoResponse, Result, Route,
+    endpoint::{Endpoint, make_sync},
     error::CorsError,
     http::{
-        Method, header,
+        Method, Uri, header,
         header::{HeaderName, HeaderValue},
     },
     middleware::Middleware,
@@ -39,12 +40,15 @@ use crate::{
 pub struct Cors {
     allow_credentials: bool,
     allow_origins: HashSet<HeaderValue>,
-    allow_origins_wildcard: Vec<WildMatch>,
+    allow_origins_wildcard: Vec<OriginPattern>,
+    allow_origins_glob: Vec<WildMatch>,
+    allow_origins_regex: Vec<Regex>,
     allow_origins_fn: Option<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
     allow_headers: HashSet<HeaderName>,
     allow_methods: HashSet<Method>,
     expose_headers: HashSet<HeaderName>,
     max_age: i32,
+    overrides: Vec<(String, Cors)>,
 }
 
 impl Cors {
@@ -136,14 +140,63 @@ impl Cors {
         self
     }
 
+    /// Add an allowed origin pattern with wildcards.
+    ///
+    /// The scheme always matches exactly. `*.` at the beginning of the host
+    /// matches one or more subdomains, and `*` as the port matches any port,
+    /// including the default port which is omitted from the `Origin` header.
+    ///
+    /// ```
+    /// use poem::middleware::Cors;
+    ///
+    /// let cors = Cors::new()
+    ///     .allow_origin_wildcard("https://*.example.com")
+    ///     .allow_origin_wildcard("http://localhost:*");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is not of the form `scheme://host[:port]`.
+    #[must_use]
+    pub fn allow_origin_wildcard(mut self, pattern: impl AsRef<str>) -> Self {
+        let pattern = match OriginPattern::parse(pattern.as_ref()) {
+            Some(pattern) => pattern,
+            None => panic!("illegal origin pattern"),
+        };
+        self.allow_origins_wildcard.push(pattern);
+        self
+    }
+
     /// Add an allowed origin that supports '*' wildcard.
     /// Example: `rust cors.allow_origin_regex("https://*.domain.url")`
+    ///
+    /// Despite its name, the origin is a glob where `*` matches any
+    /// characters, including `.` and `:`. Prefer
+    /// [`Cors::allow_origin_wildcard`] or [`Cors::allow_origin_pattern_regex`].
     pub fn allow_origin_regex(mut self, origin: impl AsRef<str>) -> Self {
-        self.allow_origins_wildcard
+        self.allow_origins_glob
             .push(WildMatch::new(origin.as_ref()));
         self
     }
 
+    /// Add an allowed origin regex.
+    ///
+    /// The regex must match the whole `Origin` header value, for example
+    /// `r"https://[a-z0-9-]+\.example\.com"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the regex is invalid.
+    #[must_use]
+    pub fn allow_origin_pattern_regex(mut self, regex: impl AsRef<str>) -> Self {
+        let regex = match Regex::new(&format!("^(?:{})$", regex.as_ref())) {
+            Ok(regex) => regex,
+            Err(_) => panic!("illegal origin regex"),
+        };
+        self.allow_origins_regex.push(regex);
+        self
+    }
+
     /// Add many allow origins.
     #[must_use]
     pub fn allow_origins<I, T>(self, origins: I) -> Self
@@ -202,17 +255,83 @@ impl Cors {
         self.max_age = max_age;
         self
     }
-}
 
-impl<E: Endpoint> Middleware<E> for Cors {
-    type Output = CorsEndpoint<E>;
+    /// Use a different configuration for the requests that match `path`.
+    ///
+    /// The path uses the same syntax as [`Route::at`] and is matched against
+    /// the request path as seen by this middleware. The configuration of the
+    /// first matching override replaces this configuration for the request,
+    /// including the preflight requests, so the response never contains the
+    /// `Access-Control-*` headers of both.
+    ///
+    /// The overrides of `cors` itself are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is invalid or is already overridden.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     EndpointExt, Route, handler, http::header, middleware::Cors, post, test::TestClient,
+    /// };
+    ///
+    /// #[handler]
+    /// fn index() {}
+    ///
+    /// let cors = Cors::new()
+    ///     .allow_origin("https://example.com")
+    ///     .override_path(
+    ///         "/webhooks/*",
+    ///         Cors::new().allow_origin("https://partner.example.com"),
+    ///     );
+    /// let app = Route::new()
+    ///     .at("/users", post(index))
+    ///     .at("/webhooks/github", post(index))
+    ///     .with(cors);
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli
+    ///     .post("/webhooks/github")
+    ///     .header(header::ORIGIN, "https://partner.example.com")
+    ///     .send()
+    ///     .await;
+    /// resp.assert_status_is_ok();
+    /// resp.assert_header(
+    ///     header::ACCESS_CONTROL_ALLOW_ORIGIN,
+    ///     "https://partner.example.com",
+    /// );
+    ///
+    /// let resp = cli
+    ///     .post("/users")
+    ///     .header(header::ORIGIN, "https://partner.example.com")
+    ///     .send()
+    ///     .await;
+    /// resp.assert_status(poem::http::StatusCode::FORBIDDEN);
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn override_path(mut self, path: impl Into<String>, cors: Cors) -> Self {
+        let path = path.into();
+        assert!(
+            self.overrides.iter().all(|(p, _)| *p != path),
+            "path `{path}` is already overridden"
+        );
+        self.overrides.push((path, cors));
+        self
+    }
+}
 
-    fn transform(&self, ep: E) -> Self::Output {
-        CorsEndpoint {
-            inner: ep,
+impl Cors {
+    fn config(&self) -> CorsConfig {
+        CorsConfig {
             allow_credentials: self.allow_credentials,
             allow_origins: self.allow_origins.clone(),
             allow_origins_wildcard: self.allow_origins_wildcard.clone(),
+            allow_origins_glob: self.allow_origins_glob.clone(),
+            allow_origins_regex: self.allow_origins_regex.clone(),
             allow_origins_fn: self.allow_origins_fn.clone(),
             allow_headers: self.allow_headers.clone(),
             allow_methods: self.allow_methods.clone(),
@@ -225,13 +344,133 @@ impl<E: Endpoint> Middleware<E> for Cors {
     }
 }
 
-/// Endpoint for Cors middleware.
+impl<E: Endpoint> Middleware<E> for Cors {
+    type Output = CorsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let overrides = (!self.overrides.is_empty()).then(|| {
+            let route =
+                self.overrides
+                    .iter()
+                    .enumerate()
+                    .fold(Route::new(), |route, (idx, (path, _))| {
+                        route.at(
+                            path,
+                            make_sync(move |_| {
+                                Response::builder().extension(OverrideIndex(idx)).finish()
+                            }),
+                        )
+                    });
+            let configs = self
+                .overrides
+                .iter()
+                .map(|(_, cors)| cors.config())
+                .collect();
+            (route, configs)
+        });
+
+        CorsEndpoint {
+            inner: ep,
+            config: self.config(),
+            overrides,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum HostPattern {
+    Exact(String),
+    // The suffix with the leading dot, e.g. `.example.com`
+    Subdomain(String),
+}
+
+#[derive(Debug, Clone)]
+enum PortPattern {
+    Any,
+    Exact(Option<u16>),
+}
+
+#[derive(Debug, Clone)]
+struct OriginPattern {
+    scheme: String,
+    host: HostPattern,
+    port: PortPattern,
+}
+
+impl OriginPattern {
+    fn parse(pattern: &str) -> Option<Self> {
+        let (scheme, authority) = pattern.split_once("://")?;
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, "*")) => (host, PortPattern::Any),
+            Some((host, port)) if !port.ends_with(']') => {
+                (host, PortPattern::Exact(Some(port.parse().ok()?)))
+            }
+            _ => (authority, PortPattern::Exact(None)),
+        };
+        let host = match host.strip_prefix('*') {
+            Some(suffix) if suffix.starts_with('.') => {
+                HostPattern::Subdomain(suffix.to_ascii_lowercase())
+            }
+            _ => HostPattern::Exact(host.to_ascii_lowercase()),
+        };
+        if scheme.is_empty()
+            || matches!(&host, HostPattern::Exact(host) | HostPattern::Subdomain(host)
+                if host.is_empty() || host.contains(['*', '/', '@']))
+        {
+            return None;
+        }
+        Some(Self {
+            scheme: scheme.to_ascii_lowercase(),
+            host,
+            port,
+        })
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        // an origin has no userinfo, path, query or fragment
+        if origin
+            .split_once("://")
+            .is_none_or(|(_, authority)| authority.contains(['@', '/', '?', '#']))
+        {
+            return false;
+        }
+        let Ok(uri) = origin.parse::<Uri>() else {
+            return false;
+        };
+        let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+            return false;
+        };
+        if !scheme.eq_ignore_ascii_case(&self.scheme) {
+            return false;
+        }
+
+        let host = authority.host();
+        let host_matches = match &self.host {
+            HostPattern::Exact(pattern) => host.eq_ignore_ascii_case(pattern),
+            HostPattern::Subdomain(suffix) => {
+                host.len() > suffix.len()
+                    && host.is_char_boundary(host.len() - suffix.len())
+                    && host[host.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+            }
+        };
+        let port_matches = match self.port {
+            PortPattern::Any => true,
+            PortPattern::Exact(port) => authority.port_u16() == port,
+        };
+        host_matches && port_matches
+    }
+}
+
+#[derive(Copy, Clone)]
+struct OverrideIndex(usize);
+
 #[allow(clippy::type_complexity)]
-pub struct CorsEndpoint<E> {
-    inner: E,
+struct CorsConfig {
     allow_credentials: bool,
     allow_origins: HashSet<HeaderValue>,
-    allow_origins_wildcard: Vec<WildMatch>,
+    allow_origins_wildcard: Vec<OriginPattern>,
+    allow_origins_glob: Vec<WildMatch>,
+    allow_origins_regex: Vec<Regex>,
     allow_origins_fn: Option<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
     allow_headers: HashSet<HeaderName>,
     allow_methods: HashSet<Method>,
@@ -242,18 +481,29 @@ pub struct CorsEndpoint<E> {
     max_age: i32,
 }
 
-impl<E: Endpoint> CorsEndpoint<E> {
+/// Endpoint for Cors middleware.
+pub struct CorsEndpoint<E> {
+    inner: E,
+    config: CorsConfig,
+    overrides: Option<(Route, Vec<CorsConfig>)>,
+}
+
+impl CorsConfig {
     fn is_valid_origin(&self, origin: &HeaderValue) -> (bool, bool) {
         if self.allow_origins.contains(origin) {
             return (true, false);
         }
 
-        if self
-            .allow_origins_wildcard
-            .iter()
-            .any(|m| m.matches(origin.to_str().unwrap()))
-        {
-            return (true, true);
+        if let Ok(origin) = origin.to_str() {
+            if self
+                .allow_origins_wildcard
+                .iter()
+                .any(|m| m.matches(origin))
+                || self.allow_origins_glob.iter().any(|m| m.matches(origin))
+                || self.allow_origins_regex.iter().any(|m| m.is_match(origin))
+            {
+                return (true, true);
+            }
         }
 
         if let Some(allow_origins_fn) = &self.allow_origins_fn {
@@ -267,7 +517,9 @@ impl<E: Endpoint> CorsEndpoint<E> {
         (
             self.allow_origins.is_empty()
                 && self.allow_origins_fn.is_none()
-                && self.allow_origins_wildcard.is_empty(),
+                && self.allow_origins_wildcard.is_empty()
+                && self.allow_origins_glob.is_empty()
+                && self.allow_origins_regex.is_empty(),
             true,
         )
     }
@@ -360,7 +612,18 @@ impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
             }
         };
 
-        let (origin_is_allow, vary_header) = self.is_valid_origin(&origin);
+        let config = match &self.overrides {
+            Some((route, configs)) => route
+                .get_response(req.clone_without_body())
+                .await
+                .extensions()
+                .get::<OverrideIndex>()
+                .map(|idx| &configs[idx.0])
+                .unwrap_or(&self.config),
+            None => &self.config,
+        };
+
+        let (origin_is_allow, vary_header) = config.is_valid_origin(&origin);
         if !origin_is_allow {
             return Err(CorsError::OriginNotAllowed.into());
         }
@@ -372,23 +635,23 @@ impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
                 .and_then(|value| value.to_str().ok())
                 .and_then(|value| value.parse::<Method>().ok())
                 .map(|method| {
-                    if self.allow_methods.is_empty() {
+                    if config.allow_methods.is_empty() {
                         true
                     } else {
-                        self.allow_methods.contains(&method)
+                        config.allow_methods.contains(&method)
                     }
                 });
             if !matches!(allow_method, Some(true)) {
                 return Err(CorsError::MethodNotAllowed.into());
             }
 
-            let (allow_headers, request_headers) = self.check_allow_headers(&req);
+            let (allow_headers, request_headers) = config.check_allow_headers(&req);
 
             if !allow_headers {
                 return Err(CorsError::HeadersNotAllowed.into());
             }
 
-            return Ok(self.build_preflight_response(&origin, request_headers));
+            return Ok(config.build_preflight_response(&origin, request_headers));
         }
 
         let mut resp = self.inner.get_response(req).await;
@@ -396,16 +659,16 @@ impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
         resp.headers_mut()
             .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
 
-        if self.allow_credentials {
+        if config.allow_credentials {
             resp.headers_mut().insert(
                 header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
                 HeaderValue::from_static("true"),
             );
         }
 
-        if !self.expose_headers.is_empty() {
+        if !config.expose_headers.is_empty() {
             resp.headers_mut()
-                .typed_insert(self.expose_headers_header.clone());
+                .typed_insert(config.expose_headers_header.clone());
         }
 
         if vary_header {
@@ -722,4 +985,132 @@ mod tests {
         resp.assert_status_is_ok();
         resp.assert_header(header::ACCESS_CONTROL_ALLOW_HEADERS, "content-type");
     }
+
+    #[tokio::test]
+    async fn override_path() {
+        const OTHER_ORIGIN: &str = "https://other.com";
+
+        let ep = Route::new()
+            .at("/users/:id", make_sync(|_| "user"))
+            .at("/public", make_sync(|_| "public"))
+            .with(
+                cors().override_path(
+                    "/users/:id",
+                    Cors::new()
+                        .allow_origin(OTHER_ORIGIN)
+                        .allow_method(Method::PUT),
+                ),
+            );
+        let cli = TestClient::new(ep);
+
+        let resp = cli
+            .options("/users/1")
+            .header(header::ORIGIN, OTHER_ORIGIN)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::ACCESS_CONTROL_ALLOW_ORIGIN, OTHER_ORIGIN);
+        resp.assert_header(header::ACCESS_CONTROL_ALLOW_METHODS, "PUT");
+        resp.assert_header_is_not_exist(header::ACCESS_CONTROL_ALLOW_CREDENTIALS);
+
+        let resp = cli
+            .get("/users/1")
+            .header(header::ORIGIN, OTHER_ORIGIN)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header_all(header::ACCESS_CONTROL_ALLOW_ORIGIN, [OTHER_ORIGIN]);
+        resp.assert_header_is_not_exist(header::ACCESS_CONTROL_EXPOSE_HEADERS);
+        resp.assert_text("user").await;
+
+        // the override replaces the outer configuration
+        let resp = cli
+            .get("/users/1")
+            .header(header::ORIGIN, ALLOW_ORIGIN)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+
+        let resp = cli
+            .get("/public")
+            .header(header::ORIGIN, OTHER_ORIGIN)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        let resp = cli
+            .get("/public")
+            .header(header::ORIGIN, ALLOW_ORIGIN)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(header::ACCESS_CONTROL_EXPOSE_HEADERS, "x-my-custom-header");
+    }
+
+    #[tokio::test]
+    async fn allow_origin_wildcard() {
+        let ep = make_sync(|_| "hello").with(
+            Cors::new()
+                .allow_credentials(true)
+                .allow_origin_wildcard("https://*.example.com")
+                .allow_origin_wildcard("http://localhost:*"),
+        );
+        let cli = TestClient::new(ep);
+
+        for origin in [
+            "https://api.example.com",
+            "https://a.b.Example.com",
+            "http://localhost:3000",
+            "http://localhost:8080",
+            "http://localhost",
+        ] {
+            let resp = cli.get("/").header(header::ORIGIN, origin).send().await;
+            resp.assert_status_is_ok();
+            resp.assert_header(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+            resp.assert_header(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+            resp.assert_header(header::VARY, "Origin");
+        }
+
+        for origin in [
+            "http://api.example.com",
+            "https://example.com",
+            "https://api.example.com:8443",
+            "https://evilexample.com",
+            "https://api.example.com.evil.com",
+            "https://localhost:3000",
+            "http://localhost.evil.com:3000",
+            "null",
+        ] {
+            cli.get("/")
+                .header(header::ORIGIN, origin)
+                .send()
+                .await
+                .assert_status(StatusCode::FORBIDDEN);
+        }
+    }
+
+    #[tokio::test]
+    async fn allow_origin_pattern_regex() {
+        let ep = make_sync(|_| "hello")
+            .with(Cors::new().allow_origin_pattern_regex(r"https://(app|admin)\.example\.com"));
+        let cli = TestClient::new(ep);
+
+        let resp = cli
+            .get("/")
+            .header(header::ORIGIN, "https://admin.example.com")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(
+            header::ACCESS_CONTROL_ALLOW_ORIGIN,
+            "https://admin.example.com",
+        );
+
+        // the regex must match the whole origin
+        cli.get("/")
+            .header(header::ORIGIN, "https://app.example.com.evil.com")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
 }
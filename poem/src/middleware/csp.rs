@@ -0,0 +1,360 @@
+use std::{fmt::Write, sync::Arc};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use http::{HeaderValue, header};
+use rand::{Rng, rng};
+
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+/// A per-request nonce generated by the [`Csp`] middleware.
+///
+/// It is inserted into the request extensions when any directive uses
+/// [`CspSource::Nonce`], so templates can read it with
+/// [`Data<&CspNonce>`](crate::web::Data) and add it to inline `<script>` or
+/// `<style>` elements.
+#[cfg_attr(docsrs, doc(cfg(feature = "csp")))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CspNonce(pub String);
+
+/// A source expression of a `Content-Security-Policy` directive.
+#[cfg_attr(docsrs, doc(cfg(feature = "csp")))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CspSource {
+    /// `'self'`
+    SelfSource,
+    /// `'none'`
+    None,
+    /// `*`
+    Any,
+    /// `'unsafe-inline'`
+    UnsafeInline,
+    /// `'unsafe-eval'`
+    UnsafeEval,
+    /// `'strict-dynamic'`
+    StrictDynamic,
+    /// `'nonce-<value>'`, with a random value generated for each request
+    Nonce,
+    /// `'sha256-<value>'`, the value is a base64 encoded hash
+    Sha256(String),
+    /// A host source, such as `https://example.com` or `*.example.com`
+    Host(String),
+    /// A scheme source, such as `data:` or `https:`
+    Scheme(String),
+}
+
+impl CspSource {
+    fn write_to(&self, s: &mut String, nonce: Option<&str>) {
+        match self {
+            CspSource::SelfSource => s.push_str("'self'"),
+            CspSource::None => s.push_str("'none'"),
+            CspSource::Any => s.push('*'),
+            CspSource::UnsafeInline => s.push_str("'unsafe-inline'"),
+            CspSource::UnsafeEval => s.push_str("'unsafe-eval'"),
+            CspSource::StrictDynamic => s.push_str("'strict-dynamic'"),
+            CspSource::Nonce => {
+                let _ = write!(s, "'nonce-{}'", nonce.unwrap_or_default());
+            }
+            CspSource::Sha256(hash) => {
+                let _ = write!(s, "'sha256-{hash}'");
+            }
+            CspSource::Host(host) => s.push_str(host),
+            CspSource::Scheme(scheme) => s.push_str(scheme),
+        }
+    }
+}
+
+macro_rules! define_directives {
+    ($($(#[$docs:meta])* ($method:ident, $name:literal);)*) => {
+        $(
+        $(#[$docs])*
+        #[must_use]
+        pub fn $method(self, sources: impl IntoIterator<Item = CspSource>) -> Self {
+            self.directive($name, sources)
+        }
+        )*
+    };
+}
+
+/// Middleware for adding the `Content-Security-Policy` header to responses.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, handler,
+///     middleware::{Csp, CspNonce, CspSource},
+///     test::TestClient,
+///     web::{Data, Html},
+/// };
+///
+/// #[handler]
+/// fn index(Data(nonce): Data<&CspNonce>) -> Html<String> {
+///     Html(format!(r#"<script nonce="{}">alert(1)</script>"#, nonce.0))
+/// }
+///
+/// let ep = index.with(
+///     Csp::new()
+///         .default_src([CspSource::SelfSource])
+///         .script_src([CspSource::Nonce])
+///         .report_uri("/csp-report"),
+/// );
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// let policy = resp.0.header("content-security-policy").unwrap();
+/// assert!(policy.starts_with("default-src 'self'; script-src 'nonce-"));
+/// assert!(policy.ends_with("; report-uri /csp-report"));
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "csp")))]
+#[derive(Default)]
+pub struct Csp {
+    directives: Vec<(String, Vec<CspSource>)>,
+    upgrade_insecure_requests: bool,
+    report_uri: Option<String>,
+    report_only: bool,
+}
+
+impl Csp {
+    /// Create `Csp` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the sources of a directive by its name.
+    ///
+    /// If the directive has already been set, its sources are replaced.
+    #[must_use]
+    pub fn directive(
+        mut self,
+        name: impl Into<String>,
+        sources: impl IntoIterator<Item = CspSource>,
+    ) -> Self {
+        let name = name.into();
+        let sources = sources.into_iter().collect();
+        match self.directives.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, value)) => *value = sources,
+            None => self.directives.push((name, sources)),
+        }
+        self
+    }
+
+    define_directives!(
+        /// Sets the `default-src` directive.
+        (default_src, "default-src");
+        /// Sets the `script-src` directive.
+        (script_src, "script-src");
+        /// Sets the `style-src` directive.
+        (style_src, "style-src");
+        /// Sets the `img-src` directive.
+        (img_src, "img-src");
+        /// Sets the `connect-src` directive.
+        (connect_src, "connect-src");
+        /// Sets the `font-src` directive.
+        (font_src, "font-src");
+        /// Sets the `object-src` directive.
+        (object_src, "object-src");
+        /// Sets the `media-src` directive.
+        (media_src, "media-src");
+        /// Sets the `frame-src` directive.
+        (frame_src, "frame-src");
+        /// Sets the `worker-src` directive.
+        (worker_src, "worker-src");
+        /// Sets the `manifest-src` directive.
+        (manifest_src, "manifest-src");
+        /// Sets the `frame-ancestors` directive.
+        (frame_ancestors, "frame-ancestors");
+        /// Sets the `base-uri` directive.
+        (base_uri, "base-uri");
+        /// Sets the `form-action` directive.
+        (form_action, "form-action");
+    );
+
+    /// Sets whether to add the `upgrade-insecure-requests` directive.
+    #[must_use]
+    pub fn upgrade_insecure_requests(self, enable: bool) -> Self {
+        Self {
+            upgrade_insecure_requests: enable,
+            ..self
+        }
+    }
+
+    /// Sets the `report-uri` directive.
+    #[must_use]
+    pub fn report_uri(self, uri: impl Into<String>) -> Self {
+        Self {
+            report_uri: Some(uri.into()),
+            ..self
+        }
+    }
+
+    /// If `true`, the policy is sent with the
+    /// `Content-Security-Policy-Report-Only` header, so violations are only
+    /// reported and not enforced.
+    #[must_use]
+    pub fn report_only(self, report_only: bool) -> Self {
+        Self {
+            report_only,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Csp {
+    type Output = CspEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let uses_nonce = self
+            .directives
+            .iter()
+            .any(|(_, sources)| sources.contains(&CspSource::Nonce));
+
+        CspEndpoint {
+            inner: ep,
+            directives: self.directives.clone().into(),
+            upgrade_insecure_requests: self.upgrade_insecure_requests,
+            report_uri: self.report_uri.clone(),
+            report_only: self.report_only,
+            uses_nonce,
+        }
+    }
+}
+
+/// Endpoint for the Csp middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "csp")))]
+pub struct CspEndpoint<E> {
+    inner: E,
+    directives: Arc<[(String, Vec<CspSource>)]>,
+    upgrade_insecure_requests: bool,
+    report_uri: Option<String>,
+    report_only: bool,
+    uses_nonce: bool,
+}
+
+impl<E> CspEndpoint<E> {
+    fn policy(&self, nonce: Option<&str>) -> String {
+        fn append(policy: &mut String) {
+            if !policy.is_empty() {
+                policy.push_str("; ");
+            }
+        }
+
+        let mut policy = String::new();
+
+        for (name, sources) in self.directives.iter() {
+            append(&mut policy);
+            policy.push_str(name);
+            for source in sources {
+                policy.push(' ');
+                source.write_to(&mut policy, nonce);
+            }
+        }
+        if self.upgrade_insecure_requests {
+            append(&mut policy);
+            policy.push_str("upgrade-insecure-requests");
+        }
+        if let Some(report_uri) = &self.report_uri {
+            append(&mut policy);
+            policy.push_str("report-uri ");
+            policy.push_str(report_uri);
+        }
+        policy
+    }
+}
+
+/// Generates a nonce with 128 bits of entropy from a cryptographically secure
+/// random number generator.
+fn generate_nonce() -> String {
+    STANDARD.encode(rng().random::<[u8; 16]>())
+}
+
+impl<E: Endpoint> Endpoint for CspEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let nonce = if self.uses_nonce {
+            let nonce = generate_nonce();
+            req.extensions_mut().insert(CspNonce(nonce.clone()));
+            Some(nonce)
+        } else {
+            None
+        };
+
+        let mut resp = self.inner.call(req).await?.into_response();
+        if let Ok(value) = HeaderValue::try_from(self.policy(nonce.as_deref())) {
+            let name = if self.report_only {
+                header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+            } else {
+                header::CONTENT_SECURITY_POLICY
+            };
+            resp.headers_mut().insert(name, value);
+        }
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient, web::Data};
+
+    #[tokio::test]
+    async fn csp() {
+        #[handler(internal)]
+        fn index() {}
+
+        let ep = index.with(
+            Csp::new()
+                .default_src([CspSource::SelfSource])
+                .img_src([
+                    CspSource::SelfSource,
+                    CspSource::Scheme("data:".to_string()),
+                    CspSource::Host("https://cdn.example.com".to_string()),
+                ])
+                .object_src([CspSource::None])
+                .upgrade_insecure_requests(true)
+                .report_uri("/csp-report"),
+        );
+        let resp = TestClient::new(ep).get("/").send().await;
+        resp.assert_header(
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'self'; img-src 'self' data: https://cdn.example.com; object-src 'none'; upgrade-insecure-requests; report-uri /csp-report",
+        );
+        resp.assert_header_is_not_exist(header::CONTENT_SECURITY_POLICY_REPORT_ONLY);
+    }
+
+    #[tokio::test]
+    async fn nonce() {
+        #[handler(internal)]
+        fn index(Data(nonce): Data<&CspNonce>) -> String {
+            nonce.0.clone()
+        }
+
+        let ep = index.with(
+            Csp::new()
+                .script_src([CspSource::Nonce, CspSource::StrictDynamic])
+                .report_only(true),
+        );
+        let cli = TestClient::new(ep);
+
+        let mut nonces = Vec::new();
+        for _ in 0..2 {
+            let resp = cli.get("/").send().await;
+            resp.assert_header_is_not_exist(header::CONTENT_SECURITY_POLICY);
+            let policy = resp
+                .0
+                .header(header::CONTENT_SECURITY_POLICY_REPORT_ONLY)
+                .unwrap()
+                .to_string();
+            let nonce = resp.0.into_body().into_string().await.unwrap();
+            assert_eq!(
+                policy,
+                format!("script-src 'nonce-{nonce}' 'strict-dynamic'")
+            );
+            nonces.push(nonce);
+        }
+        assert_ne!(nonces[0], nonces[1]);
+    }
+}
@@ -1,13 +1,19 @@
 use std::{borrow::Cow, sync::Arc, time::Duration};
 
-use base64::{Engine, engine::general_purpose::STANDARD};
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use http::Method;
 use libcsrf::{
     AesGcmCsrfProtection, CsrfCookie as RawCsrfCookie, CsrfProtection, CsrfToken as RawCsrfToken,
     UnencryptedCsrfCookie,
 };
+use rand::{Rng, rng};
 
 use crate::{
     Endpoint, Middleware, Request, Result,
+    error::CsrfTokenMismatchError,
     middleware::{CookieJarManager, CookieJarManagerEndpoint},
     web::{
         CsrfToken, CsrfVerifier,
@@ -17,6 +23,13 @@ use crate::{
 
 /// Middleware for Cross-Site Request Forgery (CSRF) protection.
 ///
+/// By default, the middleware sets an encrypted cookie and provides a
+/// [`CsrfToken`] that the handler sends to the client, and a [`CsrfVerifier`]
+/// that the handler uses to check the token sent back by the client.
+///
+/// With [`Csrf::double_submit`], the middleware implements the double-submit
+/// cookie pattern instead, see its documentation.
+///
 /// # Example
 ///
 /// ```
@@ -86,6 +99,8 @@ pub struct Csrf {
     // to be a static str, and if it's not, we don't want to have to copy it
     // into every endpoint.
     path: Arc<Cow<'static, str>>,
+    double_submit: bool,
+    header_name: String,
 }
 
 impl Default for Csrf {
@@ -98,6 +113,8 @@ impl Default for Csrf {
             same_site: Some(SameSite::Strict),
             ttl: Duration::from_secs(24 * 60 * 60),
             path: Arc::new(Cow::Borrowed("/")),
+            double_submit: false,
+            header_name: "X-CSRF-Token".to_string(),
         }
     }
 }
@@ -171,6 +188,73 @@ impl Csrf {
     pub fn ttl(self, ttl: Duration) -> Self {
         Self { ttl, ..self }
     }
+
+    /// Uses the double-submit cookie pattern, for clients such as single-page
+    /// applications that can read cookies but can not easily embed the token
+    /// in a form.
+    ///
+    /// A random token is stored in a cookie that is readable by scripts,
+    /// `HttpOnly` is never set in this mode, and the token is available to
+    /// handlers with the [`CsrfToken`] extractor. Requests with a method
+    /// other than `GET`, `HEAD`, `OPTIONS` and `TRACE` must send the same
+    /// token in the header set by [`Csrf::header_name`], otherwise the
+    /// middleware returns `403 Forbidden`. [`CsrfVerifier`] is not available
+    /// in this mode.
+    ///
+    /// # Errors
+    ///
+    /// - [`CsrfTokenMismatchError`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     EndpointExt, Route, get, handler, http::StatusCode, middleware::Csrf, test::TestClient,
+    ///     web::CsrfToken,
+    /// };
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let app = Route::new()
+    ///     .at("/", get(index).post(index))
+    ///     .with(Csrf::new().double_submit());
+    /// let cli = TestClient::new(app).with_cookie_store();
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.get("/").send().await.assert_status_is_ok();
+    /// let token = cli.cookie_store().unwrap().get("poem-csrf-token").unwrap();
+    ///
+    /// let resp = cli.post("/").send().await;
+    /// resp.assert_status(StatusCode::FORBIDDEN);
+    ///
+    /// let resp = cli
+    ///     .post("/")
+    ///     .header("X-CSRF-Token", token.value_str())
+    ///     .send()
+    ///     .await;
+    /// resp.assert_status_is_ok();
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn double_submit(self) -> Self {
+        Self {
+            double_submit: true,
+            ..self
+        }
+    }
+
+    /// Sets the name of the header that contains the token in the
+    /// double-submit cookie mode. Default is `X-CSRF-Token`.
+    #[must_use]
+    pub fn header_name(self, value: impl Into<String>) -> Self {
+        Self {
+            header_name: value.into(),
+            ..self
+        }
+    }
 }
 
 impl<E: Endpoint> Middleware<E> for Csrf {
@@ -186,6 +270,8 @@ impl<E: Endpoint> Middleware<E> for Csrf {
             same_site: self.same_site,
             ttl: self.ttl,
             path: Arc::clone(&self.path),
+            double_submit: self.double_submit,
+            header_name: self.header_name.clone(),
         })
     }
 }
@@ -201,9 +287,21 @@ pub struct CsrfEndpoint<E> {
     same_site: Option<SameSite>,
     ttl: Duration,
     path: Arc<Cow<'static, str>>,
+    double_submit: bool,
+    header_name: String,
 }
 
 impl<E> CsrfEndpoint<E> {
+    fn cookie(&self, value: String, http_only: bool) -> Cookie {
+        let mut cookie = Cookie::new_with_str(&self.cookie_name, value);
+        cookie.set_secure(self.secure);
+        cookie.set_http_only(http_only);
+        cookie.set_same_site(self.same_site);
+        cookie.set_max_age(self.ttl);
+        cookie.set_path(&**self.path);
+        cookie
+    }
+
     fn generate_token(
         &self,
         existing_cookie: Option<&UnencryptedCsrfCookie>,
@@ -229,6 +327,10 @@ impl<E: Endpoint> Endpoint for CsrfEndpoint<E> {
     type Output = E::Output;
 
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if self.double_submit {
+            return self.call_double_submit(req).await;
+        }
+
         let existing_cookie = req
             .cookie()
             .get(&self.cookie_name)
@@ -236,16 +338,7 @@ impl<E: Endpoint> Endpoint for CsrfEndpoint<E> {
             .and_then(|value| self.protect.parse_cookie(&value).ok());
 
         let (token, cookie) = self.generate_token(existing_cookie.as_ref());
-        let csrf_cookie = {
-            let mut cookie =
-                Cookie::new_with_str(&self.cookie_name, STANDARD.encode(cookie.value()));
-            cookie.set_secure(self.secure);
-            cookie.set_http_only(self.http_only);
-            cookie.set_same_site(self.same_site);
-            cookie.set_max_age(self.ttl);
-            cookie.set_path(&**self.path);
-            cookie
-        };
+        let csrf_cookie = self.cookie(STANDARD.encode(cookie.value()), self.http_only);
 
         req.cookie().add(csrf_cookie);
         req.extensions_mut()
@@ -257,12 +350,62 @@ impl<E: Endpoint> Endpoint for CsrfEndpoint<E> {
     }
 }
 
+impl<E: Endpoint> CsrfEndpoint<E> {
+    async fn call_double_submit(&self, mut req: Request) -> Result<E::Output> {
+        let existing_token = req
+            .cookie()
+            .get(&self.cookie_name)
+            .map(|cookie| cookie.value_str().to_string())
+            .filter(|token| is_valid_token(token));
+
+        if !matches!(
+            *req.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+        ) {
+            let header_token = req.header(&self.header_name);
+            match (&existing_token, header_token) {
+                (Some(token), Some(header_token)) if constant_time_eq(token, header_token) => {}
+                _ => return Err(CsrfTokenMismatchError.into()),
+            }
+        }
+
+        let token = match existing_token {
+            Some(token) => token,
+            None => {
+                let token = URL_SAFE_NO_PAD.encode(rng().random::<[u8; TOKEN_LEN]>());
+                req.cookie().add(self.cookie(token.clone(), false));
+                token
+            }
+        };
+        req.extensions_mut().insert(CsrfToken(token));
+
+        self.inner.call(req).await
+    }
+}
+
+/// The number of random bytes of a token in the double-submit cookie mode.
+const TOKEN_LEN: usize = 32;
+
+fn is_valid_token(token: &str) -> bool {
+    URL_SAFE_NO_PAD
+        .decode(token)
+        .is_ok_and(|data| data.len() == TOKEN_LEN)
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
 #[cfg(test)]
 mod tests {
     use http::{Method, StatusCode, header};
 
     use super::*;
-    use crate::{EndpointExt, Error, IntoResponse, Result, get, handler};
+    use crate::{EndpointExt, Error, IntoResponse, Result, get, handler, test::TestClient};
 
     const CSRF_TOKEN_NAME: &str = "X-CSRF-Token";
 
@@ -335,4 +478,63 @@ mod tests {
             "invalid token"
         );
     }
+
+    #[tokio::test]
+    async fn double_submit() {
+        #[handler(internal)]
+        fn index(token: &CsrfToken) -> String {
+            token.0.clone()
+        }
+
+        let app = get(index)
+            .post(index)
+            .with(Csrf::new().double_submit().header_name("X-XSRF-Token"));
+        let cli = TestClient::new(app).with_cookie_store();
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let set_cookie = resp.0.header(header::SET_COOKIE).unwrap().to_string();
+        assert!(!set_cookie.contains("HttpOnly"));
+        let cookie = cli.cookie_store().unwrap().get("poem-csrf-token").unwrap();
+        let token = cookie.value_str().to_string();
+        assert!(is_valid_token(&token));
+        resp.assert_text(&token).await;
+
+        // the existing token is kept
+        let resp = cli.get("/").send().await;
+        resp.assert_header_is_not_exist(header::SET_COOKIE);
+        resp.assert_text(&token).await;
+
+        cli.post("/")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.post("/")
+            .header(CSRF_TOKEN_NAME, &token)
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.post("/")
+            .header("X-XSRF-Token", URL_SAFE_NO_PAD.encode([0; TOKEN_LEN]))
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        cli.post("/")
+            .header("X-XSRF-Token", &token)
+            .send()
+            .await
+            .assert_text(&token)
+            .await;
+
+        // a cookie that was not generated by the middleware is ignored
+        cli.cookie_store().unwrap().clear();
+        cli.cookie_store()
+            .unwrap()
+            .add(Cookie::new_with_str("poem-csrf-token", "forged"));
+        cli.post("/")
+            .header("X-XSRF-Token", "forged")
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
 }
@@ -0,0 +1,258 @@
+use std::{
+    io::Error as IoError,
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{
+    Body, Endpoint, Middleware, Request, Result,
+    error::{DecompressionError, ReadBodyError},
+    http::header,
+    web::CompressionAlgo,
+};
+
+const DEFAULT_MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Middleware to decompress the request body according to the
+/// `Content-Encoding` header.
+///
+/// After decoding, the `Content-Encoding` and `Content-Length` headers are
+/// removed, so extractors such as [`Json`](crate::web::Json) see the plain
+/// body.
+///
+/// # Errors
+///
+/// - [`DecompressionError`]
+/// - [`ReadBodyError::PayloadTooLarge`], when the body is read past the
+///   [maximum size](Decompression::max_size)
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, Route, handler, middleware::Decompression, web::Json};
+/// use serde_json::Value;
+///
+/// #[handler]
+/// fn upload(Json(value): Json<Value>) {}
+///
+/// let app = Route::new()
+///     .at("/upload", upload)
+///     .with(Decompression::new().max_size(10 * 1024 * 1024));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct Decompression {
+    max_size: u64,
+}
+
+impl Default for Decompression {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+impl Decompression {
+    /// Creates a new `Decompression` middleware.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of the decompressed body.
+    ///
+    /// Reading past this limit fails with
+    /// [`ReadBodyError::PayloadTooLarge`] (`413 Payload Too Large`), which
+    /// protects against decompression bombs. Default is `16 MiB`, use
+    /// `u64::MAX` to disable the limit.
+    #[must_use]
+    pub fn max_size(self, max_size: u64) -> Self {
+        Self { max_size }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Decompression {
+    type Output = DecompressionEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        DecompressionEndpoint {
+            ep,
+            max_size: self.max_size,
+        }
+    }
+}
+
+/// Endpoint for the Decompression middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct DecompressionEndpoint<E> {
+    ep: E,
+    max_size: u64,
+}
+
+impl<E: Endpoint> Endpoint for DecompressionEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let Some(value) = req.headers_mut().remove(header::CONTENT_ENCODING) else {
+            return self.ep.call(req).await;
+        };
+        let value = value
+            .to_str()
+            .map_err(|_| DecompressionError::UnsupportedEncoding("".to_string()))?;
+
+        // Codings are listed in the order they were applied, so they are
+        // decoded in reverse.
+        let mut algorithms = Vec::new();
+        for coding in value.split(',').map(str::trim).rev() {
+            if coding.eq_ignore_ascii_case("identity") || coding.is_empty() {
+                continue;
+            }
+            algorithms.push(
+                CompressionAlgo::from_str(&coding.to_ascii_lowercase())
+                    .map_err(|_| DecompressionError::UnsupportedEncoding(coding.to_string()))?,
+            );
+        }
+
+        if !algorithms.is_empty() {
+            let mut reader: Pin<Box<dyn AsyncRead + Send>> =
+                Box::pin(req.take_body().into_async_read());
+            for algo in algorithms {
+                reader = algo.decompress(reader);
+            }
+            req.set_body(Body::from_async_read(LimitReader {
+                inner: reader,
+                remaining: self.max_size,
+            }));
+            req.headers_mut().remove(header::CONTENT_LENGTH);
+        }
+
+        self.ep.call(req).await
+    }
+}
+
+struct LimitReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for LimitReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        match Pin::new(&mut self.inner).poll_read(cx, buf) {
+            Poll::Ready(Ok(())) => {
+                let n = (buf.filled().len() - filled) as u64;
+                if n > self.remaining {
+                    return Poll::Ready(Err(IoError::other(ReadBodyError::PayloadTooLarge)));
+                }
+                self.remaining -= n;
+                Poll::Ready(Ok(()))
+            }
+            res => res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    const DATA: &str = "abcdefghijklmnopqrstuvwxyz1234567890";
+
+    #[handler(internal)]
+    async fn index(req: &Request, data: String) -> String {
+        assert!(req.headers().get(header::CONTENT_ENCODING).is_none());
+        data
+    }
+
+    #[tokio::test]
+    async fn decompression() {
+        let cli = TestClient::new(index.with(Decompression::new()));
+
+        for algo in [
+            CompressionAlgo::BR,
+            CompressionAlgo::DEFLATE,
+            CompressionAlgo::GZIP,
+            CompressionAlgo::ZSTD,
+        ] {
+            let resp = cli
+                .post("/")
+                .header("Content-Encoding", algo.as_str())
+                .body(Body::from_async_read(algo.compress(DATA.as_bytes(), None)))
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            resp.assert_text(DATA).await;
+        }
+
+        let resp = cli.post("/").body(DATA).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text(DATA).await;
+    }
+
+    #[tokio::test]
+    async fn multiple_encodings() {
+        let cli = TestClient::new(index.with(Decompression::new()));
+
+        let reader = CompressionAlgo::GZIP.compress(DATA.as_bytes(), None);
+        let reader = CompressionAlgo::BR.compress(reader, None);
+        let resp = cli
+            .post("/")
+            .header("Content-Encoding", "gzip, br")
+            .body(Body::from_async_read(reader))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text(DATA).await;
+    }
+
+    #[tokio::test]
+    async fn unsupported_encoding() {
+        let cli = TestClient::new(index.with(Decompression::new()));
+
+        cli.post("/")
+            .header("Content-Encoding", "compress")
+            .body(DATA)
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn max_size() {
+        let cli = TestClient::new(index.with(Decompression::new().max_size(10)));
+
+        cli.post("/")
+            .header("Content-Encoding", "gzip")
+            .body(Body::from_async_read(
+                CompressionAlgo::GZIP.compress(DATA.as_bytes(), None),
+            ))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn default_max_size() {
+        let cli = TestClient::new(index.with(Decompression::new()));
+
+        let data = vec![b'a'; DEFAULT_MAX_SIZE as usize + 1];
+        cli.post("/")
+            .header("Content-Encoding", "gzip")
+            .body(Body::from_async_read(
+                CompressionAlgo::GZIP.compress(std::io::Cursor::new(data), None),
+            ))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,289 @@
+use std::{error::Error as StdError, sync::Arc};
+
+use http::StatusCode;
+use serde_json::Value;
+
+use crate::{
+    Endpoint, Error, IntoResponse, Middleware, Request, Response, Result,
+    middleware::problem_details::merge_error_response, web::Json,
+};
+
+type EnvelopeFn = Arc<dyn Fn(&Error) -> Value + Send + Sync>;
+type MapFn = Arc<dyn Fn(&Error) -> Option<Value> + Send + Sync>;
+
+fn default_envelope(err: &Error) -> Value {
+    serde_json::json!({
+        "status": err.status().as_u16(),
+        "message": err.to_string(),
+    })
+}
+
+/// Middleware for rendering the errors of the built-in extractors, such as
+/// [`ParseJsonError`](crate::error::ParseJsonError) and
+/// [`ParseQueryError`](crate::error::ParseQueryError), as JSON envelopes.
+///
+/// By default, the client errors of the built-in extractors are rendered as
+/// `{"status": 400, "message": "..."}` with the status code of the error.
+/// The envelope can be replaced with [`ExtractorErrors::envelope`], and the
+/// errors of a specific type, including the types that are not extractor
+/// errors, can be rendered differently with [`ExtractorErrors::map`]. Other
+/// errors are returned unchanged. The envelope can use
+/// [`Error::extractor_error`] to include the kind of the error and the field
+/// involved.
+///
+/// Headers of the original error response are kept. Unlike
+/// [`EndpointExt::catch_error`](crate::EndpointExt::catch_error), one
+/// middleware handles the whole family of extractor errors, so it is usually
+/// applied to the whole application.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route,
+///     error::ParseQueryError,
+///     handler,
+///     http::StatusCode,
+///     middleware::ExtractorErrors,
+///     post,
+///     test::TestClient,
+///     web::{Json, Query},
+/// };
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     id: u32,
+/// }
+///
+/// #[handler]
+/// fn create(_params: Query<Params>, _value: Json<serde_json::Value>) {}
+///
+/// let app = Route::new().at("/", post(create)).with(
+///     ExtractorErrors::new()
+///         .envelope(|err| {
+///             json!({
+///                 "error": { "code": err.status().as_u16(), "message": err.to_string() },
+///             })
+///         })
+///         .map::<ParseQueryError>(|err, status| {
+///             json!({
+///                 "error": {
+///                     "code": status.as_u16(),
+///                     "message": err.to_string(),
+///                     "location": "query",
+///                 },
+///             })
+///         }),
+/// );
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/").query("id", &"abc").send().await;
+/// resp.assert_status(StatusCode::BAD_REQUEST);
+/// resp.assert_json(json!({
+///     "error": {
+///         "code": 400,
+///         "message": "id: invalid digit found in string",
+///         "location": "query",
+///     }
+/// }))
+/// .await;
+///
+/// let resp = cli.post("/").query("id", &1).body("{}").send().await;
+/// resp.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+/// resp.assert_json(json!({
+///     "error": {
+///         "code": 415,
+///         "message": "expect content type `application/json`",
+///     }
+/// }))
+/// .await;
+/// # });
+/// ```
+pub struct ExtractorErrors {
+    envelope: EnvelopeFn,
+    mappers: Vec<MapFn>,
+}
+
+impl Default for ExtractorErrors {
+    fn default() -> Self {
+        Self {
+            envelope: Arc::new(default_envelope),
+            mappers: Vec::new(),
+        }
+    }
+}
+
+impl ExtractorErrors {
+    /// Create `ExtractorErrors` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the function that renders the errors of the built-in extractors
+    /// that are not handled by [`ExtractorErrors::map`].
+    #[must_use]
+    pub fn envelope(self, f: impl Fn(&Error) -> Value + Send + Sync + 'static) -> Self {
+        Self {
+            envelope: Arc::new(f),
+            ..self
+        }
+    }
+
+    /// Renders the errors of type `T` with `f`, which receives the error and
+    /// its status code.
+    ///
+    /// If several functions handle an error, the one added first is used.
+    #[must_use]
+    pub fn map<T>(mut self, f: impl Fn(&T, StatusCode) -> Value + Send + Sync + 'static) -> Self
+    where
+        T: StdError + Send + Sync + 'static,
+    {
+        self.mappers.push(Arc::new(move |err: &Error| {
+            err.downcast_ref::<T>().map(|e| f(e, err.status()))
+        }));
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ExtractorErrors {
+    type Output = ExtractorErrorsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ExtractorErrorsEndpoint {
+            inner: ep,
+            envelope: self.envelope.clone(),
+            mappers: self.mappers.clone(),
+        }
+    }
+}
+
+/// Endpoint for the ExtractorErrors middleware.
+pub struct ExtractorErrorsEndpoint<E> {
+    inner: E,
+    envelope: EnvelopeFn,
+    mappers: Vec<MapFn>,
+}
+
+impl<E: Endpoint> ExtractorErrorsEndpoint<E> {
+    fn render(&self, err: &Error) -> Option<Value> {
+        self.mappers.iter().find_map(|f| f(err)).or_else(|| {
+            (err.extractor_error().is_some() && err.status().is_client_error())
+                .then(|| (self.envelope)(err))
+        })
+    }
+}
+
+impl<E: Endpoint> Endpoint for ExtractorErrorsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let err = match self.inner.call(req).await {
+            Ok(resp) => return Ok(resp.into_response()),
+            Err(err) => err,
+        };
+        let Some(value) = self.render(&err) else {
+            return Err(err);
+        };
+
+        let mut resp = Json(value).with_status(err.status()).into_response();
+        merge_error_response(&mut resp, err);
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        EndpointExt, Route,
+        error::{MethodNotAllowedError, ParseQueryError},
+        get, handler,
+        test::TestClient,
+        web::{Path, Query},
+    };
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    #[handler(internal)]
+    fn index(_path: Path<u32>, _query: Query<Params>) -> &'static str {
+        "hello"
+    }
+
+    fn app(mw: ExtractorErrors) -> impl Endpoint {
+        Route::new().at("/:id", get(index)).with(mw)
+    }
+
+    #[tokio::test]
+    async fn extractor_errors() {
+        let cli = TestClient::new(app(ExtractorErrors::new()));
+
+        cli.get("/1")
+            .query("name", &"a")
+            .send()
+            .await
+            .assert_text("hello")
+            .await;
+
+        let resp = cli.get("/abc").query("name", &"a").send().await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_content_type("application/json; charset=utf-8");
+        resp.assert_json(json!({
+            "status": 400,
+            "message": r#"invalid path parameter "id": can not parse `"abc"` to a `u32`"#,
+        }))
+        .await;
+
+        let resp = cli.get("/1").send().await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_json(json!({ "status": 400, "message": "missing field `name`" }))
+            .await;
+
+        // the other errors are unchanged
+        let resp = cli.post("/1").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "GET, HEAD, OPTIONS");
+        resp.assert_text("method not allowed").await;
+    }
+
+    #[tokio::test]
+    async fn map() {
+        let cli = TestClient::new(app(ExtractorErrors::new()
+            .envelope(|err| json!({ "error": err.to_string() }))
+            .map::<ParseQueryError>(
+                |err, status| json!({ "query": err.to_string(), "status": status.as_u16() }),
+            )
+            .map::<ParseQueryError>(|_, _| json!("unused"))
+            .map::<MethodNotAllowedError>(|err, _| json!({ "method": err.to_string() }))));
+
+        cli.get("/1")
+            .send()
+            .await
+            .assert_json(json!({ "query": "missing field `name`", "status": 400 }))
+            .await;
+
+        cli.get("/abc")
+            .query("name", &"a")
+            .send()
+            .await
+            .assert_json(json!({
+                "error": r#"invalid path parameter "id": can not parse `"abc"` to a `u32`"#,
+            }))
+            .await;
+
+        let resp = cli.post("/1").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "GET, HEAD, OPTIONS");
+        resp.assert_json(json!({ "method": "method not allowed" }))
+            .await;
+    }
+}
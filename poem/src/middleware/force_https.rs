@@ -1,16 +1,25 @@
 use std::{borrow::Cow, sync::Arc};
 
 use http::{Uri, header, uri::Scheme};
+use ipnet::IpNet;
 
-use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, web::Redirect};
+use crate::{
+    Endpoint, IntoResponse, Middleware, Request, Response, Result, middleware::parse_nets,
+    web::Redirect,
+};
 
 type FilterFn = Arc<dyn Fn(&Request) -> bool + Send + Sync>;
 
 /// Middleware which forces redirects to a HTTPS uri.
+///
+/// By default, only the scheme of the connection is checked. Behind a reverse
+/// proxy that terminates TLS, use [`ForceHttps::trusted_proxies`] to also
+/// accept the `X-Forwarded-Proto` and `Forwarded` headers set by the proxy.
 #[derive(Default)]
 pub struct ForceHttps {
     https_port: Option<u16>,
     filter_fn: Option<FilterFn>,
+    trusted_proxies: Arc<[IpNet]>,
 }
 
 impl ForceHttps {
@@ -36,6 +45,22 @@ impl ForceHttps {
             ..self
         }
     }
+
+    /// Sets the networks of the trusted reverse proxies.
+    ///
+    /// When the peer is a trusted proxy, the scheme is read from the
+    /// `X-Forwarded-Proto` header, or the `Forwarded` header if there is none.
+    #[must_use]
+    pub fn trusted_proxies<I, T>(self, nets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self {
+            trusted_proxies: parse_nets(nets).into(),
+            ..self
+        }
+    }
 }
 
 impl<E> Middleware<E> for ForceHttps
@@ -49,6 +74,7 @@ where
             inner: ep,
             https_port: self.https_port,
             filter_fn: self.filter_fn.clone(),
+            trusted_proxies: self.trusted_proxies.clone(),
         }
     }
 }
@@ -58,6 +84,7 @@ pub struct ForceHttpsEndpoint<E> {
     inner: E,
     https_port: Option<u16>,
     filter_fn: Option<FilterFn>,
+    trusted_proxies: Arc<[IpNet]>,
 }
 
 impl<E> Endpoint for ForceHttpsEndpoint<E>
@@ -67,7 +94,8 @@ where
     type Output = Response;
 
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
-        if req.scheme() == &Scheme::HTTP && self.filter_fn.as_ref().map(|f| f(&req)).unwrap_or(true)
+        if !is_https(&req, &self.trusted_proxies)
+            && self.filter_fn.as_ref().map(|f| f(&req)).unwrap_or(true)
         {
             if let Some(host) = req.headers().get(header::HOST).cloned() {
                 if let Ok(host) = host.to_str() {
@@ -88,6 +116,42 @@ where
     }
 }
 
+/// Returns `true` if the request arrived over HTTPS, either directly or through
+/// one of the `trusted_proxies` that sets the `X-Forwarded-Proto` or
+/// `Forwarded` header.
+pub(crate) fn is_https(req: &Request, trusted_proxies: &[IpNet]) -> bool {
+    if req.scheme() == &Scheme::HTTPS {
+        return true;
+    }
+
+    let trusted = req
+        .remote_addr()
+        .as_socket_addr()
+        .is_some_and(|addr| trusted_proxies.iter().any(|net| net.contains(&addr.ip())));
+    if !trusted {
+        return false;
+    }
+
+    if let Some(proto) = req
+        .headers()
+        .get("x-forwarded-proto")
+        .and_then(|value| value.to_str().ok())
+    {
+        return proto
+            .split(',')
+            .next()
+            .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"));
+    }
+
+    req.headers()
+        .get(header::FORWARDED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| rfc7239::parse(value).next())
+        .and_then(|item| item.ok())
+        .and_then(|item| item.protocol)
+        .is_some_and(|proto| proto.eq_ignore_ascii_case("https"))
+}
+
 fn redirect_host(host: &str, https_port: Option<u16>) -> Cow<'_, str> {
     match (host.split_once(':'), https_port) {
         (Some((host, _)), Some(port)) => Cow::Owned(format!("{host}:{port}")),
@@ -98,7 +162,69 @@ fn redirect_host(host: &str, https_port: Option<u16>) -> Cow<'_, str> {
 
 #[cfg(test)]
 mod tests {
+    use std::net::SocketAddr;
+
+    use http::StatusCode;
+
     use super::*;
+    use crate::{Addr, EndpointExt, endpoint::make_sync, web::RemoteAddr};
+
+    fn request(peer: &str, name: &str, value: &str) -> Request {
+        let mut req = Request::builder().header(name, value).finish();
+        req.state_mut().remote_addr = RemoteAddr(Addr::SocketAddr(SocketAddr::new(
+            peer.parse().unwrap(),
+            1234,
+        )));
+        req
+    }
+
+    #[test]
+    fn test_is_https() {
+        let trusted = parse_nets(["10.0.0.0/8"]);
+        assert!(!is_https(&Request::builder().finish(), &trusted));
+        assert!(is_https(
+            &request("10.0.0.1", "x-forwarded-proto", "https"),
+            &trusted
+        ));
+        assert!(!is_https(
+            &request("10.0.0.1", "x-forwarded-proto", "http"),
+            &trusted
+        ));
+        assert!(is_https(
+            &request("10.0.0.1", "forwarded", "for=192.0.2.60;proto=https"),
+            &trusted
+        ));
+
+        // the headers are ignored when the peer is not a trusted proxy
+        assert!(!is_https(
+            &request("192.168.1.1", "x-forwarded-proto", "https"),
+            &trusted
+        ));
+        assert!(!is_https(
+            &request("10.0.0.1", "forwarded", "proto=https"),
+            &[]
+        ));
+    }
+
+    #[tokio::test]
+    async fn force_https_trusted_proxies() {
+        let ep = make_sync(|_| "hello").with(ForceHttps::new().trusted_proxies(["10.0.0.1"]));
+
+        let mut req = request("10.0.0.1", "x-forwarded-proto", "https");
+        req.headers_mut()
+            .insert(header::HOST, "example.com".parse().unwrap());
+        assert_eq!(ep.get_response(req).await.status(), StatusCode::OK);
+
+        let mut req = request("10.0.0.2", "x-forwarded-proto", "https");
+        req.headers_mut()
+            .insert(header::HOST, "example.com".parse().unwrap());
+        let resp = ep.get_response(req).await;
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            resp.headers().get(header::LOCATION).unwrap(),
+            "https://example.com/"
+        );
+    }
 
     #[test]
     fn test_redirect_host() {
@@ -0,0 +1,200 @@
+use std::{sync::Arc, time::Duration};
+
+use http::{HeaderValue, header};
+use ipnet::IpNet;
+
+use crate::{
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    middleware::{force_https::is_https, parse_nets},
+};
+
+/// Middleware for adding the `Strict-Transport-Security` header to responses.
+///
+/// The header is only added to responses of requests that arrived over HTTPS,
+/// including requests forwarded by one of the
+/// [trusted proxies](Hsts::trusted_proxies) with the `X-Forwarded-Proto` or
+/// `Forwarded` header, because browsers ignore it on plain HTTP.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     EndpointExt, Route, handler,
+///     middleware::{ForceHttps, Hsts},
+/// };
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = Route::new().at("/", index).with(ForceHttps::new()).with(
+///     Hsts::new(Duration::from_secs(365 * 24 * 60 * 60))
+///         .include_subdomains(true)
+///         .preload(true),
+/// );
+/// ```
+pub struct Hsts {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+    trusted_proxies: Arc<[IpNet]>,
+}
+
+impl Hsts {
+    /// Create `Hsts` middleware with the `max-age` directive.
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            include_subdomains: false,
+            preload: false,
+            trusted_proxies: Arc::new([]),
+        }
+    }
+
+    /// Sets whether to add the `includeSubDomains` directive.
+    #[must_use]
+    pub fn include_subdomains(self, include_subdomains: bool) -> Self {
+        Self {
+            include_subdomains,
+            ..self
+        }
+    }
+
+    /// Sets whether to add the `preload` directive.
+    #[must_use]
+    pub fn preload(self, preload: bool) -> Self {
+        Self { preload, ..self }
+    }
+
+    /// Sets the networks of the trusted reverse proxies.
+    ///
+    /// When the peer is a trusted proxy, the scheme is read from the
+    /// `X-Forwarded-Proto` header, or the `Forwarded` header if there is none.
+    #[must_use]
+    pub fn trusted_proxies<I, T>(self, nets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self {
+            trusted_proxies: parse_nets(nets).into(),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Hsts {
+    type Output = HstsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let mut value = format!("max-age={}", self.max_age.as_secs());
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+
+        HstsEndpoint {
+            inner: ep,
+            value: HeaderValue::try_from(value).unwrap(),
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
+}
+
+/// Endpoint for the Hsts middleware.
+pub struct HstsEndpoint<E> {
+    inner: E,
+    value: HeaderValue,
+    trusted_proxies: Arc<[IpNet]>,
+}
+
+impl<E: Endpoint> Endpoint for HstsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let https = is_https(&req, &self.trusted_proxies);
+        let mut resp = self.inner.call(req).await?.into_response();
+        if https {
+            resp.headers_mut()
+                .insert(header::STRICT_TRANSPORT_SECURITY, self.value.clone());
+        }
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use http::uri::Scheme;
+
+    use super::*;
+    use crate::{Addr, EndpointExt, endpoint::make_sync, web::RemoteAddr};
+
+    fn request(peer: &str, forwarded_proto: Option<&str>) -> Request {
+        let mut builder = Request::builder();
+        if let Some(proto) = forwarded_proto {
+            builder = builder.header("x-forwarded-proto", proto);
+        }
+        let mut req = builder.finish();
+        req.state_mut().remote_addr = RemoteAddr(Addr::SocketAddr(SocketAddr::new(
+            peer.parse().unwrap(),
+            1234,
+        )));
+        req
+    }
+
+    #[tokio::test]
+    async fn hsts() {
+        let ep = make_sync(|_| ()).with(
+            Hsts::new(Duration::from_secs(31536000))
+                .include_subdomains(true)
+                .preload(true)
+                .trusted_proxies(["10.0.0.0/8"]),
+        );
+
+        let resp = ep.get_response(request("10.0.0.1", None)).await;
+        assert!(
+            !resp
+                .headers()
+                .contains_key(header::STRICT_TRANSPORT_SECURITY)
+        );
+
+        let resp = ep.get_response(request("10.0.0.1", Some("https"))).await;
+        assert_eq!(
+            resp.headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .unwrap(),
+            "max-age=31536000; includeSubDomains; preload"
+        );
+
+        // the header of an untrusted peer is ignored
+        let resp = ep.get_response(request("192.168.1.1", Some("https"))).await;
+        assert!(
+            !resp
+                .headers()
+                .contains_key(header::STRICT_TRANSPORT_SECURITY)
+        );
+
+        let ep = make_sync(|_| ()).with(Hsts::new(Duration::from_secs(60)));
+        let resp = ep.get_response(request("10.0.0.1", Some("https"))).await;
+        assert!(
+            !resp
+                .headers()
+                .contains_key(header::STRICT_TRANSPORT_SECURITY)
+        );
+        let mut req = request("10.0.0.1", None);
+        req.state_mut().scheme = Scheme::HTTPS;
+        assert_eq!(
+            ep.get_response(req)
+                .await
+                .headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .unwrap(),
+            "max-age=60"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::{net::IpAddr, str::FromStr, sync::Arc};
+
+use ipnet::IpNet;
+use rfc7239::{Forwarded, NodeIdentifier, NodeName};
+
+use crate::{Endpoint, Middleware, Request, Result, error::IpFilterError, http::header};
+
+fn parse_net(s: &str) -> IpNet {
+    match IpNet::from_str(s) {
+        Ok(net) => net,
+        Err(_) => match IpAddr::from_str(s) {
+            Ok(addr) => IpNet::from(addr),
+            Err(_) => panic!("illegal ip network `{s}`"),
+        },
+    }
+}
+
+pub(crate) fn parse_nets<I, T>(nets: I) -> Vec<IpNet>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    nets.into_iter().map(|s| parse_net(s.as_ref())).collect()
+}
+
+fn contains(nets: &[IpNet], addr: &IpAddr) -> bool {
+    nets.iter().any(|net| net.contains(addr))
+}
+
+/// Resolves the client address of a request.
+///
+/// If the peer is one of the `trusted_proxies`, the proxy chain in the
+/// `Forwarded` header, or the `X-Forwarded-For` header if there is none, is
+/// walked from right to left and the first address that is not a trusted
+/// proxy is returned. Without either header, the `X-Real-IP` header set by
+/// the trusted proxy is used.
+pub(crate) fn client_ip(req: &Request, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    let peer = req.remote_addr().as_socket_addr()?.ip();
+    if !contains(trusted_proxies, &peer) {
+        return Some(peer);
+    }
+
+    let headers = req.headers();
+    let hops = if headers.contains_key(header::FORWARDED) {
+        headers
+            .get_all(header::FORWARDED)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(rfc7239::parse)
+            .map(|item| match item {
+                Ok(Forwarded {
+                    forwarded_for:
+                        Some(NodeIdentifier {
+                            name: NodeName::Ip(addr),
+                            ..
+                        }),
+                    ..
+                }) => Some(addr),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    } else if headers.contains_key("x-forwarded-for") {
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .collect::<Vec<_>>()
+    } else {
+        headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().parse::<IpAddr>().ok())
+            .into_iter()
+            .collect::<Vec<_>>()
+    };
+
+    let mut client = peer;
+    for hop in hops.into_iter().rev() {
+        match hop {
+            Some(addr) => {
+                client = addr;
+                if !contains(trusted_proxies, &addr) {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    Some(client)
+}
+
+enum Mode {
+    Allow,
+    Deny,
+}
+
+/// Middleware for restricting access by the IP address of the client.
+///
+/// In the allow mode, only clients in one of the networks are accepted. In
+/// the deny mode, clients in one of the networks are rejected. Rejected
+/// requests get the `FORBIDDEN` status code.
+///
+/// Networks are written in CIDR notation, such as `10.0.0.0/8` or
+/// `2001:db8::/32`. A plain address matches only itself.
+///
+/// # Errors
+///
+/// - [`IpFilterError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, Route, handler, middleware::IpFilter};
+///
+/// #[handler]
+/// fn admin() {}
+///
+/// let app = Route::new()
+///     .at("/admin", admin)
+///     .with(IpFilter::allow(["10.0.0.0/8", "192.168.1.1"]).trusted_proxies(["172.16.0.0/12"]));
+/// ```
+pub struct IpFilter {
+    mode: Mode,
+    nets: Arc<[IpNet]>,
+    trusted_proxies: Arc<[IpNet]>,
+}
+
+impl IpFilter {
+    /// Create an `IpFilter` middleware that only accepts clients in one of
+    /// the networks.
+    pub fn allow<I, T>(nets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self {
+            mode: Mode::Allow,
+            nets: parse_nets(nets).into(),
+            trusted_proxies: Arc::new([]),
+        }
+    }
+
+    /// Create an `IpFilter` middleware that rejects clients in one of the
+    /// networks.
+    pub fn deny<I, T>(nets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self {
+            mode: Mode::Deny,
+            nets: parse_nets(nets).into(),
+            trusted_proxies: Arc::new([]),
+        }
+    }
+
+    /// Sets the networks of the trusted reverse proxies.
+    ///
+    /// When the peer is a trusted proxy, the client address is read from the
+    /// rightmost untrusted hop of the `X-Forwarded-For` header.
+    #[must_use]
+    pub fn trusted_proxies<I, T>(self, nets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self {
+            trusted_proxies: parse_nets(nets).into(),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for IpFilter {
+    type Output = IpFilterEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        IpFilterEndpoint {
+            inner: ep,
+            allow: matches!(self.mode, Mode::Allow),
+            nets: self.nets.clone(),
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
+}
+
+/// Endpoint for the IpFilter middleware.
+pub struct IpFilterEndpoint<E> {
+    inner: E,
+    allow: bool,
+    nets: Arc<[IpNet]>,
+    trusted_proxies: Arc<[IpNet]>,
+}
+
+impl<E: Endpoint> Endpoint for IpFilterEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let matched = client_ip(&req, &self.trusted_proxies)
+            .map(|addr| contains(&self.nets, &addr))
+            .unwrap_or_default();
+        if matched != self.allow {
+            return Err(IpFilterError.into());
+        }
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{Addr, EndpointExt, endpoint::make_sync, web::RemoteAddr};
+
+    async fn status(ep: &impl Endpoint, peer: &str, forwarded_for: Option<&str>) -> StatusCode {
+        let mut builder = Request::builder();
+        if let Some(forwarded_for) = forwarded_for {
+            builder = builder.header("x-forwarded-for", forwarded_for);
+        }
+        let mut req = builder.finish();
+        req.state_mut().remote_addr = RemoteAddr(Addr::SocketAddr(SocketAddr::new(
+            peer.parse().unwrap(),
+            1234,
+        )));
+        ep.get_response(req).await.status()
+    }
+
+    #[tokio::test]
+    async fn allow() {
+        let ep = make_sync(|_| ()).with(IpFilter::allow(["10.0.0.0/8", "192.168.1.1"]));
+        assert_eq!(status(&ep, "10.1.2.3", None).await, StatusCode::OK);
+        assert_eq!(status(&ep, "192.168.1.1", None).await, StatusCode::OK);
+        assert_eq!(
+            status(&ep, "192.168.1.2", None).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(&ep, "::1", None).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(&ep, "192.168.1.2", Some("10.0.0.1")).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn deny() {
+        let ep = make_sync(|_| ()).with(IpFilter::deny(["10.0.0.0/8", "2001:db8::/32"]));
+        assert_eq!(status(&ep, "10.1.2.3", None).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(&ep, "2001:db8::1", None).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(&ep, "192.168.1.2", None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn trusted_proxies() {
+        let ep = make_sync(|_| ())
+            .with(IpFilter::allow(["10.0.0.0/8"]).trusted_proxies(["172.16.0.0/12"]));
+
+        assert_eq!(
+            status(&ep, "172.16.0.1", Some("10.0.0.1")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&ep, "172.16.0.1", Some("10.0.0.1, 172.16.0.2")).await,
+            StatusCode::OK
+        );
+        // the leftmost value may be spoofed by the client
+        assert_eq!(
+            status(&ep, "172.16.0.1", Some("10.0.0.1, 8.8.8.8")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(status(&ep, "172.16.0.1", None).await, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use http::header::HeaderName;
+
+use crate::{Endpoint, Middleware, Request, Result, error::LoadShedError};
+
+/// The priority of a request in the [`LoadShed`] middleware.
+///
+/// It can be inserted into the extensions of the request by an outer
+/// middleware, or read from a header with [`LoadShed::priority_header`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Priority {
+    /// Shed first.
+    Low,
+    /// The priority of the requests without a priority.
+    #[default]
+    Normal,
+    /// Shed last.
+    High,
+    /// Never shed, unless thresholds are set for it.
+    Critical,
+}
+
+impl Priority {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            s if s.eq_ignore_ascii_case("low") => Some(Priority::Low),
+            s if s.eq_ignore_ascii_case("normal") => Some(Priority::Normal),
+            s if s.eq_ignore_ascii_case("high") => Some(Priority::High),
+            s if s.eq_ignore_ascii_case("critical") => Some(Priority::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// The load at which a priority starts being shed and the load at which all
+/// of its requests are shed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Thresholds {
+    start: f64,
+    end: f64,
+}
+
+impl Thresholds {
+    const NEVER: Self = Self {
+        start: f64::INFINITY,
+        end: f64::INFINITY,
+    };
+
+    /// Returns the probability that a request is shed at `load`.
+    fn shed_probability(&self, load: f64) -> f64 {
+        if load < self.start {
+            0.0
+        } else if load >= self.end {
+            1.0
+        } else {
+            (load - self.start) / (self.end - self.start)
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Load {
+    InFlight,
+    Custom(Arc<dyn Fn() -> f64 + Send + Sync>),
+}
+
+/// Middleware that rejects a fraction of the requests with
+/// `503 Service Unavailable` when the server is overloaded, preferring the
+/// requests with a low [`Priority`].
+///
+/// The load is the number of requests executing the inner endpoint, or the
+/// value returned by the function set with [`LoadShed::load_fn`], such as the
+/// CPU usage or the queue length of a database pool. Between the `start` and
+/// `end` thresholds of a priority, its requests are rejected with a
+/// probability that grows linearly from `0` to `1`, so the load is reduced
+/// gradually instead of hitting a hard limit like
+/// [`ConcurrencyLimit`](crate::middleware::ConcurrencyLimit).
+///
+/// [`LoadShed::new`] sets the thresholds of [`Priority::Low`], and each higher
+/// priority is shed from where the previous one is fully shed. For example,
+/// with `LoadShed::new(50.0, 100.0)`, the low priority requests are shed
+/// between 50 and 100, the normal ones between 100 and 150, and the high ones
+/// between 150 and 200. [`Priority::Critical`] requests are never shed.
+///
+/// The priority is read from the [`Priority`] extension of the request, then
+/// from the header set with [`LoadShed::priority_header`], with the values
+/// `low`, `normal`, `high` and `critical`. The requests without a priority
+/// are [`Priority::Normal`].
+///
+/// # Errors
+///
+/// - [`LoadShedError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler,
+///     middleware::{LoadShed, Priority},
+/// };
+///
+/// #[handler]
+/// async fn index() {}
+///
+/// let app = Route::new().at("/", index).with(
+///     LoadShed::new(200.0, 400.0)
+///         .thresholds(Priority::Critical, 1000.0, 1200.0)
+///         .priority_header("x-priority"),
+/// );
+/// ```
+pub struct LoadShed {
+    load: Load,
+    in_flight: Arc<AtomicUsize>,
+    thresholds: [Thresholds; 4],
+    priority_header: Option<HeaderName>,
+    random: Arc<Random>,
+}
+
+impl LoadShed {
+    /// Create `LoadShed` middleware that starts shedding the low priority
+    /// requests when the load reaches `start` and sheds all of them when it
+    /// reaches `end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is greater than `end`.
+    pub fn new(start: f64, end: f64) -> Self {
+        assert!(start <= end, "`start` must not be greater than `end`");
+        let step = end - start;
+        let thresholds = |n: f64| Thresholds {
+            start: start + step * n,
+            end: end + step * n,
+        };
+        Self {
+            load: Load::InFlight,
+            in_flight: Default::default(),
+            thresholds: [
+                thresholds(0.0),
+                thresholds(1.0),
+                thresholds(2.0),
+                Thresholds::NEVER,
+            ],
+            priority_header: None,
+            random: Default::default(),
+        }
+    }
+
+    /// Sets the thresholds of `priority`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is greater than `end`.
+    #[must_use]
+    pub fn thresholds(mut self, priority: Priority, start: f64, end: f64) -> Self {
+        assert!(start <= end, "`start` must not be greater than `end`");
+        self.thresholds[priority as usize] = Thresholds { start, end };
+        self
+    }
+
+    /// Sets the function that returns the current load, instead of the number
+    /// of requests in flight.
+    ///
+    /// It is called for each request, so it should be cheap, for example
+    /// reading a value that is updated periodically by a background task.
+    #[must_use]
+    pub fn load_fn(self, f: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+        Self {
+            load: Load::Custom(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Sets the header to read the priority from when the request does not
+    /// have a [`Priority`] extension.
+    ///
+    /// The header can be set by clients, so it should only be used when it is
+    /// set by a trusted proxy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid header name.
+    #[must_use]
+    pub fn priority_header(self, name: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        Self {
+            priority_header: Some(
+                HeaderName::try_from(name)
+                    .unwrap_or_else(|_| panic!("invalid header name: {name}")),
+            ),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for LoadShed {
+    type Output = LoadShedEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        LoadShedEndpoint {
+            inner: ep,
+            load: self.load.clone(),
+            in_flight: self.in_flight.clone(),
+            thresholds: self.thresholds,
+            priority_header: self.priority_header.clone(),
+            random: self.random.clone(),
+        }
+    }
+}
+
+/// Endpoint for the LoadShed middleware.
+pub struct LoadShedEndpoint<E> {
+    inner: E,
+    load: Load,
+    in_flight: Arc<AtomicUsize>,
+    thresholds: [Thresholds; 4],
+    priority_header: Option<HeaderName>,
+    random: Arc<Random>,
+}
+
+impl<E> LoadShedEndpoint<E> {
+    fn priority(&self, req: &Request) -> Priority {
+        if let Some(priority) = req.extensions().get::<Priority>() {
+            return *priority;
+        }
+        self.priority_header
+            .as_ref()
+            .and_then(|name| req.headers().get(name))
+            .and_then(|value| value.to_str().ok())
+            .and_then(Priority::parse)
+            .unwrap_or_default()
+    }
+}
+
+impl<E: Endpoint> Endpoint for LoadShedEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let load = match &self.load {
+            Load::InFlight => self.in_flight.load(Ordering::Relaxed) as f64,
+            Load::Custom(f) => f(),
+        };
+        let probability = self.thresholds[self.priority(&req) as usize].shed_probability(load);
+        if probability > 0.0 && self.random.next() < probability {
+            return Err(LoadShedError.into());
+        }
+
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let _guard = InFlightGuard(&self.in_flight);
+        self.inner.call(req).await
+    }
+}
+
+/// Decrements the number of requests in flight when it is dropped.
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A cheap source of random numbers, good enough to pick the requests to
+/// shed or to add jitter to delays.
+#[derive(Default)]
+pub(crate) struct Random {
+    state: RandomState,
+    counter: AtomicU64,
+}
+
+impl Random {
+    /// Returns a random number in `[0, 1)`.
+    pub(crate) fn next(&self) -> f64 {
+        let n = self
+            .state
+            .hash_one(self.counter.fetch_add(1, Ordering::Relaxed));
+        (n >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[handler(internal)]
+    async fn index() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[test]
+    fn shed_probability() {
+        let thresholds = Thresholds {
+            start: 10.0,
+            end: 20.0,
+        };
+        assert_eq!(thresholds.shed_probability(0.0), 0.0);
+        assert_eq!(thresholds.shed_probability(10.0), 0.0);
+        assert_eq!(thresholds.shed_probability(15.0), 0.5);
+        assert_eq!(thresholds.shed_probability(20.0), 1.0);
+        assert_eq!(thresholds.shed_probability(30.0), 1.0);
+        assert_eq!(Thresholds::NEVER.shed_probability(f64::MAX), 0.0);
+
+        let mw = LoadShed::new(10.0, 20.0);
+        assert_eq!(mw.thresholds[Priority::Normal as usize].start, 20.0);
+        assert_eq!(mw.thresholds[Priority::High as usize].end, 40.0);
+    }
+
+    #[test]
+    fn random() {
+        let random = Random::default();
+        let values = (0..1000).map(|_| random.next()).collect::<Vec<_>>();
+        assert!(values.iter().all(|n| (0.0..1.0).contains(n)));
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((0.4..0.6).contains(&mean));
+    }
+
+    #[tokio::test]
+    async fn priority() {
+        let cli = TestClient::new(
+            index.with(
+                LoadShed::new(0.0, 10.0)
+                    .load_fn(|| 20.0)
+                    .priority_header("x-priority"),
+            ),
+        );
+        // the extension is preferred to the header
+        cli.get("/")
+            .header("x-priority", "critical")
+            .data(Priority::Low)
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        for priority in ["low", "normal", "invalid"] {
+            cli.get("/")
+                .header("x-priority", priority)
+                .send()
+                .await
+                .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        for priority in ["high", "Critical"] {
+            cli.get("/")
+                .header("x-priority", priority)
+                .send()
+                .await
+                .assert_status_is_ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn in_flight() {
+        let cli = TestClient::new(index.with(LoadShed::new(1.0, 1.0).thresholds(
+            Priority::High,
+            2.0,
+            2.0,
+        )));
+
+        let (a, b, c) = tokio::join!(
+            cli.get("/").send(),
+            async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                cli.get("/").send().await
+            },
+            async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                cli.get("/").data(Priority::High).send().await
+            }
+        );
+        a.assert_status_is_ok();
+        b.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        c.assert_status_is_ok();
+
+        cli.get("/").send().await.assert_status_is_ok();
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http::{HeaderValue, StatusCode, header};
+
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+/// A handle to switch the maintenance mode of the [`Maintenance`] middleware
+/// on and off at runtime.
+///
+/// Cloned handles share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceHandle(Arc<AtomicBool>);
+
+impl MaintenanceHandle {
+    /// Create a handle with the maintenance mode disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables the maintenance mode.
+    pub fn enable(&self) {
+        self.set(true);
+    }
+
+    /// Disables the maintenance mode.
+    pub fn disable(&self) {
+        self.set(false);
+    }
+
+    /// Enables or disables the maintenance mode.
+    pub fn set(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the maintenance mode is enabled.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Middleware that rejects requests with `503 Service Unavailable` while the
+/// maintenance mode is enabled.
+///
+/// The maintenance mode is switched with the [`MaintenanceHandle`] returned
+/// by [`Maintenance::handle`], for example from an admin endpoint. The
+/// rejected responses have a `Retry-After` header (60 seconds by default)
+/// and the configured body, the requests to the allowed paths, such as
+/// health checks, are always passed to the inner endpoint.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::StatusCode,
+///     middleware::{Maintenance, MaintenanceHandle},
+///     post,
+///     test::TestClient,
+///     web::Data,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// #[handler]
+/// fn healthz() {}
+///
+/// #[handler]
+/// fn enable(handle: Data<&MaintenanceHandle>) {
+///     handle.enable();
+/// }
+///
+/// let maintenance = Maintenance::new()
+///     .allow_path("/healthz")
+///     .allow_path("/admin/maintenance")
+///     .body("down for maintenance");
+/// let handle = maintenance.handle();
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .at("/healthz", get(healthz))
+///     .at("/admin/maintenance", post(enable))
+///     .with(maintenance)
+///     .data(handle);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/").send().await.assert_text("hello").await;
+///
+/// cli.post("/admin/maintenance")
+///     .send()
+///     .await
+///     .assert_status_is_ok();
+///
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+/// resp.assert_header("Retry-After", "60");
+/// resp.assert_text("down for maintenance").await;
+///
+/// cli.get("/healthz").send().await.assert_status_is_ok();
+/// # });
+/// ```
+pub struct Maintenance {
+    handle: MaintenanceHandle,
+    allow_paths: HashSet<String>,
+    retry_after: Duration,
+    body: Bytes,
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Maintenance {
+    /// Create `Maintenance` middleware with the maintenance mode disabled.
+    pub fn new() -> Self {
+        Self::with_handle(MaintenanceHandle::new())
+    }
+
+    /// Create `Maintenance` middleware controlled by an existing handle.
+    pub fn with_handle(handle: MaintenanceHandle) -> Self {
+        Self {
+            handle,
+            allow_paths: HashSet::new(),
+            retry_after: Duration::from_secs(60),
+            body: Bytes::new(),
+        }
+    }
+
+    /// Returns the handle that switches the maintenance mode.
+    pub fn handle(&self) -> MaintenanceHandle {
+        self.handle.clone()
+    }
+
+    /// Allows the requests to `path` during maintenance.
+    ///
+    /// The path is compared with the path of the original request URI.
+    #[must_use]
+    pub fn allow_path(mut self, path: impl Into<String>) -> Self {
+        self.allow_paths.insert(path.into());
+        self
+    }
+
+    /// Sets the value of the `Retry-After` header, rounded down to seconds.
+    #[must_use]
+    pub fn retry_after(self, retry_after: Duration) -> Self {
+        Self {
+            retry_after,
+            ..self
+        }
+    }
+
+    /// Sets the body of the rejected responses.
+    #[must_use]
+    pub fn body(self, body: impl Into<Bytes>) -> Self {
+        Self {
+            body: body.into(),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Maintenance {
+    type Output = MaintenanceEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        MaintenanceEndpoint {
+            inner: ep,
+            handle: self.handle.clone(),
+            allow_paths: self.allow_paths.clone(),
+            retry_after: HeaderValue::from(self.retry_after.as_secs()),
+            body: self.body.clone(),
+        }
+    }
+}
+
+/// Endpoint for the Maintenance middleware.
+pub struct MaintenanceEndpoint<E> {
+    inner: E,
+    handle: MaintenanceHandle,
+    allow_paths: HashSet<String>,
+    retry_after: HeaderValue,
+    body: Bytes,
+}
+
+impl<E: Endpoint> Endpoint for MaintenanceEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if self.handle.is_enabled() && !self.allow_paths.contains(req.original_uri().path()) {
+            return Ok(Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .header(header::RETRY_AFTER, self.retry_after.clone())
+                .body(self.body.clone()));
+        }
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointExt, Route, handler, test::TestClient};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn maintenance() {
+        let maintenance = Maintenance::new()
+            .allow_path("/api/healthz")
+            .retry_after(Duration::from_secs(300))
+            .body("maintenance");
+        let handle = maintenance.handle();
+        let cli = TestClient::new(
+            Route::new()
+                .nest(
+                    "/api",
+                    Route::new().at("/healthz", index).at("/users", index),
+                )
+                .with(maintenance),
+        );
+
+        cli.get("/api/users")
+            .send()
+            .await
+            .assert_text("hello")
+            .await;
+
+        handle.enable();
+        assert!(handle.is_enabled());
+        let resp = cli.get("/api/users").send().await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        resp.assert_header(header::RETRY_AFTER, "300");
+        resp.assert_text("maintenance").await;
+        cli.get("/api/healthz")
+            .send()
+            .await
+            .assert_text("hello")
+            .await;
+
+        handle.disable();
+        cli.get("/api/users")
+            .send()
+            .await
+            .assert_text("hello")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn shared_handle() {
+        let handle = MaintenanceHandle::new();
+        let cli = TestClient::new(index.with(Maintenance::with_handle(handle.clone())));
+
+        handle.set(true);
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        resp.assert_header(header::RETRY_AFTER, "60");
+        resp.assert_text("").await;
+    }
+}
@@ -0,0 +1,280 @@
+use http::{HeaderName, Method, header};
+
+use crate::{
+    Body, Endpoint, Middleware, Request, Result,
+    error::MethodOverrideError,
+    web::{BodyLimit, is_form_content_type},
+};
+
+/// Middleware for overriding the method of `POST` requests.
+///
+/// HTML forms can only send `GET` and `POST` requests, so the real method is
+/// tunneled with the `X-HTTP-Method-Override` header or the `_method` field of
+/// an `application/x-www-form-urlencoded` body. Only `PUT`, `PATCH` and
+/// `DELETE` are accepted.
+///
+/// The method must be rewritten before the [`Route`](crate::Route) matches it,
+/// so apply this middleware to the whole application.
+///
+/// A form body is read into memory to find the field, so its size is limited
+/// by the [`BodyLimit`] of the request, or the default limit of `2 MiB`.
+///
+/// # Errors
+///
+/// - [`MethodOverrideError`]
+/// - [`ReadBodyError`](crate::error::ReadBodyError)
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, delete, handler, http::StatusCode, middleware::MethodOverride,
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn remove() -> &'static str {
+///     "deleted"
+/// }
+///
+/// let app = Route::new()
+///     .at("/item", delete(remove))
+///     .with(MethodOverride::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/item")
+///     .form(&[("_method", "DELETE")])
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("deleted").await;
+/// # });
+/// ```
+pub struct MethodOverride {
+    header: HeaderName,
+    form_field: Option<String>,
+}
+
+impl Default for MethodOverride {
+    fn default() -> Self {
+        Self {
+            header: HeaderName::from_static("x-http-method-override"),
+            form_field: Some("_method".to_string()),
+        }
+    }
+}
+
+impl MethodOverride {
+    /// Create `MethodOverride` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the name of the header that contains the method.
+    ///
+    /// Default is `X-HTTP-Method-Override`.
+    #[must_use]
+    pub fn header<K>(self, key: K) -> Self
+    where
+        K: TryInto<HeaderName>,
+    {
+        match key.try_into() {
+            Ok(header) => Self { header, ..self },
+            Err(_) => self,
+        }
+    }
+
+    /// Sets the name of the form field that contains the method, or `None`
+    /// to never read the body.
+    ///
+    /// Default is `_method`.
+    #[must_use]
+    pub fn form_field(self, name: Option<impl Into<String>>) -> Self {
+        Self {
+            form_field: name.map(Into::into),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for MethodOverride {
+    type Output = MethodOverrideEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        MethodOverrideEndpoint {
+            inner: ep,
+            header: self.header.clone(),
+            form_field: self.form_field.clone(),
+        }
+    }
+}
+
+/// Endpoint for the MethodOverride middleware.
+pub struct MethodOverrideEndpoint<E> {
+    inner: E,
+    header: HeaderName,
+    form_field: Option<String>,
+}
+
+impl<E> MethodOverrideEndpoint<E> {
+    async fn override_method(&self, req: &mut Request) -> Result<Option<String>> {
+        if let Some(value) = req.headers().get(&self.header) {
+            return Ok(Some(
+                value
+                    .to_str()
+                    .map_err(|_| MethodOverrideError::InvalidMethod(String::new()))?
+                    .to_string(),
+            ));
+        }
+
+        let Some(form_field) = &self.form_field else {
+            return Ok(None);
+        };
+        if !req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(is_form_content_type)
+        {
+            return Ok(None);
+        }
+
+        // The body is put back, so the handler can still extract the form.
+        let limit = BodyLimit::of(req)?;
+        let mut body = req.take_body();
+        if let Some(limit) = limit {
+            body = BodyLimit(limit).apply(body);
+        }
+        let data = body.into_bytes().await?;
+        let method = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&data)
+            .ok()
+            .and_then(|fields| {
+                fields
+                    .into_iter()
+                    .find(|(name, _)| name == form_field)
+                    .map(|(_, value)| value)
+            });
+        req.set_body(Body::from(data));
+        Ok(method)
+    }
+}
+
+impl<E: Endpoint> Endpoint for MethodOverrideEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if req.method() == Method::POST {
+            if let Some(method) = self.override_method(&mut req).await? {
+                req.set_method(match method.to_ascii_uppercase().as_str() {
+                    "PUT" => Method::PUT,
+                    "PATCH" => Method::PATCH,
+                    "DELETE" => Method::DELETE,
+                    _ => return Err(MethodOverrideError::InvalidMethod(method).into()),
+                });
+            }
+        }
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, Route, handler, post, test::TestClient, web::Form};
+
+    #[handler(internal)]
+    fn index(method: Method) -> String {
+        method.to_string()
+    }
+
+    fn app(middleware: MethodOverride) -> impl Endpoint {
+        Route::new()
+            .at("/", post(index).put(index).patch(index).delete(index))
+            .with(middleware)
+    }
+
+    #[tokio::test]
+    async fn header() {
+        let cli = TestClient::new(app(MethodOverride::new()));
+
+        for method in ["PUT", "PATCH", "DELETE", "delete"] {
+            let resp = cli
+                .post("/")
+                .header("X-HTTP-Method-Override", method)
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            resp.assert_text(method.to_ascii_uppercase()).await;
+        }
+
+        let resp = cli.post("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("POST").await;
+
+        cli.post("/")
+            .header("X-HTTP-Method-Override", "GET")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        let cli = TestClient::new(app(MethodOverride::new().header("x-method")));
+        let resp = cli.post("/").header("x-method", "PUT").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("PUT").await;
+    }
+
+    #[tokio::test]
+    async fn form_field() {
+        #[handler(internal)]
+        fn form(method: Method, Form(fields): Form<Vec<(String, String)>>) -> String {
+            format!("{method} {}", fields.len())
+        }
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/", post(form).patch(form))
+                .with(MethodOverride::new()),
+        );
+
+        let resp = cli
+            .post("/")
+            .form(&[("_method", "PATCH"), ("name", "abc")])
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("PATCH 2").await;
+
+        let resp = cli.post("/").form(&[("name", "abc")]).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("POST 1").await;
+
+        cli.post("/")
+            .form(&[("_method", "TRACE")])
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        let cli = TestClient::new(app(MethodOverride::new().form_field(None::<String>)));
+        let resp = cli.post("/").form(&[("_method", "PATCH")]).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("POST").await;
+    }
+
+    #[tokio::test]
+    async fn body_limit() {
+        let cli = TestClient::new(app(MethodOverride::new()).data(BodyLimit(16)));
+        let resp = cli.post("/").form(&[("_method", "PUT")]).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("PUT").await;
+
+        cli.post("/")
+            .form(&[("_method", "PUT"), ("name", "abc")])
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
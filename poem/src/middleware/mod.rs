@@ -1,31 +1,58 @@
 //! Commonly used middleware.
 
+mod access_log;
 mod add_data;
+mod basic_auth;
+mod bytes_sent;
+mod cache;
+mod capture_body;
 mod catch_panic;
+mod circuit_breaker;
 #[cfg(feature = "compression")]
 mod compression;
+mod concurrency_limit;
+mod conditional;
+mod consumes;
 #[cfg(feature = "cookie")]
 mod cookie_jar_manager;
 mod cors;
+#[cfg(feature = "csp")]
+mod csp;
 #[cfg(feature = "csrf")]
 mod csrf;
+#[cfg(feature = "compression")]
+mod decompression;
+mod extractor_errors;
 mod force_https;
+mod hsts;
+mod ip_filter;
+mod load_shed;
+mod maintenance;
+mod method_override;
 mod normalize_path;
 #[cfg(feature = "opentelemetry")]
 mod opentelemetry_metrics;
 #[cfg(feature = "opentelemetry")]
 mod opentelemetry_tracing;
+mod problem_details;
+mod produces;
 mod propagate_header;
+mod rate_limit;
 #[cfg(feature = "requestid")]
 mod requestid;
+mod retry;
 mod sensitive_header;
+mod server_timing;
 mod set_header;
 mod size_limit;
+mod timeout;
 #[cfg(feature = "tokio-metrics")]
 mod tokio_metrics_mw;
 #[cfg(feature = "tower-compat")]
 mod tower_compat;
 mod tracing_mw;
+#[cfg(feature = "digest")]
+mod verify_digest;
 
 use std::marker::PhantomData;
 
@@ -33,8 +60,12 @@ use std::marker::PhantomData;
 pub use self::compression::{Compression, CompressionEndpoint};
 #[cfg(feature = "cookie")]
 pub use self::cookie_jar_manager::{CookieJarManager, CookieJarManagerEndpoint};
+#[cfg(feature = "csp")]
+pub use self::csp::{Csp, CspEndpoint, CspNonce, CspSource};
 #[cfg(feature = "csrf")]
 pub use self::csrf::{Csrf, CsrfEndpoint};
+#[cfg(feature = "compression")]
+pub use self::decompression::{Decompression, DecompressionEndpoint};
 #[cfg(feature = "opentelemetry")]
 pub use self::opentelemetry_metrics::{OpenTelemetryMetrics, OpenTelemetryMetricsEndpoint};
 #[cfg(feature = "opentelemetry")]
@@ -45,17 +76,46 @@ pub use self::requestid::{ReqId, RequestId, RequestIdEndpoint, ReuseId};
 pub use self::tokio_metrics_mw::{TokioMetrics, TokioMetricsEndpoint};
 #[cfg(feature = "tower-compat")]
 pub use self::tower_compat::TowerLayerCompatExt;
+#[cfg(feature = "digest")]
+pub use self::verify_digest::{VerifyDigest, VerifyDigestEndpoint};
 pub use self::{
+    access_log::{AccessLog, AccessLogEndpoint, AccessLogFormat},
     add_data::{AddData, AddDataEndpoint},
-    catch_panic::{CatchPanic, CatchPanicEndpoint, PanicHandler},
+    basic_auth::{BasicAuth, BasicAuthEndpoint, BasicAuthUser},
+    bytes_sent::{BytesSent, BytesSentEndpoint},
+    cache::{Cache, CacheEndpoint, CacheStore, CachedResponse, MemoryCacheStore},
+    capture_body::{CaptureBody, CaptureBodyEndpoint, CapturedBody},
+    catch_panic::{CatchPanic, CatchPanicEndpoint, PanicHandler, PanicReport, panic_message},
+    circuit_breaker::{CircuitBreaker, CircuitBreakerEndpoint},
+    concurrency_limit::{ConcurrencyLimit, ConcurrencyLimitEndpoint, WhenFull},
+    conditional::{Conditional, ConditionalEndpoint},
+    consumes::{Consumes, ConsumesEndpoint},
     cors::{Cors, CorsEndpoint},
+    extractor_errors::{ExtractorErrors, ExtractorErrorsEndpoint},
     force_https::ForceHttps,
+    hsts::{Hsts, HstsEndpoint},
+    ip_filter::{IpFilter, IpFilterEndpoint},
+    load_shed::{LoadShed, LoadShedEndpoint, Priority},
+    maintenance::{Maintenance, MaintenanceEndpoint, MaintenanceHandle},
+    method_override::{MethodOverride, MethodOverrideEndpoint},
     normalize_path::{NormalizePath, NormalizePathEndpoint, TrailingSlash},
+    problem_details::{ProblemDetails, ProblemDetailsEndpoint},
+    produces::{Produces, ProducesEndpoint},
     propagate_header::{PropagateHeader, PropagateHeaderEndpoint},
+    rate_limit::{
+        MemoryRateLimitStore, RateLimit, RateLimitDecision, RateLimitEndpoint, RateLimitStore,
+    },
+    retry::{Retry, RetryEndpoint, Retryable},
     sensitive_header::{SensitiveHeader, SensitiveHeaderEndpoint},
+    server_timing::{ServerTiming, ServerTimingHeader, ServerTimingHeaderEndpoint},
     set_header::{SetHeader, SetHeaderEndpoint},
     size_limit::{SizeLimit, SizeLimitEndpoint},
-    tracing_mw::{Tracing, TracingEndpoint},
+    timeout::{Deadline, Timeout, TimeoutEndpoint, TimeoutOverride},
+    tracing_mw::{CustomTracing, Tracing, TracingEndpoint},
+};
+pub(crate) use self::{
+    ip_filter::{client_ip, parse_nets},
+    load_shed::Random,
 };
 use crate::endpoint::{EitherEndpoint, Endpoint};
 
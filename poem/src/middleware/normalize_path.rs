@@ -3,10 +3,11 @@ use std::str::FromStr;
 use http::{Uri, uri::PathAndQuery};
 use regex::Regex;
 
-use crate::{Endpoint, Middleware, Request, Result};
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, web::Redirect};
 
 /// Determines the behavior of the [`NormalizePath`] middleware.
 #[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
 pub enum TrailingSlash {
     /// Trim trailing slashes from the end of the path.
     #[default]
@@ -17,11 +18,22 @@ pub enum TrailingSlash {
 
     /// Always add a trailing slash to the end of the path.
     Always,
+
+    /// Like [`TrailingSlash::Trim`], but responds with a `301 Moved
+    /// Permanently` redirect to the normalized path instead of rewriting it.
+    RedirectTrim,
+
+    /// Like [`TrailingSlash::Always`], but responds with a `301 Moved
+    /// Permanently` redirect to the normalized path instead of rewriting it.
+    RedirectAppend,
 }
 
 /// Middleware for normalizing a request's path so that routes can be matched
 /// more flexibly.
 ///
+/// The output of the endpoint is a [`Response`] rather than the output of the
+/// inner endpoint, because the redirect modes respond without calling it.
+///
 /// # Example
 ///
 /// ```
@@ -48,6 +60,35 @@ pub enum TrailingSlash {
 /// resp.assert_text("hello").await;
 /// # });
 /// ```
+///
+/// With [`TrailingSlash::RedirectTrim`] or [`TrailingSlash::RedirectAppend`],
+/// the client is redirected to the canonical URL, and the query string is
+/// preserved.
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::StatusCode,
+///     middleware::{NormalizePath, TrailingSlash},
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Route::new()
+///     .at("/foo/bar", get(index))
+///     .with(NormalizePath::new(TrailingSlash::RedirectTrim));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/foo/bar/?page=2").send().await;
+/// resp.assert_status(StatusCode::MOVED_PERMANENTLY);
+/// resp.assert_header("Location", "/foo/bar?page=2");
+/// # });
+/// ```
 pub struct NormalizePath(TrailingSlash);
 
 impl NormalizePath {
@@ -71,50 +112,78 @@ impl<E: Endpoint> Middleware<E> for NormalizePath {
 }
 
 /// Endpoint for the NormalizePath middleware.
+///
+/// Its output is always a [`Response`], whatever the output of `E` is.
 pub struct NormalizePathEndpoint<E> {
     inner: E,
     merge_slash: Regex,
     style: TrailingSlash,
 }
 
+impl<E> NormalizePathEndpoint<E> {
+    /// Returns the normalized path, or `None` if the path is already
+    /// normalized.
+    fn normalize(&self, original_path: &str) -> Option<String> {
+        if original_path.is_empty() {
+            return None;
+        }
+
+        let path = match self.style {
+            TrailingSlash::Always | TrailingSlash::RedirectAppend => format!("{original_path}/"),
+            TrailingSlash::MergeOnly => original_path.to_string(),
+            TrailingSlash::Trim | TrailingSlash::RedirectTrim => {
+                original_path.trim_end_matches('/').to_string()
+            }
+        };
+
+        let path = self.merge_slash.replace_all(&path, "/");
+        let path = if path.is_empty() { "/" } else { path.as_ref() };
+        (path != original_path).then(|| path.to_string())
+    }
+}
+
 impl<E: Endpoint> Endpoint for NormalizePathEndpoint<E> {
-    type Output = E::Output;
+    type Output = Response;
 
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if matches!(
+            self.style,
+            TrailingSlash::RedirectTrim | TrailingSlash::RedirectAppend
+        ) {
+            let uri = req.original_uri();
+            if let Some(path) = self.normalize(uri.path()) {
+                let location = match uri.query() {
+                    Some(query) => format!("{path}?{query}"),
+                    None => path,
+                };
+                return Ok(Redirect::moved_permanent(location).into_response());
+            }
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        }
+
         let original_path = req
             .uri()
             .path_and_query()
             .map(|x| x.path())
             .unwrap_or_default();
 
-        if !original_path.is_empty() {
-            let path = match self.style {
-                TrailingSlash::Always => format!("{original_path}/"),
-                TrailingSlash::MergeOnly => original_path.to_string(),
-                TrailingSlash::Trim => original_path.trim_end_matches('/').to_string(),
+        if let Some(path) = self.normalize(original_path) {
+            let (mut parts, body) = req.into_parts();
+            let mut uri_parts = parts.uri.into_parts();
+            let query = uri_parts.path_and_query.as_ref().and_then(|pq| pq.query());
+            let path = match query {
+                Some(query) => format!("{path}?{query}"),
+                None => path,
             };
+            uri_parts.path_and_query = Some(PathAndQuery::from_str(&path).unwrap());
 
-            let path = self.merge_slash.replace_all(&path, "/");
-            let path = if path.is_empty() { "/" } else { path.as_ref() };
-
-            if path != original_path {
-                let (mut parts, body) = req.into_parts();
-                let mut uri_parts = parts.uri.into_parts();
-                let query = uri_parts.path_and_query.as_ref().and_then(|pq| pq.query());
-                let path = match query {
-                    Some(query) => format!("{path}?{query}"),
-                    None => path.to_string(),
-                };
-                uri_parts.path_and_query = Some(PathAndQuery::from_str(&path).unwrap());
+            let new_uri = Uri::from_parts(uri_parts).unwrap();
+            parts.uri = new_uri;
 
-                let new_uri = Uri::from_parts(uri_parts).unwrap();
-                parts.uri = new_uri;
-
-                req = Request::from_parts(parts, body);
-            }
+            req = Request::from_parts(parts, body);
         }
 
-        self.inner.call(req).await
+        self.inner.call(req).await.map(IntoResponse::into_response)
     }
 }
 
@@ -290,4 +359,46 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn redirect() {
+        let ep = Route::new()
+            .at("/", make_sync(|_| "root"))
+            .nest(
+                "/api",
+                Route::new()
+                    .at("/v1/something", make_sync(|_| "trim"))
+                    .with(NormalizePath::new(TrailingSlash::RedirectTrim)),
+            )
+            .nest(
+                "/app",
+                Route::new()
+                    .at("/v1/something/", make_sync(|_| "append"))
+                    .with(NormalizePath::new(TrailingSlash::RedirectAppend)),
+            );
+        let cli = TestClient::new(ep);
+
+        for (uri, location) in [
+            ("/api/v1/something/", "/api/v1/something"),
+            ("/api/v1/something//?a=1&b=2", "/api/v1/something?a=1&b=2"),
+            ("/api//v1//something", "/api/v1/something"),
+            ("/app/v1/something", "/app/v1/something/"),
+            ("/app/v1/something?a=1", "/app/v1/something/?a=1"),
+        ] {
+            let resp = cli.get(uri).send().await;
+            resp.assert_status(StatusCode::MOVED_PERMANENTLY);
+            resp.assert_header("Location", location);
+        }
+
+        cli.get("/api/v1/something")
+            .send()
+            .await
+            .assert_text("trim")
+            .await;
+        cli.get("/app/v1/something/?a=1")
+            .send()
+            .await
+            .assert_text("append")
+            .await;
+    }
 }
@@ -1,7 +1,7 @@
 use std::time::Instant;
 
 use libopentelemetry::{
-    Key, KeyValue, global,
+    KeyValue, global,
     metrics::{Counter, Histogram},
 };
 use opentelemetry_semantic_conventions::trace;
@@ -9,6 +9,12 @@ use opentelemetry_semantic_conventions::trace;
 use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, route::PathPattern};
 
 /// Middleware for metrics with OpenTelemetry.
+///
+/// The requests are counted and timed by `http.request.method`,
+/// `http.response.status_code` and `http.route`, the pattern of the matched
+/// [`Route`](crate::Route) such as `/users/:id`, or `<unmatched>` when no route
+/// matches the request. The middleware should be applied outside the route,
+/// which reports the matched pattern in the response.
 #[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
 pub struct OpenTelemetryMetrics {
     request_count: Counter<u64>,
@@ -72,15 +78,11 @@ impl<E: Endpoint> Endpoint for OpenTelemetryMetricsEndpoint<E> {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
-        let mut labels = Vec::with_capacity(3);
+        let mut labels = Vec::with_capacity(4);
         labels.push(KeyValue::new(
             trace::HTTP_REQUEST_METHOD,
             req.method().to_string(),
         ));
-        labels.push(KeyValue::new(
-            trace::URL_FULL,
-            req.original_uri().to_string(),
-        ));
 
         let s = Instant::now();
         let res = self.inner.call(req).await.map(IntoResponse::into_response);
@@ -88,22 +90,14 @@ impl<E: Endpoint> Endpoint for OpenTelemetryMetricsEndpoint<E> {
 
         match &res {
             Ok(resp) => {
-                if let Some(path_pattern) = resp.data::<PathPattern>() {
-                    const HTTP_PATH_PATTERN: Key = Key::from_static_str("http.path_pattern");
-                    labels.push(KeyValue::new(HTTP_PATH_PATTERN, path_pattern.0.to_string()));
-                }
-
+                labels.push(route_label(resp.data::<PathPattern>()));
                 labels.push(KeyValue::new(
                     trace::HTTP_RESPONSE_STATUS_CODE,
                     resp.status().as_u16() as i64,
                 ));
             }
             Err(err) => {
-                if let Some(path_pattern) = err.data::<PathPattern>() {
-                    const HTTP_PATH_PATTERN: Key = Key::from_static_str("http.path_pattern");
-                    labels.push(KeyValue::new(HTTP_PATH_PATTERN, path_pattern.0.to_string()));
-                }
-
+                labels.push(route_label(err.data::<PathPattern>()));
                 labels.push(KeyValue::new(
                     trace::HTTP_RESPONSE_STATUS_CODE,
                     err.status().as_u16() as i64,
@@ -120,3 +114,16 @@ impl<E: Endpoint> Endpoint for OpenTelemetryMetricsEndpoint<E> {
         res
     }
 }
+
+/// Returns the `http.route` label, the pattern of the matched route or
+/// `<unmatched>`, so that requests to unknown paths do not create new time
+/// series.
+fn route_label(path_pattern: Option<&PathPattern>) -> KeyValue {
+    KeyValue::new(
+        trace::HTTP_ROUTE,
+        path_pattern.map_or_else(
+            || "<unmatched>".to_string(),
+            |pattern| pattern.0.to_string(),
+        ),
+    )
+}
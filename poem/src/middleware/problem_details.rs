@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use http::header;
+
+use crate::{
+    Endpoint, Error, IntoResponse, Middleware, Request, Response, Result,
+    web::{Problem, ProblemBuilder},
+};
+
+type CustomizeFn = Arc<dyn Fn(&Error, &mut ProblemBuilder) + Send + Sync>;
+
+/// Middleware for rendering errors as
+/// [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) problem details.
+///
+/// Errors returned by the inner endpoint are converted to a [`Problem`] with
+/// the status code of the error as `status`, the error message as `detail`
+/// and the request path as `instance`, and sent with the
+/// `application/problem+json` content type. Headers of the original error
+/// response, such as `Retry-After`, are kept.
+///
+/// Without this middleware, errors are rendered as plain text.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Error, Route, handler, http::StatusCode, middleware::ProblemDetails,
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index() -> poem::Result<()> {
+///     Err(Error::from_string("out of credit", StatusCode::FORBIDDEN))
+/// }
+///
+/// let app = Route::new()
+///     .at("/", index)
+///     .with(ProblemDetails::new().customize(|_, problem| {
+///         problem.type_uri("https://example.com/probs/out-of-credit");
+///     }));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status(StatusCode::FORBIDDEN);
+/// resp.assert_content_type("application/problem+json");
+/// let json = resp.json().await;
+/// let json = json.value().object();
+/// json.get("type")
+///     .assert_string("https://example.com/probs/out-of-credit");
+/// json.get("detail").assert_string("out of credit");
+/// # });
+/// ```
+#[derive(Default)]
+pub struct ProblemDetails {
+    customize: Option<CustomizeFn>,
+}
+
+impl ProblemDetails {
+    /// Create `ProblemDetails` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Uses a closure to add or change fields of the problem generated from
+    /// an error.
+    #[must_use]
+    pub fn customize(
+        self,
+        f: impl Fn(&Error, &mut ProblemBuilder) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            customize: Some(Arc::new(f)),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ProblemDetails {
+    type Output = ProblemDetailsEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ProblemDetailsEndpoint {
+            inner: ep,
+            customize: self.customize.clone(),
+        }
+    }
+}
+
+/// Endpoint for the ProblemDetails middleware.
+pub struct ProblemDetailsEndpoint<E> {
+    inner: E,
+    customize: Option<CustomizeFn>,
+}
+
+impl<E: Endpoint> Endpoint for ProblemDetailsEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let instance = req.uri().path().to_string();
+        let err = match self.inner.call(req).await {
+            Ok(resp) => return Ok(resp.into_response()),
+            Err(err) => err,
+        };
+
+        let mut builder = Problem::builder(err.status());
+        builder.detail(err.to_string()).instance(instance);
+        if let Some(customize) = &self.customize {
+            customize(&err, &mut builder);
+        }
+
+        let mut resp = builder.build().into_response();
+        merge_error_response(&mut resp, err);
+        Ok(resp)
+    }
+}
+
+/// Moves the headers and extensions of the response of `err` to `resp`,
+/// except the headers that describe the body.
+pub(crate) fn merge_error_response(resp: &mut Response, err: Error) {
+    let mut err_resp = err.into_response();
+    for (name, value) in std::mem::take(err_resp.headers_mut()) {
+        if let Some(name) = name {
+            if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                resp.headers_mut().append(name, value);
+            }
+        }
+    }
+    *resp.extensions_mut() = std::mem::take(err_resp.extensions_mut());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::StatusCode;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::{
+        EndpointExt,
+        endpoint::make_sync,
+        error::{NotFoundError, RateLimitError},
+        handler,
+        test::TestClient,
+    };
+
+    #[tokio::test]
+    async fn problem_details() {
+        #[handler(internal)]
+        fn index() -> Result<(), NotFoundError> {
+            Err(NotFoundError)
+        }
+
+        let resp = TestClient::new(index.with(ProblemDetails::new()))
+            .get("/users/1")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_FOUND);
+        resp.assert_content_type("application/problem+json");
+        assert_eq!(
+            serde_json::from_str::<Value>(&resp.0.into_body().into_string().await.unwrap())
+                .unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "detail": "not found",
+                "instance": "/users/1",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn customize() {
+        let ep = make_sync(|_| -> Result<(), RateLimitError> {
+            Err(RateLimitError::TooManyRequests {
+                retry_after: Duration::from_secs(10),
+            })
+        })
+        .with(ProblemDetails::new().customize(|err, problem| {
+            if err.is::<RateLimitError>() {
+                problem
+                    .type_uri("https://example.com/probs/rate-limit")
+                    .extension("retry_after", 10);
+            }
+        }));
+
+        let resp = TestClient::new(ep).get("/").send().await;
+        resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        resp.assert_header(header::RETRY_AFTER, "10");
+        resp.assert_content_type("application/problem+json");
+        let json = resp.json().await;
+        let json = json.value().object();
+        json.get("type")
+            .assert_string("https://example.com/probs/rate-limit");
+        json.get("detail").assert_string("too many requests");
+        json.get("retry_after").assert_i64(10);
+    }
+
+    #[tokio::test]
+    async fn ok_response() {
+        let resp = TestClient::new(make_sync(|_| "hello").with(ProblemDetails::new()))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello").await;
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use mime::Mime;
+
+use crate::{
+    Endpoint, Middleware, Request, Result,
+    error::NotAcceptableError,
+    middleware::consumes::{media_type_matches, parse_media_types},
+    web::parse_accept,
+};
+
+/// Returns `true` if the `Accept` ranges accept `mime`.
+///
+/// The quality value of the most specific range that matches `mime` is used,
+/// so `text/*;q=0` rejects `text/plain` even with `*/*`.
+fn is_acceptable(accept: &[Mime], mime: &Mime) -> bool {
+    accept
+        .iter()
+        .filter(|range| media_type_matches(range, mime))
+        .max_by_key(|range| {
+            (range.type_() != mime::STAR) as u8 + (range.subtype() != mime::STAR) as u8
+        })
+        .is_some_and(|range| {
+            range
+                .get_param("q")
+                .is_none_or(|q| q.as_str().parse::<f32>().map_or(true, |q| q > 0.0))
+        })
+}
+
+/// Middleware that rejects the requests whose `Accept` header does not
+/// accept any of the media types produced by the inner endpoint with
+/// `406 Not Acceptable`.
+///
+/// The `Accept` ranges can be `type/*` or `*/*` wildcards with quality
+/// values, a range with `q=0` excludes the types it matches. Requests
+/// without an `Accept` header accept any type.
+///
+/// Some clients send an `Accept` header that does not match the response
+/// they expect, so the middleware should only wrap the endpoints of strict
+/// APIs.
+///
+/// # Errors
+///
+/// - [`NotAcceptableError`]
+///
+/// # Panics
+///
+/// [`Produces::new`] panics if a media type is invalid.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::{StatusCode, header},
+///     middleware::Produces,
+///     test::TestClient,
+///     web::Json,
+/// };
+///
+/// #[handler]
+/// fn index() -> Json<i32> {
+///     Json(1)
+/// }
+///
+/// let app = Route::new().at("/", get(index.with(Produces::new(["application/json"]))));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/")
+///     .header(header::ACCEPT, "text/html, application/*;q=0.8")
+///     .send()
+///     .await
+///     .assert_status_is_ok();
+///
+/// cli.get("/")
+///     .header(header::ACCEPT, "text/html")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::NOT_ACCEPTABLE);
+/// # });
+/// ```
+pub struct Produces {
+    types: Arc<[Mime]>,
+}
+
+impl Produces {
+    /// Create `Produces` middleware for an endpoint that produces `types`.
+    pub fn new<T: AsRef<str>>(types: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            types: parse_media_types(types),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Produces {
+    type Output = ProducesEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ProducesEndpoint {
+            inner: ep,
+            types: self.types.clone(),
+        }
+    }
+}
+
+/// Endpoint for the Produces middleware.
+pub struct ProducesEndpoint<E> {
+    inner: E,
+    types: Arc<[Mime]>,
+}
+
+impl<E: Endpoint> Endpoint for ProducesEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let accept = parse_accept(req.headers());
+        if !accept.is_empty() && !self.types.iter().any(|mime| is_acceptable(&accept, mime)) {
+            return Err(NotAcceptableError.into());
+        }
+        self.inner.call(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{StatusCode, header};
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[test]
+    fn acceptable() {
+        let acceptable = |accept: &str, mime: &str| {
+            let mut headers = http::HeaderMap::new();
+            headers.insert(header::ACCEPT, accept.parse().unwrap());
+            is_acceptable(&parse_accept(&headers), &mime.parse().unwrap())
+        };
+        assert!(acceptable("application/json", "application/json"));
+        assert!(acceptable(
+            "text/html, application/*;q=0.5",
+            "application/xml"
+        ));
+        assert!(acceptable("*/*", "image/png"));
+        assert!(acceptable("text/*;q=0, */*", "image/png"));
+        assert!(!acceptable("text/*;q=0, */*", "text/plain"));
+        assert!(acceptable("text/*;q=0, text/plain", "text/plain"));
+        assert!(!acceptable("application/json;q=0.0", "application/json"));
+        assert!(!acceptable("text/html", "application/json"));
+    }
+
+    #[tokio::test]
+    async fn produces() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let cli = TestClient::new(index.with(Produces::new(["text/plain", "text/csv"])));
+
+        cli.get("/").send().await.assert_text("hello").await;
+        for accept in ["text/csv", "application/json, */*;q=0.1", "text/*"] {
+            cli.get("/")
+                .header(header::ACCEPT, accept)
+                .send()
+                .await
+                .assert_text("hello")
+                .await;
+        }
+        for accept in ["application/json", "text/plain;q=0, text/csv;q=0"] {
+            cli.get("/")
+                .header(header::ACCEPT, accept)
+                .send()
+                .await
+                .assert_status(StatusCode::NOT_ACCEPTABLE);
+        }
+    }
+}
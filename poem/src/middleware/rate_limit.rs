@@ -0,0 +1,377 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::{BuildHasher, RandomState},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{Endpoint, Middleware, Request, Result, error::RateLimitError};
+
+type KeyFn = Arc<dyn Fn(&Request) -> String + Send + Sync>;
+
+/// The result of trying to take a token from a bucket.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RateLimitDecision {
+    /// The request is allowed.
+    Allow,
+    /// The bucket is empty, the request should be retried after the specified
+    /// duration.
+    Deny {
+        /// Time until the next token is available.
+        retry_after: Duration,
+    },
+}
+
+/// Represents a back-end storage for the token buckets of the [`RateLimit`]
+/// middleware.
+pub trait RateLimitStore: Send + Sync {
+    /// Take a token from the bucket identified by `key`.
+    ///
+    /// The bucket holds at most `max_requests` tokens and is fully refilled
+    /// in `per`. Implementations must perform the check-and-take atomically.
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        max_requests: u64,
+        per: Duration,
+    ) -> impl Future<Output = Result<RateLimitDecision>> + Send + 'a;
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+struct InnerStore {
+    buckets: HashMap<String, Bucket>,
+    cleanup_at: Instant,
+}
+
+const SHARDS: usize = 32;
+
+/// A rate limit storage using memory.
+///
+/// The buckets are split into shards by the hash of their key, so concurrent
+/// requests with different keys rarely wait for each other.
+pub struct MemoryRateLimitStore {
+    hasher: RandomState,
+    shards: Box<[Mutex<InnerStore>]>,
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            hasher: RandomState::new(),
+            shards: (0..SHARDS)
+                .map(|_| {
+                    Mutex::new(InnerStore {
+                        buckets: HashMap::new(),
+                        cleanup_at: now,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MemoryRateLimitStore {
+    /// Create a `MemoryRateLimitStore`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        max_requests: u64,
+        per: Duration,
+    ) -> Result<RateLimitDecision> {
+        let now = Instant::now();
+        let capacity = max_requests as f64;
+        let rate = capacity / per.as_secs_f64();
+        let shard = self.hasher.hash_one(key) as usize % self.shards.len();
+        let mut inner = self.shards[shard].lock();
+
+        // Buckets that have been idle for a full period are refilled, so they
+        // can be dropped without changing the outcome of later requests.
+        if now >= inner.cleanup_at {
+            inner
+                .buckets
+                .retain(|_, bucket| now.duration_since(bucket.updated_at) < per);
+            inner.cleanup_at = now + per;
+        }
+
+        let bucket = inner.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(RateLimitDecision::Allow)
+        } else {
+            Ok(RateLimitDecision::Deny {
+                retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rate),
+            })
+        }
+    }
+}
+
+/// Middleware for limiting the request rate with token buckets.
+///
+/// Each key owns a bucket of `max_requests` tokens that is refilled evenly
+/// over `per`. By default, requests are keyed by the IP address of the remote
+/// peer.
+///
+/// # Errors
+///
+/// - [`RateLimitError`]
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     EndpointExt, Request, handler, http::StatusCode, middleware::RateLimit, test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let ep = index.with(
+///     RateLimit::new(1, Duration::from_secs(60))
+///         .key_fn(|req: &Request| req.header("X-Api-Token").unwrap_or_default().to_string()),
+/// );
+/// let cli = TestClient::new(ep);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("X-Api-Token", "a").send().await;
+/// resp.assert_status_is_ok();
+///
+/// let resp = cli.get("/").header("X-Api-Token", "a").send().await;
+/// resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+///
+/// let resp = cli.get("/").header("X-Api-Token", "b").send().await;
+/// resp.assert_status_is_ok();
+/// # });
+/// ```
+pub struct RateLimit<T = MemoryRateLimitStore> {
+    max_requests: u64,
+    per: Duration,
+    key_fn: Option<KeyFn>,
+    store: Arc<T>,
+}
+
+impl RateLimit {
+    /// Create a `RateLimit` middleware that allows `max_requests` requests
+    /// every `per` for each key, backed by a [`MemoryRateLimitStore`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_requests` is `0` or `per` is zero.
+    pub fn new(max_requests: u64, per: Duration) -> Self {
+        assert!(max_requests > 0, "`max_requests` must be greater than 0");
+        assert!(!per.is_zero(), "`per` must be greater than zero");
+        Self {
+            max_requests,
+            per,
+            key_fn: None,
+            store: Arc::new(MemoryRateLimitStore::new()),
+        }
+    }
+}
+
+impl<T> RateLimit<T> {
+    /// Uses a closure to compute the bucket key of a request.
+    #[must_use]
+    pub fn key_fn(self, f: impl Fn(&Request) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            key_fn: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Sets the storage of the token buckets.
+    #[must_use]
+    pub fn store<T2>(self, store: T2) -> RateLimit<T2> {
+        RateLimit {
+            max_requests: self.max_requests,
+            per: self.per,
+            key_fn: self.key_fn,
+            store: Arc::new(store),
+        }
+    }
+}
+
+impl<T: RateLimitStore, E: Endpoint> Middleware<E> for RateLimit<T> {
+    type Output = RateLimitEndpoint<T, E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitEndpoint {
+            inner: ep,
+            max_requests: self.max_requests,
+            per: self.per,
+            key_fn: self.key_fn.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// Endpoint for the `RateLimit` middleware.
+pub struct RateLimitEndpoint<T, E> {
+    inner: E,
+    max_requests: u64,
+    per: Duration,
+    key_fn: Option<KeyFn>,
+    store: Arc<T>,
+}
+
+fn remote_ip_key(req: &Request) -> String {
+    match req.remote_addr().as_socket_addr() {
+        Some(addr) => addr.ip().to_string(),
+        None => req.remote_addr().to_string(),
+    }
+}
+
+impl<T: RateLimitStore, E: Endpoint> Endpoint for RateLimitEndpoint<T, E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let key = match &self.key_fn {
+            Some(key_fn) => key_fn(&req),
+            None => remote_ip_key(&req),
+        };
+
+        match self
+            .store
+            .acquire(&key, self.max_requests, self.per)
+            .await?
+        {
+            RateLimitDecision::Allow => self.inner.call(req).await,
+            RateLimitDecision::Deny { retry_after } => {
+                Err(RateLimitError::TooManyRequests { retry_after }.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[tokio::test]
+    async fn rate_limit() {
+        #[handler(internal)]
+        fn index() {}
+
+        let ep = index.with(RateLimit::new(2, Duration::from_secs(10)));
+        let cli = TestClient::new(ep);
+
+        cli.get("/").send().await.assert_status_is_ok();
+        cli.get("/").send().await.assert_status_is_ok();
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        resp.assert_header("retry-after", "5");
+    }
+
+    #[tokio::test]
+    async fn rate_limit_key_fn() {
+        #[handler(internal)]
+        fn index() {}
+
+        let ep = index.with(
+            RateLimit::new(1, Duration::from_secs(60))
+                .key_fn(|req| req.header("x-token").unwrap_or_default().to_string()),
+        );
+        let cli = TestClient::new(ep);
+
+        cli.get("/")
+            .header("x-token", "a")
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.get("/")
+            .header("x-token", "a")
+            .send()
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+        cli.get("/")
+            .header("x-token", "b")
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[test]
+    #[should_panic(expected = "`max_requests` must be greater than 0")]
+    fn zero_max_requests() {
+        let _ = RateLimit::new(0, Duration::from_secs(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "`per` must be greater than zero")]
+    fn zero_per() {
+        let _ = RateLimit::new(1, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn memory_store_concurrent_acquire() {
+        let store = Arc::new(MemoryRateLimitStore::new());
+        let tasks = (0..16)
+            .map(|_| {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    store
+                        .acquire("key", 5, Duration::from_secs(60))
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut allowed = 0;
+        for task in tasks {
+            if task.await.unwrap() == RateLimitDecision::Allow {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, 5);
+    }
+
+    #[tokio::test]
+    async fn memory_store_refill() {
+        let store = MemoryRateLimitStore::new();
+        let per = Duration::from_millis(100);
+
+        assert_eq!(
+            store.acquire("key", 1, per).await.unwrap(),
+            RateLimitDecision::Allow
+        );
+        assert!(matches!(
+            store.acquire("key", 1, per).await.unwrap(),
+            RateLimitDecision::Deny { .. }
+        ));
+
+        tokio::time::sleep(per).await;
+        assert_eq!(
+            store.acquire("key", 1, per).await.unwrap(),
+            RateLimitDecision::Allow
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use tracing::{Instrument, error, error_span};
+use tracing::{Instrument, Span, error, error_span};
 use uuid::Uuid;
 
 use crate::{
@@ -7,6 +7,7 @@ use crate::{
 };
 
 const X_REQUEST_ID: &str = "x-request-id";
+const MAX_ID_LEN: usize = 128;
 
 /// Whether to use the request ID supplied in the request.
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
@@ -20,9 +21,36 @@ pub enum ReuseId {
 }
 
 /// Middleware to add a unique ID to every incoming request.
+///
+/// The ID is written into a response header, recorded in the `request_id`
+/// field of the current tracing span (for example the span of the
+/// [`Tracing`](crate::middleware::Tracing) middleware, when it runs before
+/// this one), and can be extracted in handlers with [`ReqId`].
+///
+/// With [`ReuseId::Use`], the ID of the incoming request header is used if it
+/// is at most 128 characters long and only contains ASCII letters, digits and
+/// `-`, `_`, `.`, `:`. Otherwise a new ID is generated.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler,
+///     middleware::{RequestId, ReuseId, Tracing},
+/// };
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = Route::new()
+///     .at("/", index)
+///     .with(RequestId::new().reuse_id(ReuseId::Use))
+///     .with(Tracing);
+/// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "requestid")))]
 pub struct RequestId {
     header_name: String,
+    response_header_name: Option<String>,
     use_incoming_id: ReuseId,
 }
 
@@ -42,6 +70,16 @@ impl RequestId {
         }
     }
 
+    /// Sets the response header that the ID is written into, the ID header is
+    /// used by default.
+    #[must_use]
+    pub fn response_header_name(self, header_name: impl AsRef<str>) -> Self {
+        Self {
+            response_header_name: Some(header_name.as_ref().to_string()),
+            ..self
+        }
+    }
+
     /// Configure whether to use the incoming ID.
     #[must_use]
     pub fn reuse_id(self, reuse_id: ReuseId) -> Self {
@@ -56,6 +94,7 @@ impl Default for RequestId {
     fn default() -> Self {
         Self {
             header_name: X_REQUEST_ID.to_string(),
+            response_header_name: None,
             use_incoming_id: ReuseId::default(),
         }
     }
@@ -67,6 +106,10 @@ impl<E: Endpoint> Middleware<E> for RequestId {
         RequestIdEndpoint {
             next,
             header_name: self.header_name.clone(),
+            response_header_name: self
+                .response_header_name
+                .clone()
+                .unwrap_or_else(|| self.header_name.clone()),
             use_incoming_id: self.use_incoming_id,
         }
     }
@@ -77,26 +120,37 @@ impl<E: Endpoint> Middleware<E> for RequestId {
 pub struct RequestIdEndpoint<E> {
     next: E,
     header_name: String,
+    response_header_name: String,
     use_incoming_id: ReuseId,
 }
 
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.' | b':'))
+}
+
 impl<E: Endpoint> Endpoint for RequestIdEndpoint<E> {
     type Output = Response;
 
     async fn call(&self, mut request: Request) -> Result<Self::Output> {
-        let request_id = if self.use_incoming_id == ReuseId::Use {
-            request
+        let incoming_id = match self.use_incoming_id {
+            ReuseId::Use => request
                 .header(&self.header_name)
-                .map_or_else(|| Uuid::new_v4().to_string(), ToString::to_string)
-        } else {
-            Uuid::new_v4().to_string()
+                .filter(|id| is_valid_id(id)),
+            ReuseId::Ignore => None,
         };
+        let request_id =
+            incoming_id.map_or_else(|| Uuid::new_v4().to_string(), ToString::to_string);
         request.set_data(ReqId(request_id.clone()));
+        Span::current().record("request_id", request_id.as_str());
         let response = self.next.call(request);
         let response = response.instrument(error_span!("", %request_id));
         match response.await {
             Ok(res) => Ok(res
-                .with_header(&self.header_name, request_id.to_string())
+                .with_header(&self.response_header_name, request_id)
                 .into_response()),
             Err(e) => Err(e),
         }
@@ -231,4 +285,35 @@ mod tests {
         response.assert_header_exist(header_name);
         assert_ne!(response.0.header(header_name), Some(id));
     }
+
+    #[tokio::test]
+    async fn invalid_incoming_id() {
+        let app = app(RequestId::default().reuse_id(ReuseId::Use));
+        let cli = TestClient::new(app);
+        let long_id = "a".repeat(MAX_ID_LEN + 1);
+        for id in ["", "foo bar", "foo;bar=baz", long_id.as_str()] {
+            let response = cli.get("/").header(X_REQUEST_ID, id).send().await;
+            let header_value = response.0.header(X_REQUEST_ID).unwrap();
+            assert_ne!(header_value, id);
+            assert!(Uuid::parse_str(header_value).is_ok());
+        }
+
+        let id = "a".repeat(MAX_ID_LEN);
+        let response = cli.get("/").header(X_REQUEST_ID, &id).send().await;
+        assert_eq!(response.0.header(X_REQUEST_ID), Some(id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn response_header_name() {
+        let id = "gateway-1:42";
+        let app = app(RequestId::default()
+            .response_header_name("x-correlation-id")
+            .reuse_id(ReuseId::Use));
+        let cli = TestClient::new(app);
+        let response = cli.get("/").header(X_REQUEST_ID, id).send().await;
+
+        response.assert_header_is_not_exist(X_REQUEST_ID);
+        response.assert_header("x-correlation-id", id);
+        response.assert_text(id).await;
+    }
 }
@@ -0,0 +1,403 @@
+use std::{collections::HashSet, time::Duration};
+
+use bytes::BytesMut;
+use futures_util::{StreamExt, stream};
+use http::{Method, StatusCode};
+
+use crate::{
+    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result, middleware::Random,
+};
+
+/// The maximum size of a request body that is buffered to be replayed.
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// A marker that allows the [`Retry`] middleware to retry a request with a
+/// non-idempotent method, such as `POST`.
+///
+/// Insert it into the request extensions or the request data.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     EndpointExt, Request, Route, handler,
+///     middleware::{Retry, Retryable},
+///     post,
+/// };
+///
+/// #[handler]
+/// async fn search() {}
+///
+/// let app = Route::new()
+///     .at("/search", post(search))
+///     .with(Retry::new(3))
+///     .before(|mut req: Request| async move {
+///         req.extensions_mut().insert(Retryable);
+///         Ok(req)
+///     });
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Retryable;
+
+/// Middleware that calls the inner endpoint again when it fails with a
+/// retryable status code or returns an error.
+///
+/// Only requests with an idempotent method (`GET`, `HEAD`, `OPTIONS`,
+/// `TRACE`, `PUT` and `DELETE`) are retried, unless the request has the
+/// [`Retryable`] marker. The request body is buffered so that it can be sent
+/// again, a body larger than the limit (1MB by default) is passed through and
+/// the request is not retried.
+///
+/// The delay before the `n`-th retry is `base * 2^(n - 1)`, capped by `max`,
+/// with a random jitter of up to half of the delay. When all attempts fail,
+/// the last response or error is returned.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{EndpointExt, Route, get, handler, http::StatusCode, middleware::Retry};
+///
+/// #[handler]
+/// async fn upstream() -> StatusCode {
+///     StatusCode::OK
+/// }
+///
+/// let app = Route::new().at(
+///     "/",
+///     get(upstream).with(
+///         Retry::new(3)
+///             .backoff(Duration::from_millis(50), Duration::from_secs(1))
+///             .retry_on([StatusCode::BAD_GATEWAY, StatusCode::SERVICE_UNAVAILABLE]),
+///     ),
+/// );
+/// ```
+pub struct Retry {
+    max_attempts: usize,
+    base: Duration,
+    max: Duration,
+    status_codes: HashSet<StatusCode>,
+    max_body_size: usize,
+}
+
+impl Retry {
+    /// Create `Retry` middleware that calls the inner endpoint at most
+    /// `max_attempts` times.
+    ///
+    /// By default, the backoff starts at 100ms and is capped at 10s, and the
+    /// request is retried on the `BAD_GATEWAY`, `SERVICE_UNAVAILABLE` and
+    /// `GATEWAY_TIMEOUT` status codes.
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            status_codes: [
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ]
+            .into_iter()
+            .collect(),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Sets the delay before the first retry, and the maximum delay between
+    /// two attempts.
+    #[must_use]
+    pub fn backoff(self, base: Duration, max: Duration) -> Self {
+        Self { base, max, ..self }
+    }
+
+    /// Sets the status codes of the responses that are retried.
+    #[must_use]
+    pub fn retry_on(self, status_codes: impl IntoIterator<Item = StatusCode>) -> Self {
+        Self {
+            status_codes: status_codes.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of a request body that is buffered to be
+    /// replayed.
+    #[must_use]
+    pub fn max_body_size(self, max_body_size: usize) -> Self {
+        Self {
+            max_body_size,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Retry {
+    type Output = RetryEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RetryEndpoint {
+            inner: ep,
+            max_attempts: self.max_attempts,
+            base: self.base,
+            max: self.max,
+            status_codes: self.status_codes.clone(),
+            max_body_size: self.max_body_size,
+            random: Random::default(),
+        }
+    }
+}
+
+/// Endpoint for the Retry middleware.
+pub struct RetryEndpoint<E> {
+    inner: E,
+    max_attempts: usize,
+    base: Duration,
+    max: Duration,
+    status_codes: HashSet<StatusCode>,
+    max_body_size: usize,
+    random: Random,
+}
+
+impl<E> RetryEndpoint<E> {
+    fn delay(&self, retry: usize) -> Duration {
+        let exp = u32::try_from(retry - 1).unwrap_or(u32::MAX).min(31);
+        let delay = self.base.saturating_mul(1 << exp).min(self.max);
+        let half = delay / 2;
+        half + half.mul_f64(self.random.next())
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+    )
+}
+
+impl<E: Endpoint> Endpoint for RetryEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let retryable = is_idempotent(req.method())
+            || req.extensions().get::<Retryable>().is_some()
+            || req.data::<Retryable>().is_some();
+        if !retryable || self.max_attempts == 1 {
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        }
+
+        let mut body = req.take_body().into_bytes_stream();
+        let mut chunks = Vec::new();
+        let mut size = 0;
+        loop {
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    size += chunk.len();
+                    chunks.push(chunk);
+                    if size > self.max_body_size {
+                        // too large to be replayed
+                        req.set_body(Body::from_bytes_stream(
+                            stream::iter(chunks.into_iter().map(Ok)).chain(body),
+                        ));
+                        return self.inner.call(req).await.map(IntoResponse::into_response);
+                    }
+                }
+                Some(Err(err)) => {
+                    req.set_body(Body::from_bytes_stream(
+                        stream::iter(chunks.into_iter().map(Ok))
+                            .chain(stream::once(async move { Err(err) })),
+                    ));
+                    return self.inner.call(req).await.map(IntoResponse::into_response);
+                }
+                None => break,
+            }
+        }
+        let data = match chunks.len() {
+            1 => chunks.pop().unwrap(),
+            _ => {
+                let mut data = BytesMut::with_capacity(size);
+                chunks
+                    .iter()
+                    .for_each(|chunk| data.extend_from_slice(chunk));
+                data.freeze()
+            }
+        };
+
+        for attempt in 1..self.max_attempts {
+            let mut attempt_req = req.clone_without_body();
+            attempt_req.set_body(Body::from_bytes(data.clone()));
+            let res = self.inner.call(attempt_req).await;
+            let res = res.map(IntoResponse::into_response);
+
+            let retry = match &res {
+                Ok(resp) => self.status_codes.contains(&resp.status()),
+                Err(_) => true,
+            };
+            if !retry {
+                return res;
+            }
+
+            tokio::time::sleep(self.delay(attempt)).await;
+        }
+
+        // the last attempt takes the original request
+        req.set_body(Body::from_bytes(data));
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::{
+        EndpointExt, Error,
+        endpoint::{make, make_sync},
+        test::TestClient,
+    };
+
+    fn retry(max_attempts: usize) -> Retry {
+        Retry::new(max_attempts).backoff(Duration::from_millis(1), Duration::from_millis(2))
+    }
+
+    #[tokio::test]
+    async fn retry_until_success() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let ep = make({
+            let calls = calls.clone();
+            move |req: Request| {
+                let calls = calls.clone();
+                async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    let body = req.into_body().into_string().await.unwrap();
+                    if n <= 2 {
+                        StatusCode::SERVICE_UNAVAILABLE
+                            .with_body(body)
+                            .into_response()
+                    } else {
+                        format!("{n}: {body}").into_response()
+                    }
+                }
+            }
+        });
+        let cli = TestClient::new(ep.with(retry(3)));
+        let resp = cli.put("/").body("data").send().await;
+        resp.assert_status_is_ok();
+        // the body is replayed for every attempt
+        resp.assert_text("3: data").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn exhausted() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream = {
+            let calls = calls.clone();
+            move |_: Request| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        };
+
+        let cli = TestClient::new(make_sync(upstream.clone()).with(retry(3)));
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 3);
+
+        // not a retryable status code
+        let cli =
+            TestClient::new(make_sync(upstream).with(retry(3).retry_on([StatusCode::BAD_GATEWAY])));
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn non_idempotent() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream = {
+            let calls = calls.clone();
+            move |_: Request| match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::OK,
+            }
+        };
+
+        let cli = TestClient::new(make_sync(upstream.clone()).with(retry(3)));
+        cli.post("/")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+
+        let cli = TestClient::new(make_sync(upstream).with(retry(3)));
+        cli.post("/")
+            .data(Retryable)
+            .send()
+            .await
+            .assert_status_is_ok();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn body_too_large() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let ep = make({
+            let calls = calls.clone();
+            move |req: Request| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let body = req.into_body().into_string().await.unwrap();
+                    StatusCode::SERVICE_UNAVAILABLE.with_body(body)
+                }
+            }
+        });
+        let cli = TestClient::new(ep.with(retry(3).max_body_size(3)));
+        let resp = cli.put("/").body("data").send().await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        // the body is passed through unchanged
+        resp.assert_text("data").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let ep = make({
+            let calls = calls.clone();
+            move |_| {
+                let calls = calls.clone();
+                async move {
+                    match calls.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err(Error::from_status(StatusCode::BAD_REQUEST)),
+                        _ => Ok("ok"),
+                    }
+                }
+            }
+        });
+        let cli = TestClient::new(ep.with(retry(2)));
+        cli.get("/").send().await.assert_text("ok").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn delay() {
+        let ep = Retry::new(10)
+            .backoff(Duration::from_millis(100), Duration::from_secs(1))
+            .transform(make(|_| async {}));
+        for (retry, max) in [(1, 100), (2, 200), (3, 400), (4, 800), (5, 1000), (9, 1000)] {
+            let max = Duration::from_millis(max);
+            let delay = ep.delay(retry);
+            assert!(delay >= max / 2 && delay <= max, "{retry}: {delay:?}");
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use http::{HeaderValue, header::HeaderName};
+use parking_lot::Mutex;
+
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+#[derive(Debug)]
+struct Metric {
+    name: Cow<'static, str>,
+    description: Option<Cow<'static, str>>,
+    duration: Duration,
+}
+
+/// A request-scoped collector of the metrics written in the `Server-Timing`
+/// response header by the [`ServerTimingHeader`] middleware.
+///
+/// The middleware inserts it into the extensions of the request, so handlers
+/// and inner middleware can extract it with `Data<&ServerTiming>` and record
+/// the durations of their operations. The names must be HTTP tokens, such as
+/// `db` or `cache-read`.
+#[derive(Debug, Clone, Default)]
+pub struct ServerTiming(Arc<Mutex<Vec<Metric>>>);
+
+impl ServerTiming {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a metric.
+    pub fn record(&self, name: impl Into<Cow<'static, str>>, duration: Duration) {
+        self.push(name.into(), None, duration);
+    }
+
+    /// Records a metric with a description.
+    pub fn record_with_description(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+        duration: Duration,
+    ) {
+        self.push(name.into(), Some(description.into()), duration);
+    }
+
+    fn push(
+        &self,
+        name: Cow<'static, str>,
+        description: Option<Cow<'static, str>>,
+        duration: Duration,
+    ) {
+        self.0.lock().push(Metric {
+            name,
+            description,
+            duration,
+        });
+    }
+
+    /// Returns the value of the `Server-Timing` header, or `None` if there are
+    /// no metrics or a name is not a valid header value.
+    fn header_value(&self) -> Option<HeaderValue> {
+        let metrics = self.0.lock();
+        if metrics.is_empty() {
+            return None;
+        }
+
+        let mut value = String::new();
+        for (idx, metric) in metrics.iter().enumerate() {
+            if idx > 0 {
+                value.push_str(", ");
+            }
+            value.push_str(&metric.name);
+            if let Some(description) = &metric.description {
+                value.push_str(";desc=\"");
+                for c in description.chars() {
+                    if c == '"' || c == '\\' {
+                        value.push('\\');
+                    }
+                    value.push(c);
+                }
+                value.push('"');
+            }
+            let _ = write!(value, ";dur={:.3}", metric.duration.as_secs_f64() * 1000.0);
+        }
+        HeaderValue::try_from(value).ok()
+    }
+}
+
+/// Middleware that writes the metrics recorded in the [`ServerTiming`]
+/// extension in the `Server-Timing` response header, to show the latency
+/// breakdown of the requests in the browser developer tools.
+///
+/// The duration of the inner endpoint is recorded as the `total` metric. The
+/// metrics are written in milliseconds, in the order they were recorded,
+/// after the existing `Server-Timing` headers of the response. If the inner
+/// endpoint returns an error, the error is returned unchanged.
+///
+/// The header reveals how long the operations of the server take, so it
+/// should only be enabled for trusted clients, for example with
+/// [`EndpointExt::with_if`](crate::EndpointExt::with_if).
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     middleware::{ServerTiming, ServerTimingHeader},
+///     test::TestClient,
+///     web::Data,
+/// };
+///
+/// #[handler]
+/// async fn index(timing: Data<&ServerTiming>) -> &'static str {
+///     let start = Instant::now();
+///     // query the database
+///     timing.record("db", start.elapsed());
+///     timing.record_with_description("cache", "Cache Read", Duration::from_millis(2));
+///     "hello"
+/// }
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .with(ServerTimingHeader::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// // db;dur=0.001, cache;desc="Cache Read";dur=2.000, total;dur=0.015
+/// let value = resp
+///     .0
+///     .headers()
+///     .get("server-timing")
+///     .unwrap()
+///     .to_str()
+///     .unwrap();
+/// assert!(value.starts_with("db;dur="));
+/// # });
+/// ```
+#[derive(Debug, Default)]
+pub struct ServerTimingHeader;
+
+impl ServerTimingHeader {
+    /// Create `ServerTimingHeader` middleware.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ServerTimingHeader {
+    type Output = ServerTimingHeaderEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ServerTimingHeaderEndpoint { inner: ep }
+    }
+}
+
+/// Endpoint for the ServerTimingHeader middleware.
+pub struct ServerTimingHeaderEndpoint<E> {
+    inner: E,
+}
+
+impl<E: Endpoint> Endpoint for ServerTimingHeaderEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let timing = ServerTiming::new();
+        req.extensions_mut().insert(timing.clone());
+
+        let start = Instant::now();
+        let mut resp = self.inner.call(req).await?.into_response();
+        timing.record("total", start.elapsed());
+
+        if let Some(value) = timing.header_value() {
+            resp.headers_mut().append(SERVER_TIMING, value);
+        }
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointExt, Error, handler, http::StatusCode, test::TestClient, web::Data};
+
+    #[test]
+    fn header_value() {
+        let timing = ServerTiming::new();
+        assert_eq!(timing.header_value(), None);
+
+        timing.record("db", Duration::from_micros(53200));
+        timing.record_with_description("cache", "a \"b\" \\", Duration::from_millis(2));
+        assert_eq!(
+            timing.header_value().unwrap(),
+            r#"db;dur=53.200, cache;desc="a \"b\" \\";dur=2.000"#
+        );
+
+        timing.record("bad\r\n", Duration::ZERO);
+        assert_eq!(timing.header_value(), None);
+    }
+
+    #[tokio::test]
+    async fn server_timing() {
+        #[handler(internal)]
+        async fn index(timing: Data<&ServerTiming>) -> Response {
+            timing.record("db", Duration::from_millis(5));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Response::builder()
+                .header(SERVER_TIMING, "cdn;dur=1")
+                .finish()
+        }
+
+        let cli = TestClient::new(index.with(ServerTimingHeader::new()));
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let values = resp
+            .0
+            .headers()
+            .get_all(SERVER_TIMING)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], "cdn;dur=1");
+        let (db, total) = values[1].split_once(", ").unwrap();
+        assert_eq!(db, "db;dur=5.000");
+        let total = total.strip_prefix("total;dur=").unwrap();
+        assert!(total.parse::<f64>().unwrap() >= 20.0);
+    }
+
+    #[tokio::test]
+    async fn error() {
+        #[handler(internal)]
+        async fn index() -> Result<()> {
+            Err(Error::from_status(StatusCode::BAD_REQUEST))
+        }
+
+        let cli = TestClient::new(index.with(ServerTimingHeader::new()));
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_header_is_not_exist(SERVER_TIMING);
+    }
+}
@@ -0,0 +1,223 @@
+use std::time::{Duration, Instant};
+
+use crate::{Endpoint, Middleware, Request, Result, error::TimeoutError};
+
+/// A request extension that overrides the duration of the [`Timeout`]
+/// middleware for the current request.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     EndpointExt, Request, Route, handler,
+///     middleware::{Timeout, TimeoutOverride},
+/// };
+///
+/// #[handler]
+/// async fn index() {}
+///
+/// #[handler]
+/// async fn export() {}
+///
+/// let app = Route::new()
+///     .at("/", index)
+///     .at("/export", export)
+///     .with(Timeout::new(Duration::from_secs(30)))
+///     .before(|mut req: Request| async move {
+///         if req.uri().path() == "/export" {
+///             req.extensions_mut()
+///                 .insert(TimeoutOverride(Duration::from_secs(300)));
+///         }
+///         Ok(req)
+///     });
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimeoutOverride(pub Duration);
+
+/// A request extension with the instant at which the [`Timeout`] middleware
+/// aborts the current request.
+///
+/// The middleware inserts it before calling the inner endpoint, so handlers
+/// can pass the remaining time to their outbound calls instead of using an
+/// independent timeout that outlives the request. If the request is already
+/// inside another `Timeout`, the earlier deadline is kept, and the request
+/// is aborted at that deadline.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     EndpointExt, handler,
+///     middleware::{Deadline, Timeout},
+///     test::TestClient,
+///     web::Data,
+/// };
+///
+/// #[handler]
+/// async fn index(deadline: Data<&Deadline>) -> String {
+///     // e.g. `client.get(url).timeout(deadline.remaining())`
+///     format!("{}", deadline.remaining() <= Duration::from_secs(10))
+/// }
+///
+/// let app = index.with(Timeout::new(Duration::from_secs(10)));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.get("/").send().await.assert_text("true").await;
+/// # });
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Deadline(pub Instant);
+
+impl Deadline {
+    /// Returns the time left until the deadline, or zero if it has passed.
+    #[inline]
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    /// Returns `true` if the deadline has passed.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.0 <= Instant::now()
+    }
+}
+
+/// Middleware for limiting the time it takes to handle a request.
+///
+/// If the inner endpoint does not complete within the timeout, its future is
+/// dropped and the middleware returns the `GATEWAY_TIMEOUT` status code.
+///
+/// The timeout can be changed for a specific request by inserting a
+/// [`TimeoutOverride`] extension. The resulting deadline is available to the
+/// inner endpoint as a [`Deadline`] extension.
+///
+/// # Errors
+///
+/// - [`TimeoutError`]
+pub struct Timeout {
+    duration: Duration,
+}
+
+impl Timeout {
+    /// Create `Timeout` middleware.
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Timeout {
+    type Output = TimeoutEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TimeoutEndpoint {
+            inner: ep,
+            duration: self.duration,
+        }
+    }
+}
+
+/// Endpoint for the Timeout middleware.
+pub struct TimeoutEndpoint<E> {
+    inner: E,
+    duration: Duration,
+}
+
+impl<E: Endpoint> Endpoint for TimeoutEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let duration = req
+            .extensions()
+            .get::<TimeoutOverride>()
+            .map(|value| value.0)
+            .unwrap_or(self.duration);
+        let mut deadline = Deadline(Instant::now() + duration);
+        if let Some(outer) = req.extensions().get::<Deadline>() {
+            deadline = deadline.min(*outer);
+        }
+        req.extensions_mut().insert(deadline);
+
+        match tokio::time::timeout_at(deadline.0.into(), self.inner.call(req)).await {
+            Ok(res) => res,
+            Err(_) => Err(TimeoutError.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, middleware::AddData, test::TestClient};
+
+    #[handler(internal)]
+    async fn sleep(req: &Request) -> &'static str {
+        let ms = req.header("x-sleep").unwrap().parse().unwrap();
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        "done"
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let cli = TestClient::new(sleep.with(Timeout::new(Duration::from_millis(50))));
+
+        let resp = cli.get("/").header("x-sleep", 10).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("done").await;
+
+        cli.get("/")
+            .header("x-sleep", 200)
+            .send()
+            .await
+            .assert_status(StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn timeout_override() {
+        let cli = TestClient::new(
+            sleep
+                .with(Timeout::new(Duration::from_millis(50)))
+                .with(AddData::new(TimeoutOverride(Duration::from_millis(500)))),
+        );
+
+        let resp = cli.get("/").header("x-sleep", 200).send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("done").await;
+    }
+
+    #[tokio::test]
+    async fn deadline() {
+        #[handler(internal)]
+        async fn index(deadline: crate::web::Data<&Deadline>) -> String {
+            deadline.remaining().as_millis().div_ceil(100).to_string()
+        }
+
+        let cli = TestClient::new(index.with(Timeout::new(Duration::from_millis(500))));
+        cli.get("/").send().await.assert_text("5").await;
+
+        // the earlier deadline is kept
+        let cli = TestClient::new(
+            index
+                .with(Timeout::new(Duration::from_secs(10)))
+                .with(Timeout::new(Duration::from_millis(300))),
+        );
+        cli.get("/").send().await.assert_text("3").await;
+
+        let cli = TestClient::new(
+            index
+                .with(Timeout::new(Duration::from_millis(300)))
+                .with(Timeout::new(Duration::from_secs(10))),
+        );
+        cli.get("/").send().await.assert_text("3").await;
+
+        let deadline = Deadline(Instant::now() - Duration::from_secs(1));
+        assert!(deadline.is_expired());
+        assert_eq!(deadline.remaining(), Duration::ZERO);
+    }
+}
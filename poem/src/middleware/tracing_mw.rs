@@ -1,85 +1,316 @@
-use std::time::Instant;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, LazyLock, OnceLock},
+    time::Instant,
+};
 
-use tracing::{Instrument, Level};
+use parking_lot::Mutex;
+use tracing::{
+    Instrument, Level, Metadata, Span,
+    callsite::{Callsite, Identifier},
+    field::{FieldSet, display},
+    metadata::Kind,
+    subscriber::Interest,
+};
 
 use crate::{
     Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result, route::PathPattern,
     web::RealIp,
 };
 
+type FieldFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+type SpanNameFn = Arc<dyn Fn(&Request) -> String + Send + Sync>;
+
+const FIELDS: &[&str] = &[
+    "remote_addr",
+    "version",
+    "method",
+    "uri",
+    "request_id",
+    "path_pattern",
+    "status",
+    "duration",
+    "otel.name",
+];
+
 /// Middleware for [`tracing`](https://crates.io/crates/tracing).
-#[derive(Default)]
+///
+/// Each request is processed in a `request` span with the `remote_addr`,
+/// `version`, `method` and `uri` fields, and the `request_id` field when the
+/// [`RequestId`](crate::middleware::RequestId) middleware is used. When the
+/// response is ready, the `status`, `duration` and `path_pattern` (the pattern
+/// of the matched [`Route`](crate::Route)) fields are recorded, and a
+/// `response` or `error` event is emitted.
+///
+/// Use [`CustomTracing`] to add fields to the span or to change its name or
+/// level.
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, Route, handler, middleware::Tracing};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Route::new().at("/", index).with(Tracing);
+/// ```
+#[derive(Debug, Default, Copy, Clone)]
 pub struct Tracing;
 
 impl<E: Endpoint> Middleware<E> for Tracing {
     type Output = TracingEndpoint<E>;
 
     fn transform(&self, ep: E) -> Self::Output {
-        TracingEndpoint { inner: ep }
+        CustomTracing::new().transform(ep)
+    }
+}
+
+/// [`Tracing`] middleware with custom span fields, span name and level.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Request, Route, handler,
+///     middleware::CustomTracing,
+///     web::headers::{HeaderMapExt, UserAgent},
+/// };
+/// use tracing::Level;
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Route::new().at("/", index).with(
+///     CustomTracing::new()
+///         .level(Level::DEBUG)
+///         .with_field("tenant", |req: &Request| {
+///             req.header("x-tenant-id").map(ToString::to_string)
+///         })
+///         .with_field("user_agent", |req: &Request| {
+///             req.headers().typed_get::<UserAgent>()
+///         }),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CustomTracing {
+    level: Level,
+    fields: Vec<(&'static str, FieldFn)>,
+    span_name: Option<SpanNameFn>,
+}
+
+impl Default for CustomTracing {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO,
+            fields: Vec::new(),
+            span_name: None,
+        }
+    }
+}
+
+impl CustomTracing {
+    /// Create a `CustomTracing` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the level of the span and the events, default is
+    /// [`Level::INFO`].
+    #[must_use]
+    pub fn level(self, level: Level) -> Self {
+        Self { level, ..self }
+    }
+
+    /// Adds a field to the span, with the value returned by `f`.
+    ///
+    /// The field is not recorded when `f` returns `None`.
+    #[must_use]
+    pub fn with_field<F, V>(mut self, name: &'static str, f: F) -> Self
+    where
+        F: Fn(&Request) -> Option<V> + Send + Sync + 'static,
+        V: Display,
+    {
+        self.fields.push((
+            name,
+            Arc::new(move |req| f(req).map(|value| value.to_string())),
+        ));
+        self
+    }
+
+    /// Sets the name of the span with the value returned by `f`.
+    ///
+    /// The names of [`tracing`] spans are static, so the name is recorded as
+    /// the `otel.name` field, which is used as the span name by
+    /// [`tracing-opentelemetry`](https://crates.io/crates/tracing-opentelemetry).
+    ///
+    /// By default, `otel.name` is `{method} {path_pattern}` when a route
+    /// matches the request, so that the name does not depend on the path
+    /// parameters.
+    #[must_use]
+    pub fn span_name<F>(self, f: F) -> Self
+    where
+        F: Fn(&Request) -> String + Send + Sync + 'static,
+    {
+        Self {
+            span_name: Some(Arc::new(f)),
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for CustomTracing {
+    type Output = TracingEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let mut fields = FIELDS.to_vec();
+        fields.extend(self.fields.iter().map(|(name, _)| *name));
+
+        TracingEndpoint {
+            inner: ep,
+            metadata: span_metadata(self.level, fields),
+            level: self.level,
+            fields: self.fields.clone(),
+            span_name: self.span_name.clone(),
+        }
     }
 }
 
+struct SpanCallsite(OnceLock<Metadata<'static>>);
+
+impl Callsite for SpanCallsite {
+    fn set_interest(&self, _interest: Interest) {}
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.0.get().expect("the metadata is initialized")
+    }
+}
+
+type MetadataKey = (Level, Vec<&'static str>);
+
+static METADATA: LazyLock<Mutex<HashMap<MetadataKey, &'static Metadata<'static>>>> =
+    LazyLock::new(Default::default);
+
+/// Returns the metadata of the span with the given fields.
+///
+/// The fields of a span are part of its static metadata, so a callsite is
+/// leaked for each distinct level and set of fields, and shared by all the
+/// endpoints that use them.
+fn span_metadata(level: Level, fields: Vec<&'static str>) -> &'static Metadata<'static> {
+    let mut cache = METADATA.lock();
+    if let Some(metadata) = cache.get(&(level, fields.clone())) {
+        return metadata;
+    }
+
+    let callsite: &'static SpanCallsite = Box::leak(Box::new(SpanCallsite(OnceLock::new())));
+    let field_names = Box::leak(fields.clone().into_boxed_slice());
+    let metadata = callsite.0.get_or_init(|| {
+        Metadata::new(
+            "request",
+            module_path!(),
+            level,
+            Some(file!()),
+            Some(line!()),
+            Some(module_path!()),
+            FieldSet::new(field_names, Identifier(callsite)),
+            Kind::SPAN,
+        )
+    });
+    tracing::callsite::register(callsite);
+    cache.insert((level, fields), metadata);
+    metadata
+}
+
+macro_rules! event {
+    ($level:expr, $($arg:tt)+) => {
+        match $level {
+            Level::TRACE => tracing::trace!($($arg)+),
+            Level::DEBUG => tracing::debug!($($arg)+),
+            Level::WARN => tracing::warn!($($arg)+),
+            Level::ERROR => tracing::error!($($arg)+),
+            _ => tracing::info!($($arg)+),
+        }
+    };
+}
+
 /// Endpoint for the `Tracing` middleware.
 pub struct TracingEndpoint<E> {
     inner: E,
+    metadata: &'static Metadata<'static>,
+    level: Level,
+    fields: Vec<(&'static str, FieldFn)>,
+    span_name: Option<SpanNameFn>,
+}
+
+impl<E> TracingEndpoint<E> {
+    fn new_span(&self) -> Span {
+        let enabled = tracing::level_enabled!(self.level)
+            && tracing::dispatcher::get_default(|dispatch| dispatch.enabled(self.metadata));
+        if !enabled {
+            return Span::none();
+        }
+        let values: [(&tracing::field::Field, Option<&dyn tracing::Value>); 0] = [];
+        Span::new(self.metadata, &self.metadata.fields().value_set(&values))
+    }
 }
 
 impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
     type Output = Response;
 
     async fn call(&self, req: Request) -> Result<Self::Output> {
-        let remote_addr = RealIp::from_request_without_body(&req)
-            .await
-            .ok()
-            .and_then(|real_ip| real_ip.0)
-            .map(|addr| addr.to_string())
-            .unwrap_or_else(|| req.remote_addr().to_string());
-
-        #[cfg(not(feature = "requestid"))]
-        let span = tracing::span!(
-            target: module_path!(),
-            Level::INFO,
-            "request",
-            remote_addr = %remote_addr,
-            version = ?req.version(),
-            method = %req.method(),
-            uri = %req.original_uri(),
-        );
-        #[cfg(feature = "requestid")]
-        let span = {
-            req.extensions()
+        let span = self.new_span();
+
+        if !span.is_disabled() {
+            let remote_addr = RealIp::from_request_without_body(&req)
+                .await
+                .ok()
+                .and_then(|real_ip| real_ip.0)
+                .map(|addr| addr.to_string())
+                .unwrap_or_else(|| req.remote_addr().to_string());
+
+            span.record("remote_addr", remote_addr.as_str());
+            span.record("version", tracing::field::debug(req.version()));
+            span.record("method", display(req.method()));
+            span.record("uri", display(req.original_uri()));
+            #[cfg(feature = "requestid")]
+            if let Some(request_id) = req
+                .extensions()
                 .get::<crate::middleware::requestid::ReqId>()
-                .map_or_else(
-                    || {
-                        tracing::span!(
-                            target: module_path!(),
-                            Level::INFO,
-                            "request",
-                            remote_addr = %remote_addr,
-                            version = ?req.version(),
-                            method = %req.method(),
-                            uri = %req.original_uri(),
-                        )
-                    },
-                    |request_id| {
-                        tracing::span!(
-                            target: module_path!(),
-                            Level::INFO,
-                            "request",
-                            remote_addr = %remote_addr,
-                            version = ?req.version(),
-                            method = %req.method(),
-                            uri = %req.original_uri(),
-                            %request_id
-                        )
-                    },
-                )
-        };
-
-        if let Some(path_pattern) = req.data::<PathPattern>() {
-            span.record("path_pattern", path_pattern.0.as_ref());
+            {
+                span.record("request_id", display(request_id));
+            }
+            if let Some(span_name) = &self.span_name {
+                span.record("otel.name", span_name(&req).as_str());
+            }
+            for (name, f) in &self.fields {
+                if let Some(value) = f(&req) {
+                    span.record(*name, value.as_str());
+                }
+            }
         }
 
+        let method = req.method().clone();
+        let record_response =
+            |span: &Span, status, path_pattern: Option<&PathPattern>, duration| {
+                span.record("status", display(status));
+                span.record("duration", tracing::field::debug(duration));
+                if let Some(path_pattern) = path_pattern {
+                    span.record("path_pattern", &*path_pattern.0);
+                    if self.span_name.is_none() {
+                        span.record(
+                            "otel.name",
+                            format!("{} {}", method, path_pattern.0).as_str(),
+                        );
+                    }
+                }
+            };
+
+        let current_span = span.clone();
         async move {
             let now = Instant::now();
             let res = self.inner.call(req).await;
@@ -88,7 +319,14 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
             match res {
                 Ok(resp) => {
                     let resp = resp.into_response();
-                    tracing::info!(
+                    record_response(
+                        &current_span,
+                        resp.status(),
+                        resp.data::<PathPattern>(),
+                        duration,
+                    );
+                    event!(
+                        self.level,
                         status = %resp.status(),
                         duration = ?duration,
                         "response"
@@ -96,7 +334,14 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
                     Ok(resp)
                 }
                 Err(err) => {
-                    tracing::info!(
+                    record_response(
+                        &current_span,
+                        err.status(),
+                        err.data::<PathPattern>(),
+                        duration,
+                    );
+                    event!(
+                        self.level,
                         status = %err.status(),
                         error = %err,
                         duration = ?duration,
@@ -110,3 +355,153 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use parking_lot::Mutex;
+    use tracing::{
+        Event, Subscriber,
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+    };
+
+    use super::*;
+    use crate::{EndpointExt, Route, get, handler, test::TestClient};
+
+    #[derive(Clone, Default)]
+    struct Fields(Arc<Mutex<BTreeMap<String, String>>>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0
+                .lock()
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    struct SpanSubscriber {
+        level: Level,
+        fields: Fields,
+    }
+
+    impl Subscriber for SpanSubscriber {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.level() <= &self.level
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut self.fields.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, values: &Record<'_>) {
+            values.record(&mut self.fields.clone());
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    async fn span_fields(
+        tracing: CustomTracing,
+        level: Level,
+        uri: &str,
+    ) -> BTreeMap<String, String> {
+        let fields = Fields::default();
+        let _guard = tracing::subscriber::set_default(SpanSubscriber {
+            level,
+            fields: fields.clone(),
+        });
+        let cli = TestClient::new(Route::new().at("/users/:id", get(index)).with(tracing));
+        cli.get(uri).header("x-tenant-id", "acme").send().await;
+        fields.0.lock().clone()
+    }
+
+    #[tokio::test]
+    async fn fields() {
+        let fields = span_fields(
+            CustomTracing::new()
+                .with_field("tenant", |req: &Request| {
+                    req.header("x-tenant-id").map(ToString::to_string)
+                })
+                .with_field("missing", |_: &Request| None::<String>),
+            Level::INFO,
+            "/users/42",
+        )
+        .await;
+        assert_eq!(fields["method"], "GET");
+        assert_eq!(fields["tenant"], "acme");
+        assert_eq!(fields["status"], "200 OK");
+        assert_eq!(fields["path_pattern"], "/users/:id");
+        assert_eq!(fields["otel.name"], "GET /users/:id");
+        assert!(fields.contains_key("duration"));
+        assert!(!fields.contains_key("missing"));
+
+        let fields = span_fields(CustomTracing::new(), Level::INFO, "/other").await;
+        assert_eq!(fields["status"], "404 Not Found");
+        assert!(!fields.contains_key("path_pattern"));
+        assert!(!fields.contains_key("otel.name"));
+    }
+
+    #[tokio::test]
+    async fn span_name() {
+        let fields = span_fields(
+            CustomTracing::new().span_name(|req| format!("custom {}", req.method())),
+            Level::INFO,
+            "/users/42",
+        )
+        .await;
+        assert_eq!(fields["otel.name"], "custom GET");
+    }
+
+    #[tokio::test]
+    async fn level() {
+        let fields = span_fields(
+            CustomTracing::new().level(Level::DEBUG),
+            Level::INFO,
+            "/users/42",
+        )
+        .await;
+        assert!(fields.is_empty());
+
+        let fields = span_fields(
+            CustomTracing::new().level(Level::DEBUG),
+            Level::DEBUG,
+            "/users/42",
+        )
+        .await;
+        assert_eq!(fields["status"], "200 OK");
+    }
+
+    #[test]
+    fn metadata_interned() {
+        let a = <Tracing as Middleware<_>>::transform(&Tracing, index);
+        let b = CustomTracing::new().transform(index);
+        assert!(std::ptr::eq(a.metadata, b.metadata));
+
+        let c = CustomTracing::new()
+            .with_field("tenant", |_: &Request| None::<String>)
+            .transform(index);
+        assert!(!std::ptr::eq(a.metadata, c.metadata));
+        let d = CustomTracing::new().level(Level::DEBUG).transform(index);
+        assert!(!std::ptr::eq(a.metadata, d.metadata));
+    }
+}
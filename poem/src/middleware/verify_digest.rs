@@ -0,0 +1,343 @@
+use std::{
+    io::Error as IoError,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::Bytes;
+use http::{HeaderMap, header::HeaderName};
+use hyper::body::{Body as HttpBody, Frame, SizeHint};
+use md5::{Digest, Md5};
+use sha2::Sha256;
+
+use crate::{Body, Endpoint, Middleware, Request, Result, body::BoxBody, error::VerifyDigestError};
+
+const CONTENT_MD5: HeaderName = HeaderName::from_static("content-md5");
+const DIGEST: HeaderName = HeaderName::from_static("digest");
+
+enum Hasher {
+    Md5(Md5),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Md5(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+/// A digest declared by the request, and the hasher that computes the actual
+/// one.
+struct Check {
+    hasher: Hasher,
+    expected: Vec<u8>,
+}
+
+impl Check {
+    fn new(hasher: Hasher, len: usize, value: &str) -> Result<Self, VerifyDigestError> {
+        let expected = STANDARD
+            .decode(value.trim())
+            .map_err(|_| VerifyDigestError::InvalidDigest)?;
+        if expected.len() != len {
+            return Err(VerifyDigestError::InvalidDigest);
+        }
+        Ok(Self { hasher, expected })
+    }
+}
+
+/// Parses the `Content-MD5` and `Digest` headers, the algorithms of `Digest`
+/// other than `MD5` and `SHA-256` are ignored.
+fn parse_checks(headers: &HeaderMap) -> Result<Vec<Check>, VerifyDigestError> {
+    let mut checks = Vec::new();
+
+    if let Some(value) = headers.get(CONTENT_MD5) {
+        let value = value
+            .to_str()
+            .map_err(|_| VerifyDigestError::InvalidDigest)?;
+        checks.push(Check::new(Hasher::Md5(Md5::new()), 16, value)?);
+    }
+
+    for value in headers.get_all(DIGEST) {
+        let value = value
+            .to_str()
+            .map_err(|_| VerifyDigestError::InvalidDigest)?;
+        for item in value.split(',') {
+            let (algorithm, value) = item
+                .split_once('=')
+                .ok_or(VerifyDigestError::InvalidDigest)?;
+            let algorithm = algorithm.trim();
+            if algorithm.eq_ignore_ascii_case("md5") {
+                checks.push(Check::new(Hasher::Md5(Md5::new()), 16, value)?);
+            } else if algorithm.eq_ignore_ascii_case("sha-256") {
+                checks.push(Check::new(Hasher::Sha256(Sha256::new()), 32, value)?);
+            }
+        }
+    }
+
+    Ok(checks)
+}
+
+/// Middleware that verifies the request body against the digests declared in
+/// the `Content-MD5` and `Digest` headers, such as `Digest: sha-256=<base64>`.
+///
+/// The digests are computed while the inner endpoint reads the body, without
+/// buffering it. When the end of the body is reached and a digest does not
+/// match, reading the body fails with [`VerifyDigestError::Mismatch`], so the
+/// body extractors return `400 Bad Request` and the handler is not called.
+/// An endpoint that reads the body as a stream receives the error as the last
+/// item of the stream, so it must not commit the data before the end. A body
+/// that is not read to the end is not verified.
+///
+/// The `MD5` and `SHA-256` algorithms are supported, the others are ignored.
+/// Requests without a supported digest are passed through unchanged.
+///
+/// # Errors
+///
+/// - [`VerifyDigestError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler, http::StatusCode, middleware::VerifyDigest, put,
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn upload(data: Vec<u8>) {}
+///
+/// let app = Route::new().at("/upload", put(upload).with(VerifyDigest::new()));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// cli.put("/upload")
+///     .header(
+///         "digest",
+///         "sha-256=LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=",
+///     )
+///     .body("hello")
+///     .send()
+///     .await
+///     .assert_status_is_ok();
+///
+/// cli.put("/upload")
+///     .header("content-md5", "XUFAKrxLKna5cZ2REBfFkg==")
+///     .body("hello!")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::BAD_REQUEST);
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+#[derive(Debug, Default)]
+pub struct VerifyDigest;
+
+impl VerifyDigest {
+    /// Create `VerifyDigest` middleware.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for VerifyDigest {
+    type Output = VerifyDigestEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        VerifyDigestEndpoint { inner: ep }
+    }
+}
+
+/// Endpoint for the VerifyDigest middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+pub struct VerifyDigestEndpoint<E> {
+    inner: E,
+}
+
+impl<E: Endpoint> Endpoint for VerifyDigestEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let checks = parse_checks(req.headers())?;
+        if !checks.is_empty() {
+            let body = req.take_body();
+            req.set_body(Body(BoxBody::new(VerifyingBody {
+                inner: body.0,
+                checks: Some(checks),
+            })));
+        }
+        self.inner.call(req).await
+    }
+}
+
+struct VerifyingBody {
+    inner: BoxBody,
+    /// `None` once the body has been verified.
+    checks: Option<Vec<Check>>,
+}
+
+impl HttpBody for VerifyingBody {
+    type Data = Bytes;
+    type Error = IoError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let res = Pin::new(&mut this.inner).poll_frame(cx);
+        match (&res, &mut this.checks) {
+            (Poll::Ready(Some(Ok(frame))), Some(checks)) => {
+                if let Some(data) = frame.data_ref() {
+                    for check in checks {
+                        check.hasher.update(data);
+                    }
+                }
+            }
+            (Poll::Ready(None), checks @ Some(_)) => {
+                let matched = checks
+                    .take()
+                    .into_iter()
+                    .flatten()
+                    .all(|check| check.hasher.finalize() == check.expected);
+                if !matched {
+                    return Poll::Ready(Some(Err(IoError::other(VerifyDigestError::Mismatch))));
+                }
+            }
+            _ => {}
+        }
+        res
+    }
+
+    fn is_end_stream(&self) -> bool {
+        // the end must be polled to verify the body
+        self.checks.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{StreamExt, stream};
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[handler(internal)]
+    fn index(data: String) -> String {
+        data
+    }
+
+    fn md5(data: &str) -> String {
+        STANDARD.encode(Md5::digest(data))
+    }
+
+    fn sha256(data: &str) -> String {
+        STANDARD.encode(Sha256::digest(data))
+    }
+
+    fn chunked(data: &'static str) -> Body {
+        Body::from_bytes_stream(
+            stream::iter(data.as_bytes().chunks(2))
+                .map(|chunk| Ok::<_, IoError>(Bytes::copy_from_slice(chunk))),
+        )
+    }
+
+    #[test]
+    fn parse() {
+        let parse = |name: HeaderName, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, value.parse().unwrap());
+            parse_checks(&headers).map(|checks| checks.len())
+        };
+        assert_eq!(parse(CONTENT_MD5, &md5("a")), Ok(1));
+        assert_eq!(
+            parse(
+                DIGEST,
+                &format!("SHA-256={}, md5={}", sha256("a"), md5("a"))
+            ),
+            Ok(2)
+        );
+        assert_eq!(parse(DIGEST, "sha-512=abcd, unixsum=30637"), Ok(0));
+        assert_eq!(
+            parse(CONTENT_MD5, &sha256("a")),
+            Err(VerifyDigestError::InvalidDigest)
+        );
+        assert_eq!(
+            parse(DIGEST, "sha-256=not base64"),
+            Err(VerifyDigestError::InvalidDigest)
+        );
+        assert_eq!(
+            parse(DIGEST, "sha-256"),
+            Err(VerifyDigestError::InvalidDigest)
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_digest() {
+        let cli = TestClient::new(index.with(VerifyDigest::new()));
+
+        cli.post("/")
+            .body("hello")
+            .send()
+            .await
+            .assert_text("hello")
+            .await;
+
+        for body in [Body::from("hello world"), chunked("hello world")] {
+            cli.post("/")
+                .header(CONTENT_MD5, md5("hello world"))
+                .header(DIGEST, format!("sha-256={}", sha256("hello world")))
+                .body(body)
+                .send()
+                .await
+                .assert_text("hello world")
+                .await;
+        }
+
+        cli.post("/")
+            .header(DIGEST, format!("sha-256={}", sha256("")))
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        for body in [Body::from("hello world"), chunked("hello world")] {
+            cli.post("/")
+                .header(DIGEST, format!("sha-256={}", sha256("hello")))
+                .body(body)
+                .send()
+                .await
+                .assert_status(StatusCode::BAD_REQUEST);
+        }
+
+        // one of the digests does not match
+        cli.post("/")
+            .header(CONTENT_MD5, md5("hello"))
+            .header(DIGEST, format!("sha-256={}", sha256("hello world")))
+            .body("hello world")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        cli.post("/")
+            .header(CONTENT_MD5, "invalid")
+            .body("hello")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+}
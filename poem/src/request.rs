@@ -338,9 +338,7 @@ impl Request {
     /// # });
     /// ```
     pub fn params<T: DeserializeOwned>(&self) -> Result<T, ParseQueryError> {
-        Ok(serde_urlencoded::from_str(
-            self.uri().query().unwrap_or_default(),
-        )?)
+        crate::web::parse_query(self.uri().query().unwrap_or_default())
     }
 
     /// Returns the content type of this request.
@@ -424,6 +422,29 @@ impl Request {
         &mut self.state
     }
 
+    /// Returns a copy of this request with an empty body and without the
+    /// connection upgrade.
+    pub(crate) fn clone_without_body(&self) -> Request {
+        Request {
+            method: self.method.clone(),
+            uri: self.uri.clone(),
+            version: self.version,
+            headers: self.headers.clone(),
+            extensions: self.extensions.clone(),
+            body: Body::empty(),
+            state: RequestState {
+                local_addr: self.state.local_addr.clone(),
+                remote_addr: self.state.remote_addr.clone(),
+                scheme: self.state.scheme.clone(),
+                original_uri: self.state.original_uri.clone(),
+                match_params: self.state.match_params.clone(),
+                #[cfg(feature = "cookie")]
+                cookie_jar: self.state.cookie_jar.clone(),
+                on_upgrade: Default::default(),
+            },
+        }
+    }
+
     /// Returns the parameters used by the extractor.
     pub fn split(mut self) -> (Request, RequestBody) {
         let body = self.take_body();
@@ -604,15 +625,21 @@ impl RequestBuilder {
 
     /// Consumes this builder, using the provided body to return a constructed
     /// [Request].
+    ///
+    /// The [original URI](Request::original_uri) of the request is the URI
+    /// set with [`RequestBuilder::uri`].
     pub fn body(self, body: impl Into<Body>) -> Request {
         Request {
             method: self.method,
-            uri: self.uri,
+            uri: self.uri.clone(),
             version: self.version,
             headers: self.headers,
             extensions: self.extensions,
             body: body.into(),
-            state: Default::default(),
+            state: RequestState {
+                original_uri: self.uri,
+                ..Default::default()
+            },
         }
     }
 
@@ -622,3 +649,22 @@ impl RequestBuilder {
         self.body(Body::empty())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_original_uri() {
+        let req = Request::builder()
+            .uri(Uri::from_static("/a/b?c=1"))
+            .finish();
+        assert_eq!(req.uri(), "/a/b?c=1");
+        assert_eq!(req.original_uri(), "/a/b?c=1");
+
+        // the original URI is kept when the URI is rewritten
+        let mut req = req;
+        *req.uri_mut() = Uri::from_static("/b");
+        assert_eq!(req.original_uri(), "/a/b?c=1");
+    }
+}
@@ -13,7 +13,7 @@ fn longest_common_prefix(a: &[u8], b: &[u8]) -> usize {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-enum RawSegment<'a> {
+pub(crate) enum RawSegment<'a> {
     Static(&'a [u8]),
     Param(&'a [u8]),
     CatchAll(Option<&'a [u8]>),
@@ -36,7 +36,7 @@ fn find_slash(path: &[u8]) -> Option<usize> {
     None
 }
 
-fn parse_path_segments(path: &[u8]) -> Result<Vec<RawSegment<'_>>, ()> {
+pub(crate) fn parse_path_segments(path: &[u8]) -> Result<Vec<RawSegment<'_>>, ()> {
     fn parse_static<'a>(path: &'a [u8], i: &mut usize) -> &'a [u8] {
         let s = *i;
         while *i < path.len() {
@@ -76,33 +76,46 @@ impl<T> Trie<T> {
         }
     }
 
-    pub(crate) fn matches(&self, domain: &str) -> Option<&T> {
+    /// Returns the data of the matched pattern and the labels captured by its
+    /// `+` and `*` segments, from left to right.
+    pub(crate) fn matches(&self, domain: &str) -> Option<(&T, Vec<String>)> {
+        let mut captures = Vec::new();
         if domain.is_empty() {
-            return self.root.star_child.as_ref();
+            return self.root.star_child.as_ref().map(|data| (data, captures));
         }
         let segments = domain.split('.').rev().collect::<Vec<_>>();
-        Self::internal_matches(&segments, &self.root)
+        let data = Self::internal_matches(&segments, &self.root, &mut captures)?;
+        captures.reverse();
+        Some((data, captures))
     }
 
-    fn internal_matches<'a>(segments: &[&str], parent_node: &'a Node<T>) -> Option<&'a T> {
+    fn internal_matches<'a>(
+        segments: &[&str],
+        parent_node: &'a Node<T>,
+        captures: &mut Vec<String>,
+    ) -> Option<&'a T> {
         let (segment, tail) = match segments.split_first() {
             Some((segment, tail)) => (*segment, tail),
             None => return parent_node.data.as_ref(),
         };
 
         if let Some(node) = parent_node.named_children.get(segment) {
-            if let Some(data) = Self::internal_matches(tail, node) {
+            if let Some(data) = Self::internal_matches(tail, node, captures) {
                 return Some(data);
             }
         }
 
         if let Some(plus_child) = &parent_node.plus_child {
-            if let Some(data) = Self::internal_matches(tail, plus_child) {
+            captures.push(segment.to_string());
+            if let Some(data) = Self::internal_matches(tail, plus_child, captures) {
                 return Some(data);
             }
+            captures.pop();
         }
 
         if let Some(data) = &parent_node.star_child {
+            let rest = segments.iter().rev().copied().collect::<Vec<_>>();
+            captures.push(rest.join("."));
             return Some(data);
         }
 
@@ -253,7 +266,33 @@ mod tests {
         ];
 
         for (domain, id) in matches {
-            assert_eq!(tree.matches(domain), id);
+            assert_eq!(tree.matches(domain).map(|(data, _)| data), id);
+        }
+    }
+
+    #[test]
+    fn test_captures() {
+        let mut tree = Trie::default();
+        tree.add("+.example.com", 1).unwrap();
+        tree.add("+.+.org", 2).unwrap();
+        tree.add("*.example.net", 3).unwrap();
+        tree.add("www.+.+.io", 4).unwrap();
+        tree.add("*", 5).unwrap();
+
+        let matches = vec![
+            ("a.example.com", 1, vec!["a"]),
+            ("a.b.org", 2, vec!["a", "b"]),
+            ("a.b.example.net", 3, vec!["a.b"]),
+            ("www.a.b.io", 4, vec!["a", "b"]),
+            ("x.a.b.io", 5, vec!["x.a.b.io"]),
+            ("", 5, vec![]),
+        ];
+
+        for (domain, id, captures) in matches {
+            assert_eq!(
+                tree.matches(domain),
+                Some((&id, captures.into_iter().map(ToString::to_string).collect()))
+            );
         }
     }
 }
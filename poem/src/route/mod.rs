@@ -7,9 +7,9 @@ mod router_method;
 mod router_scheme;
 
 pub(crate) use internal::radix_tree::PathParams;
-pub use router::{PathPattern, Route};
+pub use router::{PathPattern, Route, RouteUrls};
 #[allow(unreachable_pub)]
-pub use router_domain::RouteDomain;
+pub use router_domain::{DomainCaptures, RouteDomain};
 #[allow(unreachable_pub)]
 pub use router_method::{
     RouteMethod, connect, delete, get, head, options, patch, post, put, trace,
@@ -27,5 +27,6 @@ pub(crate) fn check_result<T>(res: Result<T, RouteError>) -> T {
         Err(RouteError::InvalidRegex { path, regex }) => {
             panic!("invalid regex in path: {path} `{regex}`")
         }
+        Err(RouteError::DuplicateName(name)) => panic!("duplicate route name: {name}"),
     }
 }
@@ -1,26 +1,57 @@
-use std::{str::FromStr, sync::Arc};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
+use parking_lot::Mutex;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
 use regex::Regex;
 
 use crate::{
     Endpoint, EndpointExt, IntoEndpoint, IntoResponse, Request, Response, Result,
     endpoint::BoxEndpoint,
-    error::{NotFoundError, ParsePathError, RouteError},
-    http::{Uri, uri::PathAndQuery},
-    route::{check_result, internal::radix_tree::RadixTree},
+    error::{NotFoundError, ParsePathError, RouteError, UrlForError},
+    http::{Method, StatusCode, Uri, uri::PathAndQuery},
+    route::{
+        check_result,
+        internal::radix_tree::{RadixTree, RawSegment, parse_path_segments},
+    },
 };
 
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+const PATH: &AsciiSet = &PATH_SEGMENT.remove(b'/');
+
 #[derive(Debug, Clone, Copy)]
 struct PathPrefix(usize);
 
+/// The part of the request path stripped by the nested routes, used to
+/// generate the URLs of the named routes.
+#[derive(Debug, Clone)]
+struct StrippedPrefix(String);
+
 /// Routing object
 ///
 /// You can match the full path or wildcard path, and use the
 /// [`Path`](crate::web::Path) extractor to get the path parameters.
 ///
+/// A catch-all parameter such as `*path` in `/files/*path` captures the rest
+/// of the path without the leading slash, so `/files/a/b/c.txt` gives
+/// `a/b/c.txt`. Like all parameters the value is percent-decoded, which means
+/// it can contain `..` segments, including ones sent as `%2E%2E%2F`. The
+/// router does not normalize it, so an endpoint that uses it to access the
+/// filesystem must reject or resolve these segments itself, as
+/// `StaticFilesEndpoint` does.
+///
+/// A server-wide `OPTIONS *` request is answered with `204 No Content`.
+/// `OPTIONS` requests to a path are answered by the
+/// [`RouteMethod`](crate::RouteMethod) of that path, see
+/// [`RouteMethod::auto_options`](crate::RouteMethod::auto_options).
+///
 /// # Errors
 ///
-/// - [`NotFoundError`]
+/// - [`NotFoundError`], when no route matches and no
+///   [fallback](Route::fallback) is set
 ///
 /// # Example
 ///
@@ -128,6 +159,9 @@ struct PathPrefix(usize);
 #[derive(Default)]
 pub struct Route {
     tree: RadixTree<BoxEndpoint<'static>>,
+    urls: RouteUrls,
+    merged_urls: Mutex<Option<MergedUrls>>,
+    fallback: Option<BoxEndpoint<'static>>,
 }
 
 impl Route {
@@ -161,6 +195,66 @@ impl Route {
         Ok(self)
     }
 
+    /// Add an [Endpoint] to the specified path, and register the path under
+    /// `name` so that its URL can be generated with [`Route::url_for`].
+    ///
+    /// # Panics
+    ///
+    /// Panic when there are duplicate paths or names in the routing table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{Route, handler};
+    ///
+    /// #[handler]
+    /// fn user() {}
+    ///
+    /// let app = Route::new().at_named("user", "/users/:id", user);
+    /// assert_eq!(app.url_for("user", &[("id", "42")]).unwrap(), "/users/42");
+    /// ```
+    #[must_use]
+    pub fn at_named<E>(self, name: impl Into<String>, path: impl AsRef<str>, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        check_result(self.try_at_named(name, path, ep))
+    }
+
+    /// Attempts to add an [Endpoint] to the specified path, and register the
+    /// path under `name`.
+    pub fn try_at_named<E>(
+        self,
+        name: impl Into<String>,
+        path: impl AsRef<str>,
+        ep: E,
+    ) -> Result<Self, RouteError>
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        let name = name.into();
+        if self.urls.0.contains_key(&name) {
+            return Err(RouteError::DuplicateName(name));
+        }
+        let path = normalize_path(path.as_ref());
+        let mut route = self.try_at(&path, ep)?;
+        Arc::make_mut(&mut route.urls.0).insert(name, path);
+        Ok(route)
+    }
+
+    /// Returns the registry of the named routes.
+    pub fn urls(&self) -> &RouteUrls {
+        &self.urls
+    }
+
+    /// Generates the URL of the route registered with `name`, see
+    /// [`RouteUrls::url_for`].
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, UrlForError> {
+        self.urls.url_for(name, params)
+    }
+
     /// Add an [Endpoint] to the `/` path.
     ///
     /// Same as `self.at("/", ep)`.
@@ -223,6 +317,66 @@ impl Route {
         self.internal_nest(&normalize_path(path.as_ref()), ep, false)
     }
 
+    /// Sets the [Endpoint] that handles the requests whose path matches none
+    /// of the routes.
+    ///
+    /// The fallback of a nested route only applies to the paths under its
+    /// prefix, so each nested scope can have its own fallback. The fallback of
+    /// the parent route is not used for these paths, even if the nested route
+    /// has no fallback.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     Route, get, handler,
+    ///     http::StatusCode,
+    ///     test::TestClient,
+    ///     web::{Html, Json},
+    /// };
+    /// use serde_json::json;
+    ///
+    /// #[handler]
+    /// fn api_not_found() -> (StatusCode, Json<serde_json::Value>) {
+    ///     (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" })))
+    /// }
+    ///
+    /// #[handler]
+    /// fn not_found() -> (StatusCode, Html<&'static str>) {
+    ///     (StatusCode::NOT_FOUND, Html("<h1>Not Found</h1>"))
+    /// }
+    ///
+    /// #[handler]
+    /// fn index() {}
+    ///
+    /// let app = Route::new()
+    ///     .at("/", get(index))
+    ///     .nest("/api", Route::new().at("/users", get(index)).fallback(api_not_found))
+    ///     .fallback(not_found);
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli.get("/api/missing").send().await;
+    /// resp.assert_status(StatusCode::NOT_FOUND);
+    /// resp.assert_json(json!({ "error": "not found" })).await;
+    ///
+    /// let resp = cli.get("/missing").send().await;
+    /// resp.assert_status(StatusCode::NOT_FOUND);
+    /// resp.assert_text("<h1>Not Found</h1>").await;
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn fallback<E>(self, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        Self {
+            fallback: Some(ep.map_to_response().boxed()),
+            ..self
+        }
+    }
+
     fn internal_nest<E>(mut self, path: &str, ep: E, strip: bool) -> Result<Self, RouteError>
     where
         E: IntoEndpoint,
@@ -254,6 +408,15 @@ impl Route {
                     params.pop().expect("can't be empty due to a check above");
                 }
 
+                if self.prefix_len > 0 {
+                    let stripped = &req.uri().path()[..self.prefix_len];
+                    let prefix = match req.data::<StrippedPrefix>() {
+                        Some(parent) => format!("{}{stripped}", parent.0),
+                        None => stripped.to_string(),
+                    };
+                    req.set_data(StrippedPrefix(prefix));
+                }
+
                 let new_uri = {
                     let uri = std::mem::take(req.uri_mut());
                     let mut uri_parts = uri.into_parts();
@@ -322,13 +485,185 @@ impl Route {
 }
 
 /// Container that can be used to obtain path pattern from the request.
+///
+/// When a [`Route`] matches a request, it adds the pattern of the matched
+/// route, such as `/users/:id`, to the request data before calling the
+/// endpoint, so that the endpoint and the middlewares applied to it can
+/// extract it with [`Data<&PathPattern>`](crate::web::Data). In a nested
+/// route, the pattern includes the prefixes of the parent routes.
+///
+/// The pattern is also added to the data of the response, or of the error,
+/// so middlewares applied outside the route, such as
+/// [`Tracing`](crate::middleware::Tracing), can use it to report the
+/// requests by route instead of by path.
+///
+/// # Example
+///
+/// ```
+/// use poem::{PathPattern, Route, get, handler, test::TestClient, web::Data};
+///
+/// #[handler]
+/// fn get_user(Data(pattern): Data<&PathPattern>) -> String {
+///     pattern.0.to_string()
+/// }
+///
+/// let app = Route::new().nest("/api", Route::new().at("/users/:id", get(get_user)));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/api/users/42").send().await;
+/// resp.assert_text("/api/users/:id").await;
+/// # });
+/// ```
 #[derive(Debug, Clone)]
 pub struct PathPattern(pub Arc<str>);
 
+/// The named routes of a [`Route`], used to generate their URLs.
+///
+/// The route adds its registry to the data of the requests it dispatches, so
+/// handlers can extract it with [`Data<&RouteUrls>`](crate::web::Data). The
+/// names of a nested route are combined with the names of the parent route,
+/// and their URLs include the prefix that the parent route stripped.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, RouteUrls, get, handler,
+///     test::TestClient,
+///     web::{Data, Redirect},
+/// };
+///
+/// #[handler]
+/// fn user() {}
+///
+/// #[handler]
+/// fn me(Data(urls): Data<&RouteUrls>) -> Redirect {
+///     Redirect::see_other(urls.url_for("user", &[("id", "42")]).unwrap())
+/// }
+///
+/// let app = Route::new().nest(
+///     "/api",
+///     Route::new()
+///         .at_named("user", "/users/:id", get(user))
+///         .at("/me", get(me)),
+/// );
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/api/me").send().await;
+/// resp.assert_header("location", "/api/users/42");
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RouteUrls(Arc<BTreeMap<String, String>>);
+
+impl RouteUrls {
+    /// Generates the URL of the route registered with `name`, replacing its
+    /// parameters with the percent-encoded values in `params`.
+    ///
+    /// The values of regex parameters are not checked against the regex.
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, UrlForError> {
+        let path = self
+            .0
+            .get(name)
+            .ok_or_else(|| UrlForError::RouteNotFound(name.to_string()))?;
+        let segments = parse_path_segments(path.as_bytes())
+            .expect("the path has been validated when adding the route");
+
+        let param = |param: &[u8]| {
+            let param = String::from_utf8_lossy(param);
+            params
+                .iter()
+                .find(|(name, _)| *name == param)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| UrlForError::MissingParam {
+                    name: name.to_string(),
+                    param: param.into_owned(),
+                })
+        };
+
+        let mut url = String::new();
+        for segment in segments {
+            match segment {
+                RawSegment::Static(value) => url.push_str(&String::from_utf8_lossy(value)),
+                RawSegment::Param(param_name) | RawSegment::Regex(Some(param_name), _) => {
+                    url.extend(utf8_percent_encode(param(param_name)?, PATH_SEGMENT));
+                }
+                RawSegment::CatchAll(Some(param_name)) => {
+                    url.extend(utf8_percent_encode(param(param_name)?, PATH));
+                }
+                RawSegment::CatchAll(None) | RawSegment::Regex(None, _) => {
+                    return Err(UrlForError::UnnamedSegment(name.to_string()));
+                }
+            }
+        }
+        Ok(url)
+    }
+
+    fn merge(&self, parent: Option<&RouteUrls>, prefix: &str) -> RouteUrls {
+        let mut urls = parent.map(|parent| (*parent.0).clone()).unwrap_or_default();
+        urls.extend(
+            self.0
+                .iter()
+                .map(|(name, path)| (name.clone(), format!("{prefix}{path}"))),
+        );
+        RouteUrls(Arc::new(urls))
+    }
+}
+
+/// The names of a nested [`Route`] combined with the names of its parent.
+struct MergedUrls {
+    parent: Option<RouteUrls>,
+    prefix: String,
+    urls: RouteUrls,
+}
+
+impl Route {
+    /// Returns the named routes combined with the names of the parent route.
+    ///
+    /// A nested route is always reached through the same parent and prefix,
+    /// so they are merged once and shared by the following requests.
+    fn merged_urls(&self, parent: Option<&RouteUrls>, prefix: &str) -> RouteUrls {
+        if parent.is_none() && prefix.is_empty() {
+            return self.urls.clone();
+        }
+
+        let mut merged_urls = self.merged_urls.lock();
+        if let Some(merged) = &*merged_urls {
+            let same_parent = match (&merged.parent, parent) {
+                (Some(a), Some(b)) => Arc::ptr_eq(&a.0, &b.0),
+                (None, None) => true,
+                _ => false,
+            };
+            if same_parent && merged.prefix == prefix {
+                return merged.urls.clone();
+            }
+        }
+
+        let urls = self.urls.merge(parent, prefix);
+        *merged_urls = Some(MergedUrls {
+            parent: parent.cloned(),
+            prefix: prefix.to_string(),
+            urls: urls.clone(),
+        });
+        urls
+    }
+}
+
 impl Endpoint for Route {
     type Output = Response;
 
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if !self.urls.0.is_empty() {
+            let prefix = req
+                .data::<StrippedPrefix>()
+                .map(|prefix| prefix.0.as_str())
+                .unwrap_or_default();
+            let urls = self.merged_urls(req.data::<RouteUrls>(), prefix);
+            req.set_data(urls);
+        }
+
         match self.tree.matches(req.uri().path()) {
             Some(matches) => {
                 req.state_mut().match_params.extend(matches.params);
@@ -367,7 +702,13 @@ impl Endpoint for Route {
                     }
                 }
             }
-            None => Err(NotFoundError.into()),
+            None if req.method() == Method::OPTIONS && req.uri().path() == "*" => {
+                Ok(StatusCode::NO_CONTENT.into_response())
+            }
+            None => match &self.fallback {
+                Some(fallback) => fallback.call(req).await,
+                None => Err(NotFoundError.into()),
+            },
         }
     }
 }
@@ -781,4 +1122,168 @@ mod tests {
             "/nest_no_strip1/nest_no_strip2/:id"
         );
     }
+
+    #[tokio::test]
+    async fn url_for() {
+        let r = Route::new()
+            .at_named("user", "/users/:id", h)
+            .at_named("file", "/files/:dir/*path", h)
+            .at_named("number", "/numbers/:n<\\d+>", h)
+            .at_named("anonymous", "/a/<\\d+>", h);
+
+        assert_eq!(r.url_for("user", &[("id", "42")]).unwrap(), "/users/42");
+        assert_eq!(
+            r.url_for("user", &[("id", "a b/c?")]).unwrap(),
+            "/users/a%20b%2Fc%3F"
+        );
+        assert_eq!(
+            r.url_for("file", &[("dir", "x"), ("path", "a/b c.txt")])
+                .unwrap(),
+            "/files/x/a/b%20c.txt"
+        );
+        assert_eq!(r.url_for("number", &[("n", "1")]).unwrap(), "/numbers/1");
+        assert_eq!(
+            r.url_for("file", &[("dir", "x")]).unwrap_err(),
+            UrlForError::MissingParam {
+                name: "file".to_string(),
+                param: "path".to_string()
+            }
+        );
+        assert_eq!(
+            r.url_for("anonymous", &[]).unwrap_err(),
+            UrlForError::UnnamedSegment("anonymous".to_string())
+        );
+        assert_eq!(
+            r.url_for("unknown", &[]).unwrap_err(),
+            UrlForError::RouteNotFound("unknown".to_string())
+        );
+
+        assert!(matches!(
+            Route::new()
+                .at_named("a", "/a", h)
+                .try_at_named("a", "/b", h),
+            Err(RouteError::DuplicateName(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn url_for_nested() {
+        #[handler(internal)]
+        fn urls(req: &Request) -> String {
+            let urls = req.data::<RouteUrls>().unwrap();
+            format!(
+                "{} {}",
+                urls.url_for("index", &[]).unwrap(),
+                urls.url_for("user", &[("id", "1")]).unwrap()
+            )
+        }
+
+        let r = Route::new().at_named("index", "/", h).nest(
+            "/api",
+            Route::new().nest("/v1", Route::new().at_named("user", "/users/:id", urls)),
+        );
+        assert_eq!(get(&r, "/api/v1/users/1").await, "/ /api/v1/users/1");
+    }
+
+    #[tokio::test]
+    async fn url_for_nested_merged_once() {
+        #[handler(internal)]
+        fn urls(req: &Request) -> String {
+            format!("{:p}", Arc::as_ptr(&req.data::<RouteUrls>().unwrap().0))
+        }
+
+        let r = Route::new()
+            .at_named("index", "/", h)
+            .nest("/api", Route::new().at_named("user", "/users/:id", urls));
+        assert_eq!(get(&r, "/api/users/1").await, get(&r, "/api/users/2").await);
+    }
+
+    #[tokio::test]
+    async fn method_not_allowed() {
+        let r = Route::new().at("/a", crate::get(make_sync(|_| ())).post(make_sync(|_| ())));
+        let cli = TestClient::new(r);
+
+        let resp = cli.delete("/a").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "GET, POST, HEAD, OPTIONS");
+
+        cli.delete("/b")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn options() {
+        let r = Route::new().at("/a", crate::get(make_sync(|_| ())));
+        let cli = TestClient::new(r);
+
+        let resp = cli.options("/a").send().await;
+        resp.assert_status(StatusCode::NO_CONTENT);
+        resp.assert_header("allow", "GET, HEAD, OPTIONS");
+
+        cli.options("*")
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+        cli.options("/b")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn catch_all() {
+        #[handler(internal)]
+        fn h(crate::web::Path(path): crate::web::Path<String>) -> String {
+            path
+        }
+
+        let r = Route::new().at("/files/*path", h);
+
+        assert_eq!(get(&r, "/files/a/b/c.txt").await, "a/b/c.txt");
+        assert_eq!(get(&r, "/files/a%20b/c").await, "a b/c");
+        // the value is decoded but not normalized
+        assert_eq!(get(&r, "/files/%2E%2E%2Fetc").await, "../etc");
+    }
+
+    #[tokio::test]
+    async fn nested_fallback() {
+        use crate::web::{Html, Json};
+
+        let api = Route::new()
+            .at("/users", make_sync(|_| "users"))
+            .fallback(make_sync(|_| {
+                Json(serde_json::json!({ "error": "not found" }))
+            }));
+        let r = Route::new()
+            .at("/", make_sync(|_| "index"))
+            .nest("/api", api)
+            .nest("/static", Route::new().at("/a", make_sync(|_| "a")))
+            .fallback(make_sync(|_| Html("not found")));
+        let cli = TestClient::new(r);
+
+        cli.get("/api/users")
+            .send()
+            .await
+            .assert_text("users")
+            .await;
+
+        let resp = cli.get("/api/missing").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/json; charset=utf-8");
+        resp.assert_json(serde_json::json!({ "error": "not found" }))
+            .await;
+
+        let resp = cli.get("/missing").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("text/html; charset=utf-8");
+        resp.assert_text("not found").await;
+
+        // the parent fallback does not apply under a nested prefix
+        cli.get("/static/missing")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
 }
@@ -1,3 +1,5 @@
+use std::ops::Deref;
+
 use crate::{
     Endpoint, EndpointExt, IntoEndpoint, Request, Response, Result,
     endpoint::BoxEndpoint,
@@ -8,6 +10,20 @@ use crate::{
 
 /// Routing object for `HOST` header
 ///
+/// A pattern is matched label by label against the host of the request:
+///
+/// - `example.com` matches exactly `example.com`
+/// - `+` matches exactly one label, so `+.example.com` matches
+///   `www.example.com` but not `example.com` or `a.b.example.com`
+/// - `*` as the first label matches one or more labels, so `*.example.com`
+///   matches `www.example.com` and `a.b.example.com`
+/// - a pattern that is just `*` matches every host, including requests without
+///   a host, and can be used as a fallback
+///
+/// Matching is case-insensitive, and the port and a trailing dot of the host
+/// are ignored. The labels matched by `+` and `*` are available to the
+/// endpoint as [`DomainCaptures`] in the request data.
+///
 /// # Errors
 ///
 /// - [`NotFoundError`]
@@ -48,6 +64,30 @@ use crate::{
 /// check(&app, None, "4").await;
 /// # });
 /// ```
+///
+/// Reading the captured labels:
+///
+/// ```
+/// use poem::{DomainCaptures, RouteDomain, handler, http::header, test::TestClient, web::Data};
+///
+/// #[handler]
+/// fn tenant(Data(captures): Data<&DomainCaptures>) -> String {
+///     captures[0].clone()
+/// }
+///
+/// let app = RouteDomain::new().at("+.example.com", tenant);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header(header::HOST, "Acme.Example.com:8080")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("acme").await;
+/// # });
+/// ```
 #[derive(Default)]
 pub struct RouteDomain {
     tree: Trie<BoxEndpoint<'static>>,
@@ -80,7 +120,7 @@ impl RouteDomain {
         E::Endpoint: 'static,
     {
         self.tree.add(
-            pattern.as_ref(),
+            &pattern.as_ref().to_ascii_lowercase(),
             ep.into_endpoint().map_to_response().boxed(),
         )?;
         Ok(self)
@@ -90,25 +130,56 @@ impl RouteDomain {
 impl Endpoint for RouteDomain {
     type Output = Response;
 
-    async fn call(&self, req: Request) -> Result<Self::Output> {
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
         let host = req
             .headers()
             .get(header::HOST)
             .and_then(|host| host.to_str().ok())
+            .or_else(|| req.uri().authority().map(|authority| authority.as_str()))
+            .map(normalize_host)
             .unwrap_or_default();
-        match self.tree.matches(host) {
-            Some(ep) => ep.call(req).await,
+        match self.tree.matches(&host) {
+            Some((ep, captures)) => {
+                req.set_data(DomainCaptures(captures));
+                ep.call(req).await
+            }
             None => Err(NotFoundError.into()),
         }
     }
 }
 
+/// The labels of the host matched by the `+` and `*` segments of a
+/// [`RouteDomain`] pattern, from left to right.
+///
+/// A `+` captures a single label, and a `*` captures all the labels it
+/// matches joined with `.`. For example, the pattern `*.+.example.com`
+/// matching the host `a.b.c.example.com` captures `["a.b", "c"]`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DomainCaptures(pub Vec<String>);
+
+impl Deref for DomainCaptures {
+    type Target = [String];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Lowercases the host and removes the port and the trailing dot.
+fn normalize_host(host: &str) -> String {
+    let host = match host.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map_or(rest, |(ip, _)| ip),
+        None => host.split_once(':').map_or(host, |(host, _)| host),
+    };
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
 #[cfg(test)]
 mod tests {
     use http::StatusCode;
 
     use super::*;
-    use crate::{endpoint::make_sync, handler, http::HeaderMap, test::TestClient};
+    use crate::{endpoint::make_sync, handler, http::HeaderMap, test::TestClient, web::Data};
 
     async fn check(r: &RouteDomain, host: &str, value: &str) {
         let cli = TestClient::new(r);
@@ -168,6 +239,44 @@ mod tests {
             .assert_status(StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn case_and_port() {
+        let r = RouteDomain::new()
+            .at("Example.com", make_sync(|_| "1"))
+            .at("+.example.com", make_sync(|_| "2"));
+
+        check(&r, "EXAMPLE.com", "1").await;
+        check(&r, "example.com:8080", "1").await;
+        check(&r, "example.com.", "1").await;
+        check(&r, "WWW.example.COM:80", "2").await;
+    }
+
+    #[tokio::test]
+    async fn captures() {
+        #[handler(internal)]
+        fn h(Data(captures): Data<&DomainCaptures>) -> String {
+            captures.join(",")
+        }
+
+        let r = RouteDomain::new()
+            .at("example.com", h)
+            .at("*.+.example.com", h)
+            .at("*.example.org", h);
+
+        check(&r, "example.com", "").await;
+        check(&r, "A.b.c.example.com", "a.b,c").await;
+        check(&r, "x.y.example.org:443", "x.y").await;
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_host("Example.COM"), "example.com");
+        assert_eq!(normalize_host("example.com:3000"), "example.com");
+        assert_eq!(normalize_host("[::1]:3000"), "::1");
+        assert_eq!(normalize_host("[::1]"), "::1");
+        assert_eq!(normalize_host(""), "");
+    }
+
     #[handler(internal)]
     fn h() {}
 
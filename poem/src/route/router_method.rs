@@ -1,17 +1,31 @@
 use std::future::Future;
 
 use futures_util::{FutureExt, future::Either};
+use headers::Allow;
 
 use crate::{
-    Endpoint, EndpointExt, IntoEndpoint, Request, Response, Result, endpoint::BoxEndpoint,
-    error::MethodNotAllowedError, http::Method,
+    Endpoint, EndpointExt, IntoEndpoint, Request, Response, Result,
+    endpoint::BoxEndpoint,
+    error::method_not_allowed,
+    http::{HeaderValue, Method, StatusCode, header},
 };
 
 /// Routing object for HTTP methods
 ///
+/// A `HEAD` request is answered by the `GET` endpoint when no `HEAD` endpoint
+/// is set. The response keeps the headers of the `GET` response, and the body
+/// is discarded without being read. When the length of the body is known, for
+/// example for strings, bytes and files, it is sent as `Content-Length`. For
+/// streaming bodies the length is unknown, so the header is omitted.
+///
+/// An `OPTIONS` request is answered with `204 No Content` and an `Allow`
+/// header when no `OPTIONS` endpoint is set, see
+/// [`RouteMethod::auto_options`].
+///
 /// # Errors
 ///
-/// - [`MethodNotAllowedError`]
+/// - [`MethodNotAllowedError`](crate::error::MethodNotAllowedError), with an
+///   `Allow` header listing the methods that have an endpoint
 ///
 /// # Example
 ///
@@ -50,11 +64,21 @@ use crate::{
 ///     .get_response(Request::builder().method(Method::PUT).finish())
 ///     .await;
 /// assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+/// assert_eq!(resp.headers()["allow"], "GET, POST, HEAD, OPTIONS");
 /// # });
 /// ```
-#[derive(Default)]
 pub struct RouteMethod {
     methods: Vec<(Method, BoxEndpoint<'static>)>,
+    auto_options: bool,
+}
+
+impl Default for RouteMethod {
+    fn default() -> Self {
+        Self {
+            methods: Vec::new(),
+            auto_options: true,
+        }
+    }
 }
 
 impl RouteMethod {
@@ -164,6 +188,38 @@ impl RouteMethod {
     {
         self.method(Method::TRACE, ep)
     }
+
+    /// Sets whether `OPTIONS` requests are answered automatically when no
+    /// `OPTIONS` endpoint is set, default is `true`.
+    ///
+    /// The response is a `204 No Content` with an `Allow` header listing the
+    /// methods that have an endpoint. When disabled, these requests get a
+    /// [`MethodNotAllowedError`](crate::error::MethodNotAllowedError).
+    #[must_use]
+    pub fn auto_options(self, enable: bool) -> Self {
+        Self {
+            auto_options: enable,
+            ..self
+        }
+    }
+
+    /// Returns the methods that have an endpoint, including `HEAD` when only
+    /// `GET` is set and `OPTIONS` when it is answered automatically.
+    fn allowed_methods(&self) -> Vec<Method> {
+        let mut allowed_methods = Vec::with_capacity(self.methods.len() + 2);
+        for (method, _) in &self.methods {
+            if !allowed_methods.contains(method) {
+                allowed_methods.push(method.clone());
+            }
+        }
+        if allowed_methods.contains(&Method::GET) && !allowed_methods.contains(&Method::HEAD) {
+            allowed_methods.push(Method::HEAD);
+        }
+        if self.auto_options && !allowed_methods.contains(&Method::OPTIONS) {
+            allowed_methods.push(Method::OPTIONS);
+        }
+        allowed_methods
+    }
 }
 
 impl Endpoint for RouteMethod {
@@ -183,13 +239,30 @@ impl Endpoint for RouteMethod {
                         async move {
                             req.set_method(Method::GET);
                             let mut resp = self.call(req).await?;
-                            resp.set_body(());
+                            let body = resp.take_body();
+                            if !resp.headers().contains_key(header::CONTENT_LENGTH) {
+                                if let Some(len) = body.exact_len() {
+                                    resp.headers_mut()
+                                        .insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+                                }
+                            }
                             Ok(resp)
                         }
                         .boxed(),
                     ))
                 } else {
-                    Either::Right(Either::Right(async { Err(MethodNotAllowedError.into()) }))
+                    let auto_options = self.auto_options && req.method() == Method::OPTIONS;
+                    Either::Right(Either::Right(async move {
+                        let allowed_methods = self.allowed_methods().into_iter().collect::<Allow>();
+                        if auto_options {
+                            Ok(Response::builder()
+                                .status(StatusCode::NO_CONTENT)
+                                .typed_header(allowed_methods)
+                                .finish())
+                        } else {
+                            Err(method_not_allowed(allowed_methods))
+                        }
+                    }))
                 }
             }
         }
@@ -280,12 +353,65 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{handler, http::StatusCode, test::TestClient};
+    use crate::{Body, handler, test::TestClient};
 
     #[tokio::test]
     async fn method_not_allowed() {
         let resp = TestClient::new(RouteMethod::new()).get("/").send().await;
         resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "OPTIONS");
+
+        #[handler(internal)]
+        fn index() {}
+
+        let cli = TestClient::new(RouteMethod::new().get(index).post(index).get(index));
+        let resp = cli.put("/").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "GET, POST, HEAD, OPTIONS");
+
+        let cli = TestClient::new(RouteMethod::new().delete(index).head(index));
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "DELETE, HEAD, OPTIONS");
+
+        let err = RouteMethod::new()
+            .get(index)
+            .call(Request::builder().method(Method::PUT).finish())
+            .await
+            .unwrap_err();
+        assert!(err.is::<crate::error::MethodNotAllowedError>());
+        assert_eq!(
+            err.data::<Allow>(),
+            Some(
+                &[Method::GET, Method::HEAD, Method::OPTIONS]
+                    .into_iter()
+                    .collect()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn options_method() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let cli = TestClient::new(RouteMethod::new().get(index).post(index));
+        let resp = cli.options("/").send().await;
+        resp.assert_status(StatusCode::NO_CONTENT);
+        resp.assert_header("allow", "GET, POST, HEAD, OPTIONS");
+
+        // an explicit `OPTIONS` endpoint takes precedence
+        let cli = TestClient::new(RouteMethod::new().get(index).options(index));
+        let resp = cli.options("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello").await;
+
+        let cli = TestClient::new(RouteMethod::new().get(index).auto_options(false));
+        let resp = cli.options("/").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header("allow", "GET, HEAD");
     }
 
     #[tokio::test]
@@ -346,9 +472,36 @@ mod tests {
             "hello"
         }
 
+        #[handler(internal)]
+        fn stream() -> Body {
+            Body::from_bytes_stream(futures_util::stream::iter(vec![Ok::<_, std::io::Error>(
+                bytes::Bytes::from_static(b"hello"),
+            )]))
+        }
+
+        #[handler(internal)]
+        fn head() -> Response {
+            Response::builder().header("x-head", "1").finish()
+        }
+
         let route = RouteMethod::new().get(index);
         let resp = TestClient::new(route).head("/").send().await;
         resp.assert_status_is_ok();
+        resp.assert_header("content-length", "5");
+        resp.assert_content_type("text/plain; charset=utf-8");
+        resp.assert_text("").await;
+
+        let route = RouteMethod::new().get(stream);
+        let resp = TestClient::new(route).head("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist("content-length");
         resp.assert_text("").await;
+
+        // an explicit `HEAD` endpoint takes precedence
+        let route = RouteMethod::new().get(index).head(head);
+        let resp = TestClient::new(route).head("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header("x-head", "1");
+        resp.assert_header_is_not_exist("content-length");
     }
 }
@@ -7,12 +7,12 @@ use std::{
     pin::Pin,
     sync::{
         Arc,
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
     },
     task::{Context, Poll},
 };
 
-use futures_util::FutureExt;
+use futures_util::{FutureExt, Stream, StreamExt, future::BoxFuture, stream::BoxStream};
 use http::uri::Scheme;
 use hyper::body::Incoming;
 use hyper_util::server::conn::auto;
@@ -27,15 +27,154 @@ use tokio_util::sync::CancellationToken;
 use crate::{
     Endpoint, EndpointExt, IntoEndpoint, Response,
     endpoint::{DynEndpoint, ToDynEndpoint},
-    listener::{Acceptor, AcceptorExt, Listener},
+    listener::{Acceptor, AcceptorExt, BoxAcceptor, ConnectionExtensions, Listener},
     web::{LocalAddr, RemoteAddr},
 };
 
+/// The result of shutting down a server.
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShutdownStatus {
+    /// All connections were closed before the timeout.
+    Graceful,
+    /// The timeout elapsed and the remaining connections were closed
+    /// forcibly.
+    Forced,
+}
+
+/// A signal that is triggered when the server initiates graceful shutdown.
+///
+/// The server adds the signal to the data of every request, so handlers can
+/// extract it with `Data<&ShutdownSignal>`. Long-lived responses, such as
+/// server-sent events or websocket connections, never complete by themselves,
+/// so they should stop when the signal is triggered. Otherwise the server
+/// waits for them until the shutdown timeout elapses.
+///
+/// A stream ends at shutdown with `stream.take_until(signal.wait())`, for
+/// example in `SSE::new`, and a websocket handler can `tokio::select!` on
+/// [`ShutdownSignal::wait`] next to the incoming messages, and send a close
+/// message when the signal is triggered.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use futures_util::{StreamExt, stream};
+/// use poem::{Body, ShutdownSignal, handler, web::Data};
+///
+/// #[handler]
+/// fn events(signal: Data<&ShutdownSignal>) -> Body {
+///     let events = stream::unfold(0, |n| async move {
+///         tokio::time::sleep(Duration::from_secs(1)).await;
+///         Some((Ok::<_, std::io::Error>(format!("event {n}\n")), n + 1))
+///     });
+///     // the stream ends when the shutdown begins
+///     Body::from_bytes_stream(events.take_until(signal.wait()))
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSignal(CancellationToken);
+
+impl ShutdownSignal {
+    /// Create a signal that is not triggered.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Triggers the signal.
+    ///
+    /// The server calls it when it initiates graceful shutdown.
+    pub fn shutdown(&self) {
+        self.0.cancel();
+    }
+
+    /// Returns `true` if the signal has been triggered.
+    pub fn is_shutdown(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    /// Returns a future that completes when the signal is triggered.
+    pub fn wait(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.0.clone().cancelled_owned()
+    }
+}
+
+/// A signal that is triggered when the connection of the request is closed.
+///
+/// The server adds the signal to the data of every request, so handlers can
+/// extract it with `Data<&Disconnect>`. When the client disconnects, the
+/// futures of its requests in flight are dropped, but the work that has been
+/// moved to other tasks, or is streamed in a response body, is not, so it
+/// can stop when the signal is triggered.
+///
+/// The signal belongs to the connection, so it is not triggered when a
+/// single HTTP/2 stream is reset, and it is also triggered when the server
+/// closes the connection, for example at shutdown.
+///
+/// # Example
+///
+/// ```
+/// use poem::{Disconnect, handler, web::Data};
+///
+/// async fn build_report() -> String {
+///     // an expensive computation
+///     String::new()
+/// }
+///
+/// #[handler]
+/// async fn report(disconnect: Data<&Disconnect>) -> String {
+///     let disconnect = disconnect.wait();
+///     let task = tokio::spawn(async move {
+///         tokio::select! {
+///             report = build_report() => Some(report),
+///             _ = disconnect => None,
+///         }
+///     });
+///     task.await.ok().flatten().unwrap_or_default()
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+#[derive(Debug, Clone, Default)]
+pub struct Disconnect(CancellationToken);
+
+impl Disconnect {
+    /// Create a signal that is not triggered.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Triggers the signal.
+    ///
+    /// The server calls it when the connection is closed.
+    pub fn disconnect(&self) {
+        self.0.cancel();
+    }
+
+    /// Returns `true` if the signal has been triggered.
+    pub fn is_disconnected(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    /// Returns a future that completes when the signal is triggered.
+    pub fn wait(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.0.clone().cancelled_owned()
+    }
+}
+
 enum Either<L, A> {
     Listener(L),
     Acceptor(A),
 }
 
+struct ReloadHook {
+    signal: BoxStream<'static, ()>,
+    callback: Box<dyn FnMut() -> BoxFuture<'static, ()> + Send>,
+}
+
 /// An HTTP Server.
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub struct Server<L, A> {
@@ -45,6 +184,13 @@ pub struct Server<L, A> {
     http2_max_concurrent_streams: Option<u32>,
     http2_max_pending_accept_reset_streams: Option<u32>,
     http2_max_header_list_size: u32,
+    http2_initial_stream_window_size: Option<u32>,
+    http2_initial_connection_window_size: Option<u32>,
+    http2_adaptive_window: bool,
+    http2_max_frame_size: Option<u32>,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_keep_alive_timeout: Option<Duration>,
+    reload_hooks: Vec<ReloadHook>,
 }
 
 impl<L: Listener> Server<L, Infallible> {
@@ -57,6 +203,13 @@ impl<L: Listener> Server<L, Infallible> {
             http2_max_concurrent_streams: None,
             http2_max_pending_accept_reset_streams: Some(20),
             http2_max_header_list_size: 16384,
+            http2_initial_stream_window_size: None,
+            http2_initial_connection_window_size: None,
+            http2_adaptive_window: false,
+            http2_max_frame_size: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            reload_hooks: Vec::new(),
         }
     }
 }
@@ -71,6 +224,13 @@ impl<A: Acceptor> Server<Infallible, A> {
             http2_max_concurrent_streams: None,
             http2_max_pending_accept_reset_streams: Some(20),
             http2_max_header_list_size: 16384,
+            http2_initial_stream_window_size: None,
+            http2_initial_connection_window_size: None,
+            http2_adaptive_window: false,
+            http2_max_frame_size: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            reload_hooks: Vec::new(),
         }
     }
 }
@@ -135,6 +295,145 @@ where
         }
     }
 
+    /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
+    /// stream-level flow control.
+    ///
+    /// Passing `None` will do nothing. If not set, hyper will use a default.
+    ///
+    /// [spec]: https://http2.github.io/http2-spec/#SETTINGS_INITIAL_WINDOW_SIZE
+    #[must_use]
+    pub fn http2_initial_stream_window_size(self, size: impl Into<Option<u32>>) -> Self {
+        Self {
+            http2_initial_stream_window_size: size.into(),
+            ..self
+        }
+    }
+
+    /// Sets the max connection-level flow control for HTTP2.
+    ///
+    /// Passing `None` will do nothing. If not set, hyper will use a default.
+    #[must_use]
+    pub fn http2_initial_connection_window_size(self, size: impl Into<Option<u32>>) -> Self {
+        Self {
+            http2_initial_connection_window_size: size.into(),
+            ..self
+        }
+    }
+
+    /// Sets whether to use an adaptive flow control for HTTP2.
+    ///
+    /// Enabling this will override the limits set in
+    /// [`Server::http2_initial_stream_window_size`] and
+    /// [`Server::http2_initial_connection_window_size`].
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn http2_adaptive_window(self, enabled: bool) -> Self {
+        Self {
+            http2_adaptive_window: enabled,
+            ..self
+        }
+    }
+
+    /// Sets the maximum frame size to use for HTTP2.
+    ///
+    /// Passing `None` will do nothing. If not set, hyper will use a default.
+    #[must_use]
+    pub fn http2_max_frame_size(self, size: impl Into<Option<u32>>) -> Self {
+        Self {
+            http2_max_frame_size: size.into(),
+            ..self
+        }
+    }
+
+    /// Sets an interval for HTTP2 Ping frames should be sent to keep a
+    /// connection alive.
+    ///
+    /// Passing `None` will disable HTTP2 keep-alive.
+    ///
+    /// Default is `None`.
+    #[must_use]
+    pub fn http2_keep_alive_interval(self, interval: impl Into<Option<Duration>>) -> Self {
+        Self {
+            http2_keep_alive_interval: interval.into(),
+            ..self
+        }
+    }
+
+    /// Sets a timeout for receiving an acknowledgement of the keep-alive ping.
+    ///
+    /// If the ping is not acknowledged within the timeout, the connection will
+    /// be closed. Does nothing if [`Server::http2_keep_alive_interval`] is
+    /// disabled.
+    ///
+    /// If not set, hyper will use a default of 20 seconds.
+    #[must_use]
+    pub fn http2_keep_alive_timeout(self, timeout: Duration) -> Self {
+        Self {
+            http2_keep_alive_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Calls `callback` each time `signal` yields an item, e.g. on `SIGHUP`,
+    /// while the server is running.
+    ///
+    /// The callback runs in a separate task, so the server keeps accepting
+    /// and serving the connections during the reload, and the next reload
+    /// waits for the previous one. It can be used to swap the application
+    /// state shared with the endpoints, for example in an `ArcSwap` or a
+    /// `RwLock`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use poem::{EndpointExt, Route, Server, get, handler, listener::TcpListener, web::Data};
+    /// use tokio::sync::RwLock;
+    ///
+    /// #[derive(Default)]
+    /// struct Config {
+    ///     greeting: String,
+    /// }
+    ///
+    /// #[handler]
+    /// async fn index(config: Data<&Arc<RwLock<Config>>>) -> String {
+    ///     config.read().await.greeting.clone()
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let sighup = futures_util::stream::pending::<()>();
+    /// let config = Arc::new(RwLock::new(Config::default()));
+    /// let app = Route::new().at("/", get(index)).data(config.clone());
+    ///
+    /// Server::new(TcpListener::bind("0.0.0.0:3000"))
+    ///     .on_reload(sighup, move || {
+    ///         let config = config.clone();
+    ///         async move {
+    ///             if let Ok(greeting) = tokio::fs::read_to_string("greeting.txt").await {
+    ///                 config.write().await.greeting = greeting;
+    ///             }
+    ///         }
+    ///     })
+    ///     .run(app)
+    ///     .await
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn on_reload<S, F, Fut>(mut self, signal: S, mut callback: F) -> Self
+    where
+        S: Stream + Send + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.reload_hooks.push(ReloadHook {
+            signal: signal.map(|_| ()).boxed(),
+            callback: Box::new(move || callback().boxed()),
+        });
+        self
+    }
+
     /// Run this server.
     pub async fn run<E>(self, ep: E) -> IoResult<()>
     where
@@ -152,6 +451,51 @@ where
         signal: impl Future<Output = ()>,
         timeout: Option<Duration>,
     ) -> IoResult<()>
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.run_with_graceful_shutdown_status(ep, signal, timeout)
+            .await
+            .map(|_| ())
+    }
+
+    /// Run this server and a signal to initiate graceful shutdown, and
+    /// returns whether the shutdown was graceful.
+    ///
+    /// After the signal, the server stops accepting connections and waits for
+    /// the alive connections to close. If they are still open when `timeout`
+    /// elapses, they are closed forcibly and [`ShutdownStatus::Forced`] is
+    /// returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use poem::{Route, Server, ShutdownStatus, listener::TcpListener};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// # let shutdown_signal = futures_util::future::pending();
+    /// let status = Server::new(TcpListener::bind("0.0.0.0:3000"))
+    ///     .run_with_graceful_shutdown_status(
+    ///         Route::new(),
+    ///         shutdown_signal,
+    ///         Some(Duration::from_secs(25)),
+    ///     )
+    ///     .await?;
+    /// if status == ShutdownStatus::Forced {
+    ///     eprintln!("some requests were interrupted");
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// # });
+    /// ```
+    pub async fn run_with_graceful_shutdown_status<E>(
+        self,
+        ep: E,
+        signal: impl Future<Output = ()>,
+        timeout: Option<Duration>,
+    ) -> IoResult<ShutdownStatus>
     where
         E: IntoEndpoint,
         E::Endpoint: 'static,
@@ -164,18 +508,40 @@ where
             http2_max_concurrent_streams,
             http2_max_pending_accept_reset_streams,
             http2_max_header_list_size,
+            http2_initial_stream_window_size,
+            http2_initial_connection_window_size,
+            http2_adaptive_window,
+            http2_max_frame_size,
+            http2_keep_alive_interval,
+            http2_keep_alive_timeout,
+            reload_hooks,
         } = self;
         let name = name.as_deref();
         let alive_connections = Arc::new(AtomicUsize::new(0));
         let notify = Arc::new(Notify::new());
         let timeout_token = CancellationToken::new();
+        let forced = Arc::new(AtomicBool::new(false));
         let server_graceful_shutdown_token = CancellationToken::new();
+        let shutdown_signal = ShutdownSignal(server_graceful_shutdown_token.clone());
 
         let mut acceptor = match listener {
             Either::Listener(listener) => listener.into_acceptor().await?.boxed(),
             Either::Acceptor(acceptor) => acceptor.boxed(),
         };
 
+        let reload_tasks = reload_hooks
+            .into_iter()
+            .map(|mut hook| {
+                let name = name.map(ToString::to_string);
+                tokio::spawn(async move {
+                    while hook.signal.next().await.is_some() {
+                        tracing::info!(name = name, "reloading");
+                        (hook.callback)().await;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
         tokio::pin!(signal);
 
         for addr in acceptor.local_addr() {
@@ -207,13 +573,16 @@ where
                 res = acceptor.accept() => {
                     if let Ok((socket, local_addr, remote_addr, scheme)) = res {
                         alive_connections.fetch_add(1, Ordering::Release);
+                        let connection_extensions = BoxAcceptor::connection_extensions(&socket);
 
                         let ep = ep.clone();
                         let alive_connections = alive_connections.clone();
                         let notify = notify.clone();
                         let timeout_token = timeout_token.clone();
+                        let forced = forced.clone();
                         let server_graceful_shutdown_token = server_graceful_shutdown_token.clone();
                         let server_graceful_shutdown_token_clone = server_graceful_shutdown_token.clone();
+                        let shutdown_signal = shutdown_signal.clone();
 
                         let spawn_fut = AssertUnwindSafe(async move {
                             let serve_connection = serve_connection(ConnectionOptions{
@@ -221,18 +590,28 @@ where
                                 local_addr,
                                 remote_addr,
                                 scheme,
+                                connection_extensions,
                                 ep,
                                 server_graceful_shutdown_token: server_graceful_shutdown_token.clone(),
+                                shutdown_signal,
                                 idle_connection_close_timeout: idle_timeout,
                                 http2_max_concurrent_streams,
                                 http2_max_pending_accept_reset_streams,
                                 http2_max_header_list_size,
+                                http2_initial_stream_window_size,
+                                http2_initial_connection_window_size,
+                                http2_adaptive_window,
+                                http2_max_frame_size,
+                                http2_keep_alive_interval,
+                                http2_keep_alive_timeout,
                             });
 
                             if timeout.is_some() {
                                 tokio::select! {
                                     _ = serve_connection => {}
-                                    _ = timeout_token.cancelled() => {}
+                                    _ = timeout_token.cancelled() => {
+                                        forced.store(true, Ordering::Release);
+                                    }
                                 }
                             } else {
                                serve_connection.await;
@@ -260,13 +639,20 @@ where
         }
 
         drop(acceptor);
+        for reload_task in reload_tasks {
+            reload_task.abort();
+        }
         if alive_connections.load(Ordering::Acquire) > 0 {
             tracing::info!(name = name, "wait for all connections to close.");
             notify.notified().await;
         }
 
         tracing::info!(name = name, "server stopped");
-        Ok(())
+        if forced.load(Ordering::Acquire) {
+            Ok(ShutdownStatus::Forced)
+        } else {
+            Ok(ShutdownStatus::Graceful)
+        }
     }
 }
 
@@ -386,12 +772,20 @@ struct ConnectionOptions<Io> {
     local_addr: LocalAddr,
     remote_addr: RemoteAddr,
     scheme: Scheme,
+    connection_extensions: ConnectionExtensions,
     ep: Arc<dyn DynEndpoint<Output = Response>>,
     server_graceful_shutdown_token: CancellationToken,
+    shutdown_signal: ShutdownSignal,
     idle_connection_close_timeout: Option<Duration>,
     http2_max_concurrent_streams: Option<u32>,
     http2_max_pending_accept_reset_streams: Option<u32>,
     http2_max_header_list_size: u32,
+    http2_initial_stream_window_size: Option<u32>,
+    http2_initial_connection_window_size: Option<u32>,
+    http2_adaptive_window: bool,
+    http2_max_frame_size: Option<u32>,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_keep_alive_timeout: Option<Duration>,
 }
 
 async fn serve_connection<Io>(opts: ConnectionOptions<Io>)
@@ -403,15 +797,25 @@ where
         local_addr,
         remote_addr,
         scheme,
+        connection_extensions,
         ep,
         server_graceful_shutdown_token,
+        shutdown_signal,
         idle_connection_close_timeout,
         http2_max_concurrent_streams,
         http2_max_pending_accept_reset_streams,
         http2_max_header_list_size,
+        http2_initial_stream_window_size,
+        http2_initial_connection_window_size,
+        http2_adaptive_window,
+        http2_max_frame_size,
+        http2_keep_alive_interval,
+        http2_keep_alive_timeout,
     } = opts;
 
     let connection_shutdown_token = CancellationToken::new();
+    let disconnect = Disconnect::new();
+    let _disconnect_guard = disconnect.0.clone().drop_guard();
 
     let service = hyper::service::service_fn({
         let remote_addr = remote_addr.clone();
@@ -421,12 +825,17 @@ where
             let local_addr = local_addr.clone();
             let remote_addr = remote_addr.clone();
             let scheme = scheme.clone();
+            let connection_extensions = connection_extensions.clone();
+            let shutdown_signal = shutdown_signal.clone();
+            let disconnect = disconnect.clone();
             async move {
-                Ok::<http::Response<_>, Infallible>(
-                    ep.get_response((req, local_addr, remote_addr, scheme).into())
-                        .await
-                        .into(),
-                )
+                let mut req: crate::Request = (req, local_addr, remote_addr, scheme).into();
+                if let Some(extensions) = connection_extensions.get() {
+                    req.extensions_mut().extend(extensions.clone());
+                }
+                req.extensions_mut().insert(shutdown_signal);
+                req.extensions_mut().insert(disconnect);
+                Ok::<http::Response<_>, Infallible>(ep.get_response(req).await.into())
             }
         }
     });
@@ -454,7 +863,19 @@ where
         .max_pending_accept_reset_streams(
             http2_max_pending_accept_reset_streams.map(|x| x as usize),
         )
-        .max_header_list_size(http2_max_header_list_size);
+        .max_header_list_size(http2_max_header_list_size)
+        .initial_stream_window_size(http2_initial_stream_window_size)
+        .initial_connection_window_size(http2_initial_connection_window_size)
+        .adaptive_window(http2_adaptive_window)
+        .max_frame_size(http2_max_frame_size);
+    if let Some(interval) = http2_keep_alive_interval {
+        builder
+            .timer(hyper_util::rt::TokioTimer::new())
+            .keep_alive_interval(interval);
+        if let Some(timeout) = http2_keep_alive_timeout {
+            builder.keep_alive_timeout(timeout);
+        }
+    }
 
     let conn =
         builder.serve_connection_with_upgrades(hyper_util::rt::TokioIo::new(socket), service);
@@ -462,7 +883,9 @@ where
 
     tokio::select! {
         _ = &mut conn => {
-            // Connection completed successfully.
+            // The connection is closed, and the futures of the requests in flight are
+            // dropped with it.
+            return;
         },
         _ = connection_shutdown_token.cancelled() => {
             tracing::info!(remote_addr=%remote_addr, "closing connection due to inactivity");
@@ -476,3 +899,241 @@ where
     // requests.
     let _ = conn.await;
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+    use crate::{handler, listener::TcpListener};
+
+    async fn run(timeout: Duration, connect: bool) -> ShutdownStatus {
+        #[handler(internal)]
+        async fn index() {
+            futures_util::future::pending::<()>().await;
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(
+            Server::new_with_acceptor(acceptor).run_with_graceful_shutdown_status(
+                index,
+                async move {
+                    let _ = rx.await;
+                },
+                Some(timeout),
+            ),
+        );
+
+        let _stream = if connect {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Some(stream)
+        } else {
+            None
+        };
+
+        tx.send(()).unwrap();
+        server.await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown() {
+        assert_eq!(
+            run(Duration::from_secs(5), false).await,
+            ShutdownStatus::Graceful
+        );
+    }
+
+    #[tokio::test]
+    async fn forced_shutdown() {
+        assert_eq!(
+            run(Duration::from_millis(100), true).await,
+            ShutdownStatus::Forced
+        );
+    }
+
+    #[tokio::test]
+    async fn shutdown_signal() {
+        #[handler(internal)]
+        async fn index(signal: crate::web::Data<&ShutdownSignal>) -> &'static str {
+            assert!(!signal.is_shutdown());
+            signal.wait().await;
+            "bye"
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(
+            Server::new_with_acceptor(acceptor).run_with_graceful_shutdown_status(
+                index,
+                async move {
+                    let _ = rx.await;
+                },
+                Some(Duration::from_secs(5)),
+            ),
+        );
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        tx.send(()).unwrap();
+        let mut resp = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut resp)
+            .await
+            .unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.ends_with("bye"));
+        assert_eq!(server.await.unwrap().unwrap(), ShutdownStatus::Graceful);
+    }
+
+    #[tokio::test]
+    async fn on_reload() {
+        use std::sync::RwLock;
+
+        #[handler(internal)]
+        fn index(config: crate::web::Data<&Arc<RwLock<String>>>) -> String {
+            config.read().unwrap().clone()
+        }
+
+        async fn get(addr: std::net::SocketAddr) -> String {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut resp = String::new();
+            tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut resp)
+                .await
+                .unwrap();
+            resp
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let config = Arc::new(RwLock::new("v1".to_string()));
+        let (signal_tx, signal_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let (reloaded_tx, mut reloaded_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let signal = futures_util::stream::unfold(signal_rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        });
+        let server = Server::new_with_acceptor(acceptor).on_reload(signal, {
+            let config = config.clone();
+            let mut version = 1;
+            move || {
+                version += 1;
+                *config.write().unwrap() = format!("v{version}");
+                let reloaded_tx = reloaded_tx.clone();
+                async move {
+                    let _ = reloaded_tx.send(());
+                }
+            }
+        });
+        let (tx, rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(server.run_with_graceful_shutdown(
+            index.data(config),
+            async move {
+                let _ = rx.await;
+            },
+            None,
+        ));
+
+        assert!(get(addr).await.ends_with("v1"));
+        signal_tx.send(()).unwrap();
+        reloaded_rx.recv().await.unwrap();
+        assert!(get(addr).await.ends_with("v2"));
+        signal_tx.send(()).unwrap();
+        reloaded_rx.recv().await.unwrap();
+        assert!(get(addr).await.ends_with("v3"));
+
+        tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn disconnect() {
+        struct Guard(Arc<AtomicBool>);
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        #[handler(internal)]
+        async fn index(
+            disconnect: crate::web::Data<&Disconnect>,
+            dropped: crate::web::Data<&Arc<AtomicBool>>,
+            tx: crate::web::Data<&tokio::sync::mpsc::UnboundedSender<Disconnect>>,
+        ) {
+            let _guard = Guard(dropped.clone());
+            assert!(!disconnect.is_disconnected());
+            tx.send(disconnect.clone()).unwrap();
+            futures_util::future::pending::<()>().await;
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Disconnect>();
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(index.data(dropped.clone()).data(tx)));
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let disconnect = rx.recv().await.unwrap();
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        drop(stream);
+        tokio::time::timeout(Duration::from_secs(5), disconnect.wait())
+            .await
+            .unwrap();
+        // the future of the handler is dropped
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
@@ -76,10 +76,16 @@ impl Session {
     }
 
     /// Sets a key-value pair into the session.
+    ///
+    /// The session is only marked as changed if the value differs from the
+    /// current one, so the storage is not written needlessly.
     pub fn set(&self, name: &str, value: impl Serialize) {
         let mut inner = self.inner.write();
         if inner.status != SessionStatus::Purged {
             if let Ok(value) = serde_json::to_value(&value) {
+                if inner.entries.get(name) == Some(&value) {
+                    return;
+                }
                 inner.entries.insert(name.to_string(), value);
                 if inner.status != SessionStatus::Renewed {
                     inner.status = SessionStatus::Changed;
@@ -91,11 +97,11 @@ impl Session {
     /// Remove value from the session.
     pub fn remove(&self, name: &str) {
         let mut inner = self.inner.write();
-        if inner.status != SessionStatus::Purged {
-            inner.entries.remove(name);
-            if inner.status != SessionStatus::Renewed {
-                inner.status = SessionStatus::Changed;
-            }
+        if inner.status != SessionStatus::Purged
+            && inner.entries.remove(name).is_some()
+            && inner.status != SessionStatus::Renewed
+        {
+            inner.status = SessionStatus::Changed;
         }
     }
 
@@ -202,6 +208,20 @@ mod tests {
         assert_eq!(session.entries().into_iter().collect::<Vec<_>>(), vec![]);
     }
 
+    #[test]
+    fn unchanged_session() {
+        let session = Session::new([("a".to_string(), 1.into())].into_iter().collect());
+
+        session.set("a", 1);
+        assert_eq!(session.status(), SessionStatus::Unchanged);
+
+        session.remove("b");
+        assert_eq!(session.status(), SessionStatus::Unchanged);
+
+        session.set("a", 2);
+        assert_eq!(session.status(), SessionStatus::Changed);
+    }
+
     #[test]
     fn purge_session() {
         let session = Session::default();
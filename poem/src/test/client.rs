@@ -17,6 +17,8 @@ macro_rules! impl_methods {
 pub struct TestClient<E> {
     pub(crate) ep: E,
     pub(crate) default_headers: HeaderMap,
+    #[cfg(feature = "cookie")]
+    pub(crate) cookie_store: Option<crate::test::TestCookieStore>,
 }
 
 impl<E: Endpoint> TestClient<E> {
@@ -28,6 +30,8 @@ impl<E: Endpoint> TestClient<E> {
         TestClient {
             ep: ep.into_endpoint(),
             default_headers: Default::default(),
+            #[cfg(feature = "cookie")]
+            cookie_store: None,
         }
     }
 
@@ -123,11 +127,84 @@ impl<E: Endpoint> TestClient<E> {
         self.default_header(header::CONTENT_TYPE, content_type.as_ref())
     }
 
+    /// Enables the cookie store of this client.
+    ///
+    /// The cookies set by the responses are stored in a
+    /// [`TestCookieStore`](crate::test::TestCookieStore), and sent with the
+    /// following requests that match their `Domain`, `Path` and expiry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poem::{
+    ///     EndpointExt, Route, handler,
+    ///     http::StatusCode,
+    ///     middleware::CookieJarManager,
+    ///     post,
+    ///     test::TestClient,
+    ///     web::cookie::{Cookie, CookieJar},
+    /// };
+    ///
+    /// #[handler]
+    /// fn login(cookie_jar: &CookieJar) {
+    ///     cookie_jar.add(Cookie::new_with_str("session", "abc"));
+    /// }
+    ///
+    /// #[handler]
+    /// fn me(cookie_jar: &CookieJar) -> Result<String, StatusCode> {
+    ///     cookie_jar
+    ///         .get("session")
+    ///         .map(|cookie| cookie.value_str().to_string())
+    ///         .ok_or(StatusCode::UNAUTHORIZED)
+    /// }
+    ///
+    /// let app = Route::new()
+    ///     .at("/login", post(login))
+    ///     .at("/me", me)
+    ///     .with(CookieJarManager::new());
+    /// let cli = TestClient::new(app).with_cookie_store();
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.post("/login").send().await.assert_status_is_ok();
+    /// cli.get("/me").send().await.assert_text("abc").await;
+    ///
+    /// cli.cookie_store().unwrap().clear();
+    /// cli.get("/me")
+    ///     .send()
+    ///     .await
+    ///     .assert_status(StatusCode::UNAUTHORIZED);
+    /// # });
+    /// ```
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    #[must_use]
+    pub fn with_cookie_store(self) -> Self {
+        Self {
+            cookie_store: Some(Default::default()),
+            ..self
+        }
+    }
+
+    /// Returns the cookie store of this client, if it is enabled with
+    /// [`TestClient::with_cookie_store`].
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    pub fn cookie_store(&self) -> Option<&crate::test::TestCookieStore> {
+        self.cookie_store.as_ref()
+    }
+
     /// Create a [`TestRequestBuilder`].
     pub fn request(&self, method: Method, uri: impl Into<String>) -> TestRequestBuilder<'_, E> {
         TestRequestBuilder::new(self, method, uri.into())
     }
 
+    /// Create a [`TestWebSocketBuilder`] to connect to a websocket endpoint.
+    #[cfg(all(feature = "websocket", feature = "server"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
+    pub fn ws(&self, uri: impl Into<String>) -> crate::test::TestWebSocketBuilder<'_, E> {
+        crate::test::TestWebSocketBuilder::new(self, uri.into())
+    }
+
     impl_methods!(
         /// Create a [`TestRequestBuilder`] with `GET` method.
         (get, GET),
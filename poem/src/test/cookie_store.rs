@@ -0,0 +1,410 @@
+use std::{cmp::Reverse, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use http::{HeaderMap, HeaderValue, Uri, header};
+use parking_lot::Mutex;
+
+use crate::web::cookie::Cookie;
+
+struct StoredCookie {
+    cookie: Cookie,
+    domain: String,
+    host_only: bool,
+    path: String,
+    expires: Option<DateTime<Utc>>,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.expires, Some(expires) if expires <= now)
+    }
+
+    fn matches(&self, host: &str, path: &str) -> bool {
+        let domain_match = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(host, &self.domain)
+        };
+        domain_match && path_match(path, &self.path)
+    }
+}
+
+/// Returns `true` if `host` is `domain` or a subdomain of it (RFC 6265,
+/// section 5.1.3).
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Returns `true` if the request path is within the cookie path (RFC 6265,
+/// section 5.1.4).
+fn path_match(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || path
+            .strip_prefix(cookie_path)
+            .is_some_and(|rest| cookie_path.ends_with('/') || rest.starts_with('/'))
+}
+
+/// Returns the directory of the request path, used when the cookie has no
+/// `Path` attribute (RFC 6265, section 5.1.4).
+fn default_path(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(idx) => &path[..idx],
+    }
+}
+
+/// Returns the host of the request, `localhost` if it has none.
+fn request_host(uri: &Uri, headers: &HeaderMap) -> String {
+    headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|host| host.parse::<http::uri::Authority>().ok())
+        .map(|authority| authority.host().to_string())
+        .or_else(|| uri.host().map(ToString::to_string))
+        .unwrap_or_else(|| "localhost".to_string())
+        .to_ascii_lowercase()
+}
+
+/// The cookies stored by a [`TestClient`](crate::test::TestClient), created by
+/// [`TestClient::with_cookie_store`](crate::test::TestClient::with_cookie_store).
+///
+/// The cookies set by the responses are sent with the following requests
+/// whose host and path match the `Domain` and `Path` of the cookie, until they
+/// expire. Requests without a `Host` header are sent to `localhost`. The
+/// `Secure` attribute is ignored, since the test client does not use TLS.
+///
+/// Cloning the store returns a handle to the same cookies.
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+#[derive(Clone, Default)]
+pub struct TestCookieStore {
+    cookies: Arc<Mutex<Vec<StoredCookie>>>,
+}
+
+impl TestCookieStore {
+    /// Returns the unexpired cookie with the given name.
+    ///
+    /// If several cookies have the same name, for example with different
+    /// paths, the one stored first is returned.
+    pub fn get(&self, name: &str) -> Option<Cookie> {
+        let now = Utc::now();
+        self.cookies
+            .lock()
+            .iter()
+            .find(|stored| stored.cookie.name() == name && !stored.is_expired(now))
+            .map(|stored| stored.cookie.clone())
+    }
+
+    /// Returns all unexpired cookies.
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let now = Utc::now();
+        self.cookies
+            .lock()
+            .iter()
+            .filter(|stored| !stored.is_expired(now))
+            .map(|stored| stored.cookie.clone())
+            .collect()
+    }
+
+    /// Adds a cookie as if it was set by a response from `localhost` to `/`.
+    pub fn add(&self, cookie: Cookie) {
+        self.store(cookie, "localhost", "/");
+    }
+
+    /// Removes all cookies with the given name.
+    pub fn remove(&self, name: &str) {
+        self.cookies
+            .lock()
+            .retain(|stored| stored.cookie.name() != name);
+    }
+
+    /// Removes all cookies.
+    pub fn clear(&self) {
+        self.cookies.lock().clear();
+    }
+
+    fn store(&self, cookie: Cookie, host: &str, request_path: &str) {
+        let now = Utc::now();
+        let (domain, host_only) = match cookie.domain() {
+            Some(domain) => {
+                let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+                if !domain_match(host, &domain) {
+                    // a response cannot set cookies for another domain
+                    return;
+                }
+                (domain, false)
+            }
+            None => (host.to_string(), true),
+        };
+        let path = match cookie.path() {
+            Some(path) if path.starts_with('/') => path.to_string(),
+            _ => default_path(request_path).to_string(),
+        };
+        let expires = match cookie.max_age() {
+            Some(max_age) => Some(
+                chrono::Duration::from_std(max_age)
+                    .ok()
+                    .and_then(|max_age| now.checked_add_signed(max_age))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            ),
+            None => cookie.expires(),
+        };
+
+        let mut cookies = self.cookies.lock();
+        cookies.retain(|stored| {
+            let replaced = stored.cookie.name() == cookie.name()
+                && stored.domain == domain
+                && stored.path == path;
+            !replaced && !stored.is_expired(now)
+        });
+        let stored = StoredCookie {
+            cookie,
+            domain,
+            host_only,
+            path,
+            expires,
+        };
+        if !stored.is_expired(now) {
+            cookies.push(stored);
+        }
+    }
+
+    /// Adds the matching cookies to the `Cookie` header of a request.
+    pub(crate) fn apply_to_request(&self, uri: &Uri, headers: &mut HeaderMap) {
+        let host = request_host(uri, headers);
+        let now = Utc::now();
+        let cookies = self.cookies.lock();
+        let mut matched = cookies
+            .iter()
+            .filter(|stored| !stored.is_expired(now) && stored.matches(&host, uri.path()))
+            .collect::<Vec<_>>();
+        if matched.is_empty() {
+            return;
+        }
+
+        // cookies with longer paths are listed first (RFC 6265, section 5.4)
+        matched.sort_by_key(|stored| Reverse(stored.path.len()));
+        let mut value = headers
+            .get(header::COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        for stored in matched {
+            if !value.is_empty() {
+                value.push_str("; ");
+            }
+            let pair = Cookie::new_with_str(stored.cookie.name(), stored.cookie.value_str());
+            value.push_str(&pair.to_string());
+        }
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(header::COOKIE, value);
+        }
+    }
+
+    /// Stores the cookies set by the response to a request.
+    pub(crate) fn extract_from_response(
+        &self,
+        uri: &Uri,
+        req_headers: &HeaderMap,
+        resp_headers: &HeaderMap,
+    ) {
+        let host = request_host(uri, req_headers);
+        for value in resp_headers.get_all(header::SET_COOKIE) {
+            if let Some(cookie) = value
+                .to_str()
+                .ok()
+                .and_then(|value| Cookie::parse(value).ok())
+            {
+                self.store(cookie, &host, uri.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EndpointExt, IntoResponse, Route, get, handler,
+        http::StatusCode,
+        middleware::CookieJarManager,
+        test::TestClient,
+        web::cookie::{Cookie, CookieJar},
+    };
+
+    #[handler(internal)]
+    fn echo(headers: &HeaderMap) -> String {
+        headers
+            .get(header::COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn login_flow() {
+        #[handler(internal)]
+        fn login(cookie_jar: &CookieJar) {
+            cookie_jar.add(Cookie::new_with_str("session", "a b"));
+        }
+
+        #[handler(internal)]
+        fn me(cookie_jar: &CookieJar) -> impl IntoResponse {
+            match cookie_jar.get("session") {
+                Some(cookie) => cookie.value_str().to_string().into_response(),
+                None => StatusCode::UNAUTHORIZED.into_response(),
+            }
+        }
+
+        #[handler(internal)]
+        fn logout(cookie_jar: &CookieJar) {
+            cookie_jar.remove("session");
+        }
+
+        let app = Route::new()
+            .at("/login", login)
+            .at("/me", me)
+            .at("/logout", logout)
+            .with(CookieJarManager::new());
+
+        // without a cookie store, the cookie is not sent
+        let cli = TestClient::new(app);
+        cli.post("/login").send().await.assert_status_is_ok();
+        cli.get("/me")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        let cli = cli.with_cookie_store();
+        let store = cli.cookie_store().unwrap().clone();
+        cli.post("/login").send().await.assert_status_is_ok();
+        assert_eq!(store.get("session").unwrap().value_str(), "a b");
+        cli.get("/me").send().await.assert_text("a b").await;
+
+        cli.post("/logout").send().await.assert_status_is_ok();
+        assert!(store.get("session").is_none());
+        cli.get("/me")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        cli.post("/login").send().await.assert_status_is_ok();
+        store.clear();
+        assert!(store.cookies().is_empty());
+        cli.get("/me")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn path_and_domain() {
+        #[handler(internal)]
+        fn set() -> impl IntoResponse {
+            ().with_header(header::SET_COOKIE, "a=1; Path=/api")
+                .with_header(header::SET_COOKIE, "b=2")
+                .with_header(header::SET_COOKIE, "c=3; Domain=example.com; Path=/")
+                .with_header(header::SET_COOKIE, "d=4; Domain=other.com")
+        }
+
+        let app = Route::new().at("/account/set", set).at("/*path", get(echo));
+        let cli = TestClient::new(app).with_cookie_store();
+        cli.get("/account/set")
+            .header(header::HOST, "www.example.com:3000")
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        let check = |host: &'static str, path: &'static str, expected: &'static str| {
+            let cli = &cli;
+            async move {
+                cli.get(path)
+                    .header(header::HOST, host)
+                    .send()
+                    .await
+                    .assert_text(expected)
+                    .await;
+            }
+        };
+        check("www.example.com", "/api/users", "a=1; c=3").await;
+        check("www.example.com", "/apis", "c=3").await;
+        check("www.example.com", "/account/other", "b=2; c=3").await;
+        check("api.example.com", "/api", "c=3").await;
+        check("example.com", "/", "c=3").await;
+        check("other.com", "/", "").await;
+        check("localhost", "/api", "").await;
+
+        // the explicit cookie header is kept
+        cli.get("/api")
+            .header(header::HOST, "example.com")
+            .header(header::COOKIE, "x=0")
+            .send()
+            .await
+            .assert_text("x=0; c=3")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn expiry() {
+        #[handler(internal)]
+        fn set() -> impl IntoResponse {
+            ().with_header(header::SET_COOKIE, "a=1; Max-Age=3600")
+                .with_header(
+                    header::SET_COOKIE,
+                    "b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+                )
+                .with_header(
+                    header::SET_COOKIE,
+                    "c=3; Max-Age=3600; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+                )
+                .with_header(header::SET_COOKIE, "d=4")
+        }
+
+        #[handler(internal)]
+        fn delete() -> impl IntoResponse {
+            ().with_header(header::SET_COOKIE, "a=; Max-Age=0")
+        }
+
+        let app = Route::new()
+            .at("/set", set)
+            .at("/delete", delete)
+            .at("/", echo);
+        let cli = TestClient::new(app).with_cookie_store();
+        cli.get("/set").send().await.assert_status_is_ok();
+        cli.get("/").send().await.assert_text("a=1; c=3; d=4").await;
+
+        cli.get("/delete").send().await.assert_status_is_ok();
+        cli.get("/").send().await.assert_text("c=3; d=4").await;
+
+        let store = cli.cookie_store().unwrap();
+        store.remove("c");
+        store.add(Cookie::new_with_str("e", "5"));
+        let names = store
+            .cookies()
+            .iter()
+            .map(|cookie| cookie.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["d", "e"]);
+        cli.get("/").send().await.assert_text("d=4; e=5").await;
+    }
+
+    #[test]
+    fn matching() {
+        assert!(domain_match("example.com", "example.com"));
+        assert!(domain_match("a.example.com", "example.com"));
+        assert!(!domain_match("aexample.com", "example.com"));
+
+        assert!(path_match("/api", "/api"));
+        assert!(path_match("/api/users", "/api"));
+        assert!(path_match("/api/users", "/api/"));
+        assert!(!path_match("/apis", "/api"));
+        assert!(!path_match("/", "/api"));
+
+        assert_eq!(default_path("/"), "/");
+        assert_eq!(default_path("/login"), "/");
+        assert_eq!(default_path("/account/login"), "/account");
+        assert_eq!(default_path(""), "/");
+    }
+}
@@ -122,6 +122,25 @@ impl TestForm {
         self
     }
 
+    /// Adds a file field with the given file name and content type, like a
+    /// file selected in an `<input type="file">` element.
+    #[must_use]
+    pub fn file(
+        mut self,
+        name: impl Into<String>,
+        filename: impl Into<String>,
+        value: impl Into<Vec<u8>>,
+        content_type: impl AsRef<str>,
+    ) -> Self {
+        self.fields.push(
+            TestFormField::bytes(value)
+                .name(name)
+                .filename(filename)
+                .content_type(content_type),
+        );
+        self
+    }
+
     #[inline]
     pub(crate) fn boundary(&self) -> &str {
         BOUNDARY_STRING
@@ -290,4 +309,37 @@ mod tests {
             .await;
         resp.assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn file() {
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) {
+            let field = multipart.next_field().await.unwrap().unwrap();
+            assert_eq!(field.name(), Some("name"));
+            assert!(field.file_name().is_none());
+            assert!(field.content_type().is_none());
+            assert_eq!(field.text().await.unwrap(), "foo");
+
+            let field = multipart.next_field().await.unwrap().unwrap();
+            assert_eq!(field.name(), Some("avatar"));
+            assert_eq!(field.file_name(), Some("avatar.png"));
+            assert_eq!(field.content_type(), Some("image/png"));
+            assert_eq!(field.bytes().await.unwrap(), vec![0x89, b'P', b'N', b'G']);
+
+            assert!(multipart.next_field().await.unwrap().is_none());
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli
+            .post("/")
+            .multipart(TestForm::new().text("name", "foo").file(
+                "avatar",
+                "avatar.png",
+                vec![0x89, b'P', b'N', b'G'],
+                "image/png",
+            ))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+    }
 }
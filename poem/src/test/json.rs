@@ -286,3 +286,94 @@ impl<'a> TestJsonObject<'a> {
         assert!(self.is_empty());
     }
 }
+
+#[derive(Debug, Eq, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses a path like `$.data.items[0]["first name"]`, the leading `$` and
+/// `.` are optional.
+fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let path = if path.is_empty() || path.starts_with(['.', '[']) {
+        path.to_string()
+    } else {
+        format!(".{path}")
+    };
+    let mut s = path.as_str();
+
+    while !s.is_empty() {
+        if let Some(rest) = s.strip_prefix('.') {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            segments.push(PathSegment::Key(rest[..end].to_string()));
+            s = &rest[end..];
+        } else if let Some(rest) = s.strip_prefix('[') {
+            let end = rest.find(']')?;
+            let inner = &rest[..end];
+            let segment = match inner
+                .strip_prefix('"')
+                .and_then(|inner| inner.strip_suffix('"'))
+                .or_else(|| {
+                    inner
+                        .strip_prefix('\'')
+                        .and_then(|inner| inner.strip_suffix('\''))
+                }) {
+                Some(key) => PathSegment::Key(key.to_string()),
+                None => PathSegment::Index(inner.parse().ok()?),
+            };
+            segments.push(segment);
+            s = &rest[end + 1..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(segments)
+}
+
+/// Returns the value at `path` in `value`, or `None` if it does not exist.
+///
+/// # Panics
+///
+/// Panic if the path is invalid.
+pub(crate) fn value_at_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let segments = parse_path(path).unwrap_or_else(|| panic!("invalid json path `{path}`"));
+    segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            PathSegment::Key(key) => value.get(key),
+            PathSegment::Index(idx) => value.get(idx),
+        })
+}
+
+/// Returns `true` if `value` contains `expected`.
+///
+/// Objects contain the expected object if they contain all of its keys with
+/// values that contain the expected values, arrays must have the same length
+/// and contain the expected elements in order, and other values must be
+/// equal.
+pub(crate) fn contains(value: &Value, expected: &Value) -> bool {
+    match (value, expected) {
+        (Value::Object(value), Value::Object(expected)) => {
+            expected.iter().all(|(key, expected)| {
+                value
+                    .get(key)
+                    .is_some_and(|value| contains(value, expected))
+            })
+        }
+        (Value::Array(value), Value::Array(expected)) => {
+            value.len() == expected.len()
+                && value
+                    .iter()
+                    .zip(expected)
+                    .all(|(value, expected)| contains(value, expected))
+        }
+        _ => value == expected,
+    }
+}
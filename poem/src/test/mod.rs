@@ -59,6 +59,35 @@
 //! # });
 //! ```
 //!
+//! # Test a websocket endpoint
+//!
+//! ```
+//! use futures_util::{SinkExt, StreamExt};
+//! use poem::{
+//!     IntoResponse, Route, get, handler,
+//!     test::TestClient,
+//!     web::websocket::{Message, WebSocket},
+//! };
+//!
+//! #[handler]
+//! async fn echo(ws: WebSocket) -> impl IntoResponse {
+//!     ws.on_upgrade(|mut socket| async move {
+//!         while let Some(Ok(Message::Text(text))) = socket.next().await {
+//!             let _ = socket.send(Message::Text(text)).await;
+//!         }
+//!     })
+//! }
+//!
+//! let app = Route::new().at("/ws", get(echo));
+//! let cli = TestClient::new(app);
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let mut ws = cli.ws("/ws").connect().await;
+//! ws.send(Message::text("hello")).await;
+//! assert_eq!(ws.recv().await, Some(Message::text("hello")));
+//! # });
+//! ```
+//!
 //! # Post multipart data
 //!
 //! ```ignore
@@ -110,13 +139,21 @@
 //! ```
 
 mod client;
+#[cfg(feature = "cookie")]
+mod cookie_store;
 mod form;
 mod json;
 mod request_builder;
 mod response;
+#[cfg(all(feature = "websocket", feature = "server"))]
+mod websocket;
 
 pub use client::TestClient;
+#[cfg(feature = "cookie")]
+pub use cookie_store::TestCookieStore;
 pub use form::{TestForm, TestFormField};
 pub use json::{TestJson, TestJsonArray, TestJsonObject, TestJsonValue};
 pub use request_builder::TestRequestBuilder;
 pub use response::TestResponse;
+#[cfg(all(feature = "websocket", feature = "server"))]
+pub use websocket::{TestWebSocket, TestWebSocketBuilder};
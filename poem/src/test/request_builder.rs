@@ -1,10 +1,13 @@
 use headers::{Header, HeaderMapExt};
-use http::{Extensions, HeaderMap, HeaderValue, Method, header, header::HeaderName};
+use http::{
+    Extensions, HeaderMap, HeaderValue, Method, StatusCode, Uri, header, header::HeaderName,
+    uri::Scheme,
+};
 use serde::Serialize;
 use serde_json::Value;
 
 use crate::{
-    Body, Endpoint, Request,
+    Body, Endpoint, Request, Response,
     test::{TestClient, TestForm, TestResponse},
 };
 
@@ -17,6 +20,8 @@ pub struct TestRequestBuilder<'a, E> {
     headers: HeaderMap,
     body: Body,
     extensions: Extensions,
+    follow_redirects: bool,
+    max_redirects: usize,
 }
 
 impl<'a, E> TestRequestBuilder<'a, E> {
@@ -29,6 +34,8 @@ impl<'a, E> TestRequestBuilder<'a, E> {
             headers: Default::default(),
             body: Body::empty(),
             extensions: Default::default(),
+            follow_redirects: false,
+            max_redirects: 10,
         }
     }
 
@@ -137,6 +144,25 @@ impl<'a, E> TestRequestBuilder<'a, E> {
             .body(quick_xml::se::to_string(&body).expect("valid xml"))
     }
 
+    /// Sets the CBOR body for this request with `application/cbor` content
+    /// type.
+    #[cfg(feature = "cbor")]
+    #[must_use]
+    pub fn body_cbor(self, body: &impl Serialize) -> Self {
+        let mut data = Vec::new();
+        ciborium::into_writer(body, &mut data).expect("valid cbor");
+        self.content_type("application/cbor").body(data)
+    }
+
+    /// Sets the MessagePack body for this request with `application/msgpack`
+    /// content type.
+    #[cfg(feature = "msgpack")]
+    #[must_use]
+    pub fn body_msgpack(self, body: &impl Serialize) -> Self {
+        self.content_type("application/msgpack")
+            .body(rmp_serde::to_vec_named(body).expect("valid msgpack"))
+    }
+
     /// Sets the form data for this request with
     /// `application/x-www-form-urlencoded` content type.
     #[must_use]
@@ -206,14 +232,287 @@ impl<'a, E> TestRequestBuilder<'a, E> {
         self
     }
 
+    /// Follows the redirect responses, and returns the response of the last
+    /// request.
+    ///
+    /// When the response is `301`, `302`, `303`, `307` or `308` and has a
+    /// `Location` header, the request is sent again to the location. A
+    /// `303 See Other` changes the method to `GET` (except `HEAD`), `301`
+    /// and `302` change a `POST` to `GET`, and the body and its
+    /// `Content-Type` are removed when the method is changed. Otherwise the
+    /// method, headers and body are kept.
+    ///
+    /// All requests are sent to the endpoint of the client. For an absolute
+    /// location, the `Host` header and the scheme of the request are set
+    /// from it, which makes it possible to test redirections to `https`.
+    ///
+    /// At most [`max_redirects`](Self::max_redirects) redirections are
+    /// followed (10 by default), and the last redirect response is returned
+    /// when the limit is reached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{Route, get, handler, http::StatusCode, post, test::TestClient, web::Redirect};
+    ///
+    /// #[handler]
+    /// fn create() -> Redirect {
+    ///     Redirect::see_other("/items/1")
+    /// }
+    ///
+    /// #[handler]
+    /// fn item() -> &'static str {
+    ///     "item 1"
+    /// }
+    ///
+    /// let app = Route::new()
+    ///     .at("/items", post(create))
+    ///     .at("/items/1", get(item));
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli.post("/items").send().await;
+    /// resp.assert_status(StatusCode::SEE_OTHER);
+    ///
+    /// let resp = cli.post("/items").follow_redirects(true).send().await;
+    /// resp.assert_status_is_ok();
+    /// resp.assert_text("item 1").await;
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn follow_redirects(self, enable: bool) -> Self {
+        Self {
+            follow_redirects: enable,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of redirections followed when
+    /// [`follow_redirects`](Self::follow_redirects) is enabled.
+    #[must_use]
+    pub fn max_redirects(self, max: usize) -> Self {
+        Self {
+            max_redirects: max,
+            ..self
+        }
+    }
+
     /// Send this request to endpoint to get the response.
     pub async fn send(self) -> TestResponse
     where
         E: Endpoint,
     {
-        let ep = &self.cli.ep;
-        let req = self.make_request();
-        let resp = ep.get_response(req).await;
-        TestResponse::new(resp)
+        let cli = self.cli;
+        let follow_redirects = self.follow_redirects;
+        let max_redirects = self.max_redirects;
+        let mut req = self.make_request();
+        if !follow_redirects {
+            return TestResponse::new(send_request(cli, req).await);
+        }
+
+        let mut method = req.method().clone();
+        let mut uri = req.uri().clone();
+        let mut scheme = Scheme::HTTP;
+        let mut headers = req.headers().clone();
+        let extensions = req.extensions().clone();
+        let mut body = Some(
+            req.take_body()
+                .into_bytes()
+                .await
+                .expect("read request body"),
+        );
+        let mut redirects = 0;
+
+        loop {
+            let (mut parts, _) = Request::builder()
+                .method(method.clone())
+                .uri(uri.clone())
+                .finish()
+                .into_parts();
+            parts.headers = headers.clone();
+            parts.extensions = extensions.clone();
+            parts.state.scheme = scheme.clone();
+            let req = Request::from_parts(parts, body.clone().map(Body::from).unwrap_or_default());
+            let resp = send_request(cli, req).await;
+
+            let status = resp.status();
+            let location = resp
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|location| resolve_location(&uri, location));
+            let Some(location) = location.filter(|_| {
+                redirects < max_redirects
+                    && matches!(
+                        status,
+                        StatusCode::MOVED_PERMANENTLY
+                            | StatusCode::FOUND
+                            | StatusCode::SEE_OTHER
+                            | StatusCode::TEMPORARY_REDIRECT
+                            | StatusCode::PERMANENT_REDIRECT
+                    )
+            }) else {
+                return TestResponse::new(resp);
+            };
+            redirects += 1;
+
+            if (status == StatusCode::SEE_OTHER && method != Method::HEAD)
+                || (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                    && method == Method::POST)
+            {
+                method = Method::GET;
+                body = None;
+                headers.remove(header::CONTENT_TYPE);
+                headers.remove(header::CONTENT_LENGTH);
+            }
+            if let (Some(new_scheme), Some(authority)) = (location.scheme(), location.authority()) {
+                scheme = new_scheme.clone();
+                if let Ok(host) = HeaderValue::from_str(authority.as_str()) {
+                    headers.insert(header::HOST, host);
+                }
+            }
+            uri = location
+                .path_and_query()
+                .map(|path_and_query| Uri::from(path_and_query.clone()))
+                .unwrap_or_else(|| Uri::from_static("/"));
+        }
+    }
+}
+
+/// Sends a request with the cookies of the client.
+async fn send_request<E: Endpoint>(cli: &TestClient<E>, mut req: Request) -> Response {
+    #[cfg(feature = "cookie")]
+    if let Some(cookie_store) = &cli.cookie_store {
+        let uri = req.uri().clone();
+        cookie_store.apply_to_request(&uri, req.headers_mut());
+        let req_headers = req.headers().clone();
+        let resp = cli.ep.get_response(req).await;
+        cookie_store.extract_from_response(&uri, &req_headers, resp.headers());
+        return resp;
+    }
+
+    cli.ep.get_response(req).await
+}
+
+/// Resolves the `Location` of a redirect response against the request URI.
+fn resolve_location(uri: &Uri, location: &str) -> Option<Uri> {
+    if location.starts_with('/') || location.contains("://") {
+        return location.parse().ok();
+    }
+
+    let path = uri.path();
+    if location.is_empty() || location.starts_with('?') {
+        return format!("{path}{location}").parse().ok();
+    }
+    let dir = &path[..path.rfind('/').map(|idx| idx + 1).unwrap_or_default()];
+    format!("{dir}{location}").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointExt, Route, get, handler, middleware::ForceHttps, web::Redirect};
+
+    #[handler(internal)]
+    fn echo(req: &Request, body: String) -> String {
+        format!(
+            "{} {} {} {}",
+            req.scheme(),
+            req.method(),
+            req.uri(),
+            if body.is_empty() { "-" } else { &body }
+        )
+    }
+
+    #[tokio::test]
+    async fn follow_redirects() {
+        #[handler(internal)]
+        fn redirect(req: &Request) -> Response {
+            let status = req.uri().path()[1..].parse::<u16>().unwrap();
+            Response::builder()
+                .status(StatusCode::from_u16(status).unwrap())
+                .header(header::LOCATION, "/echo")
+                .finish()
+        }
+
+        let app = Route::new().at("/echo", echo).at("/:status", redirect);
+        let cli = TestClient::new(app);
+
+        for (method, status, expected) in [
+            (Method::POST, 301, "http GET /echo -"),
+            (Method::POST, 302, "http GET /echo -"),
+            (Method::PUT, 302, "http PUT /echo body"),
+            (Method::POST, 303, "http GET /echo -"),
+            (Method::PUT, 303, "http GET /echo -"),
+            (Method::POST, 307, "http POST /echo body"),
+            (Method::POST, 308, "http POST /echo body"),
+        ] {
+            let resp = cli
+                .request(method.clone(), format!("/{status}"))
+                .content_type("text/plain")
+                .body("body")
+                .follow_redirects(true)
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            resp.assert_text(expected).await;
+        }
+
+        // not followed by default
+        cli.post("/303")
+            .send()
+            .await
+            .assert_status(StatusCode::SEE_OTHER);
+    }
+
+    #[tokio::test]
+    async fn max_redirects() {
+        #[handler(internal)]
+        fn redirect(req: &Request) -> Redirect {
+            let n = req.uri().path()[1..].parse::<u32>().unwrap();
+            Redirect::see_other(format!("/{}", n + 1))
+        }
+
+        let cli = TestClient::new(Route::new().at("/:n", get(redirect)));
+        let resp = cli.get("/0").follow_redirects(true).send().await;
+        resp.assert_status(StatusCode::SEE_OTHER);
+        resp.assert_header(header::LOCATION, "/11");
+
+        let resp = cli
+            .get("/0")
+            .follow_redirects(true)
+            .max_redirects(2)
+            .send()
+            .await;
+        resp.assert_header(header::LOCATION, "/3");
+    }
+
+    #[tokio::test]
+    async fn force_https() {
+        let app = Route::new().at("/a/b", echo).with(ForceHttps::new());
+        let cli = TestClient::new(app);
+
+        let resp = cli
+            .get("/a/b?c=1")
+            .header(header::HOST, "example.com")
+            .follow_redirects(true)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("https GET /a/b?c=1 -").await;
+    }
+
+    #[test]
+    fn location() {
+        let uri = Uri::from_static("/a/b?c=1");
+        for (location, expected) in [
+            ("/d", "/d"),
+            ("d", "/a/d"),
+            ("d?e=2", "/a/d?e=2"),
+            ("?e=2", "/a/b?e=2"),
+            ("https://example.com/d", "https://example.com/d"),
+        ] {
+            assert_eq!(resolve_location(&uri, location).unwrap(), expected);
+        }
     }
 }
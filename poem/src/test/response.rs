@@ -6,7 +6,15 @@ use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 
-use crate::{Response, test::json::TestJson, web::sse::Event};
+use crate::{
+    Response,
+    test::json::{TestJson, contains, value_at_path},
+    web::sse::Event,
+};
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
 
 /// A response object for testing.
 pub struct TestResponse(pub Response);
@@ -166,6 +174,115 @@ impl TestResponse {
         );
     }
 
+    /// Asserts that the response body is JSON and the value at `path` equals
+    /// to `json`.
+    ///
+    /// The path is a list of keys and array indices like
+    /// `$.data.items[0].id`. Keys can also be written in brackets, such as
+    /// `$["first name"]`, and the leading `$` is optional.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the path is invalid, or if the value does not exist or does
+    /// not equal to `json`. The panic message contains the whole response
+    /// body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{handler, test::TestClient, web::Json};
+    /// use serde_json::{Value, json};
+    ///
+    /// #[handler]
+    /// fn index() -> Json<Value> {
+    ///     Json(json!({ "data": { "id": 42, "tags": ["a", "b"] } }))
+    /// }
+    ///
+    /// let cli = TestClient::new(index);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.get("/")
+    ///     .send()
+    ///     .await
+    ///     .assert_json_path("$.data.id", 42)
+    ///     .await;
+    /// cli.get("/")
+    ///     .send()
+    ///     .await
+    ///     .assert_json_path("data.tags[1]", "b")
+    ///     .await;
+    /// # });
+    /// ```
+    pub async fn assert_json_path(self, path: impl AsRef<str>, json: impl Serialize) {
+        let path = path.as_ref();
+        let value = self.into_json_value().await;
+        let expected = serde_json::to_value(json).expect("valid json");
+        match value_at_path(&value, path) {
+            Some(actual) => assert_eq!(
+                actual,
+                &expected,
+                "unexpected value at json path `{path}`, body: {}",
+                pretty(&value)
+            ),
+            None => panic!(
+                "json path `{path}` does not exist, body: {}",
+                pretty(&value)
+            ),
+        }
+    }
+
+    /// Asserts that the response body is JSON and it contains `json`.
+    ///
+    /// An object contains the expected object if it has all of its keys, with
+    /// values containing the expected values, so other keys are ignored.
+    /// Arrays must have the same length and contain the expected elements in
+    /// order, and other values must be equal.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the body does not contain `json`. The panic message contains
+    /// the whole response body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{handler, test::TestClient, web::Json};
+    /// use serde_json::{Value, json};
+    ///
+    /// #[handler]
+    /// fn index() -> Json<Value> {
+    ///     Json(json!({ "status": "ok", "data": { "id": 42, "name": "poem" } }))
+    /// }
+    ///
+    /// let cli = TestClient::new(index);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.get("/")
+    ///     .send()
+    ///     .await
+    ///     .assert_json_contains(json!({ "status": "ok", "data": { "id": 42 } }))
+    ///     .await;
+    /// # });
+    /// ```
+    pub async fn assert_json_contains(self, json: impl Serialize) {
+        let value = self.into_json_value().await;
+        let expected = serde_json::to_value(json).expect("valid json");
+        assert!(
+            contains(&value, &expected),
+            "json body does not contain the expected value\n  expected: {}\n      body: {}",
+            pretty(&expected),
+            pretty(&value)
+        );
+    }
+
+    async fn into_json_value(self) -> Value {
+        self.0
+            .into_body()
+            .into_json::<Value>()
+            .await
+            .expect("expect body")
+    }
+
     /// Asserts that the response body is XML and it equals to `xml`.
     #[cfg(feature = "xml")]
     pub async fn assert_xml(self, xml: impl Serialize) {
@@ -184,6 +301,27 @@ impl TestResponse {
         );
     }
 
+    /// Asserts that the response body is CBOR and it equals to `cbor`.
+    #[cfg(feature = "cbor")]
+    pub async fn assert_cbor(self, cbor: impl Serialize) {
+        let mut data = Vec::new();
+        ciborium::into_writer(&cbor, &mut data).expect("valid cbor");
+        assert_eq!(
+            self.0.into_body().into_vec().await.expect("expect body"),
+            data
+        );
+    }
+
+    /// Asserts that the response body is MessagePack and it equals to
+    /// `msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub async fn assert_msgpack(self, msgpack: impl Serialize) {
+        assert_eq!(
+            self.0.into_body().into_vec().await.expect("expect body"),
+            rmp_serde::to_vec_named(&msgpack).expect("valid msgpack")
+        );
+    }
+
     /// Consumes this object and return the [`TestJson`].
     pub async fn json(self) -> TestJson {
         self.0
@@ -234,3 +372,102 @@ impl TestResponse {
         self.typed_sse_stream::<TestJson>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{handler, test::TestClient, web::Json};
+
+    #[handler(internal)]
+    fn index() -> Json<Value> {
+        Json(json!({
+            "status": "ok",
+            "data": {
+                "id": 42,
+                "first name": "a",
+                "items": [{ "id": 1, "tags": ["x"] }, { "id": 2 }],
+            },
+        }))
+    }
+
+    #[tokio::test]
+    async fn json_path() {
+        let cli = TestClient::new(index);
+        for (path, expected) in [
+            ("$.status", json!("ok")),
+            ("status", json!("ok")),
+            ("$.data.id", json!(42)),
+            ("$.data[\"first name\"]", json!("a")),
+            ("$['data'].items[1].id", json!(2)),
+            ("data.items[0].tags[0]", json!("x")),
+            ("$.data.items[1]", json!({ "id": 2 })),
+        ] {
+            cli.get("/")
+                .send()
+                .await
+                .assert_json_path(path, expected)
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "unexpected value at json path `$.data.id`, body: {")]
+    async fn json_path_mismatch() {
+        TestClient::new(index)
+            .get("/")
+            .send()
+            .await
+            .assert_json_path("$.data.id", 41)
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "json path `$.data.items[2]` does not exist")]
+    async fn json_path_not_exist() {
+        TestClient::new(index)
+            .get("/")
+            .send()
+            .await
+            .assert_json_path("$.data.items[2]", 3)
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "invalid json path `$.data..id`")]
+    async fn json_path_invalid() {
+        TestClient::new(index)
+            .get("/")
+            .send()
+            .await
+            .assert_json_path("$.data..id", 42)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn json_contains() {
+        let cli = TestClient::new(index);
+        cli.get("/")
+            .send()
+            .await
+            .assert_json_contains(json!({ "status": "ok" }))
+            .await;
+        cli.get("/")
+            .send()
+            .await
+            .assert_json_contains(json!({ "data": { "items": [{ "id": 1 }, {}] } }))
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "\"status\": \"ok\"")]
+    async fn json_contains_mismatch() {
+        TestClient::new(index)
+            .get("/")
+            .send()
+            .await
+            .assert_json_contains(json!({ "data": { "items": [{ "id": 1 }] } }))
+            .await;
+    }
+}
@@ -0,0 +1,274 @@
+use std::convert::Infallible;
+
+use futures_util::{SinkExt, StreamExt};
+use http::{HeaderMap, HeaderValue, header, header::HeaderName};
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use tokio::io::DuplexStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+use crate::{
+    Endpoint, Request,
+    http::uri::Scheme,
+    test::TestClient,
+    web::{
+        LocalAddr, RemoteAddr,
+        websocket::{Message, WebSocketConfig},
+    },
+};
+
+/// A websocket request builder for testing, created by [`TestClient::ws`].
+#[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
+pub struct TestWebSocketBuilder<'a, E> {
+    cli: &'a TestClient<E>,
+    uri: String,
+    headers: HeaderMap,
+    config: Option<WebSocketConfig>,
+}
+
+impl<'a, E> TestWebSocketBuilder<'a, E> {
+    pub(crate) fn new(cli: &'a TestClient<E>, uri: String) -> Self {
+        Self {
+            cli,
+            uri,
+            headers: Default::default(),
+            config: None,
+        }
+    }
+
+    /// Sets the header value for this request.
+    #[must_use]
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        V: TryInto<HeaderValue>,
+    {
+        let key = key.try_into().map_err(|_| ()).expect("valid header name");
+        let value = value
+            .try_into()
+            .map_err(|_| ())
+            .expect("valid header value");
+        self.headers.append(key, value);
+        self
+    }
+
+    /// Sets the subprotocols requested by the client, in order of preference.
+    #[must_use]
+    pub fn protocols<I>(self, protocols: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let protocols = protocols
+            .into_iter()
+            .map(|protocol| protocol.as_ref().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.header(header::SEC_WEBSOCKET_PROTOCOL, protocols)
+    }
+
+    /// Sets the configuration of the client side of the connection.
+    #[must_use]
+    pub fn config(self, config: WebSocketConfig) -> Self {
+        Self {
+            config: Some(config),
+            ..self
+        }
+    }
+
+    /// Performs the websocket handshake with the endpoint.
+    ///
+    /// The request is served over an in-memory connection by the same HTTP
+    /// implementation as the server, so the upgrade runs exactly as it would
+    /// over the network.
+    ///
+    /// # Panics
+    ///
+    /// Panic when the endpoint does not accept the upgrade. Send the upgrade
+    /// request with [`TestClient::get`] to check how it is rejected.
+    pub async fn connect(self) -> TestWebSocket
+    where
+        E: Endpoint,
+    {
+        let mut request = format!("ws://localhost{}", self.uri)
+            .into_client_request()
+            .expect("valid uri");
+        request
+            .headers_mut()
+            .extend(self.cli.default_headers.clone());
+        request.headers_mut().extend(self.headers);
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let ep = &self.cli.ep;
+        let service = hyper::service::service_fn(move |req: http::Request<Incoming>| async move {
+            let req = Request::from((
+                req,
+                LocalAddr::default(),
+                RemoteAddr::default(),
+                Scheme::HTTP,
+            ));
+            Ok::<http::Response<_>, Infallible>(ep.get_response(req).await.into())
+        });
+        let conn = hyper::server::conn::http1::Builder::new()
+            .serve_connection(TokioIo::new(server_io), service)
+            .with_upgrades();
+        let handshake =
+            tokio_tungstenite::client_async_with_config(request, client_io, self.config);
+        tokio::pin!(conn, handshake);
+
+        let mut conn_finished = false;
+        let res = loop {
+            tokio::select! {
+                res = &mut handshake => break res,
+                _ = &mut conn, if !conn_finished => conn_finished = true,
+            }
+        };
+        let (stream, resp) = match res {
+            Ok(res) => res,
+            Err(err) => panic!("websocket handshake failed: {err}"),
+        };
+        if !conn_finished {
+            // hand the connection over to the upgraded websocket
+            let _ = conn.await;
+        }
+
+        TestWebSocket {
+            stream,
+            protocol: resp
+                .headers()
+                .get(header::SEC_WEBSOCKET_PROTOCOL)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string),
+            close_received: false,
+        }
+    }
+}
+
+/// The client side of a websocket connection to an endpoint, created by
+/// [`TestWebSocketBuilder::connect`].
+///
+/// Pings sent by the endpoint are answered automatically.
+#[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
+pub struct TestWebSocket {
+    stream: tokio_tungstenite::WebSocketStream<DuplexStream>,
+    protocol: Option<String>,
+    close_received: bool,
+}
+
+impl TestWebSocket {
+    /// Returns the subprotocol selected by the endpoint.
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
+    /// Sends a message to the endpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panic when the connection is closed.
+    pub async fn send(&mut self, msg: Message) {
+        self.stream
+            .send(msg.into())
+            .await
+            .expect("send websocket message");
+    }
+
+    /// Receives the next message from the endpoint, returns `None` when the
+    /// connection is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panic when the connection fails.
+    pub async fn recv(&mut self) -> Option<Message> {
+        use tokio_tungstenite::tungstenite::{Error, Message as RawMessage};
+
+        loop {
+            match self.stream.next().await? {
+                Ok(RawMessage::Frame(_)) => continue,
+                Ok(msg) => {
+                    self.close_received |= msg.is_close();
+                    return Some(msg.into());
+                }
+                Err(Error::ConnectionClosed | Error::AlreadyClosed) => return None,
+                // the endpoint may drop the connection right after sending the
+                // close frame, before the reply of the client is written
+                Err(_) if self.close_received => return None,
+                Err(err) => panic!("receive websocket message: {err}"),
+            }
+        }
+    }
+
+    /// Closes the connection.
+    pub async fn close(&mut self) {
+        let _ = self.stream.close(None).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        IntoResponse, Route, get, handler,
+        web::websocket::{CloseCode, WebSocket},
+    };
+
+    #[handler(internal)]
+    async fn echo(ws: WebSocket) -> impl IntoResponse {
+        ws.protocols(["v1", "v2"])
+            .on_upgrade(|mut socket| async move {
+                while let Some(Ok(msg)) = socket.next().await {
+                    match msg {
+                        Message::Text(text) if text == "bye" => {
+                            let _ = socket
+                                .send(Message::close_with(CloseCode::Normal, "bye"))
+                                .await;
+                            break;
+                        }
+                        Message::Text(_) | Message::Binary(_) => {
+                            let _ = socket.send(msg).await;
+                        }
+                        _ => {}
+                    }
+                }
+            })
+    }
+
+    #[tokio::test]
+    async fn echo_socket() {
+        let cli = TestClient::new(Route::new().at("/ws", get(echo)));
+        let mut ws = cli.ws("/ws").protocols(["v3", "v2"]).connect().await;
+        assert_eq!(ws.protocol(), Some("v2"));
+
+        ws.send(Message::text("hello")).await;
+        assert_eq!(ws.recv().await, Some(Message::text("hello")));
+        ws.send(Message::binary([1, 2, 3])).await;
+        assert_eq!(ws.recv().await, Some(Message::binary([1, 2, 3])));
+
+        ws.send(Message::text("bye")).await;
+        assert_eq!(
+            ws.recv().await,
+            Some(Message::close_with(CloseCode::Normal, "bye"))
+        );
+        assert_eq!(ws.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn without_protocol() {
+        let cli = TestClient::new(echo);
+        let mut ws = cli.ws("/").connect().await;
+        assert_eq!(ws.protocol(), None);
+        ws.send(Message::text("hello")).await;
+        assert_eq!(ws.recv().await, Some(Message::text("hello")));
+        ws.close().await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "websocket handshake failed")]
+    async fn rejected() {
+        #[handler(internal)]
+        fn index() {}
+
+        let cli = TestClient::new(index);
+        cli.ws("/").connect().await;
+    }
+}
@@ -7,7 +7,7 @@ use crate::{FromRequest, Request, RequestBody, Result};
 #[derive(Debug, Clone)]
 pub struct Accept(pub Vec<Mime>);
 
-fn parse_accept(headers: &HeaderMap) -> Vec<Mime> {
+pub(crate) fn parse_accept(headers: &HeaderMap) -> Vec<Mime> {
     let mut items = headers
         .get_all(header::ACCEPT)
         .iter()
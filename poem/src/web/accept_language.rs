@@ -0,0 +1,206 @@
+use http::{HeaderMap, header};
+
+use crate::{FromRequest, Request, RequestBody, Result};
+
+/// `Accept-Language` header, defined in [RFC7231](https://tools.ietf.org/html/rfc7231#section-5.3.5)
+///
+/// Contains the language ranges of the header ordered by their quality
+/// values, ranges with a quality value of `0` and invalid ranges are skipped.
+/// If the header is missing or invalid, the list is empty.
+///
+/// # Example
+///
+/// ```
+/// use poem::{Route, get, handler, http::header, test::TestClient, web::AcceptLanguage};
+///
+/// #[handler]
+/// fn index(accept_language: AcceptLanguage) -> &'static str {
+///     match accept_language.negotiate(&["en", "fr", "de"]) {
+///         Some("fr") => "bonjour",
+///         Some("de") => "hallo",
+///         _ => "hello",
+///     }
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header(header::ACCEPT_LANGUAGE, "fr-CH, fr;q=0.9, en;q=0.8")
+///     .send()
+///     .await;
+/// resp.assert_text("bonjour").await;
+///
+/// let resp = cli.get("/").send().await;
+/// resp.assert_text("hello").await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AcceptLanguage(pub Vec<String>);
+
+fn is_language_range(range: &str) -> bool {
+    range == "*"
+        || range.split('-').enumerate().all(|(idx, subtag)| {
+            (1..=8).contains(&subtag.len())
+                && if idx == 0 {
+                    subtag.bytes().all(|c| c.is_ascii_alphabetic())
+                } else {
+                    subtag.bytes().all(|c| c.is_ascii_alphanumeric())
+                }
+        })
+}
+
+fn parse_accept_language(headers: &HeaderMap) -> Vec<String> {
+    let mut items = headers
+        .get_all(header::ACCEPT_LANGUAGE)
+        .iter()
+        .filter_map(|hval| hval.to_str().ok())
+        .flat_map(|s| s.split(','))
+        .filter_map(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let range = parts.next()?;
+            if !is_language_range(range) {
+                return None;
+            }
+            let q = match parts.next() {
+                Some(param) => {
+                    let (name, value) = param.split_once('=')?;
+                    if !name.trim().eq_ignore_ascii_case("q") {
+                        return None;
+                    }
+                    value
+                        .trim()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|q| (0.0..=1.0).contains(q))?
+                }
+                None => 1.0,
+            };
+            (q > 0.0).then(|| (range.to_string(), (q * 1000.0) as i32))
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|(_, qa), (_, qb)| qb.cmp(qa));
+    items.into_iter().map(|(range, _)| range).collect()
+}
+
+/// Returns the first supported language that the range matches.
+///
+/// A range matches a language that is equal, or that starts with the range
+/// followed by `-` (`en` matches `en-US`). Otherwise, subtags are removed from
+/// the end of the range until it is equal to a language (`en-US` matches
+/// `en`).
+fn lookup<'a>(range: &str, supported: &[&'a str]) -> Option<&'a str> {
+    if range == "*" {
+        return supported.first().copied();
+    }
+
+    let is_prefix = |prefix: &str, tag: &str| {
+        tag.as_bytes().get(prefix.len()) == Some(&b'-')
+            && tag.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+    };
+    if let Some(tag) = supported
+        .iter()
+        .find(|tag| tag.eq_ignore_ascii_case(range))
+        .or_else(|| supported.iter().find(|tag| is_prefix(range, tag)))
+    {
+        return Some(tag);
+    }
+
+    let mut range = range;
+    while let Some((prefix, _)) = range.rsplit_once('-') {
+        range = prefix;
+        if let Some(tag) = supported.iter().find(|tag| tag.eq_ignore_ascii_case(range)) {
+            return Some(tag);
+        }
+    }
+    None
+}
+
+impl AcceptLanguage {
+    /// Returns the supported language that best matches the ranges of the
+    /// header.
+    ///
+    /// `supported` is ordered by preference, its first language is returned
+    /// when no range matches or the header is missing. Returns `None` only if
+    /// `supported` is empty.
+    pub fn negotiate<'a>(&self, supported: &[&'a str]) -> Option<&'a str> {
+        self.0
+            .iter()
+            .find_map(|range| lookup(range, supported))
+            .or_else(|| supported.first().copied())
+    }
+}
+
+impl<'a> FromRequest<'a> for AcceptLanguage {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self(parse_accept_language(req.headers())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn accept_language(value: &str) -> AcceptLanguage {
+        let req = Request::builder()
+            .header(header::ACCEPT_LANGUAGE, value)
+            .finish();
+        AcceptLanguage::from_request_without_body(&req)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn parse() {
+        assert_eq!(
+            accept_language("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5")
+                .await
+                .0,
+            ["fr-CH", "fr", "en", "de", "*"]
+        );
+        assert_eq!(
+            accept_language("de;q=0.5, en-US, zh-Hant-TW;q=0.8").await.0,
+            ["en-US", "zh-Hant-TW", "de"]
+        );
+        // invalid ranges and zero quality values are skipped
+        assert_eq!(
+            accept_language("en;q=0, fr;q=abc, de;q=2, es;level=1, en_US, 1de, it ; q = 0.3")
+                .await
+                .0,
+            ["it"]
+        );
+
+        let req = Request::default();
+        let accept_language = AcceptLanguage::from_request_without_body(&req)
+            .await
+            .unwrap();
+        assert!(accept_language.0.is_empty());
+        assert_eq!(accept_language.negotiate(&["en", "fr"]), Some("en"));
+    }
+
+    #[tokio::test]
+    async fn negotiate() {
+        let supported = ["en", "fr", "de", "pt-BR"];
+        for (value, expected) in [
+            ("fr", Some("fr")),
+            ("FR", Some("fr")),
+            ("fr-CH, en;q=0.9", Some("fr")),
+            ("it, de;q=0.5", Some("de")),
+            ("pt", Some("pt-BR")),
+            ("pt-PT, pt;q=0.9", Some("pt-BR")),
+            ("en;q=0.1, de-AT", Some("de")),
+            ("*", Some("en")),
+            ("it", Some("en")),
+            ("invalid header", Some("en")),
+        ] {
+            assert_eq!(
+                accept_language(value).await.negotiate(&supported),
+                expected,
+                "{value}"
+            );
+        }
+        assert_eq!(accept_language("fr").await.negotiate(&[]), None);
+    }
+}
@@ -0,0 +1,213 @@
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+
+use crate::{
+    Body, IntoResponse, Response,
+    http::{HeaderValue, header},
+};
+
+/// The characters that are percent-encoded in an extended parameter value,
+/// all except the `attr-char` of [RFC 5987](https://datatracker.ietf.org/doc/html/rfc5987#section-3.2.1).
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// The disposition type of an [`Attachment`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum AttachmentType {
+    /// The browser displays the content, if possible.
+    Inline,
+    /// The browser downloads the content.
+    #[default]
+    Attachment,
+}
+
+/// A download response with a `Content-Disposition` header.
+///
+/// The file name is written as a quoted `filename` parameter, with the
+/// characters that are not printable ASCII replaced with `_`, and if it is
+/// not ASCII-safe, also as an RFC 5987 encoded `filename*` parameter, which
+/// is preferred by the browsers.
+///
+/// The `Content-Type` is `application/octet-stream` unless it is set
+/// explicitly.
+///
+/// # Example
+///
+/// ```
+/// use poem::{Route, get, handler, http::header, test::TestClient, web::Attachment};
+///
+/// #[handler]
+/// fn report() -> Attachment<String> {
+///     Attachment::new("id,name\n1,foo\n".to_string())
+///         .filename("résumé 2024.csv")
+///         .content_type("text/csv")
+/// }
+///
+/// let app = Route::new().at("/report", get(report));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/report").send().await;
+/// resp.assert_content_type("text/csv");
+/// resp.assert_header(
+///     header::CONTENT_DISPOSITION,
+///     r#"attachment; filename="r_sum_ 2024.csv"; filename*=UTF-8''r%C3%A9sum%C3%A9%202024.csv"#,
+/// );
+/// resp.assert_text("id,name\n1,foo\n").await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Attachment<T> {
+    data: T,
+    attachment_type: AttachmentType,
+    filename: Option<String>,
+    content_type: Option<String>,
+}
+
+impl<T: Into<Body>> Attachment<T> {
+    /// Create an attachment with `data` as the body, it can be anything that
+    /// converts to a [`Body`], including a stream of bytes.
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            attachment_type: AttachmentType::Attachment,
+            filename: None,
+            content_type: None,
+        }
+    }
+
+    /// Sets the disposition type.
+    ///
+    /// Default is [`AttachmentType::Attachment`].
+    #[must_use]
+    pub fn attachment_type(self, attachment_type: AttachmentType) -> Self {
+        Self {
+            attachment_type,
+            ..self
+        }
+    }
+
+    /// Sets the disposition type to [`AttachmentType::Inline`].
+    #[must_use]
+    pub fn inline(self) -> Self {
+        self.attachment_type(AttachmentType::Inline)
+    }
+
+    /// Sets the file name.
+    #[must_use]
+    pub fn filename(self, filename: impl Into<String>) -> Self {
+        Self {
+            filename: Some(filename.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `Content-Type`.
+    #[must_use]
+    pub fn content_type(self, content_type: impl Into<String>) -> Self {
+        Self {
+            content_type: Some(content_type.into()),
+            ..self
+        }
+    }
+
+    fn content_disposition(&self) -> String {
+        let mut value = match self.attachment_type {
+            AttachmentType::Inline => "inline".to_string(),
+            AttachmentType::Attachment => "attachment".to_string(),
+        };
+        if let Some(filename) = &self.filename {
+            let fallback = filename
+                .chars()
+                .map(|c| match c {
+                    ' '..='~' if c != '"' && c != '\\' => c,
+                    _ => '_',
+                })
+                .collect::<String>();
+            value.push_str(&format!("; filename=\"{fallback}\""));
+            if fallback != *filename {
+                value.push_str(&format!(
+                    "; filename*=UTF-8''{}",
+                    utf8_percent_encode(filename, ATTR_CHAR)
+                ));
+            }
+        }
+        value
+    }
+}
+
+impl<T: Into<Body> + Send> IntoResponse for Attachment<T> {
+    fn into_response(self) -> Response {
+        let content_disposition = self.content_disposition();
+        let mut builder = Response::builder();
+        if let Ok(value) = HeaderValue::from_str(&content_disposition) {
+            builder = builder.header(header::CONTENT_DISPOSITION, value);
+        }
+        builder
+            .content_type(
+                self.content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+            )
+            .body(self.data.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content_disposition(attachment: Attachment<()>) -> String {
+        attachment
+            .into_response()
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn disposition() {
+        assert_eq!(content_disposition(Attachment::new(())), "attachment");
+        assert_eq!(
+            content_disposition(Attachment::new(()).inline().filename("a b.pdf")),
+            r#"inline; filename="a b.pdf""#
+        );
+        assert_eq!(
+            content_disposition(Attachment::new(()).filename("say \"hi\"\\.txt")),
+            r#"attachment; filename="say _hi__.txt"; filename*=UTF-8''say%20%22hi%22%5C.txt"#
+        );
+        assert_eq!(
+            content_disposition(Attachment::new(()).filename("报告.csv")),
+            r#"attachment; filename="__.csv"; filename*=UTF-8''%E6%8A%A5%E5%91%8A.csv"#
+        );
+        assert_eq!(
+            content_disposition(Attachment::new(()).filename("a\r\nb")),
+            r#"attachment; filename="a__b"; filename*=UTF-8''a%0D%0Ab"#
+        );
+    }
+
+    #[tokio::test]
+    async fn content_type() {
+        let resp = Attachment::new("hello").into_response();
+        assert_eq!(resp.content_type(), Some("application/octet-stream"));
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "hello");
+
+        let resp = Attachment::new("hello")
+            .content_type("text/plain")
+            .into_response();
+        assert_eq!(resp.content_type(), Some("text/plain"));
+    }
+}
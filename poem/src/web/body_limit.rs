@@ -0,0 +1,266 @@
+use futures_util::{StreamExt, future::ready};
+
+use crate::{Body, Request, error::ReadBodyError, web::headers::HeaderMapExt};
+
+/// The maximum size of the request body read by the body extractors.
+///
+/// When this value is in the request data, for example added with
+/// [`EndpointExt::data`](crate::EndpointExt::data), the [`String`],
+/// [`Bytes`](bytes::Bytes), [`Vec<u8>`], [`Json`](crate::web::Json),
+/// [`Form`](crate::web::Form) and [`Multipart`](crate::web::Multipart)
+/// extractors, as well as the other built-in extractors that read the body,
+/// return a [`ReadBodyError::PayloadTooLarge`] (`413 Payload Too Large`) when
+/// the body is larger than the limit.
+///
+/// A `Content-Length` larger than the limit is rejected before reading the
+/// body. Otherwise the body is counted while it is read, so a chunked body is
+/// rejected as soon as it exceeds the limit.
+///
+/// Without a `BodyLimit`, the [default limit](BodyLimit::DEFAULT) of `2 MiB`
+/// is used, and [`BodyLimit::UNLIMITED`] disables it. The extractors that do
+/// not buffer the body, [`Body`] and [`TempFile`](crate::web::TempFile), are
+/// only limited by an explicit `BodyLimit`.
+///
+/// Unlike the [`SizeLimit`](crate::middleware::SizeLimit) middleware, this
+/// does not require a `Content-Length`, and different endpoints can use
+/// different limits.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, handler,
+///     http::StatusCode,
+///     post,
+///     test::TestClient,
+///     web::{BodyLimit, Json},
+/// };
+///
+/// #[handler]
+/// fn create(Json(value): Json<serde_json::Value>) {}
+///
+/// #[handler]
+/// fn upload(data: Vec<u8>) {}
+///
+/// let app = Route::new()
+///     .at("/create", post(create).data(BodyLimit(1024 * 1024)))
+///     .at("/upload", post(upload).data(BodyLimit(100 * 1024 * 1024)));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/create")
+///     .content_type("application/json")
+///     .body(vec![b' '; 2 * 1024 * 1024])
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+///
+/// let resp = cli
+///     .post("/upload")
+///     .body(vec![0; 2 * 1024 * 1024])
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// # });
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BodyLimit(pub usize);
+
+impl BodyLimit {
+    /// The limit used when the request has no `BodyLimit`.
+    pub const DEFAULT: BodyLimit = BodyLimit(2 * 1024 * 1024);
+
+    /// Disables the limit, including the default one.
+    pub const UNLIMITED: BodyLimit = BodyLimit(usize::MAX);
+
+    /// Returns the body limit of the request, or the default limit if it has
+    /// none, or an error if the `Content-Length` of the request already
+    /// exceeds it.
+    pub(crate) fn of(req: &Request) -> Result<Option<usize>, ReadBodyError> {
+        let limit = req.data::<BodyLimit>().copied().unwrap_or(Self::DEFAULT);
+        limit.check(req)
+    }
+
+    /// Same as [`BodyLimit::of`], but without the default limit.
+    pub(crate) fn explicit_of(req: &Request) -> Result<Option<usize>, ReadBodyError> {
+        match req.data::<BodyLimit>() {
+            Some(limit) => limit.check(req),
+            None => Ok(None),
+        }
+    }
+
+    fn check(self, req: &Request) -> Result<Option<usize>, ReadBodyError> {
+        if self == Self::UNLIMITED {
+            return Ok(None);
+        }
+        let BodyLimit(limit) = self;
+        if let Some(content_length) = req.headers().typed_get::<headers::ContentLength>() {
+            if content_length.0 > limit as u64 {
+                return Err(ReadBodyError::PayloadTooLarge);
+            }
+        }
+        Ok(Some(limit))
+    }
+
+    /// Wraps the body so that reading it fails once more than `limit` bytes
+    /// have been read.
+    pub(crate) fn apply(self, body: Body) -> Body {
+        let limit = self.0;
+        Body::from_bytes_stream(body.into_bytes_stream().scan(0, move |size, res| {
+            ready(Some(res.and_then(|data| {
+                *size += data.len();
+                if *size > limit {
+                    Err(std::io::Error::other(ReadBodyError::PayloadTooLarge))
+                } else {
+                    Ok(data)
+                }
+            })))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures_util::stream;
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{
+        Endpoint, EndpointExt, handler,
+        test::TestClient,
+        web::{Form, Json},
+    };
+
+    fn chunked(data: &'static [u8]) -> Body {
+        Body::from_bytes_stream(stream::iter(
+            data.chunks(2)
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::from_static(chunk))),
+        ))
+    }
+
+    async fn check(ep: impl Endpoint, content_type: &str, ok: &'static [u8], err: &'static [u8]) {
+        let cli = TestClient::new(ep.data(BodyLimit(5)));
+
+        for body in [Body::from(ok), chunked(ok)] {
+            cli.post("/")
+                .content_type(content_type)
+                .body(body)
+                .send()
+                .await
+                .assert_status_is_ok();
+        }
+
+        cli.post("/")
+            .content_type(content_type)
+            .header("content-length", err.len())
+            .body(err)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        cli.post("/")
+            .content_type(content_type)
+            .body(chunked(err))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn body_limit() {
+        #[handler(internal)]
+        fn string(_: String) {}
+
+        #[handler(internal)]
+        fn bytes(_: Bytes) {}
+
+        #[handler(internal)]
+        fn vec(_: Vec<u8>) {}
+
+        #[handler(internal)]
+        fn json(_: Json<serde_json::Value>) {}
+
+        #[handler(internal)]
+        fn form(_: Form<Vec<(String, String)>>) {}
+
+        check(string, "text/plain", b"12345", b"123456").await;
+        check(bytes, "text/plain", b"12345", b"123456").await;
+        check(vec, "text/plain", b"12345", b"123456").await;
+        check(json, "application/json", b"[1,2]", b"[1,23]").await;
+        check(
+            form,
+            "application/x-www-form-urlencoded",
+            b"a=123",
+            b"a=1234",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn default_limit() {
+        #[handler(internal)]
+        fn bytes(_: Bytes) {}
+
+        #[handler(internal)]
+        fn stream(_: Body) {}
+
+        let data = vec![0; BodyLimit::DEFAULT.0 + 1];
+
+        TestClient::new(bytes)
+            .post("/")
+            .body(data.clone())
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        TestClient::new(bytes.data(BodyLimit::UNLIMITED))
+            .post("/")
+            .body(data.clone())
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        // the streaming body is only limited explicitly
+        TestClient::new(stream)
+            .post("/")
+            .body(data)
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[cfg(feature = "multipart")]
+    #[tokio::test]
+    async fn body_limit_multipart() {
+        use crate::web::Multipart;
+
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) -> crate::Result<()> {
+            while let Some(field) = multipart.next_field().await? {
+                field.bytes().await?;
+            }
+            Ok(())
+        }
+
+        let data =
+            "--X\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1234567890\r\n--X--\r\n";
+        let len = data.len();
+        let cli = TestClient::new(index.data(BodyLimit(len)));
+        cli.post("/")
+            .content_type("multipart/form-data; boundary=X")
+            .body(data)
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        let cli = TestClient::new(index.data(BodyLimit(len - 1)));
+        cli.post("/")
+            .content_type("multipart/form-data; boundary=X")
+            .body(data)
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,222 @@
+use std::ops::{Deref, DerefMut};
+
+use http::StatusCode;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    FromRequest, IntoResponse, Request, Response, Result, error::ParseCborError, http::header,
+    web::RequestBody,
+};
+
+/// CBOR extractor and response.
+///
+/// To extract the specified type of CBOR from the body, `T` must implement
+/// [`serde::Deserialize`].
+///
+/// # Errors
+///
+/// - [`ReadBodyError`](crate::error::ReadBodyError)
+/// - [`ParseCborError`]
+///
+/// ```
+/// use poem::{
+///     Endpoint, Request, Route, handler,
+///     http::{Method, StatusCode, header},
+///     post,
+///     test::TestClient,
+///     web::Cbor,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// async fn index(Cbor(user): Cbor<User>) -> String {
+///     format!("welcome {}!", user.name)
+/// }
+///
+/// let app = Route::new().at("/", post(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/")
+///     .body_cbor(&User {
+///         name: "foo".to_string(),
+///     })
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("welcome foo!").await;
+/// # });
+/// ```
+///
+/// # Response
+///
+/// To serialize the specified type to CBOR, `T` must implement
+/// [`serde::Serialize`].
+///
+/// ```
+/// use poem::{
+///     Endpoint, Request, Route, get, handler, http::StatusCode, test::TestClient, web::Cbor,
+/// };
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// async fn index() -> Cbor<User> {
+///     Cbor(User {
+///         name: "foo".to_string(),
+///     })
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("application/cbor");
+/// resp.assert_cbor(User {
+///     name: "foo".to_string(),
+/// })
+/// .await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Cbor<T>(pub T);
+
+impl<T> Deref for Cbor<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Cbor<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T: DeserializeOwned> FromRequest<'a> for Cbor<T> {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .ok_or(ParseCborError::ContentTypeRequired)?;
+        if !is_cbor_content_type(content_type) {
+            return Err(ParseCborError::InvalidContentType(content_type.into()).into());
+        }
+
+        Ok(Self(
+            ciborium::from_reader(&*body.take_limited(req)?.into_bytes().await?)
+                .map_err(ParseCborError::Parse)?,
+        ))
+    }
+}
+
+fn is_cbor_content_type(content_type: &str) -> bool {
+    matches!(content_type.parse::<mime::Mime>(),
+        Ok(content_type) if content_type.type_() == "application"
+        && (content_type.subtype() == "cbor"
+        || content_type
+            .suffix()
+            .is_some_and(|v| v == "cbor")))
+}
+
+impl<T: Serialize + Send> IntoResponse for Cbor<T> {
+    fn into_response(self) -> Response {
+        let mut data = Vec::new();
+        if let Err(err) = ciborium::into_writer(&self.0, &mut data) {
+            return Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err.to_string());
+        }
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/cbor")
+            .body(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+    struct CreateResource {
+        value1: i32,
+        value2: String,
+    }
+
+    #[tokio::test]
+    async fn read_cbor() {
+        #[handler(internal)]
+        async fn index(query: Cbor<CreateResource>) {
+            assert_eq!(query.value1, 10);
+            assert_eq!(query.value2, "abc");
+        }
+
+        let cli = TestClient::new(index);
+        cli.post("/")
+            .body_cbor(&CreateResource {
+                value1: 10,
+                value2: "abc".to_string(),
+            })
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        cli.post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body("{}")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        cli.post("/")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        cli.post("/")
+            .header(header::CONTENT_TYPE, "application/cbor")
+            .body(vec![0xff, 0x00])
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn write_cbor() {
+        #[handler(internal)]
+        async fn index() -> Cbor<CreateResource> {
+            Cbor(CreateResource {
+                value1: 10,
+                value2: "abc".to_string(),
+            })
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/cbor");
+        resp.assert_cbor(CreateResource {
+            value1: 10,
+            value2: "abc".to_string(),
+        })
+        .await;
+    }
+}
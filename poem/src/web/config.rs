@@ -0,0 +1,109 @@
+use std::ops::Deref;
+
+use crate::{FromRequest, Request, RequestBody, Result, error::ConfigNotRegisteredError};
+
+/// An extractor that clones the application configuration of type `T` from
+/// the request data.
+///
+/// It works like [`Data`](crate::web::Data), but owns a clone of the value,
+/// and when the configuration was not added with
+/// [`EndpointExt::data`](crate::EndpointExt::data) or
+/// [`AddData`](crate::middleware::AddData), the response is a `500 Internal
+/// Server Error` that names the missing type.
+///
+/// # Errors
+///
+/// - [`ConfigNotRegisteredError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, Route, get, handler, test::TestClient, web::Config};
+///
+/// #[derive(Clone)]
+/// struct AppConfig {
+///     greeting: String,
+/// }
+///
+/// #[handler]
+/// fn index(config: Config<AppConfig>) -> String {
+///     config.greeting.clone()
+/// }
+///
+/// let app = Route::new().at("/", get(index)).data(AppConfig {
+///     greeting: "hello".to_string(),
+/// });
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// TestClient::new(app)
+///     .get("/")
+///     .send()
+///     .await
+///     .assert_text("hello")
+///     .await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Config<T>(pub T);
+
+impl<T> Config<T> {
+    /// Consumes this to return the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Config<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, T: Clone + Send + Sync + 'static> FromRequest<'a> for Config<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Config(req.extensions().get::<T>().cloned().ok_or(
+            ConfigNotRegisteredError(std::any::type_name::<T>()),
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct AppConfig {
+        port: u16,
+    }
+
+    #[handler(internal)]
+    async fn index(config: Config<AppConfig>) -> String {
+        config.port.to_string()
+    }
+
+    #[tokio::test]
+    async fn config() {
+        TestClient::new(index.data(AppConfig { port: 3000 }))
+            .get("/")
+            .send()
+            .await
+            .assert_text("3000")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn not_registered() {
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        resp.assert_text(format!(
+            "config type `{}` not registered",
+            std::any::type_name::<AppConfig>()
+        ))
+        .await;
+    }
+}
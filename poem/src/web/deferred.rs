@@ -0,0 +1,193 @@
+use futures_util::FutureExt;
+
+use crate::{FromRequest, Request, RequestBody, Result};
+
+/// An extractor that defers the extraction of `T` until it is first used in
+/// the handler.
+///
+/// The handler can check cheap conditions first, such as the permissions of
+/// the user, and read an expensive extractor only when needed. The extracted
+/// value is cached, so [`Deferred::get`] extracts `T` at most once.
+///
+/// _This extractor will take over the requested body, so you should avoid
+/// using it together with other extractors that read the body in one
+/// handler._
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Result, Route, handler,
+///     http::{StatusCode, header},
+///     post,
+///     test::TestClient,
+///     web::{Deferred, Json, Query},
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Params {
+///     validate: bool,
+/// }
+///
+/// #[handler]
+/// async fn index(
+///     Query(params): Query<Params>,
+///     mut body: Deferred<'_, Json<serde_json::Value>>,
+/// ) -> Result<String> {
+///     if !params.validate {
+///         return Ok("skipped".to_string());
+///     }
+///     let Json(value) = body.get().await?;
+///     Ok(value.to_string())
+/// }
+///
+/// let app = Route::new().at("/", post(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.post("/?validate=false").body("not json").send().await;
+/// resp.assert_text("skipped").await;
+///
+/// let resp = cli
+///     .post("/?validate=true")
+///     .header(header::CONTENT_TYPE, "application/json")
+///     .body("[1,2]")
+///     .send()
+///     .await;
+/// resp.assert_text("[1,2]").await;
+///
+/// let resp = cli.post("/?validate=true").body("not json").send().await;
+/// resp.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+/// # });
+/// ```
+pub struct Deferred<'a, T> {
+    req: &'a Request,
+    body: RequestBody,
+    value: Option<T>,
+}
+
+impl<'a, T: FromRequest<'a>> Deferred<'a, T> {
+    /// Extracts `T` on the first call, and returns the extracted value.
+    ///
+    /// If the extraction fails, the error is returned and the next call
+    /// tries again, but the request body may have been consumed.
+    pub async fn get(&mut self) -> Result<&mut T> {
+        let value = match self.value.take() {
+            Some(value) => value,
+            // FIXME: remove the unnecessary boxed
+            // https://github.com/rust-lang/rust/issues/100013
+            None => T::from_request(self.req, &mut self.body).boxed().await?,
+        };
+        Ok(self.value.insert(value))
+    }
+
+    /// Consumes this extractor and returns the extracted value.
+    pub async fn into_inner(mut self) -> Result<T> {
+        match self.value {
+            Some(value) => Ok(value),
+            None => T::from_request(self.req, &mut self.body).boxed().await,
+        }
+    }
+
+    /// Returns `true` if `T` has been extracted.
+    #[inline]
+    pub fn is_extracted(&self) -> bool {
+        self.value.is_some()
+    }
+}
+
+impl<'a, T> FromRequest<'a> for Deferred<'a, T> {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        Ok(Self {
+            req,
+            body: std::mem::take(body),
+            value: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{
+        EndpointExt, handler,
+        test::TestClient,
+        web::{Data, Json},
+    };
+
+    struct Counted(String);
+
+    static EXTRACTED: AtomicUsize = AtomicUsize::new(0);
+
+    impl<'a> FromRequest<'a> for Counted {
+        async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+            EXTRACTED.fetch_add(1, Ordering::SeqCst);
+            Ok(Self(String::from_request(req, body).await?))
+        }
+    }
+
+    #[tokio::test]
+    async fn deferred() {
+        #[handler(internal)]
+        async fn index(mut body: Deferred<'_, Counted>) -> Result<String> {
+            assert!(!body.is_extracted());
+            let first = body.get().await?.0.clone();
+            let second = body.get().await?.0.clone();
+            assert_eq!(first, second);
+            Ok(first)
+        }
+
+        let cli = TestClient::new(index);
+        cli.post("/")
+            .body("abc")
+            .send()
+            .await
+            .assert_text("abc")
+            .await;
+        assert_eq!(EXTRACTED.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn not_used() {
+        let used = Arc::new(AtomicUsize::new(0));
+
+        #[handler(internal)]
+        async fn index(
+            body: Deferred<'_, Json<i32>>,
+            used: Data<&Arc<AtomicUsize>>,
+        ) -> Result<String> {
+            if used.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Ok("unused".to_string());
+            }
+            Ok(body.into_inner().await?.0.to_string())
+        }
+
+        let cli = TestClient::new(index.data(used));
+        cli.post("/")
+            .body("invalid")
+            .send()
+            .await
+            .assert_text("unused")
+            .await;
+        cli.post("/")
+            .body("invalid")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        cli.post("/")
+            .content_type("application/json")
+            .body("1")
+            .send()
+            .await
+            .assert_text("1")
+            .await;
+    }
+}
@@ -1,18 +1,19 @@
 use std::ops::{Deref, DerefMut};
 
-use serde::de::DeserializeOwned;
+use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
-    FromRequest, Request, Result,
+    FromRequest, IntoResponse, Request, Response, Result,
     error::ParseFormError,
     http::{
-        Method,
+        Method, StatusCode,
         header::{self},
     },
     web::RequestBody,
 };
 
-/// An extractor that can deserialize some type from query string or body.
+/// An extractor that can deserialize some type from query string or body,
+/// and a response that serializes it as a form.
 ///
 /// If the method is not `GET`, the query parameters will be parsed from the
 /// body, otherwise it is like [`Query`](crate::web::Query).
@@ -69,6 +70,41 @@ use crate::{
 /// resp.assert_text("foo:bar").await;
 /// # });
 /// ```
+///
+/// # Response
+///
+/// To serialize the specified type to the body, `T` must implement
+/// [`serde::Serialize`]. The `Content-Type` is
+/// `application/x-www-form-urlencoded`, and the serialization errors are
+/// returned as `500 Internal Server Error`.
+///
+/// ```
+/// use poem::{Route, get, handler, test::TestClient, web::Form};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Token {
+///     access_token: String,
+///     token_type: String,
+/// }
+///
+/// #[handler]
+/// fn token() -> Form<Token> {
+///     Form(Token {
+///         access_token: "a b".to_string(),
+///         token_type: "bearer".to_string(),
+///     })
+/// }
+///
+/// let app = Route::new().at("/token", get(token));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/token").send().await;
+/// resp.assert_content_type("application/x-www-form-urlencoded");
+/// resp.assert_text("access_token=a+b&token_type=bearer").await;
+/// # });
+/// ```
 pub struct Form<T>(pub T);
 
 impl<T> Deref for Form<T> {
@@ -104,14 +140,30 @@ impl<'a, T: DeserializeOwned> FromRequest<'a> for Form<T> {
             }
 
             Ok(Self(
-                serde_urlencoded::from_bytes(&body.take()?.into_vec().await?)
+                serde_urlencoded::from_bytes(&body.take_limited(req)?.into_vec().await?)
                     .map_err(ParseFormError::UrlDecode)?,
             ))
         }
     }
 }
 
-fn is_form_content_type(content_type: &str) -> bool {
+impl<T: Serialize + Send> IntoResponse for Form<T> {
+    fn into_response(self) -> Response {
+        let data = match serde_urlencoded::to_string(&self.0) {
+            Ok(data) => data,
+            Err(err) => {
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(err.to_string());
+            }
+        };
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(data)
+    }
+}
+
+pub(crate) fn is_form_content_type(content_type: &str) -> bool {
     matches!(content_type.parse::<mime::Mime>(), 
         Ok(content_type) if content_type.type_() == "application" 
         && (content_type.subtype() == "x-www-form-urlencoded"
@@ -164,4 +216,32 @@ mod tests {
             .await
             .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
     }
+
+    #[tokio::test]
+    async fn test_form_response() {
+        #[derive(Serialize)]
+        struct Resource {
+            name: String,
+            value: i32,
+        }
+
+        let resp = Form(Resource {
+            name: "a&b c".to_string(),
+            value: 100,
+        })
+        .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.content_type(),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "name=a%26b+c&value=100"
+        );
+
+        // nested values are not supported
+        let resp = Form(vec![("a", vec![1, 2])]).into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
 }
@@ -2,6 +2,7 @@ use std::ops::{Deref, DerefMut};
 
 use http::StatusCode;
 use serde::{Serialize, de::DeserializeOwned};
+use serde_json::ser::{Formatter, PrettyFormatter};
 
 use crate::{
     FromRequest, IntoResponse, Request, Response, Result, error::ParseJsonError, http::header,
@@ -102,6 +103,25 @@ impl<T> DerefMut for Json<T> {
     }
 }
 
+impl<T> Json<T> {
+    /// Returns a response that serializes `value` as pretty-printed JSON.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{IntoResponse, web::Json};
+    /// use serde_json::json;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = Json::pretty(json!({ "a": 1 })).into_response();
+    /// assert_eq!(resp.into_body().into_string().await.unwrap(), "{\n  \"a\": 1\n}");
+    /// # });
+    /// ```
+    pub fn pretty(value: T) -> JsonWith<T, PrettyFormatter<'static>> {
+        JsonWith::new(value, PrettyFormatter::new())
+    }
+}
+
 impl<'a, T: DeserializeOwned> FromRequest<'a> for Json<T> {
     async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
         let content_type = req
@@ -116,14 +136,14 @@ impl<'a, T: DeserializeOwned> FromRequest<'a> for Json<T> {
         #[cfg(not(feature = "sonic-rs"))]
         {
             Ok(Self(
-                serde_json::from_slice(&body.take()?.into_bytes().await?)
+                serde_json::from_slice(&body.take_limited(req)?.into_bytes().await?)
                     .map_err(ParseJsonError::Parse)?,
             ))
         }
         #[cfg(feature = "sonic-rs")]
         {
             Ok(Self(
-                sonic_rs::from_slice(&body.take()?.into_bytes().await?)
+                sonic_rs::from_slice(&body.take_limited(req)?.into_bytes().await?)
                     .map_err(ParseJsonError::Parse)?,
             ))
         }
@@ -160,6 +180,77 @@ impl<T: Serialize + Send> IntoResponse for Json<T> {
     }
 }
 
+/// JSON response serialized with a custom [`Formatter`].
+///
+/// It is always serialized with `serde_json`, even if the `sonic-rs`
+/// feature is enabled. The `Content-Type` is `application/json`.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, get, handler,
+///     test::TestClient,
+///     web::{Json, JsonWith},
+/// };
+/// use serde_json::{json, ser::PrettyFormatter};
+///
+/// #[handler]
+/// fn debug() -> JsonWith<serde_json::Value, PrettyFormatter<'static>> {
+///     // indented with four spaces
+///     JsonWith::new(json!({ "a": 1 }), PrettyFormatter::with_indent(b"    "))
+/// }
+///
+/// #[handler]
+/// fn pretty() -> JsonWith<serde_json::Value, PrettyFormatter<'static>> {
+///     Json::pretty(json!({ "a": 1 }))
+/// }
+///
+/// let app = Route::new()
+///     .at("/debug", get(debug))
+///     .at("/pretty", get(pretty));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/debug").send().await;
+/// resp.assert_content_type("application/json; charset=utf-8");
+/// resp.assert_text("{\n    \"a\": 1\n}").await;
+///
+/// cli.get("/pretty")
+///     .send()
+///     .await
+///     .assert_text("{\n  \"a\": 1\n}")
+///     .await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonWith<T, F> {
+    value: T,
+    formatter: F,
+}
+
+impl<T, F> JsonWith<T, F> {
+    /// Create a response that serializes `value` with `formatter`.
+    pub fn new(value: T, formatter: F) -> Self {
+        Self { value, formatter }
+    }
+}
+
+impl<T: Serialize + Send, F: Formatter + Send> IntoResponse for JsonWith<T, F> {
+    fn into_response(self) -> Response {
+        let mut data = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut data, self.formatter);
+        if let Err(err) = self.value.serialize(&mut serializer) {
+            return Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err.to_string());
+        }
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+            .body(data)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
@@ -231,4 +322,31 @@ mod tests {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_json_with() {
+        let resp = Json::pretty(CreateResource {
+            name: "abc".to_string(),
+            value: 100,
+        })
+        .into_response();
+        assert_eq!(resp.content_type(), Some("application/json; charset=utf-8"));
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "{\n  \"name\": \"abc\",\n  \"value\": 100\n}"
+        );
+
+        let resp = JsonWith::new(
+            CreateResource {
+                name: "abc".to_string(),
+                value: 100,
+            },
+            serde_json::ser::CompactFormatter,
+        )
+        .into_response();
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            r#"{"name":"abc","value":100}"#
+        );
+    }
 }
@@ -1,21 +1,37 @@
 //! Commonly used as the type of extractor or response.
 
 mod accept;
+mod accept_language;
 mod addr;
+mod attachment;
+mod body_limit;
+#[cfg(feature = "cbor")]
+mod cbor;
 #[cfg(feature = "compression")]
 mod compress;
+mod config;
 #[cfg(feature = "cookie")]
 #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
 pub mod cookie;
 mod data;
+mod deferred;
 mod form;
 mod json;
+#[cfg(feature = "msgpack")]
+mod msgpack;
 #[cfg(feature = "multipart")]
 mod multipart;
+mod ndjson;
+mod negotiate;
+mod optional;
 mod path;
+mod problem;
 mod query;
+mod raw_body;
 mod real_ip;
 mod redirect;
+#[cfg(feature = "signed-url")]
+mod signed_url;
 #[cfg(feature = "sse")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sse")))]
 pub mod sse;
@@ -23,6 +39,8 @@ pub mod sse;
 mod static_file;
 #[cfg(feature = "tempfile")]
 mod tempfile;
+#[cfg(feature = "askama")]
+mod template;
 #[cfg(feature = "xml")]
 mod xml;
 #[cfg(feature = "yaml")]
@@ -32,6 +50,8 @@ pub use headers;
 #[cfg(feature = "csrf")]
 mod csrf;
 mod typed_header;
+#[cfg(feature = "validator")]
+mod valid;
 #[cfg(feature = "websocket")]
 #[cfg_attr(docsrs, doc(cfg(feature = "websocket")))]
 pub mod websocket;
@@ -44,33 +64,57 @@ use bytes::Bytes;
 use futures_util::FutureExt;
 use http::header;
 
+#[cfg(feature = "cbor")]
+pub use self::cbor::Cbor;
 #[cfg(feature = "compression")]
 pub use self::compress::{Compress, CompressionAlgo};
 #[cfg(feature = "csrf")]
 pub use self::csrf::{CsrfToken, CsrfVerifier};
+#[cfg(feature = "msgpack")]
+pub use self::msgpack::MsgPack;
 #[cfg(feature = "multipart")]
-pub use self::multipart::{Field, Multipart};
-pub(crate) use self::path::PathDeserializer;
+pub use self::multipart::{Field, Multipart, MultipartConfig};
+#[cfg(feature = "signed-url")]
+pub use self::signed_url::{SignedUrl, SignedUrlKey};
 #[cfg(feature = "static-files")]
-pub use self::static_file::{StaticFileRequest, StaticFileResponse};
+pub(crate) use self::static_file::guess_content_type;
+#[cfg(feature = "static-files")]
+pub use self::static_file::{File, StaticFileRequest, StaticFileResponse};
 #[cfg(feature = "tempfile")]
 pub use self::tempfile::TempFile;
+#[cfg(feature = "askama")]
+pub use self::template::Template;
+#[cfg(feature = "validator")]
+pub use self::valid::Valid;
 #[cfg(feature = "xml")]
 pub use self::xml::Xml;
 #[cfg(feature = "yaml")]
 pub use self::yaml::Yaml;
 pub use self::{
     accept::Accept,
+    accept_language::AcceptLanguage,
     addr::{LocalAddr, RemoteAddr},
+    attachment::{Attachment, AttachmentType},
+    body_limit::BodyLimit,
+    config::Config,
     data::Data,
+    deferred::Deferred,
     form::Form,
-    json::Json,
-    path::Path,
+    json::{Json, JsonWith},
+    ndjson::NdJson,
+    negotiate::{Negotiate, NegotiateFormat},
+    optional::Optional,
+    path::{Path, PathParams},
+    problem::{Problem, ProblemBuilder},
     query::Query,
-    real_ip::RealIp,
+    raw_body::RawBody,
+    real_ip::{RealIp, TrustedProxies},
     redirect::Redirect,
     typed_header::TypedHeader,
 };
+pub(crate) use self::{
+    accept::parse_accept, form::is_form_content_type, path::PathDeserializer, query::parse_query,
+};
 use crate::{
     body::Body,
     error::{ReadBodyError, Result},
@@ -98,6 +142,29 @@ impl RequestBody {
         self.0.take().ok_or(ReadBodyError::BodyHasBeenTaken)
     }
 
+    /// Take a body limited by the [`BodyLimit`] of the request, or the default
+    /// limit if it has none.
+    pub(crate) fn take_limited(&mut self, req: &Request) -> Result<Body, ReadBodyError> {
+        let limit = BodyLimit::of(req)?;
+        self.take_with_limit(limit)
+    }
+
+    /// Take a body limited by the [`BodyLimit`] of the request, for the
+    /// extractors that do not buffer the body, which do not use the default
+    /// limit.
+    pub(crate) fn take_streaming(&mut self, req: &Request) -> Result<Body, ReadBodyError> {
+        let limit = BodyLimit::explicit_of(req)?;
+        self.take_with_limit(limit)
+    }
+
+    fn take_with_limit(&mut self, limit: Option<usize>) -> Result<Body, ReadBodyError> {
+        let body = self.take()?;
+        Ok(match limit {
+            Some(limit) => BodyLimit(limit).apply(body),
+            None => body,
+        })
+    }
+
     /// Returns `true` if body exists.
     #[inline]
     pub fn is_some(&self) -> bool {
@@ -163,6 +230,10 @@ impl RequestBody {
 ///
 ///    Extracts the [`Path`] from the incoming request.
 ///
+/// - **PathParams**
+///
+///    Extracts all the captures of the matched route as [`PathParams`].
+///
 /// - **Query&lt;T>**
 ///
 ///    Extracts the [`Query`] from the incoming request.
@@ -216,6 +287,11 @@ impl RequestBody {
 ///
 ///   Extracts the [`Body`] from the incoming request.
 ///
+///   The body is not buffered, so it can be consumed chunk by chunk with
+///   [`Body::into_bytes_stream`] as the data arrives. An explicit [`BodyLimit`]
+///   of the request is applied, and the stream fails once the body exceeds
+///   it.
+///
 ///   _This extractor will take over the requested body, so you should avoid
 ///   using multiple extractors of this type in one handler._
 ///
@@ -358,6 +434,12 @@ pub trait FromRequest<'a>: Sized {
 ///   `application/octet-stream`. The slice is used as the body of the
 ///   response.
 ///
+/// - **Form&lt;T>**
+///
+///   Sets the status to `OK` and the `Content-Type` to
+///   `application/x-www-form-urlencoded`. Use
+///   [`serde_urlencoded`](https://crates.io/crates/serde_urlencoded) to serialize `T`.
+///
 /// - **Html&lt;T>**
 ///
 ///   Sets the status to `OK` and the `Content-Type` to `text/html`. `T` is
@@ -368,6 +450,15 @@ pub trait FromRequest<'a>: Sized {
 ///   Sets the status to `OK` and the `Content-Type` to `application/json`. Use
 ///   [`serde_json`](https://crates.io/crates/serde_json) to serialize `T` into a json string.
 ///
+/// - **JsonWith&lt;T, F>**
+///
+///   Same as `Json<T>`, but serializes `T` with the `serde_json` formatter
+///   `F`, for example pretty-printed with [`Json::pretty`].
+///
+/// - **NdJson&lt;S>**
+///
+///   Sets the status to `OK` and the `Content-Type` to `application/x-ndjson`.
+///   Each item of the stream `S` is written as a line of JSON.
 ///
 /// - **Xml&lt;T>**
 ///
@@ -404,6 +495,10 @@ pub trait FromRequest<'a>: Sized {
 ///   Convert `T` to response and set the specified status code [`StatusCode`],
 ///   and then merge the specified [`HeaderMap`].
 ///
+/// - **TypedHeader&lt;T>**
+///
+///   Sets the status to `OK` and the typed header with an empty body.
+///
 /// - **Response**
 ///
 ///   The implementation for [`Response`] always returns itself.
@@ -767,27 +862,27 @@ impl<'a> FromRequest<'a> for &'a HeaderMap {
 }
 
 impl<'a> FromRequest<'a> for Body {
-    async fn from_request(_req: &'a Request, body: &mut RequestBody) -> Result<Self> {
-        Ok(body.take()?)
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        Ok(body.take_streaming(req)?)
     }
 }
 
 impl<'a> FromRequest<'a> for String {
-    async fn from_request(_req: &'a Request, body: &mut RequestBody) -> Result<Self> {
-        let data = body.take()?.into_bytes().await?;
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let data = body.take_limited(req)?.into_bytes().await?;
         Ok(String::from_utf8(data.to_vec()).map_err(ReadBodyError::Utf8)?)
     }
 }
 
 impl<'a> FromRequest<'a> for Bytes {
-    async fn from_request(_req: &'a Request, body: &mut RequestBody) -> Result<Self> {
-        Ok(body.take()?.into_bytes().await?)
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        Ok(body.take_limited(req)?.into_bytes().await?)
     }
 }
 
 impl<'a> FromRequest<'a> for Vec<u8> {
-    async fn from_request(_req: &'a Request, body: &mut RequestBody) -> Result<Self> {
-        Ok(body.take()?.into_vec().await?)
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        Ok(body.take_limited(req)?.into_vec().await?)
     }
 }
 
@@ -1048,4 +1143,46 @@ mod tests {
             Bytes::from_static(b"abc")
         );
     }
+
+    #[tokio::test]
+    async fn body_stream() {
+        use futures_util::{StreamExt, stream};
+
+        use crate::{Endpoint, EndpointExt, handler};
+
+        #[handler(internal)]
+        fn echo(headers: &HeaderMap, body: Body) -> Body {
+            assert!(headers.contains_key("x-name"));
+            Body::from_bytes_stream(body.into_bytes_stream())
+        }
+
+        let chunks = || {
+            stream::iter(vec![
+                Ok::<_, std::io::Error>(Bytes::from_static(b"ab")),
+                Ok(Bytes::from_static(b"cd")),
+            ])
+        };
+
+        // the chunks are forwarded as they arrive
+        let req = Request::builder()
+            .header("x-name", "a")
+            .body(Body::from_bytes_stream(chunks().chain(stream::pending())));
+        let resp = echo.call(req).await.unwrap();
+        let mut body = resp.into_body().into_bytes_stream();
+        assert_eq!(body.next().await.unwrap().unwrap(), "ab");
+        assert_eq!(body.next().await.unwrap().unwrap(), "cd");
+
+        // the stream fails once the body exceeds the limit
+        let req = Request::builder()
+            .header("x-name", "a")
+            .body(Body::from_bytes_stream(chunks()));
+        let resp = echo.data(BodyLimit(3)).call(req).await.unwrap();
+        let mut body = resp.into_body().into_bytes_stream();
+        assert_eq!(body.next().await.unwrap().unwrap(), "ab");
+        let err = body.next().await.unwrap().unwrap_err();
+        assert!(matches!(
+            ReadBodyError::from(err),
+            ReadBodyError::PayloadTooLarge
+        ));
+    }
 }
@@ -0,0 +1,240 @@
+use std::ops::{Deref, DerefMut};
+
+use http::StatusCode;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    FromRequest, IntoResponse, Request, Response, Result, error::ParseMsgPackError, http::header,
+    web::RequestBody,
+};
+
+/// MessagePack extractor and response.
+///
+/// To extract the specified type of MessagePack from the body, `T` must
+/// implement [`serde::Deserialize`]. Both the `application/msgpack` and
+/// `application/x-msgpack` content types are accepted.
+///
+/// # Errors
+///
+/// - [`ReadBodyError`](crate::error::ReadBodyError)
+/// - [`ParseMsgPackError`]
+///
+/// ```
+/// use poem::{
+///     Endpoint, Request, Route, handler,
+///     http::{Method, StatusCode, header},
+///     post,
+///     test::TestClient,
+///     web::MsgPack,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// async fn index(MsgPack(user): MsgPack<User>) -> String {
+///     format!("welcome {}!", user.name)
+/// }
+///
+/// let app = Route::new().at("/", post(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/")
+///     .body_msgpack(&User {
+///         name: "foo".to_string(),
+///     })
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("welcome foo!").await;
+/// # });
+/// ```
+///
+/// # Response
+///
+/// To serialize the specified type to MessagePack, `T` must implement
+/// [`serde::Serialize`]. Structs are serialized as maps with the field names,
+/// like JSON objects.
+///
+/// ```
+/// use poem::{
+///     Endpoint, Request, Route, get, handler, http::StatusCode, test::TestClient, web::MsgPack,
+/// };
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// async fn index() -> MsgPack<User> {
+///     MsgPack(User {
+///         name: "foo".to_string(),
+///     })
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("application/msgpack");
+/// resp.assert_msgpack(User {
+///     name: "foo".to_string(),
+/// })
+/// .await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct MsgPack<T>(pub T);
+
+impl<T> Deref for MsgPack<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for MsgPack<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T: DeserializeOwned> FromRequest<'a> for MsgPack<T> {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .ok_or(ParseMsgPackError::ContentTypeRequired)?;
+        if !is_msgpack_content_type(content_type) {
+            return Err(ParseMsgPackError::InvalidContentType(content_type.into()).into());
+        }
+
+        Ok(Self(
+            rmp_serde::from_slice(&body.take_limited(req)?.into_bytes().await?)
+                .map_err(ParseMsgPackError::Parse)?,
+        ))
+    }
+}
+
+fn is_msgpack_content_type(content_type: &str) -> bool {
+    matches!(content_type.parse::<mime::Mime>(),
+        Ok(content_type) if content_type.type_() == "application"
+        && (content_type.subtype() == "msgpack"
+        || content_type.subtype() == "x-msgpack"
+        || content_type
+            .suffix()
+            .is_some_and(|v| v == "msgpack")))
+}
+
+impl<T: Serialize + Send> IntoResponse for MsgPack<T> {
+    fn into_response(self) -> Response {
+        let data = match rmp_serde::to_vec_named(&self.0) {
+            Ok(data) => data,
+            Err(err) => {
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(err.to_string());
+            }
+        };
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/msgpack")
+            .body(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+    struct CreateResource {
+        value1: i32,
+        value2: String,
+    }
+
+    #[tokio::test]
+    async fn read_msgpack() {
+        #[handler(internal)]
+        async fn index(query: MsgPack<CreateResource>) {
+            assert_eq!(query.value1, 10);
+            assert_eq!(query.value2, "abc");
+        }
+
+        let cli = TestClient::new(index);
+        cli.post("/")
+            .body_msgpack(&CreateResource {
+                value1: 10,
+                value2: "abc".to_string(),
+            })
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        cli.post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body("{}")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        cli.post("/")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        cli.post("/")
+            .header(header::CONTENT_TYPE, "application/x-msgpack")
+            .body(
+                rmp_serde::to_vec(&CreateResource {
+                    value1: 10,
+                    value2: "abc".to_string(),
+                })
+                .unwrap(),
+            )
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        cli.post("/")
+            .header(header::CONTENT_TYPE, "application/msgpack")
+            .body(vec![0xc1])
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn write_msgpack() {
+        #[handler(internal)]
+        async fn index() -> MsgPack<CreateResource> {
+            MsgPack(CreateResource {
+                value1: 10,
+                value2: "abc".to_string(),
+            })
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/msgpack");
+        resp.assert_msgpack(CreateResource {
+            value1: 10,
+            value2: "abc".to_string(),
+        })
+        .await;
+    }
+}
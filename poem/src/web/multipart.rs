@@ -3,15 +3,19 @@ use std::{
     str::FromStr,
 };
 
-use futures_util::TryStreamExt;
+use bytes::Bytes;
+use futures_util::{Stream, TryStreamExt};
 use mime::Mime;
 #[cfg(feature = "tempfile")]
 use tokio::fs::File;
-use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::io::AsyncRead;
 #[cfg(feature = "tempfile")]
 use tokio::io::{AsyncSeekExt, SeekFrom};
 
-use crate::{FromRequest, Request, RequestBody, Result, error::ParseMultipartError, http::header};
+use crate::{
+    FromRequest, Request, RequestBody, Result, error::ParseMultipartError, http::header,
+    web::BodyLimit,
+};
 
 /// A single field in a multipart stream.
 #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
@@ -57,22 +61,20 @@ impl Field {
     }
 
     /// Get the full data of the field as bytes.
-    pub async fn bytes(self) -> Result<Vec<u8>, ParseMultipartError> {
+    pub async fn bytes(mut self) -> Result<Vec<u8>, ParseMultipartError> {
         let mut data = Vec::new();
-        let mut buf = [0; 2048];
-        let mut reader = self.into_async_read();
-        loop {
-            let sz = reader.read(&mut buf[..]).await?;
-            if sz > 0 {
-                data.extend_from_slice(&buf[..sz]);
-            } else {
-                break;
-            }
+        while let Some(chunk) = self.chunk().await? {
+            data.extend_from_slice(&chunk);
         }
-
         Ok(data)
     }
 
+    /// Yields the next chunk of the field data as it arrives, or `None` if
+    /// the field is complete.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, ParseMultipartError> {
+        Ok(self.0.chunk().await?)
+    }
+
     /// Get the full field data as text.
     #[inline]
     pub async fn text(self) -> Result<String, ParseMultipartError> {
@@ -90,18 +92,105 @@ impl Field {
         Ok(file)
     }
 
+    /// Consume this field to return a stream of the field data.
+    ///
+    /// The chunks are yielded as they are read from the request body, so the
+    /// field is never buffered in memory.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Bytes, ParseMultipartError>> + Send {
+        self.0.map_err(ParseMultipartError::Multipart)
+    }
+
     /// Consume this field to return a reader.
     pub fn into_async_read(self) -> impl AsyncRead + Send {
-        tokio_util::io::StreamReader::new(
-            self.0
-                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string())),
-        )
+        tokio_util::io::StreamReader::new(self.0.map_err(std::io::Error::other))
+    }
+}
+
+/// The size limits of a [`Multipart`] request.
+///
+/// [`Multipart`] reads this configuration from the request data, so it can be
+/// set for a group of routes with
+/// [`EndpointExt::data`](crate::EndpointExt::data). When a limit is exceeded,
+/// reading the data returns an error with the `413 Payload Too Large` status.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Result, Route,
+///     error::BadRequest,
+///     handler, post,
+///     web::{Multipart, MultipartConfig},
+/// };
+///
+/// #[handler]
+/// async fn upload(mut multipart: Multipart) -> Result<()> {
+///     while let Some(mut field) = multipart.next_field().await? {
+///         while let Some(chunk) = field.chunk().await? {
+///             println!("{} bytes", chunk.len());
+///         }
+///     }
+///     Ok(())
+/// }
+///
+/// let app = Route::new().at("/upload", post(upload)).data(
+///     MultipartConfig::new()
+///         .field_size_limit(1024 * 1024 * 1024)
+///         .total_size_limit(4 * 1024 * 1024 * 1024),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MultipartConfig {
+    field_size_limit: Option<u64>,
+    total_size_limit: Option<u64>,
+}
+
+impl MultipartConfig {
+    /// Create a configuration without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of each field in bytes.
+    #[must_use]
+    pub fn field_size_limit(self, limit: u64) -> Self {
+        Self {
+            field_size_limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the whole multipart stream in bytes.
+    #[must_use]
+    pub fn total_size_limit(self, limit: u64) -> Self {
+        Self {
+            total_size_limit: Some(limit),
+            ..self
+        }
+    }
+
+    fn constraints(&self) -> multer::Constraints {
+        let mut size_limit = multer::SizeLimit::new();
+        if let Some(limit) = self.field_size_limit {
+            size_limit = size_limit.per_field(limit);
+        }
+        if let Some(limit) = self.total_size_limit {
+            size_limit = size_limit.whole_stream(limit);
+        }
+        multer::Constraints::new().size_limit(size_limit)
     }
 }
 
 /// An extractor that parses `multipart/form-data` requests commonly used with
 /// file uploads.
 ///
+/// The fields are parsed while the request body is read, and the data of each
+/// field can be consumed as a stream with [`Field::chunk`],
+/// [`Field::into_stream`] or [`Field::into_async_read`], so large uploads are
+/// never held in memory. Size limits can be configured with
+/// [`MultipartConfig`].
+///
 /// # Errors
 ///
 /// - [`ReadBodyError`](crate::error::ReadBodyError)
@@ -147,10 +236,18 @@ impl<'a> FromRequest<'a> for Multipart {
 
         let boundary = multer::parse_boundary(content_type.as_ref())
             .map_err(ParseMultipartError::Multipart)?;
+        let mut config = req.data::<MultipartConfig>().copied().unwrap_or_default();
+        // the body limit is enforced by multer, so that it is reported as
+        // `StreamSizeExceeded` instead of a failed read
+        if let Some(limit) = BodyLimit::of(req)? {
+            let limit = limit as u64;
+            config.total_size_limit = Some(config.total_size_limit.map_or(limit, |l| l.min(limit)));
+        }
         Ok(Self {
-            inner: multer::Multipart::new(
+            inner: multer::Multipart::with_constraints(
                 tokio_util::io::ReaderStream::new(body.take()?.into_async_read()),
                 boundary,
+                config.constraints(),
             ),
         })
     }
@@ -158,6 +255,8 @@ impl<'a> FromRequest<'a> for Multipart {
 
 impl Multipart {
     /// Yields the next [`Field`] if available.
+    ///
+    /// The previous field must be dropped before the next one is requested.
     pub async fn next_field(&mut self) -> Result<Option<Field>, ParseMultipartError> {
         match self.inner.next_field().await? {
             Some(field) => Ok(Some(Field(field))),
@@ -169,7 +268,7 @@ impl Multipart {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{handler, http::StatusCode, test::TestClient};
+    use crate::{EndpointExt, handler, http::StatusCode, test::TestClient};
 
     #[tokio::test]
     async fn test_multipart_extractor_content_type() {
@@ -217,4 +316,68 @@ mod tests {
             .await;
         resp.assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn test_multipart_stream() {
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) -> String {
+            let mut field = multipart.next_field().await.unwrap().unwrap();
+            assert_eq!(field.name(), Some("file"));
+            assert_eq!(field.file_name(), Some("a.txt"));
+            let mut data = Vec::new();
+            while let Some(chunk) = field.chunk().await.unwrap() {
+                data.extend_from_slice(&chunk);
+            }
+            drop(field);
+
+            let field = multipart.next_field().await.unwrap().unwrap();
+            let rest: Vec<Bytes> = field.into_stream().try_collect().await.unwrap();
+            format!(
+                "{}:{}",
+                String::from_utf8(data).unwrap(),
+                String::from_utf8(rest.concat()).unwrap()
+            )
+        }
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\r\nhello\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\nworld\r\n--X-BOUNDARY--\r\n";
+        let resp = TestClient::new(index)
+            .post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello:world").await;
+    }
+
+    #[tokio::test]
+    async fn test_multipart_size_limit() {
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) -> Result<()> {
+            while let Some(field) = multipart.next_field().await? {
+                field.bytes().await?;
+            }
+            Ok(())
+        }
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n0123456789\r\n--X-BOUNDARY--\r\n";
+        let send = |config: MultipartConfig| async move {
+            TestClient::new(index.data(config))
+                .post("/")
+                .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+                .body(data)
+                .send()
+                .await
+        };
+
+        send(MultipartConfig::new().field_size_limit(10))
+            .await
+            .assert_status_is_ok();
+        send(MultipartConfig::new().field_size_limit(9))
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        send(MultipartConfig::new().total_size_limit(20))
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -0,0 +1,130 @@
+use std::future::ready;
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+#[cfg(not(feature = "sonic-rs"))]
+use serde_json::to_vec;
+#[cfg(feature = "sonic-rs")]
+use sonic_rs::to_vec;
+
+use crate::{Body, IntoResponse, Response};
+
+/// A [newline delimited JSON](https://github.com/ndjson/ndjson-spec) response.
+///
+/// Each item of the stream is serialized to JSON and followed by `\n`. Lines
+/// are sent as soon as the stream produces them, so the client can process
+/// the rows of a large dataset while they are still being generated.
+///
+/// The status code and headers are sent before the first item, so a
+/// serialization error can no longer turn the response into an error. If an
+/// item fails to serialize, the body ends after the previous line instead.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::stream;
+/// use poem::{Route, get, handler, test::TestClient, web::NdJson};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: i32,
+/// }
+///
+/// #[handler]
+/// fn index() -> NdJson<impl stream::Stream<Item = User> + Send> {
+///     NdJson(stream::iter((1..=3).map(|id| User { id })))
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("application/x-ndjson");
+/// resp.assert_text("{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n")
+///     .await;
+/// # });
+/// ```
+pub struct NdJson<S>(pub S);
+
+impl<S, T> IntoResponse for NdJson<S>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let stream = self.0.scan((), |_, item| {
+            ready(to_vec(&item).ok().map(|mut data| {
+                data.push(b'\n');
+                Ok::<_, std::io::Error>(Bytes::from(data))
+            }))
+        });
+
+        Response::builder()
+            .content_type("application/x-ndjson")
+            .header("X-Accel-Buffering", "no")
+            .body(Body::from_bytes_stream(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use serde::{Serializer, ser::Error};
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[tokio::test]
+    async fn ndjson() {
+        #[handler(internal)]
+        fn index() -> NdJson<impl Stream<Item = serde_json::Value> + Send> {
+            NdJson(stream::iter(vec![
+                serde_json::json!({ "a": 1 }),
+                serde_json::json!([1, 2]),
+                serde_json::json!("b"),
+            ]))
+        }
+
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/x-ndjson");
+        resp.assert_text("{\"a\":1}\n[1,2]\n\"b\"\n").await;
+    }
+
+    #[tokio::test]
+    async fn incremental() {
+        let resp = NdJson(stream::iter(vec![1, 2]).chain(stream::pending())).into_response();
+        let mut body = resp.into_body().into_bytes_stream();
+        assert_eq!(body.next().await.unwrap().unwrap(), "1\n");
+        assert_eq!(body.next().await.unwrap().unwrap(), "2\n");
+    }
+
+    #[tokio::test]
+    async fn serialize_error() {
+        enum Item {
+            Value(i32),
+            Invalid,
+        }
+
+        impl Serialize for Item {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Item::Value(value) => serializer.serialize_i32(*value),
+                    Item::Invalid => Err(S::Error::custom("invalid")),
+                }
+            }
+        }
+
+        let resp = NdJson(stream::iter(vec![
+            Item::Value(1),
+            Item::Invalid,
+            Item::Value(2),
+        ]))
+        .into_response();
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "1\n");
+    }
+}
@@ -0,0 +1,202 @@
+use http::{HeaderMap, HeaderValue, header};
+use serde::Serialize;
+
+#[cfg(feature = "cbor")]
+use crate::web::Cbor;
+#[cfg(feature = "msgpack")]
+use crate::web::MsgPack;
+use crate::{
+    FromRequest, IntoResponse, Request, RequestBody, Response, Result,
+    web::{Json, accept::parse_accept},
+};
+
+/// The serialization format chosen from the `Accept` header of the request.
+///
+/// Media types are tried in the order of their quality values, and `*/*` or
+/// `application/*` resolve to JSON. If nothing matches, JSON is used.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[non_exhaustive]
+pub enum NegotiateFormat {
+    /// `application/json`
+    #[default]
+    Json,
+    /// `application/cbor`
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    Cbor,
+    /// `application/msgpack` or `application/x-msgpack`
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    MsgPack,
+}
+
+impl NegotiateFormat {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        for mime in parse_accept(headers) {
+            if mime
+                .get_param("q")
+                .is_some_and(|q| q.as_str().parse::<f32>().is_ok_and(|q| q <= 0.0))
+            {
+                continue;
+            }
+            let format = match (mime.type_().as_str(), mime.subtype().as_str()) {
+                ("*", "*") | ("application", "*") | ("application", "json") => Some(Self::Json),
+                #[cfg(feature = "cbor")]
+                ("application", "cbor") => Some(Self::Cbor),
+                #[cfg(feature = "msgpack")]
+                ("application", "msgpack" | "x-msgpack") => Some(Self::MsgPack),
+                _ => None,
+            };
+            if let Some(format) = format {
+                return format;
+            }
+        }
+        Self::Json
+    }
+}
+
+impl<'a> FromRequest<'a> for NegotiateFormat {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self::from_headers(req.headers()))
+    }
+}
+
+/// A response that serializes `T` with the format negotiated from the
+/// `Accept` header of the request.
+///
+/// A response can not read the request, so the format is extracted with
+/// [`NegotiateFormat`] first and passed to [`Negotiate::new`]. The response
+/// also contains the `Vary: Accept` header, so caches keep the formats apart.
+///
+/// Enable the `cbor` and `msgpack` features to support CBOR and MessagePack,
+/// otherwise the response is always JSON.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, get, handler,
+///     test::TestClient,
+///     web::{Negotiate, NegotiateFormat},
+/// };
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index(format: NegotiateFormat) -> Negotiate<User> {
+///     Negotiate::new(
+///         format,
+///         User {
+///             name: "foo".to_string(),
+///         },
+///     )
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header("accept", "text/html, */*;q=0.8")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("application/json; charset=utf-8");
+/// resp.assert_text(r#"{"name":"foo"}"#).await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Negotiate<T> {
+    /// The format of the response.
+    pub format: NegotiateFormat,
+    /// The value to serialize.
+    pub value: T,
+}
+
+impl<T> Negotiate<T> {
+    /// Create a `Negotiate` response with the format and value.
+    pub fn new(format: NegotiateFormat, value: T) -> Self {
+        Self { format, value }
+    }
+}
+
+impl<T: Serialize + Send> IntoResponse for Negotiate<T> {
+    fn into_response(self) -> Response {
+        let mut resp = match self.format {
+            NegotiateFormat::Json => Json(self.value).into_response(),
+            #[cfg(feature = "cbor")]
+            NegotiateFormat::Cbor => Cbor(self.value).into_response(),
+            #[cfg(feature = "msgpack")]
+            NegotiateFormat::MsgPack => MsgPack(self.value).into_response(),
+        };
+        resp.headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+        resp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(accept: &str) -> NegotiateFormat {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, accept.parse().unwrap());
+        NegotiateFormat::from_headers(&headers)
+    }
+
+    #[test]
+    fn negotiate_format() {
+        assert_eq!(
+            NegotiateFormat::from_headers(&HeaderMap::new()),
+            NegotiateFormat::Json
+        );
+        assert_eq!(format("text/html"), NegotiateFormat::Json);
+        assert_eq!(format("*/*"), NegotiateFormat::Json);
+        assert_eq!(format("application/json"), NegotiateFormat::Json);
+
+        #[cfg(feature = "cbor")]
+        {
+            assert_eq!(format("application/cbor"), NegotiateFormat::Cbor);
+            assert_eq!(
+                format("application/json;q=0.5, application/cbor"),
+                NegotiateFormat::Cbor
+            );
+            assert_eq!(
+                format("application/cbor;q=0, */*;q=0.1"),
+                NegotiateFormat::Json
+            );
+        }
+
+        #[cfg(feature = "msgpack")]
+        {
+            assert_eq!(format("application/msgpack"), NegotiateFormat::MsgPack);
+            assert_eq!(
+                format("text/html, application/x-msgpack;q=0.9, */*;q=0.8"),
+                NegotiateFormat::MsgPack
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn negotiate() {
+        let resp = Negotiate::new(NegotiateFormat::Json, vec![1, 2, 3]).into_response();
+        assert_eq!(resp.content_type(), Some("application/json; charset=utf-8"));
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "accept");
+
+        #[cfg(feature = "msgpack")]
+        {
+            let resp = Negotiate::new(NegotiateFormat::MsgPack, vec![1, 2, 3]).into_response();
+            assert_eq!(resp.content_type(), Some("application/msgpack"));
+            assert_eq!(
+                resp.into_body().into_vec().await.unwrap(),
+                rmp_serde::to_vec(&vec![1, 2, 3]).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::ops::{Deref, DerefMut};
+
+use futures_util::FutureExt;
+use http::header;
+
+use crate::{Error, FromRequest, Request, RequestBody, Result, error::*};
+
+/// An extractor that extracts `T` if it is present in the request.
+///
+/// Unlike `Option<T>`, which returns `None` for any error, `Optional<T>`
+/// returns `None` only if the value is absent:
+///
+/// - the `Content-Type` header is missing or doesn't match the body format of
+///   `T`
+/// - an empty body can't be parsed (`Content-Length: 0` or no body at all)
+/// - a typed header is missing
+///
+/// Other errors, such as a malformed body, are still returned.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, handler,
+///     http::{StatusCode, header},
+///     post,
+///     test::TestClient,
+///     web::{Json, Optional},
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Filter {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index(filter: Optional<Json<Filter>>) -> String {
+///     match filter.0 {
+///         Some(Json(filter)) => format!("filter by {}", filter.name),
+///         None => "no filter".to_string(),
+///     }
+/// }
+///
+/// let app = Route::new().at("/", post(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/")
+///     .header(header::CONTENT_TYPE, "application/json")
+///     .body(r#"{"name": "foo"}"#)
+///     .send()
+///     .await;
+/// resp.assert_text("filter by foo").await;
+///
+/// let resp = cli.post("/").send().await;
+/// resp.assert_text("no filter").await;
+///
+/// let resp = cli
+///     .post("/")
+///     .header(header::CONTENT_TYPE, "application/json")
+///     .body("{")
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::BAD_REQUEST);
+/// # });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Optional<T>(pub Option<T>);
+
+impl<T> Deref for Optional<T> {
+    type Target = Option<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Optional<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> Optional<T> {
+    /// Consumes this extractor and returns the inner value.
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+fn is_absent(err: &Error, body_is_empty: bool) -> bool {
+    macro_rules! check_body_error {
+        ($($(#[$meta:meta])* $ty:ident),*) => {
+            $(
+                $(#[$meta])*
+                if let Some(err) = err.downcast_ref::<$ty>() {
+                    return body_is_empty
+                        || matches!(
+                            err,
+                            $ty::InvalidContentType(_) | $ty::ContentTypeRequired
+                        );
+                }
+            )*
+        };
+    }
+
+    check_body_error!(
+        ParseFormError,
+        ParseJsonError,
+        #[cfg(feature = "xml")]
+        ParseXmlError,
+        #[cfg(feature = "yaml")]
+        ParseYamlError,
+        #[cfg(feature = "cbor")]
+        ParseCborError,
+        #[cfg(feature = "msgpack")]
+        ParseMsgPackError,
+        #[cfg(feature = "multipart")]
+        ParseMultipartError
+    );
+
+    matches!(
+        err.downcast_ref::<ParseTypedHeaderError>(),
+        Some(ParseTypedHeaderError::HeaderRequired(_))
+    )
+}
+
+impl<'a, T: FromRequest<'a>> FromRequest<'a> for Optional<T> {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let body_is_empty = match &body.0 {
+            Some(body) => {
+                body.is_empty()
+                    || req
+                        .headers()
+                        .get(header::CONTENT_LENGTH)
+                        .is_some_and(|value| value == "0")
+            }
+            None => true,
+        };
+
+        // FIXME: remove the unnecessary boxed
+        // https://github.com/rust-lang/rust/issues/100013
+        match T::from_request(req, body).boxed().await {
+            Ok(value) => Ok(Self(Some(value))),
+            Err(err) if is_absent(&err, body_is_empty) => Ok(Self(None)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{
+        handler,
+        test::TestClient,
+        web::{Form, Json, TypedHeader, headers::UserAgent},
+    };
+
+    #[derive(Deserialize)]
+    struct Payload {
+        value: i32,
+    }
+
+    #[handler(internal)]
+    fn json(payload: Optional<Json<Payload>>) -> String {
+        match payload.into_inner() {
+            Some(Json(payload)) => payload.value.to_string(),
+            None => "none".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn optional_json() {
+        let cli = TestClient::new(json);
+
+        cli.post("/")
+            .content_type("application/json")
+            .body(r#"{"value": 1}"#)
+            .send()
+            .await
+            .assert_text("1")
+            .await;
+
+        // absent
+        cli.post("/").send().await.assert_text("none").await;
+        cli.post("/")
+            .content_type("application/json")
+            .send()
+            .await
+            .assert_text("none")
+            .await;
+        cli.post("/")
+            .content_type("text/plain")
+            .body(r#"{"value": 1}"#)
+            .send()
+            .await
+            .assert_text("none")
+            .await;
+
+        // invalid
+        cli.post("/")
+            .content_type("application/json")
+            .body(r#"{"value": "a"}"#)
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn optional_form_and_header() {
+        #[handler(internal)]
+        fn index(
+            form: Optional<Form<Payload>>,
+            user_agent: Optional<TypedHeader<UserAgent>>,
+        ) -> String {
+            format!(
+                "{:?} {:?}",
+                form.as_ref().map(|form| form.value),
+                user_agent.as_ref().map(|user_agent| user_agent.as_str())
+            )
+        }
+
+        let cli = TestClient::new(index);
+        cli.post("/")
+            .header(header::USER_AGENT, "test")
+            .form(&[("value", "1")])
+            .send()
+            .await
+            .assert_text(r#"Some(1) Some("test")"#)
+            .await;
+        cli.post("/").send().await.assert_text("None None").await;
+        cli.post("/")
+            .form(&[("value", "a")])
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+}
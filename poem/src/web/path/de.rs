@@ -8,12 +8,29 @@ use serde::{
 
 /// This type represents errors that can occur when deserializing.
 #[derive(Debug, Eq, PartialEq)]
-pub(crate) struct PathDeserializerError(pub(crate) String);
+pub(crate) struct PathDeserializerError {
+    /// The name of the parameter that could not be deserialized, if known.
+    pub(crate) param: Option<String>,
+    pub(crate) msg: String,
+}
+
+impl PathDeserializerError {
+    /// Attaches the name of the parameter, unless a nested value already did.
+    pub(crate) fn with_param(self, param: &str) -> Self {
+        Self {
+            param: self.param.or_else(|| Some(param.to_string())),
+            ..self
+        }
+    }
+}
 
 impl de::Error for PathDeserializerError {
     #[inline]
     fn custom<T: Display>(msg: T) -> Self {
-        PathDeserializerError(msg.to_string())
+        PathDeserializerError {
+            param: None,
+            msg: msg.to_string(),
+        }
     }
 }
 
@@ -27,9 +44,7 @@ impl std::error::Error for PathDeserializerError {
 impl fmt::Display for PathDeserializerError {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            PathDeserializerError(msg) => write!(f, "{msg}"),
-        }
+        write!(f, "{}", self.msg)
     }
 }
 
@@ -250,7 +265,7 @@ impl<'de> Deserializer<'de> for PathDeserializer<'de> {
 
 struct MapDeserializer<'de> {
     params: &'de [(String, String)],
-    value: Option<&'de str>,
+    value: Option<(&'de str, &'de str)>,
 }
 
 impl<'de> MapAccess<'de> for MapDeserializer<'de> {
@@ -262,7 +277,7 @@ impl<'de> MapAccess<'de> for MapDeserializer<'de> {
     {
         match self.params.split_first() {
             Some(((key, value), tail)) => {
-                self.value = Some(value);
+                self.value = Some((key, value));
                 self.params = tail;
                 seed.deserialize(KeyDeserializer { key }).map(Some)
             }
@@ -275,7 +290,9 @@ impl<'de> MapAccess<'de> for MapDeserializer<'de> {
         V: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(ValueDeserializer { value }),
+            Some((key, value)) => seed
+                .deserialize(ValueDeserializer { value })
+                .map_err(|err| err.with_param(key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -530,9 +547,12 @@ impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.params.split_first() {
-            Some(((_, value), tail)) => {
+            Some(((key, value), tail)) => {
                 self.params = tail;
-                Ok(Some(seed.deserialize(ValueDeserializer { value })?))
+                Ok(Some(
+                    seed.deserialize(ValueDeserializer { value })
+                        .map_err(|err| err.with_param(key))?,
+                ))
             }
             None => Ok(None),
         }
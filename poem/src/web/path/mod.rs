@@ -5,7 +5,10 @@ use std::ops::{Deref, DerefMut};
 pub(crate) use de::PathDeserializer;
 use serde::de::DeserializeOwned;
 
-use crate::{FromRequest, Request, RequestBody, Result, error::ParsePathError};
+use crate::{
+    FromRequest, Request, RequestBody, Result,
+    error::{ParsePathError, ParsePathParamError},
+};
 
 /// An extractor that will get captures from the URL and parse them using
 /// `serde`.
@@ -13,6 +16,12 @@ use crate::{FromRequest, Request, RequestBody, Result, error::ParsePathError};
 /// # Errors
 ///
 /// - [`ParsePathError`]
+/// - [`ParsePathParamError`]
+///
+/// If a parameter can not be parsed, the error contains the name of the
+/// parameter and the reason reported by its [`serde::Deserialize`]
+/// implementation. The router has already matched the request at this
+/// point, so it is a `400 Bad Request` rather than a `404 Not Found`.
 ///
 /// # Example
 ///
@@ -96,6 +105,45 @@ use crate::{FromRequest, Request, RequestBody, Result, error::ParsePathError};
 /// resp.assert_text("foo:100").await;
 /// # });
 /// ```
+///
+/// Custom types can validate the segment by deserializing through
+/// [`TryFrom`], and the error message of the conversion is returned to the
+/// client.
+///
+/// ```
+/// use poem::{Route, get, handler, http::StatusCode, test::TestClient, web::Path};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// #[serde(try_from = "String")]
+/// struct UserId(u64);
+///
+/// impl TryFrom<String> for UserId {
+///     type Error = &'static str;
+///
+///     fn try_from(value: String) -> Result<Self, Self::Error> {
+///         match value.parse() {
+///             Ok(id) if id > 0 => Ok(UserId(id)),
+///             _ => Err("expected a positive integer"),
+///         }
+///     }
+/// }
+///
+/// #[handler]
+/// async fn user_info(Path(UserId(id)): Path<UserId>) -> String {
+///     id.to_string()
+/// }
+///
+/// let app = Route::new().at("/users/:id", get(user_info));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/users/0").send().await;
+/// resp.assert_status(StatusCode::BAD_REQUEST);
+/// resp.assert_text(r#"invalid path parameter "id": expected a positive integer"#)
+///     .await;
+/// # });
+/// ```
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Path<T>(pub T);
 
@@ -113,17 +161,165 @@ impl<T> DerefMut for Path<T> {
     }
 }
 
-impl<T: DeserializeOwned> Path<T> {
-    async fn internal_from_request(req: &Request) -> Result<Self, ParsePathError> {
-        Ok(Path(
-            T::deserialize(de::PathDeserializer::new(&req.state().match_params))
-                .map_err(|_| ParsePathError)?,
-        ))
+impl<'a, T: DeserializeOwned> FromRequest<'a> for Path<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let params = &req.state().match_params;
+        T::deserialize(de::PathDeserializer::new(params))
+            .map(Path)
+            .map_err(|err| {
+                let name = match (err.param, params.as_slice()) {
+                    (Some(name), _) => name,
+                    (None, [(name, _)]) => name.clone(),
+                    (None, _) => return ParsePathError.into(),
+                };
+                ParsePathParamError {
+                    name,
+                    reason: err.msg,
+                }
+                .into()
+            })
     }
 }
 
-impl<'a, T: DeserializeOwned> FromRequest<'a> for Path<T> {
+/// An extractor that gets all the captures of the matched route as a list of
+/// names and values, in the order they appear in the path.
+///
+/// It contains the same captures that [`Path`] deserializes, including the
+/// catch-all `*name` parameters, with the values percent-decoded. It is useful
+/// for logging and generic handlers that do not know the parameters of the
+/// route.
+///
+/// # Example
+///
+/// ```
+/// use poem::{Route, get, handler, test::TestClient, web::PathParams};
+///
+/// #[handler]
+/// async fn index(params: PathParams) -> String {
+///     params
+///         .iter()
+///         .map(|(name, value)| format!("{name}={value}"))
+///         .collect::<Vec<_>>()
+///         .join(",")
+/// }
+///
+/// let app = Route::new().at("/users/:user_id/files/*path", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/users/100/files/a/b%20c.txt").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("user_id=100,path=a/b c.txt").await;
+/// # });
+/// ```
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct PathParams(pub Vec<(String, String)>);
+
+impl PathParams {
+    /// Returns the value of the capture with the specified `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl Deref for PathParams {
+    type Target = Vec<(String, String)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for PathParams {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl IntoIterator for PathParams {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> FromRequest<'a> for PathParams {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
-        Self::internal_from_request(req).await.map_err(Into::into)
+        Ok(PathParams(req.state().match_params.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{Route, get, handler, http::StatusCode, test::TestClient};
+
+    #[tokio::test]
+    async fn parse_error() {
+        #[derive(Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            name: String,
+            #[allow(dead_code)]
+            id: u32,
+        }
+
+        #[handler(internal)]
+        async fn single(_path: Path<u32>) {}
+
+        #[handler(internal)]
+        async fn tuple(_path: Path<(String, u32)>) {}
+
+        #[handler(internal)]
+        async fn strukt(_path: Path<Params>) {}
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/single/:id", get(single))
+                .at("/tuple/:name/:id", get(tuple))
+                .at("/struct/:name/:id", get(strukt)),
+        );
+
+        for path in ["/single/abc", "/tuple/a/abc", "/struct/a/abc"] {
+            let resp = cli.get(path).send().await;
+            resp.assert_status(StatusCode::BAD_REQUEST);
+            resp.assert_text(r#"invalid path parameter "id": can not parse `"abc"` to a `u32`"#)
+                .await;
+        }
+
+        cli.get("/single/1").send().await.assert_status_is_ok();
+        cli.get("/unknown/1")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn path_params() {
+        #[handler(internal)]
+        async fn index(params: PathParams) -> String {
+            assert_eq!(params.get("missing"), None);
+            format!("{:?}", params.0)
+        }
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/", get(index))
+                .nest("/a", Route::new().at("/:x/:y/*rest", get(index))),
+        );
+
+        cli.get("/").send().await.assert_text("[]").await;
+        cli.get("/a/1/%E4%BD%A0/b/c%2Fd")
+            .send()
+            .await
+            .assert_text(r#"[("x", "1"), ("y", "你"), ("rest", "b/c/d")]"#)
+            .await;
     }
 }
@@ -0,0 +1,222 @@
+use http::{StatusCode, header};
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use serde_json::{Map, Value};
+
+use crate::{IntoResponse, Response};
+
+/// A problem details object defined by [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807),
+/// which is sent with the `application/problem+json` content type.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, http::StatusCode, test::TestClient, web::Problem};
+///
+/// #[handler]
+/// fn index() -> Problem {
+///     Problem::builder(StatusCode::FORBIDDEN)
+///         .type_uri("https://example.com/probs/out-of-credit")
+///         .title("You do not have enough credit.")
+///         .detail("Your current balance is 30, but that costs 50.")
+///         .extension("balance", 30)
+///         .build()
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status(StatusCode::FORBIDDEN);
+/// resp.assert_content_type("application/problem+json");
+/// let json = resp.json().await;
+/// json.value().object().get("balance").assert_i64(30);
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    status: StatusCode,
+    type_uri: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    instance: Option<String>,
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Create a problem with the status code and its canonical reason as the
+    /// title.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            type_uri: None,
+            title: status.canonical_reason().map(ToString::to_string),
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Create a [`ProblemBuilder`] with the status code.
+    pub fn builder(status: StatusCode) -> ProblemBuilder {
+        ProblemBuilder {
+            problem: Self::new(status),
+        }
+    }
+
+    /// Returns the status code.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the URI reference that identifies the problem type.
+    ///
+    /// An absent type means `about:blank`.
+    pub fn type_uri(&self) -> Option<&str> {
+        self.type_uri.as_deref()
+    }
+
+    /// Returns the short summary of the problem type.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the explanation specific to this occurrence of the problem.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Returns the URI reference that identifies this occurrence of the
+    /// problem.
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// Returns the extension members.
+    pub fn extensions(&self) -> &Map<String, Value> {
+        &self.extensions
+    }
+}
+
+const STANDARD_MEMBERS: &[&str] = &["type", "title", "status", "detail", "instance"];
+
+impl Serialize for Problem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", self.type_uri.as_deref().unwrap_or("about:blank"))?;
+        if let Some(title) = &self.title {
+            map.serialize_entry("title", title)?;
+        }
+        map.serialize_entry("status", &self.status.as_u16())?;
+        if let Some(detail) = &self.detail {
+            map.serialize_entry("detail", detail)?;
+        }
+        if let Some(instance) = &self.instance {
+            map.serialize_entry("instance", instance)?;
+        }
+        for (key, value) in &self.extensions {
+            if !STANDARD_MEMBERS.contains(&key.as_str()) {
+                map.serialize_entry(key, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self) {
+            Ok(data) => Response::builder()
+                .status(self.status)
+                .header(header::CONTENT_TYPE, "application/problem+json")
+                .body(data),
+            Err(err) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err.to_string()),
+        }
+    }
+}
+
+/// A builder for [`Problem`].
+#[derive(Debug, Clone)]
+pub struct ProblemBuilder {
+    problem: Problem,
+}
+
+impl ProblemBuilder {
+    /// Sets the status code.
+    pub fn status(&mut self, status: StatusCode) -> &mut Self {
+        self.problem.status = status;
+        self
+    }
+
+    /// Sets the URI reference that identifies the problem type.
+    pub fn type_uri(&mut self, type_uri: impl Into<String>) -> &mut Self {
+        self.problem.type_uri = Some(type_uri.into());
+        self
+    }
+
+    /// Sets the short summary of the problem type.
+    pub fn title(&mut self, title: impl Into<String>) -> &mut Self {
+        self.problem.title = Some(title.into());
+        self
+    }
+
+    /// Sets the explanation specific to this occurrence of the problem.
+    pub fn detail(&mut self, detail: impl Into<String>) -> &mut Self {
+        self.problem.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the URI reference that identifies this occurrence of the problem.
+    pub fn instance(&mut self, instance: impl Into<String>) -> &mut Self {
+        self.problem.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member.
+    ///
+    /// Values that cannot be serialized and members named like one of the
+    /// standard fields are ignored.
+    pub fn extension(&mut self, name: impl Into<String>, value: impl Serialize) -> &mut Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.problem.extensions.insert(name.into(), value);
+        }
+        self
+    }
+
+    /// Consumes the builder state and returns the [`Problem`].
+    pub fn build(&mut self) -> Problem {
+        let status = self.problem.status;
+        std::mem::replace(&mut self.problem, Problem::new(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn problem() {
+        let resp = Problem::builder(StatusCode::NOT_FOUND)
+            .detail("user 1 not found")
+            .instance("/users/1")
+            .extension("id", 1)
+            .build()
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.content_type(), Some("application/problem+json"));
+        assert_eq!(
+            serde_json::from_slice::<Value>(&resp.into_body().into_vec().await.unwrap()).unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "detail": "user 1 not found",
+                "instance": "/users/1",
+                "id": 1,
+            })
+        );
+    }
+}
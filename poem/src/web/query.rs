@@ -1,11 +1,26 @@
 use std::ops::{Deref, DerefMut};
 
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, Error as _};
 
-use crate::{FromRequest, Request, RequestBody, Result, error::ParseQueryError};
+use crate::{
+    Error, FromRequest, Request, RequestBody, Result,
+    error::{ExtractorField, ParseQueryError},
+};
 
 /// An extractor that can deserialize some type from query string.
 ///
+/// Repeated keys are collected into sequences, so `?tags=a&tags=b` can be
+/// deserialized into a `Vec<String>` field, and a single `?tags=a` into a
+/// one element `Vec`.
+///
+/// Fields of a `#[serde(flatten)]` struct are supported, but serde passes
+/// them to the inner struct as strings, so non-string fields of a flattened
+/// struct need a `deserialize_with` function that parses them (for example
+/// `serde_with::DisplayFromStr`).
+///
+/// If a field can not be deserialized, the error message contains the name
+/// of the field.
+///
 /// # Errors
 ///
 /// - [`ParseQueryError`]
@@ -63,15 +78,41 @@ impl<T> DerefMut for Query<T> {
     }
 }
 
-impl<T: DeserializeOwned> Query<T> {
-    async fn internal_from_request(req: &Request) -> Result<Self, ParseQueryError> {
-        Ok(serde_urlencoded::from_str(req.uri().query().unwrap_or_default()).map(Self)?)
-    }
+/// Parses the query string, the error is returned with the path of the field
+/// that can not be deserialized, if any.
+fn parse_query_with_field<T: DeserializeOwned>(
+    query: &str,
+) -> Result<T, (ParseQueryError, Option<String>)> {
+    serde_path_to_error::deserialize(serde_html_form::Deserializer::from_bytes(query.as_bytes()))
+        .map_err(|err| {
+            let path = err.path().to_string();
+            let err = err.into_inner();
+            if path == "." {
+                (ParseQueryError(err), None)
+            } else {
+                let err = ParseQueryError(serde_urlencoded::de::Error::custom(format!(
+                    "{path}: {err}"
+                )));
+                (err, Some(path))
+            }
+        })
+}
+
+pub(crate) fn parse_query<T: DeserializeOwned>(query: &str) -> Result<T, ParseQueryError> {
+    parse_query_with_field(query).map_err(|(err, _)| err)
 }
 
 impl<'a, T: DeserializeOwned> FromRequest<'a> for Query<T> {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
-        Self::internal_from_request(req).await.map_err(Into::into)
+        parse_query_with_field(req.uri().query().unwrap_or_default())
+            .map(Self)
+            .map_err(|(err, field)| {
+                let mut err = Error::from(err);
+                if let Some(field) = field {
+                    err.set_data(ExtractorField(field));
+                }
+                err
+            })
     }
 }
 
@@ -80,7 +121,7 @@ mod tests {
     use serde::Deserialize;
 
     use super::*;
-    use crate::{handler, test::TestClient};
+    use crate::{handler, http::StatusCode, test::TestClient};
 
     #[tokio::test]
     async fn test_query_extractor() {
@@ -104,4 +145,84 @@ mod tests {
             .await
             .assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn test_query_repeated_and_flatten() {
+        fn from_str<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(D::Error::custom)
+        }
+
+        #[derive(Deserialize)]
+        struct Pagination {
+            #[serde(deserialize_with = "from_str")]
+            page: u32,
+            #[serde(default)]
+            sort: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct Search {
+            #[serde(default)]
+            tags: Vec<String>,
+            #[serde(flatten)]
+            pagination: Pagination,
+        }
+
+        #[handler(internal)]
+        async fn index(Query(search): Query<Search>) -> String {
+            format!(
+                "{:?}:{}:{:?}",
+                search.tags, search.pagination.page, search.pagination.sort
+            )
+        }
+
+        let cli = TestClient::new(index);
+        let resp = cli
+            .get("/")
+            .query("tags", &"a")
+            .query("tags", &"b")
+            .query("page", &2)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text(r#"["a", "b"]:2:None"#).await;
+
+        let resp = cli
+            .get("/")
+            .query("tags", &"a")
+            .query("page", &3)
+            .query("sort", &"name")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text(r#"["a"]:3:Some("name")"#).await;
+    }
+
+    #[tokio::test]
+    async fn test_query_error_field_name() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Params {
+            name: String,
+            value: i32,
+        }
+
+        #[handler(internal)]
+        async fn index(_query: Query<Params>) {}
+
+        let resp = TestClient::new(index)
+            .get("/")
+            .query("name", &"abc")
+            .query("value", &"abc")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+        resp.assert_text("value: invalid digit found in string")
+            .await;
+
+        let err = parse_query::<Params>("value=1").unwrap_err();
+        assert_eq!(err.to_string(), "missing field `name`");
+    }
 }
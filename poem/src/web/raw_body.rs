@@ -0,0 +1,111 @@
+use bytes::Bytes;
+use mime::Mime;
+
+use crate::{FromRequest, Request, RequestBody, Result, http::header};
+
+/// An extractor that reads the request body with its parsed content type.
+///
+/// `content_type` is `None` if the `Content-Type` header is missing or is not
+/// a valid media type. The body is limited by the
+/// [`BodyLimit`](crate::web::BodyLimit) of the request.
+///
+/// # Errors
+///
+/// - [`ReadBodyError`](crate::error::ReadBodyError)
+///
+/// # Example
+///
+/// ```
+/// use poem::{Route, handler, http::header, post, test::TestClient, web::RawBody};
+///
+/// #[handler]
+/// fn index(body: RawBody) -> String {
+///     match body.content_type {
+///         Some(content_type) if content_type.subtype() == mime::JSON => {
+///             format!("json: {} bytes", body.bytes.len())
+///         }
+///         _ => "unsupported".to_string(),
+///     }
+/// }
+///
+/// let app = Route::new().at("/", post(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/")
+///     .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+///     .body("{}")
+///     .send()
+///     .await;
+/// resp.assert_text("json: 2 bytes").await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawBody {
+    /// The parsed `Content-Type` header.
+    pub content_type: Option<Mime>,
+    /// The body.
+    pub bytes: Bytes,
+}
+
+impl<'a> FromRequest<'a> for RawBody {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        Ok(Self {
+            content_type,
+            bytes: body.take_limited(req)?.into_bytes().await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient, web::BodyLimit};
+
+    #[handler(internal)]
+    fn index(body: RawBody) -> String {
+        format!(
+            "{} {:?}",
+            body.content_type
+                .map(|content_type| content_type.essence_str().to_string())
+                .unwrap_or_default(),
+            body.bytes
+        )
+    }
+
+    #[tokio::test]
+    async fn raw_body() {
+        let cli = TestClient::new(index.data(BodyLimit(4)));
+
+        cli.post("/")
+            .content_type("application/graphql; charset=utf-8")
+            .body("{ a }")
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        cli.post("/")
+            .content_type("application/graphql; charset=utf-8")
+            .body("{a}")
+            .send()
+            .await
+            .assert_text(r#"application/graphql b"{a}""#)
+            .await;
+
+        cli.post("/")
+            .content_type("invalid")
+            .body("abc")
+            .send()
+            .await
+            .assert_text(r#" b"abc""#)
+            .await;
+    }
+}
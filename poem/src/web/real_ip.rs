@@ -1,15 +1,78 @@
-use std::net::IpAddr;
+use std::{net::IpAddr, sync::Arc};
 
+use ipnet::IpNet;
 use rfc7239::{NodeIdentifier, NodeName};
 
-use crate::{Addr, FromRequest, Request, RequestBody, Result};
+use crate::{
+    Addr, FromRequest, Request, RequestBody, Result,
+    middleware::{client_ip, parse_nets},
+};
+
+/// The networks of the proxies that are trusted to set the forwarding
+/// headers, used by [`RealIp`].
+///
+/// Networks are written in CIDR notation, such as `10.0.0.0/8` or
+/// `2001:db8::/32`. A plain address matches only itself.
+///
+/// # Panics
+///
+/// Panics if one of the networks is invalid.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Arc<[IpNet]>);
+
+impl TrustedProxies {
+    /// Create a set of trusted proxies from the networks.
+    pub fn new<I, T>(nets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        Self(parse_nets(nets).into())
+    }
+}
 
 /// An extractor that can extracts the real ip from request headers
+///
+/// When [`TrustedProxies`] are added to the request data, for example with
+/// [`EndpointExt::data`](crate::EndpointExt::data), the forwarding headers
+/// are only used if the peer is one of the trusted proxies, otherwise the
+/// address of the peer is returned. The proxies append the address they
+/// received the request from, so the `Forwarded` header, or the
+/// `X-Forwarded-For` header if there is none, is walked from right to left,
+/// skipping the trusted proxies. The `X-Real-IP` header is used if neither
+/// is present.
+///
+/// Without [`TrustedProxies`], the headers are always trusted, and any client
+/// can choose its address by sending them. This should only be used if the
+/// server can not be reached without going through a proxy that overwrites
+/// the headers.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     web::{RealIp, TrustedProxies},
+/// };
+///
+/// #[handler]
+/// fn index(RealIp(addr): RealIp) -> String {
+///     addr.map(|addr| addr.to_string()).unwrap_or_default()
+/// }
+///
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .data(TrustedProxies::new(["10.0.0.0/8"]));
+/// ```
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct RealIp(pub Option<IpAddr>);
 
 impl<'a> FromRequest<'a> for RealIp {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        if let Some(trusted_proxies) = req.data::<TrustedProxies>() {
+            return Ok(RealIp(client_ip(req, &trusted_proxies.0)));
+        }
+
         if let Some(real_ip) = req
             .headers()
             .get("x-real-ip")
@@ -63,11 +126,73 @@ impl<'a> FromRequest<'a> for RealIp {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::web::RemoteAddr;
 
     fn create_request(header: &str, value: &str) -> Request {
         Request::builder().header(header, value).finish()
     }
 
+    async fn trusted_real_ip(peer: &str, headers: &[(&str, &str)]) -> RealIp {
+        let mut req = Request::builder();
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let mut req = req.finish();
+        req.state_mut().remote_addr = RemoteAddr(Addr::SocketAddr(peer.parse().unwrap()));
+        req.extensions_mut()
+            .insert(TrustedProxies::new(["10.0.0.0/8"]));
+        RealIp::from_request_without_body(&req).await.unwrap()
+    }
+
+    fn ip(addr: &str) -> RealIp {
+        RealIp(Some(addr.parse().unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_trusted_proxies() {
+        // the headers of an untrusted peer are ignored
+        assert_eq!(
+            trusted_real_ip("203.0.113.1:80", &[("x-forwarded-for", "192.0.2.1")]).await,
+            ip("203.0.113.1")
+        );
+        assert_eq!(
+            trusted_real_ip("203.0.113.1:80", &[("x-real-ip", "192.0.2.1")]).await,
+            ip("203.0.113.1")
+        );
+
+        // a spoofed address in front of the chain is skipped
+        assert_eq!(
+            trusted_real_ip(
+                "10.0.0.1:80",
+                &[("x-forwarded-for", "192.0.2.1, 203.0.113.7, 10.0.0.2")]
+            )
+            .await,
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            trusted_real_ip(
+                "10.0.0.1:80",
+                &[
+                    ("forwarded", "for=192.0.2.1, for=203.0.113.7"),
+                    ("x-forwarded-for", "198.51.100.1"),
+                ]
+            )
+            .await,
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            trusted_real_ip("10.0.0.1:80", &[("x-real-ip", "203.0.113.7")]).await,
+            ip("203.0.113.7")
+        );
+
+        // only trusted proxies in the chain
+        assert_eq!(
+            trusted_real_ip("10.0.0.1:80", &[("x-forwarded-for", "10.0.0.3")]).await,
+            ip("10.0.0.3")
+        );
+        assert_eq!(trusted_real_ip("10.0.0.1:80", &[]).await, ip("10.0.0.1"));
+    }
+
     #[tokio::test]
     async fn test_realip_extractor() {
         assert_eq!(
@@ -0,0 +1,279 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ring::hmac;
+
+use crate::{FromRequest, Request, RequestBody, Result, error::SignedUrlError};
+
+const EXPIRES: &str = "expires";
+const SIGNATURE: &str = "signature";
+
+/// The key used to sign URLs and to verify them with the [`SignedUrl`]
+/// extractor.
+///
+/// The signature is an HMAC-SHA256 of the string-to-sign, encoded with the
+/// URL-safe base64 alphabet without padding. The string-to-sign is the
+/// percent-encoded path, followed by `?` and the query string without the
+/// `signature` parameter, the other parameters are kept in the order and with
+/// the encoding they have in the URL. For example, the string-to-sign of
+/// `/files/report.pdf?user=42&expires=1700000000&signature=...` is
+/// `/files/report.pdf?user=42&expires=1700000000`.
+///
+/// Add the key to the request data, for example with
+/// [`EndpointExt::data`](crate::EndpointExt::data).
+#[derive(Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "signed-url")))]
+pub struct SignedUrlKey(hmac::Key);
+
+impl SignedUrlKey {
+    /// Create a key from a secret.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self(hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref()))
+    }
+
+    /// Signs `path_and_query`, a percent-encoded path with an optional query
+    /// string, and returns it with the `expires` and `signature` parameters
+    /// appended.
+    ///
+    /// `path_and_query` must not already contain these parameters.
+    pub fn sign(&self, path_and_query: &str, expires: SystemTime) -> String {
+        let expires = expires
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let separator = if path_and_query.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let mut url = format!("{path_and_query}{separator}{EXPIRES}={expires}");
+        let signature = hmac::sign(&self.0, url.as_bytes());
+        url.push_str(&format!(
+            "&{SIGNATURE}={}",
+            URL_SAFE_NO_PAD.encode(signature.as_ref())
+        ));
+        url
+    }
+}
+
+/// An extractor that verifies the signature and the expiration time of the
+/// request URL.
+///
+/// The URL is signed with [`SignedUrlKey::sign`], and the extractor uses the
+/// [`SignedUrlKey`] in the request data to verify the original URI of the
+/// request, so the signature covers the whole path even in a nested route.
+/// The signatures are compared in constant time.
+///
+/// # Errors
+///
+/// - [`SignedUrlError`]
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     test::TestClient,
+///     web::{Path, SignedUrl, SignedUrlKey},
+/// };
+///
+/// #[handler]
+/// fn download(_signed: SignedUrl, Path(name): Path<String>) -> String {
+///     format!("contents of {name}")
+/// }
+///
+/// let key = SignedUrlKey::new(b"my secret key");
+/// let url = key.sign(
+///     "/files/report.pdf",
+///     SystemTime::now() + Duration::from_secs(3600),
+/// );
+///
+/// let app = Route::new().at("/files/:name", get(download)).data(key);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get(&url).send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("contents of report.pdf").await;
+///
+/// let resp = cli.get("/files/report.pdf").send().await;
+/// resp.assert_status(poem::http::StatusCode::FORBIDDEN);
+/// # });
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "signed-url")))]
+pub struct SignedUrl {
+    expires: SystemTime,
+}
+
+impl SignedUrl {
+    /// Returns the time when the URL expires.
+    pub fn expires(&self) -> SystemTime {
+        self.expires
+    }
+}
+
+fn verify(key: &SignedUrlKey, path: &str, query: &str) -> Result<SignedUrl, SignedUrlError> {
+    let mut signature = None;
+    let mut expires = None;
+    let mut pairs = Vec::new();
+    for pair in query.split('&') {
+        match pair.split_once('=') {
+            Some((SIGNATURE, value)) => {
+                if signature.replace(value).is_some() {
+                    return Err(SignedUrlError::InvalidSignature);
+                }
+            }
+            Some((EXPIRES, value)) if expires.is_none() => {
+                expires = Some(value);
+                pairs.push(pair);
+            }
+            _ => pairs.push(pair),
+        }
+    }
+    let (Some(signature), Some(expires)) = (signature, expires) else {
+        return Err(SignedUrlError::MissingSignature);
+    };
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| SignedUrlError::InvalidSignature)?;
+    let string_to_sign = format!("{path}?{}", pairs.join("&"));
+    hmac::verify(&key.0, string_to_sign.as_bytes(), &signature)
+        .map_err(|_| SignedUrlError::InvalidSignature)?;
+
+    let expires = expires
+        .parse::<u64>()
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or(SignedUrlError::InvalidSignature)?;
+    if SystemTime::now() >= expires {
+        return Err(SignedUrlError::Expired);
+    }
+    Ok(SignedUrl { expires })
+}
+
+impl<'a> FromRequest<'a> for SignedUrl {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        let key = req
+            .data::<SignedUrlKey>()
+            .ok_or(SignedUrlError::MissingKey)?;
+        let uri = req.original_uri();
+        Ok(verify(key, uri.path(), uri.query().unwrap_or_default())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, Route, get, handler, test::TestClient};
+
+    #[handler(internal)]
+    fn index(signed: SignedUrl) -> String {
+        signed
+            .expires()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string()
+    }
+
+    fn key() -> SignedUrlKey {
+        SignedUrlKey::new(b"secret")
+    }
+
+    fn in_one_hour() -> SystemTime {
+        SystemTime::now() + Duration::from_secs(3600)
+    }
+
+    #[test]
+    fn sign() {
+        let expires = UNIX_EPOCH + Duration::from_secs(1700000000);
+        let url = key().sign("/files/a%20b.txt?user=42", expires);
+        let (unsigned, signature) = url.split_once("&signature=").unwrap();
+        assert_eq!(unsigned, "/files/a%20b.txt?user=42&expires=1700000000");
+
+        let expected = hmac::sign(&key().0, unsigned.as_bytes());
+        assert_eq!(
+            URL_SAFE_NO_PAD.decode(signature).unwrap(),
+            expected.as_ref()
+        );
+        assert!(
+            key()
+                .sign("/files", expires)
+                .starts_with("/files?expires=1700000000&signature=")
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_url() {
+        let cli = TestClient::new(
+            Route::new()
+                .nest("/api", Route::new().at("/files/*path", get(index)))
+                .data(key()),
+        );
+
+        let expires = in_one_hour();
+        let url = key().sign("/api/files/a%20b.txt?user=42", expires);
+        cli.get(&url)
+            .send()
+            .await
+            .assert_text(
+                expires
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    .to_string(),
+            )
+            .await;
+
+        // the signature may be anywhere in the query
+        let (unsigned, signature) = url.split_once("&signature=").unwrap();
+        let (path, query) = unsigned.split_once('?').unwrap();
+        cli.get(format!("{path}?signature={signature}&{query}"))
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        for url in [
+            url.replace("a%20b", "c"),
+            url.replace("user=42", "user=43"),
+            format!("{url}&admin=true"),
+            format!("{url}&signature={signature}"),
+            url.replace(signature, "invalid"),
+        ] {
+            cli.get(&url)
+                .send()
+                .await
+                .assert_status(StatusCode::FORBIDDEN);
+        }
+
+        let url = key().sign("/api/files/a", SystemTime::now() - Duration::from_secs(1));
+        let resp = cli.get(&url).send().await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        resp.assert_text("signed url has expired").await;
+
+        let resp = cli.get("/api/files/a?expires=9999999999").send().await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        resp.assert_text("missing url signature").await;
+
+        let url = SignedUrlKey::new(b"other").sign("/api/files/a", in_one_hour());
+        cli.get(&url)
+            .send()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn missing_key() {
+        let cli = TestClient::new(index);
+        cli.get(key().sign("/", in_one_hour()))
+            .send()
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
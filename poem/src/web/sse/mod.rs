@@ -10,10 +10,11 @@ pub use response::SSE;
 mod tests {
     use std::time::Duration;
 
+    use futures_util::{StreamExt, stream};
     use tokio::{io::AsyncReadExt, time::Instant};
 
     use super::*;
-    use crate::IntoResponse;
+    use crate::{IntoResponse, handler, test::TestClient};
 
     #[tokio::test]
     async fn sse() {
@@ -71,4 +72,54 @@ data: 5
             s = now;
         }
     }
+
+    #[tokio::test]
+    async fn keep_alive_idle() {
+        #[handler(internal)]
+        fn index() -> SSE {
+            SSE::new(
+                stream::iter([Event::message("a")])
+                    .chain(stream::once(async {
+                        tokio::time::sleep(Duration::from_millis(350)).await;
+                        Event::message("b")
+                    }))
+                    .chain(stream::pending()),
+            )
+            .keep_alive(Duration::from_millis(100))
+            .retry(Duration::from_secs(3))
+        }
+
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_status_is_ok();
+        let mut body = resp.0.into_body().into_bytes_stream();
+        let mut data = Vec::new();
+        while !data.ends_with(b"data: b\n\n") {
+            data.extend_from_slice(&body.next().await.unwrap().unwrap());
+        }
+        let data = String::from_utf8(data).unwrap();
+        assert!(data.starts_with("retry: 3000\n\ndata: a\n\n:\n\n"));
+        assert!(data.matches(":\n\n").count() >= 2);
+    }
+
+    #[tokio::test]
+    async fn keep_alive_reset() {
+        let sse = SSE::new(
+            stream::iter(1..=5)
+                .then(|value| async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Event::message(value.to_string())
+                })
+                .chain(stream::pending()),
+        )
+        .keep_alive(Duration::from_millis(150));
+        let mut body = sse.into_response().into_body().into_bytes_stream();
+
+        for value in 1..=5 {
+            assert_eq!(
+                body.next().await.unwrap().unwrap(),
+                format!("data: {value}\n\n")
+            );
+        }
+        assert_eq!(body.next().await.unwrap().unwrap(), ":\n\n");
+    }
 }
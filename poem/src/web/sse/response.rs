@@ -40,6 +40,7 @@ use crate::{Body, IntoResponse, Response};
 pub struct SSE {
     stream: BoxStream<'static, Event>,
     keep_alive: Option<Duration>,
+    retry: Option<Duration>,
 }
 
 impl SSE {
@@ -48,10 +49,14 @@ impl SSE {
         Self {
             stream: stream.boxed(),
             keep_alive: None,
+            retry: None,
         }
     }
 
     /// Set the keep alive interval.
+    ///
+    /// A comment line (`:\n\n`) is sent whenever no event has been sent for
+    /// this duration, so that proxies do not close the idle connection.
     #[must_use]
     pub fn keep_alive(self, duration: Duration) -> Self {
         Self {
@@ -59,6 +64,18 @@ impl SSE {
             ..self
         }
     }
+
+    /// Set the reconnection time.
+    ///
+    /// The `retry` field is sent before the first event, and tells the client
+    /// how long to wait before reconnecting after the connection is lost.
+    #[must_use]
+    pub fn retry(self, duration: Duration) -> Self {
+        Self {
+            retry: Some(duration),
+            ..self
+        }
+    }
 }
 
 impl IntoResponse for SSE {
@@ -73,12 +90,19 @@ impl IntoResponse for SSE {
                 tokio::time::interval_at(tokio::time::Instant::now() + duration, duration);
             stream = futures_util::stream::poll_fn(move |cx| {
                 if let Poll::Ready(msg) = stream.poll_next_unpin(cx) {
+                    interval.reset();
                     return Poll::Ready(msg);
                 }
                 interval.poll_tick(cx).map(|_| Some(Ok(comment.clone())))
             })
             .boxed();
         }
+        if let Some(duration) = self.retry {
+            let retry = Event::retry(duration.as_millis() as u64);
+            stream = futures_util::stream::once(async move { Ok(Bytes::from(retry.to_string())) })
+                .chain(stream)
+                .boxed();
+        }
 
         Response::builder()
             .content_type("text/event-stream")
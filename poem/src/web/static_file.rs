@@ -1,7 +1,7 @@
 use std::{
     collections::Bound,
     fs::Metadata,
-    io::{Seek, SeekFrom},
+    io::{ErrorKind, Seek, SeekFrom},
     path::Path,
     str::FromStr,
     time::{SystemTime, UNIX_EPOCH},
@@ -15,7 +15,7 @@ use headers::{
 use http::{StatusCode, header};
 use httpdate::HttpDate;
 use mime::Mime;
-use tokio::{fs::File, io::AsyncReadExt};
+use tokio::io::AsyncReadExt;
 
 use crate::{
     Body, FromRequest, IntoResponse, Request, RequestBody, Response, Result, error::StaticFileError,
@@ -96,6 +96,140 @@ impl IntoResponse for StaticFileResponse {
     }
 }
 
+/// A file opened with async I/O, to respond a single file from a path.
+///
+/// The content type is guessed from the extension of the path. As a
+/// response, the file is streamed with the `Content-Length`, `ETag` and
+/// `Last-Modified` headers of its metadata. To also support the conditional
+/// and range requests, pass it to
+/// [`StaticFileRequest::create_response_from_file`].
+///
+/// # Errors
+///
+/// - [`StaticFileError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Result, Route, get, handler,
+///     http::{StatusCode, header},
+///     test::TestClient,
+///     web::{File, Path, StaticFileRequest, StaticFileResponse},
+/// };
+///
+/// #[handler]
+/// async fn manifest() -> Result<File> {
+///     Ok(File::open("Cargo.toml").await?)
+/// }
+///
+/// #[handler]
+/// async fn download(
+///     Path(name): Path<String>,
+///     req: StaticFileRequest,
+/// ) -> Result<StaticFileResponse> {
+///     // `name` must be validated to prevent path traversal
+///     let file = File::open(format!("{name}.toml")).await?;
+///     Ok(req.create_response_from_file(file).await?)
+/// }
+///
+/// let app = Route::new()
+///     .at("/manifest", get(manifest))
+///     .at("/download/:name", get(download));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/manifest").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/x-toml");
+///
+/// let resp = cli
+///     .get("/download/Cargo")
+///     .header(header::RANGE, "bytes=0-8")
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::PARTIAL_CONTENT);
+/// resp.assert_text("[package]").await;
+///
+/// cli.get("/download/missing")
+///     .send()
+///     .await
+///     .assert_status(StatusCode::NOT_FOUND);
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct File {
+    file: tokio::fs::File,
+    metadata: Metadata,
+    content_type: Option<String>,
+}
+
+impl File {
+    /// Opens the file at `path`.
+    ///
+    /// Returns [`StaticFileError::NotFound`] if the file does not exist or is
+    /// not a regular file, and [`StaticFileError::Forbidden`] if it cannot be
+    /// opened because of its permissions.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, StaticFileError> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|err| match err.kind() {
+                ErrorKind::NotFound => StaticFileError::NotFound,
+                ErrorKind::PermissionDenied => {
+                    StaticFileError::Forbidden(path.display().to_string())
+                }
+                _ => StaticFileError::Io(err),
+            })?;
+        let metadata = file.metadata().await?;
+        if !metadata.is_file() {
+            return Err(StaticFileError::NotFound);
+        }
+        Ok(Self {
+            file,
+            metadata,
+            content_type: guess_content_type(path, true),
+        })
+    }
+
+    /// Sets the content type, instead of the one guessed from the path.
+    #[must_use]
+    pub fn content_type(self, content_type: impl Into<String>) -> Self {
+        Self {
+            content_type: Some(content_type.into()),
+            ..self
+        }
+    }
+
+    /// Returns the metadata of the file.
+    #[inline]
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+impl IntoResponse for File {
+    fn into_response(self) -> Response {
+        let (etag, last_modified) = match self.metadata.modified() {
+            Ok(modified) => (
+                Some(etag(ino(&self.metadata), &modified, self.metadata.len())),
+                Some(HttpDate::from(modified).to_string()),
+            ),
+            Err(_) => (None, None),
+        };
+        StaticFileResponse::Ok {
+            body: Body::from_async_read(self.file),
+            content_length: self.metadata.len(),
+            content_type: self.content_type,
+            etag,
+            last_modified,
+            content_range: None,
+            cache_control: None,
+        }
+        .into_response()
+    }
+}
+
 /// An extractor for responding static files.
 #[derive(Debug)]
 pub struct StaticFileRequest {
@@ -130,33 +264,11 @@ impl StaticFileRequest {
         let mut content_length = data.len() as u64;
         let mut content_range = None;
 
-        let body = if let Some((start, end)) = self
-            .range
-            .and_then(|range| range.satisfiable_ranges(data.len() as u64).next())
-        {
-            let start = match start {
-                Bound::Included(n) => n,
-                Bound::Excluded(n) => n + 1,
-                Bound::Unbounded => 0,
-            };
-            let end = match end {
-                Bound::Included(n) => n + 1,
-                Bound::Excluded(n) => n,
-                Bound::Unbounded => content_length,
-            };
-            if end < start || end > content_length {
-                return Err(StaticFileError::RangeNotSatisfiable {
-                    size: content_length,
-                });
-            }
-
-            if start != 0 || end != content_length {
-                content_range = Some((start..end, content_length));
-            }
-
-            content_length = end - start;
+        let body = if let Some(range) = satisfiable_range(self.range, content_length)? {
+            content_range = Some((range.clone(), content_length));
+            content_length = range.end - range.start;
             Body::from_bytes(Bytes::copy_from_slice(
-                &data[start as usize..(start + content_length) as usize],
+                &data[range.start as usize..range.end as usize],
             ))
         } else {
             Body::from_bytes(Bytes::copy_from_slice(data))
@@ -191,28 +303,45 @@ impl StaticFileRequest {
         if !path.exists() || !path.is_file() {
             return Err(StaticFileError::NotFound);
         }
-        let guess = mime_guess::from_path(path);
-        let mut file = std::fs::File::open(path)?;
+        let file = std::fs::File::open(path)?;
         let metadata = file.metadata()?;
+        self.create_response_from_std_file(
+            file,
+            &metadata,
+            guess_content_type(path, prefer_utf8),
+            no_cache,
+        )
+    }
 
+    /// Create static file response from a [`File`].
+    pub async fn create_response_from_file(
+        self,
+        file: File,
+    ) -> Result<StaticFileResponse, StaticFileError> {
+        let File {
+            file,
+            metadata,
+            content_type,
+        } = file;
+        self.create_response_from_std_file(file.into_std().await, &metadata, content_type, false)
+    }
+
+    fn create_response_from_std_file(
+        self,
+        mut file: std::fs::File,
+        metadata: &Metadata,
+        content_type: Option<String>,
+        no_cache: bool,
+    ) -> Result<StaticFileResponse, StaticFileError> {
         // content length
         let mut content_length = metadata.len();
 
-        // content type
-        let content_type = guess.first().map(|mime| {
-            if prefer_utf8 {
-                equiv_utf8_text(mime).to_string()
-            } else {
-                mime.to_string()
-            }
-        });
-
         // etag and last modified
         let mut etag_str = String::new();
         let mut last_modified_str = String::new();
 
         if let Ok(modified) = metadata.modified() {
-            etag_str = etag(ino(&metadata), &modified, metadata.len());
+            etag_str = etag(ino(metadata), &modified, metadata.len());
             let etag = ETag::from_str(&etag_str).unwrap();
 
             if let Some(if_match) = self.if_match {
@@ -242,35 +371,13 @@ impl StaticFileRequest {
 
         let mut content_range = None;
 
-        let body = if let Some((start, end)) = self
-            .range
-            .and_then(|range| range.satisfiable_ranges(metadata.len()).next())
-        {
-            let start = match start {
-                Bound::Included(n) => n,
-                Bound::Excluded(n) => n + 1,
-                Bound::Unbounded => 0,
-            };
-            let end = match end {
-                Bound::Included(n) => n + 1,
-                Bound::Excluded(n) => n,
-                Bound::Unbounded => metadata.len(),
-            };
-            if end < start || end > metadata.len() {
-                return Err(StaticFileError::RangeNotSatisfiable {
-                    size: metadata.len(),
-                });
-            }
-
-            if start != 0 || end != metadata.len() {
-                content_range = Some((start..end, metadata.len()));
-            }
-
-            content_length = end - start;
-            file.seek(SeekFrom::Start(start))?;
-            Body::from_async_read(File::from_std(file).take(end - start))
+        let body = if let Some(range) = satisfiable_range(self.range, metadata.len())? {
+            content_range = Some((range.clone(), metadata.len()));
+            content_length = range.end - range.start;
+            file.seek(SeekFrom::Start(range.start))?;
+            Body::from_async_read(tokio::fs::File::from_std(file).take(content_length))
         } else {
-            Body::from_async_read(File::from_std(file))
+            Body::from_async_read(tokio::fs::File::from_std(file))
         };
 
         Ok(StaticFileResponse::Ok {
@@ -297,6 +404,55 @@ impl StaticFileRequest {
     }
 }
 
+/// Returns the byte range of the content to send for the `Range` header, or
+/// `None` if the whole content should be sent.
+///
+/// Only the first range of a multi-range request is served. An end position
+/// past the content is clamped to its size, as defined in
+/// [RFC 7233](https://www.rfc-editor.org/rfc/rfc7233#section-2.1).
+fn satisfiable_range(
+    range: Option<Range>,
+    size: u64,
+) -> Result<Option<std::ops::Range<u64>>, StaticFileError> {
+    // A suffix longer than the content is not yielded, and selects the whole
+    // content.
+    let Some((start, end)) = range.and_then(|range| range.satisfiable_ranges(size).next()) else {
+        return Ok(None);
+    };
+
+    let start = match start {
+        Bound::Included(n) => n,
+        Bound::Excluded(n) => n.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match end {
+        Bound::Included(n) => n.saturating_add(1),
+        Bound::Excluded(n) => n,
+        Bound::Unbounded => size,
+    }
+    .min(size);
+    if start >= end {
+        return Err(StaticFileError::RangeNotSatisfiable { size });
+    }
+
+    if start == 0 && end == size {
+        Ok(None)
+    } else {
+        Ok(Some(start..end))
+    }
+}
+
+/// Guesses the content type from the extension of the path.
+pub(crate) fn guess_content_type(path: &Path, prefer_utf8: bool) -> Option<String> {
+    mime_guess::from_path(path).first().map(|mime| {
+        if prefer_utf8 {
+            equiv_utf8_text(mime).to_string()
+        } else {
+            mime.to_string()
+        }
+    })
+}
+
 fn equiv_utf8_text(ct: Mime) -> Mime {
     if ct == mime::APPLICATION_JAVASCRIPT {
         return mime::APPLICATION_JAVASCRIPT_UTF_8;
@@ -553,13 +709,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_satisfiable_range() {
+        let range = |value: &str| {
+            let mut headers = http::HeaderMap::new();
+            headers.insert(header::RANGE, value.parse().unwrap());
+            headers.typed_get::<Range>()
+        };
+
+        assert_eq!(satisfiable_range(None, 100).unwrap(), None);
+        assert_eq!(
+            satisfiable_range(range("bytes=10-19"), 100).unwrap(),
+            Some(10..20)
+        );
+        assert_eq!(
+            satisfiable_range(range("bytes=90-"), 100).unwrap(),
+            Some(90..100)
+        );
+        assert_eq!(
+            satisfiable_range(range("bytes=-30"), 100).unwrap(),
+            Some(70..100)
+        );
+        assert_eq!(
+            satisfiable_range(range("bytes=50-1000"), 100).unwrap(),
+            Some(50..100)
+        );
+        assert_eq!(
+            satisfiable_range(range("bytes=0-9, 20-29"), 100).unwrap(),
+            Some(0..10)
+        );
+        assert_eq!(satisfiable_range(range("bytes=0-99"), 100).unwrap(), None);
+        assert_eq!(satisfiable_range(range("bytes=-200"), 100).unwrap(), None);
+
+        for value in ["bytes=100-", "bytes=200-300", "bytes=-0"] {
+            assert!(matches!(
+                satisfiable_range(range(value), 100),
+                Err(StaticFileError::RangeNotSatisfiable { size: 100 })
+            ));
+        }
+    }
+
     #[tokio::test]
     async fn test_range_413() {
         let md = std::fs::metadata("Cargo.toml").unwrap();
 
         let static_file = StaticFileRequest::from_request_without_body(
             &Request::builder()
-                .typed_header(Range::bytes(0..md.len() + 1).unwrap())
+                .typed_header(Range::bytes(md.len()..md.len() + 1).unwrap())
                 .finish(),
         )
         .await
@@ -604,4 +800,46 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[tokio::test]
+    async fn test_file() {
+        let md = std::fs::metadata("Cargo.toml").unwrap();
+        let resp = File::open("Cargo.toml").await.unwrap().into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.content_type(), Some("text/x-toml"));
+        assert_eq!(
+            resp.headers().get(header::CONTENT_LENGTH).unwrap(),
+            &md.len().to_string()
+        );
+        assert!(resp.headers().contains_key(header::ETAG));
+        assert!(resp.headers().contains_key(header::LAST_MODIFIED));
+        assert_eq!(
+            resp.into_body().into_vec().await.unwrap(),
+            std::fs::read("Cargo.toml").unwrap()
+        );
+
+        let static_file = StaticFileRequest::from_request_without_body(
+            &Request::builder()
+                .typed_header(Range::bytes(1..4).unwrap())
+                .finish(),
+        )
+        .await
+        .unwrap();
+        let resp = static_file
+            .create_response_from_file(File::open("Cargo.toml").await.unwrap())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "pac");
+
+        assert!(matches!(
+            File::open("missing").await,
+            Err(StaticFileError::NotFound)
+        ));
+        assert!(matches!(
+            File::open("src").await,
+            Err(StaticFileError::NotFound)
+        ));
+    }
 }
@@ -20,8 +20,11 @@ use crate::{FromRequest, Request, RequestBody, Result, error::ReadBodyError};
 pub struct TempFile(File);
 
 impl TempFile {
-    async fn internal_from_request(body: &mut RequestBody) -> Result<Self, ReadBodyError> {
-        let body = body.take()?;
+    async fn internal_from_request(
+        req: &Request,
+        body: &mut RequestBody,
+    ) -> Result<Self, ReadBodyError> {
+        let body = body.take_streaming(req)?;
         let mut reader = body.into_async_read();
         let mut file = tokio::fs::File::from_std(::libtempfile::tempfile()?);
         tokio::io::copy(&mut reader, &mut file).await?;
@@ -31,8 +34,10 @@ impl TempFile {
 }
 
 impl<'a> FromRequest<'a> for TempFile {
-    async fn from_request(_req: &'a Request, body: &mut RequestBody) -> Result<Self> {
-        Self::internal_from_request(body).await.map_err(Into::into)
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        Self::internal_from_request(req, body)
+            .await
+            .map_err(Into::into)
     }
 }
 
@@ -0,0 +1,151 @@
+use http::StatusCode;
+
+use crate::{IntoResponse, Response, http::header};
+
+/// An [`askama`] template response.
+///
+/// The template is rendered when the response is created, and sent with the
+/// `text/html; charset=utf-8` content type. If rendering fails, the response
+/// is `500 Internal Server Error`.
+///
+/// Askama does not expose the file extension of a template, so templates
+/// that do not produce HTML should set the content type with
+/// [`Template::with_content_type`].
+///
+/// # Example
+///
+/// ```
+/// use askama::Template;
+/// use poem::{Route, get, handler, test::TestClient, web};
+///
+/// #[derive(Template)]
+/// #[template(source = "<h1>Hello, {{ name }}!</h1>", ext = "html")]
+/// struct Hello {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index() -> web::Template<Hello> {
+///     web::Template::new(Hello {
+///         name: "poem".to_string(),
+///     })
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/html; charset=utf-8");
+/// resp.assert_text("<h1>Hello, poem!</h1>").await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template<T> {
+    template: T,
+    content_type: &'static str,
+}
+
+impl<T> Template<T> {
+    /// Create a template response.
+    pub fn new(template: T) -> Self {
+        Self {
+            template,
+            content_type: "text/html; charset=utf-8",
+        }
+    }
+
+    /// Sets the content type of the response.
+    ///
+    /// Default is `text/html; charset=utf-8`.
+    #[must_use]
+    pub fn with_content_type(self, content_type: &'static str) -> Self {
+        Self {
+            content_type,
+            ..self
+        }
+    }
+}
+
+impl<T> From<T> for Template<T> {
+    fn from(template: T) -> Self {
+        Self::new(template)
+    }
+}
+
+impl<T: askama::Template + Send> IntoResponse for Template<T> {
+    fn into_response(self) -> Response {
+        match self.template.render() {
+            Ok(body) => Response::builder()
+                .header(header::CONTENT_TYPE, self.content_type)
+                .body(body),
+            Err(err) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display, Formatter};
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[derive(askama::Template)]
+    #[template(source = "<p>{{ text }}</p>", ext = "html")]
+    struct Page<'a> {
+        text: &'a str,
+    }
+
+    #[derive(askama::Template)]
+    #[template(source = "{{ text }}", ext = "txt")]
+    struct Plain<'a> {
+        text: &'a str,
+    }
+
+    struct Failing;
+
+    impl Display for Failing {
+        fn fmt(&self, _f: &mut Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[derive(askama::Template)]
+    #[template(source = "{{ value }}", ext = "txt")]
+    struct Broken {
+        value: Failing,
+    }
+
+    #[tokio::test]
+    async fn render() {
+        #[handler(internal)]
+        fn index() -> Template<Page<'static>> {
+            Template::new(Page { text: "<a & b>" })
+        }
+
+        let resp = TestClient::new(index).get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("text/html; charset=utf-8");
+        resp.assert_text("<p>&#60;a &#38; b&#62;</p>").await;
+    }
+
+    #[tokio::test]
+    async fn content_type() {
+        let resp = Template::new(Plain { text: "<a & b>" })
+            .with_content_type("text/plain; charset=utf-8")
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.content_type(), Some("text/plain; charset=utf-8"));
+        assert_eq!(resp.into_body().into_string().await.unwrap(), "<a & b>");
+    }
+
+    #[test]
+    fn render_error() {
+        let resp = Template::new(Broken { value: Failing }).into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
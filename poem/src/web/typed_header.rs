@@ -2,10 +2,16 @@ use std::ops::{Deref, DerefMut};
 
 use headers::{Header, HeaderMapExt};
 
-use crate::{FromRequest, Request, RequestBody, Result, error::ParseTypedHeaderError};
+use crate::{
+    Error, FromRequest, IntoResponse, Request, RequestBody, Response, Result,
+    error::{ExtractorField, ParseTypedHeaderError},
+};
 
 /// An extractor that extracts a typed header value.
 ///
+/// If the header is missing or malformed, a `400 Bad Request` response is
+/// returned.
+///
 /// # Errors
 ///
 /// - [`ParseTypedHeaderError`]
@@ -38,6 +44,32 @@ use crate::{FromRequest, Request, RequestBody, Result, error::ParseTypedHeaderEr
 /// resp.assert_text("example.com").await;
 /// # });
 /// ```
+///
+/// # Response
+///
+/// Returns a response with the typed header and an empty body.
+///
+/// ```
+/// use poem::{
+///     Route, get, handler,
+///     test::TestClient,
+///     web::{TypedHeader, headers::CacheControl},
+/// };
+///
+/// #[handler]
+/// fn index() -> TypedHeader<CacheControl> {
+///     TypedHeader(CacheControl::new().with_no_store())
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_header("cache-control", "no-store");
+/// # });
+/// ```
 #[derive(Debug)]
 pub struct TypedHeader<T>(pub T);
 
@@ -66,17 +98,34 @@ impl<T: Header> TypedHeader<T> {
 
 impl<'a, T: Header> FromRequest<'a> for TypedHeader<T> {
     async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
-        Self::internal_from_request(req).await.map_err(Into::into)
+        Self::internal_from_request(req).await.map_err(|err| {
+            let is_parse_error = matches!(err, ParseTypedHeaderError::TypedHeader(_));
+            let mut err = Error::from(err);
+            if is_parse_error {
+                err.set_data(ExtractorField(T::name().to_string()));
+            }
+            err
+        })
+    }
+}
+
+impl<T: Header + Send> IntoResponse for TypedHeader<T> {
+    fn into_response(self) -> Response {
+        let mut resp = Response::default();
+        resp.headers_mut().typed_insert(self.0);
+        resp
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use http::StatusCode;
+
     use super::*;
     use crate::{
         handler,
         test::TestClient,
-        web::headers::{ContentLength, Host},
+        web::headers::{ContentLength, ContentType, Host},
     };
 
     #[tokio::test]
@@ -106,4 +155,24 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[tokio::test]
+    async fn test_typed_header_extractor_malformed() {
+        #[handler(internal)]
+        async fn index(_content_length: TypedHeader<ContentLength>) {}
+
+        TestClient::new(index)
+            .get("/")
+            .header("content-length", "abc")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_typed_header_response() {
+        let resp = TypedHeader(ContentType::json()).into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.content_type(), Some("application/json"));
+    }
 }
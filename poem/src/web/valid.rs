@@ -0,0 +1,205 @@
+use std::ops::{Deref, DerefMut};
+
+use futures_util::FutureExt;
+use validator::Validate;
+
+use crate::{FromRequest, Request, RequestBody, Result, error::ValidationError};
+
+/// An extractor that validates the value extracted by another extractor with
+/// the [`validator`] crate.
+///
+/// It wraps any extractor that dereferences to a type implementing
+/// [`Validate`], such as [`Json`](crate::web::Json),
+/// [`Form`](crate::web::Form) or [`Query`](crate::web::Query). The inner
+/// extractor runs first, so its own errors, for example a malformed JSON body,
+/// are returned unchanged.
+///
+/// # Errors
+///
+/// - The errors of the inner extractor
+/// - [`ValidationError`], a `422 Unprocessable Entity` response with a JSON
+///   body of the field errors
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, handler,
+///     http::StatusCode,
+///     post,
+///     test::TestClient,
+///     web::{Json, Valid},
+/// };
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct SignupForm {
+///     #[validate(email)]
+///     email: String,
+///     #[validate(length(min = 8))]
+///     password: String,
+/// }
+///
+/// #[handler]
+/// fn signup(Valid(Json(form)): Valid<Json<SignupForm>>) -> String {
+///     form.email
+/// }
+///
+/// let app = Route::new().at("/signup", post(signup));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .post("/signup")
+///     .body_json(&serde_json::json!({ "email": "a@b.com", "password": "12345678" }))
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("a@b.com").await;
+///
+/// let resp = cli
+///     .post("/signup")
+///     .body_json(&serde_json::json!({ "email": "a@b.com", "password": "short" }))
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+/// resp.assert_json(serde_json::json!({
+///     "errors": [{ "field": "password", "code": "length", "message": null }]
+/// }))
+/// .await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "validator")))]
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Valid<T>(pub T);
+
+impl<T> Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Valid<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T> FromRequest<'a> for Valid<T>
+where
+    T: FromRequest<'a> + Deref,
+    T::Target: Validate,
+{
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        // FIXME: remove the unnecessary boxed
+        // https://github.com/rust-lang/rust/issues/100013
+        let value = T::from_request(req, body).boxed().await?;
+        value.validate().map_err(ValidationError)?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        handler,
+        http::StatusCode,
+        test::TestClient,
+        web::{Form, Json, Query},
+    };
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Address {
+        #[validate(length(min = 1))]
+        city: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(email(message = "invalid email"))]
+        email: String,
+        #[validate(range(min = 18))]
+        age: u32,
+        #[validate(nested)]
+        address: Address,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Search {
+        #[validate(length(max = 3))]
+        q: String,
+    }
+
+    #[tokio::test]
+    async fn valid_json() {
+        #[handler(internal)]
+        async fn index(Valid(Json(signup)): Valid<Json<Signup>>) -> String {
+            signup.email
+        }
+
+        let cli = TestClient::new(index);
+
+        let resp = cli
+            .post("/")
+            .body_json(&json!({ "email": "a@b.com", "age": 20, "address": { "city": "x" } }))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("a@b.com").await;
+
+        let resp = cli
+            .post("/")
+            .body_json(&json!({ "email": "abc", "age": 10, "address": { "city": "" } }))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        resp.assert_content_type("application/json; charset=utf-8");
+        resp.assert_json(json!({
+            "errors": [
+                { "field": "address.city", "code": "length", "message": null },
+                { "field": "age", "code": "range", "message": null },
+                { "field": "email", "code": "email", "message": "invalid email" },
+            ]
+        }))
+        .await;
+
+        // errors of the inner extractor are unchanged
+        let resp = cli.post("/").body_json(&json!({ "email": 1 })).send().await;
+        resp.assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn valid_form_and_query() {
+        #[handler(internal)]
+        async fn form(Valid(Form(search)): Valid<Form<Search>>) -> String {
+            search.q
+        }
+
+        #[handler(internal)]
+        async fn query(Valid(Query(search)): Valid<Query<Search>>) -> String {
+            search.q
+        }
+
+        let resp = TestClient::new(form)
+            .post("/")
+            .form(&[("q", "abc")])
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+
+        let resp = TestClient::new(query)
+            .get("/")
+            .query("q", &"abcd")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, future::Future};
+use std::{borrow::Cow, future::Future, time::Duration};
 
 use futures_util::{FutureExt, future::BoxFuture};
 use headers::HeaderMapExt;
@@ -6,7 +6,7 @@ use tokio_tungstenite::tungstenite::protocol::{Role, WebSocketConfig};
 
 use super::{WebSocketStream, utils::sign};
 use crate::{
-    Body, FromRequest, IntoResponse, OnUpgrade, Request, RequestBody, Response, Result,
+    Body, Error, FromRequest, IntoResponse, OnUpgrade, Request, RequestBody, Response, Result,
     error::WebSocketError,
     http::{
         Method, StatusCode,
@@ -14,6 +14,8 @@ use crate::{
     },
 };
 
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 << 20;
+
 /// An extractor that can accept websocket connections.
 ///
 /// # Errors
@@ -24,7 +26,9 @@ pub struct WebSocket {
     on_upgrade: OnUpgrade,
     protocols: Option<Box<[Cow<'static, str>]>>,
     sec_websocket_protocol: Option<HeaderValue>,
-    config: Option<WebSocketConfig>,
+    require_protocol: bool,
+    keepalive: Option<(Duration, Duration)>,
+    config: WebSocketConfig,
 }
 
 impl WebSocket {
@@ -63,7 +67,9 @@ impl WebSocket {
             on_upgrade: req.take_upgrade()?,
             protocols: None,
             sec_websocket_protocol,
-            config: None,
+            require_protocol: false,
+            keepalive: None,
+            config: WebSocketConfig::default().max_message_size(Some(DEFAULT_MAX_MESSAGE_SIZE)),
         })
     }
 }
@@ -77,9 +83,13 @@ impl<'a> FromRequest<'a> for WebSocket {
 impl WebSocket {
     /// Set the known protocols.
     ///
-    /// If the protocol name specified by `Sec-WebSocket-Protocol` header
-    /// to match any of them, the upgrade response will include
-    /// `Sec-WebSocket-Protocol` header and return the protocol name.
+    /// The first protocol in the `Sec-WebSocket-Protocol` header of the
+    /// request that matches any of them is selected, the upgrade response
+    /// will include `Sec-WebSocket-Protocol` header with the protocol name,
+    /// and [`WebSocketStream::protocol`] returns it.
+    ///
+    /// If none of them are offered by the client, the connection is accepted
+    /// without a protocol, unless [`WebSocket::require_protocol`] is called.
     ///
     /// ```
     /// use futures_util::{SinkExt, StreamExt};
@@ -89,7 +99,14 @@ impl WebSocket {
     /// async fn index(ws: WebSocket) -> impl IntoResponse {
     ///     ws.protocols(vec!["graphql-rs", "graphql-transport-ws"])
     ///         .on_upgrade(|socket| async move {
-    ///             // ...
+    ///             match socket.protocol() {
+    ///                 Some("graphql-rs") => {
+    ///                     // ...
+    ///                 }
+    ///                 _ => {
+    ///                     // ...
+    ///                 }
+    ///             }
     ///         })
     /// }
     ///
@@ -111,10 +128,70 @@ impl WebSocket {
         self
     }
 
+    /// Rejects the upgrade with [`WebSocketError::UnsupportedProtocol`] if the
+    /// client does not offer any of the protocols set by
+    /// [`WebSocket::protocols`].
+    #[must_use]
+    pub fn require_protocol(self) -> Self {
+        Self {
+            require_protocol: true,
+            ..self
+        }
+    }
+
+    /// Enables automatic keep-alive.
+    ///
+    /// A `Ping` frame is sent every `interval`, and if no frame is received
+    /// from the peer within `timeout` after a ping, the connection is closed
+    /// with [`CloseCode::Away`](super::CloseCode::Away) and the stream yields
+    /// an error of kind [`TimedOut`](std::io::ErrorKind::TimedOut).
+    ///
+    /// The `Pong` frames answering these pings are not yielded by the stream.
+    /// Pings are only sent while the stream is polled for messages.
+    #[must_use]
+    pub fn keepalive(self, interval: Duration, timeout: Duration) -> Self {
+        Self {
+            keepalive: Some((interval, timeout)),
+            ..self
+        }
+    }
+
     /// Set the WebSocket configuration.
+    ///
+    /// This replaces the whole configuration, so the default message size
+    /// limit of 16 MiB is replaced by the one of `config`. To only change the
+    /// limits, use [`WebSocket::max_message_size`] and
+    /// [`WebSocket::max_frame_size`], which can also be called after this
+    /// method.
     pub fn config(self, config: WebSocketConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Set the maximum size of an incoming message.
+    ///
+    /// If a message exceeds this size, the connection is closed with
+    /// [`CloseCode::Size`](super::CloseCode::Size) and the stream yields an
+    /// error of kind [`InvalidData`](std::io::ErrorKind::InvalidData).
+    ///
+    /// Default is 16 MiB.
+    #[must_use]
+    pub fn max_message_size(self, size: usize) -> Self {
+        Self {
+            config: self.config.max_message_size(Some(size)),
+            ..self
+        }
+    }
+
+    /// Set the maximum size of a single incoming frame.
+    ///
+    /// If a frame exceeds this size, the connection is closed in the same way
+    /// as for [`WebSocket::max_message_size`].
+    ///
+    /// Default is 16 MiB.
+    #[must_use]
+    pub fn max_frame_size(self, size: usize) -> Self {
         Self {
-            config: Some(config),
+            config: self.config.max_frame_size(Some(size)),
             ..self
         }
     }
@@ -182,8 +259,13 @@ where
                     .split(',')
                     .map(|req_p| req_p.trim())
                     .find(|req_p| protocols.iter().any(|p| p == req_p))
+                    .map(ToString::to_string)
             });
 
+        if protocol.is_none() && self.websocket.require_protocol {
+            return Error::from(WebSocketError::UnsupportedProtocol).into_response();
+        }
+
         let mut builder = Response::builder()
             .status(StatusCode::SWITCHING_PROTOCOLS)
             .header(header::CONNECTION, "upgrade")
@@ -193,7 +275,7 @@ where
                 sign(self.websocket.key.as_bytes()),
             );
 
-        if let Some(protocol) = protocol {
+        if let Some(protocol) = &protocol {
             builder = builder.header(
                 header::SEC_WEBSOCKET_PROTOCOL,
                 HeaderValue::from_str(protocol).unwrap(),
@@ -211,10 +293,15 @@ where
             let stream = tokio_tungstenite::WebSocketStream::from_raw_socket(
                 upgraded,
                 Role::Server,
-                self.websocket.config,
+                Some(self.websocket.config),
             )
             .await;
-            (self.callback)(WebSocketStream::new(stream)).await;
+            (self.callback)(WebSocketStream::new(
+                stream,
+                protocol,
+                self.websocket.keepalive,
+            ))
+            .await;
         });
 
         resp
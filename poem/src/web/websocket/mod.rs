@@ -33,15 +33,17 @@ pub use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 
 #[cfg(test)]
 mod tests {
-    use std::net::SocketAddr;
+    use std::{net::SocketAddr, time::Duration};
 
     use futures_util::{SinkExt, StreamExt};
     use http::{HeaderValue, header};
+    use tokio::sync::mpsc;
 
     use super::*;
     use crate::{
-        IntoResponse, Server, handler,
+        EndpointExt, IntoResponse, Route, Server, handler,
         listener::{Acceptor, Listener, TcpListener},
+        web::Data,
     };
 
     #[tokio::test]
@@ -95,6 +97,223 @@ mod tests {
         handle.abort();
     }
 
+    #[tokio::test]
+    async fn test_selected_protocol() {
+        #[handler(internal)]
+        async fn index(ws: WebSocket) -> impl IntoResponse {
+            ws.protocols(["v1.json", "v1.msgpack"])
+                .on_upgrade(|mut socket| async move {
+                    let protocol = socket.protocol().unwrap_or("none").to_string();
+                    let _ = socket.send(Message::Text(protocol)).await;
+                })
+        }
+
+        #[handler(internal)]
+        async fn required(ws: WebSocket) -> impl IntoResponse {
+            ws.protocols(["v1.json"])
+                .require_protocol()
+                .on_upgrade(|_| async move {})
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = Server::new_with_acceptor(acceptor)
+                .run(Route::new().at("/", index).at("/required", required))
+                .await;
+        });
+
+        let connect = |path: &str, protocol: Option<&str>| {
+            let mut builder = http::Request::builder()
+                .uri(format!("ws://{addr}{path}"))
+                .header(header::SEC_WEBSOCKET_KEY, "test_key")
+                .header(header::UPGRADE, "websocket")
+                .header(header::HOST, "localhost")
+                .header(header::CONNECTION, "upgrade")
+                .header(header::SEC_WEBSOCKET_VERSION, "13");
+            if let Some(protocol) = protocol {
+                builder = builder.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+            }
+            tokio_tungstenite::connect_async(builder.body(()).unwrap())
+        };
+
+        for (protocol, expected) in [
+            (Some("v1.msgpack, v1.json"), "v1.msgpack"),
+            (Some("v2.json, v1.json"), "v1.json"),
+            (None, "none"),
+        ] {
+            let (mut stream, _) = connect("/", protocol).await.unwrap();
+            assert_eq!(
+                stream.next().await.unwrap().unwrap(),
+                tokio_tungstenite::tungstenite::Message::Text(expected.into())
+            );
+        }
+
+        assert!(connect("/required", Some("v1.json")).await.is_ok());
+        match connect("/required", Some("v2.json")).await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(resp)) => {
+                assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+            }
+            _ => panic!("expected the upgrade to be rejected"),
+        }
+        assert!(connect("/required", None).await.is_err());
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_keepalive() {
+        type Sender = mpsc::UnboundedSender<Option<std::io::Result<Message>>>;
+
+        #[handler(internal)]
+        async fn index(ws: WebSocket, tx: Data<&Sender>) -> impl IntoResponse {
+            let tx = tx.clone();
+            ws.keepalive(Duration::from_millis(50), Duration::from_millis(100))
+                .on_upgrade(|mut socket| async move {
+                    let _ = tx.send(socket.next().await);
+                })
+        }
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<Option<std::io::Result<Message>>>();
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = Server::new_with_acceptor(acceptor)
+                .run(index.data(tx))
+                .await;
+        });
+
+        // a silent peer is disconnected after the timeout
+        let (mut client_stream, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let res = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            res.unwrap().unwrap_err().kind(),
+            std::io::ErrorKind::TimedOut
+        );
+        // answering the pings may fail, because the connection is closed
+        loop {
+            match client_stream.next().await {
+                Some(Ok(tokio_tungstenite::tungstenite::Message::Ping(_))) => {}
+                Some(Ok(tokio_tungstenite::tungstenite::Message::Close(Some(frame)))) => {
+                    assert_eq!(CloseCode::from(frame.code), CloseCode::Away);
+                    break;
+                }
+                Some(Ok(msg)) => panic!("unexpected message: {msg:?}"),
+                Some(Err(_)) | None => break,
+            }
+        }
+
+        // a peer answering the pings stays connected, and the pongs are not
+        // yielded
+        let (client_stream, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let (mut sink, mut stream) = client_stream.split();
+        tokio::spawn(async move { while stream.next().await.is_some() {} });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        sink.send(tokio_tungstenite::tungstenite::Message::Text(
+            "hello".into(),
+        ))
+        .await
+        .unwrap();
+        let res = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.unwrap().unwrap(), Message::Text("hello".to_string()));
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_max_message_size() {
+        type Sender = mpsc::UnboundedSender<Option<std::io::Result<Message>>>;
+
+        #[handler(internal)]
+        async fn index(ws: WebSocket, tx: Data<&Sender>) -> impl IntoResponse {
+            let tx = tx.clone();
+            ws.max_message_size(1024)
+                .on_upgrade(|mut socket| async move {
+                    let _ = tx.send(socket.next().await);
+                    let _ = tx.send(socket.next().await);
+                })
+        }
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<Option<std::io::Result<Message>>>();
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = Server::new_with_acceptor(acceptor)
+                .run(index.data(tx))
+                .await;
+        });
+
+        let (mut client_stream, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        client_stream
+            .send(tokio_tungstenite::tungstenite::Message::Text(
+                "a".repeat(1000).into(),
+            ))
+            .await
+            .unwrap();
+        client_stream
+            .send(tokio_tungstenite::tungstenite::Message::Text(
+                "a".repeat(2000).into(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            rx.recv().await.unwrap().unwrap().unwrap(),
+            Message::Text("a".repeat(1000))
+        );
+        let err = rx.recv().await.unwrap().unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("Message too long"));
+
+        match client_stream.next().await.unwrap().unwrap() {
+            tokio_tungstenite::tungstenite::Message::Close(Some(frame)) => {
+                assert_eq!(CloseCode::from(frame.code), CloseCode::Size);
+            }
+            msg => panic!("unexpected message: {msg:?}"),
+        }
+
+        handle.abort();
+    }
+
     #[tokio::test]
     async fn test_websocket_echo() {
         #[handler(internal)]
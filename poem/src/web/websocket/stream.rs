@@ -1,23 +1,135 @@
 use std::{
-    io::{Error as IoError, Result as IoResult},
+    io::{Error as IoError, ErrorKind, Result as IoResult},
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use tokio::time::{Instant, Interval, MissedTickBehavior, Sleep};
+use tokio_tungstenite::tungstenite::Error;
 
-use super::{Message, WebSocketConfig, utils::tungstenite_error_to_io_error};
+use super::{CloseCode, Message, WebSocketConfig, utils::tungstenite_error_to_io_error};
 use crate::Upgraded;
 
+const KEEPALIVE_PAYLOAD: &[u8] = b"poem-keepalive";
+
+struct KeepAlive {
+    interval: Interval,
+    timeout: Duration,
+    deadline: Option<Pin<Box<Sleep>>>,
+    send_ping: bool,
+    flush: bool,
+    timed_out: bool,
+}
+
+impl KeepAlive {
+    fn new(interval: Duration, timeout: Duration) -> Self {
+        let mut interval = tokio::time::interval_at(Instant::now() + interval, interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            interval,
+            timeout,
+            deadline: None,
+            send_ping: false,
+            flush: false,
+            timed_out: false,
+        }
+    }
+
+    fn poll_ping(
+        &mut self,
+        inner: &mut tokio_tungstenite::WebSocketStream<Upgraded>,
+        cx: &mut Context<'_>,
+    ) -> IoResult<()> {
+        while self.interval.poll_tick(cx).is_ready() {
+            self.send_ping = true;
+        }
+
+        if self.send_ping {
+            if let Poll::Ready(res) = inner.poll_ready_unpin(cx) {
+                res.map_err(tungstenite_error_to_io_error)?;
+                inner
+                    .start_send_unpin(Message::Ping(KEEPALIVE_PAYLOAD.to_vec()).into())
+                    .map_err(tungstenite_error_to_io_error)?;
+                self.send_ping = false;
+                self.flush = true;
+                if self.deadline.is_none() {
+                    self.deadline = Some(Box::pin(tokio::time::sleep(self.timeout)));
+                }
+            }
+        }
+
+        if self.flush {
+            if let Poll::Ready(res) = inner.poll_flush_unpin(cx) {
+                self.flush = false;
+                res.map_err(tungstenite_error_to_io_error)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn poll_timeout(
+        &mut self,
+        inner: &mut tokio_tungstenite::WebSocketStream<Upgraded>,
+        cx: &mut Context<'_>,
+    ) -> bool {
+        let Some(deadline) = &mut self.deadline else {
+            return false;
+        };
+        if deadline.as_mut().poll(cx).is_pending() {
+            return false;
+        }
+
+        self.timed_out = true;
+        // the peer is probably gone, so closing is not waited for
+        start_close(inner, cx, CloseCode::Away, "keepalive timeout");
+        true
+    }
+}
+
+fn start_close(
+    inner: &mut tokio_tungstenite::WebSocketStream<Upgraded>,
+    cx: &mut Context<'_>,
+    code: CloseCode,
+    reason: &str,
+) {
+    if let Poll::Ready(Ok(())) = inner.poll_ready_unpin(cx) {
+        let close = Message::Close(Some((code, reason.to_string())));
+        if inner.start_send_unpin(close.into()).is_ok() {
+            let _ = inner.poll_flush_unpin(cx);
+        }
+    }
+}
+
 /// A `WebSocket` stream, which implements [`Stream<Message>`] and
 /// [`Sink<Message>`].
 pub struct WebSocketStream {
     inner: tokio_tungstenite::WebSocketStream<Upgraded>,
+    protocol: Option<String>,
+    keepalive: Option<KeepAlive>,
 }
 
 impl WebSocketStream {
-    pub(crate) fn new(inner: tokio_tungstenite::WebSocketStream<Upgraded>) -> Self {
-        Self { inner }
+    pub(crate) fn new(
+        inner: tokio_tungstenite::WebSocketStream<Upgraded>,
+        protocol: Option<String>,
+        keepalive: Option<(Duration, Duration)>,
+    ) -> Self {
+        Self {
+            inner,
+            protocol,
+            keepalive: keepalive.map(|(interval, timeout)| KeepAlive::new(interval, timeout)),
+        }
+    }
+
+    /// Returns the protocol selected from the `Sec-WebSocket-Protocol` header
+    /// of the request.
+    ///
+    /// See also [`WebSocket::protocols`](super::WebSocket::protocols).
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
     }
 
     /// Returns a reference to the configuration of the stream.
@@ -30,14 +142,51 @@ impl Stream for WebSocketStream {
     type Item = IoResult<Message>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.inner.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(msg))) => Poll::Ready(Some(Ok(msg.into()))),
-            Poll::Ready(Some(Err(err))) => {
-                Poll::Ready(Some(Err(tungstenite_error_to_io_error(err))))
+        let this = &mut *self;
+
+        if let Some(keepalive) = &mut this.keepalive {
+            if keepalive.timed_out {
+                return Poll::Ready(None);
+            }
+            if let Err(err) = keepalive.poll_ping(&mut this.inner, cx) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+
+        loop {
+            match this.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(msg))) => {
+                    if let Some(keepalive) = &mut this.keepalive {
+                        keepalive.deadline = None;
+                        if matches!(&msg, tokio_tungstenite::tungstenite::Message::Pong(data) if data.as_slice() == KEEPALIVE_PAYLOAD)
+                        {
+                            continue;
+                        }
+                    }
+                    return Poll::Ready(Some(Ok(msg.into())));
+                }
+                Poll::Ready(Some(Err(Error::Capacity(err)))) => {
+                    start_close(&mut this.inner, cx, CloseCode::Size, "message too big");
+                    return Poll::Ready(Some(Err(IoError::new(ErrorKind::InvalidData, err))));
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    return Poll::Ready(Some(Err(tungstenite_error_to_io_error(err))));
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => break,
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
         }
+
+        if let Some(keepalive) = &mut this.keepalive {
+            if keepalive.poll_timeout(&mut this.inner, cx) {
+                return Poll::Ready(Some(Err(IoError::new(
+                    ErrorKind::TimedOut,
+                    "websocket keepalive timeout",
+                ))));
+            }
+        }
+
+        Poll::Pending
     }
 }
 
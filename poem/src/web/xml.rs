@@ -117,7 +117,7 @@ impl<'a, T: DeserializeOwned> FromRequest<'a> for Xml<T> {
         }
 
         Ok(Self(
-            quick_xml::de::from_reader(body.take()?.into_bytes().await?.as_ref())
+            quick_xml::de::from_reader(body.take_limited(req)?.into_bytes().await?.as_ref())
                 .map_err(ParseXmlError::Parse)?,
         ))
     }
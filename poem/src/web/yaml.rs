@@ -118,7 +118,7 @@ impl<'a, T: DeserializeOwned> FromRequest<'a> for Yaml<T> {
         }
 
         Ok(Self(
-            serde_yaml::from_slice(&body.take()?.into_bytes().await?)
+            serde_yaml::from_slice(&body.take_limited(req)?.into_bytes().await?)
                 .map_err(ParseYamlError::Parse)?,
         ))
     }